@@ -0,0 +1,114 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use voyager_explorer::sstv::{DecoderParams, SstvDecoder};
+use voyager_explorer::test_fixtures::{encode_image_to_audio_with, textured_plane, EncodeOptions, TapeDegradation};
+
+const WIDTH: usize = 512;
+const LINES: usize = 128;
+const RATE: u32 = 48_000;
+
+/// Each impairment of [`TapeDegradation::typical`] on its own, then all of
+/// them together.
+fn impairments() -> Vec<(&'static str, TapeDegradation)> {
+    let typical = TapeDegradation::typical(1);
+    let none = TapeDegradation {
+        seed: typical.seed,
+        ..Default::default()
+    };
+    vec![
+        ("clean", none),
+        (
+            "wow_flutter",
+            TapeDegradation {
+                wow_depth: typical.wow_depth,
+                wow_hz: typical.wow_hz,
+                flutter_depth: typical.flutter_depth,
+                flutter_hz: typical.flutter_hz,
+                ..none
+            },
+        ),
+        (
+            "dropouts",
+            TapeDegradation {
+                dropouts_per_sec: 2.0,
+                dropout_ms: typical.dropout_ms,
+                dropout_depth: typical.dropout_depth,
+                ..none
+            },
+        ),
+        (
+            "drift_hum",
+            TapeDegradation {
+                dc_drift: typical.dc_drift,
+                hum_amplitude: typical.hum_amplitude,
+                hum_hz: typical.hum_hz,
+                ..none
+            },
+        ),
+        (
+            "band_limit",
+            TapeDegradation {
+                highpass_hz: typical.highpass_hz,
+                lowpass_hz: typical.lowpass_hz,
+                ..none
+            },
+        ),
+        ("clip", TapeDegradation { clip_level: 0.5, ..none }),
+        (
+            "lossy",
+            TapeDegradation {
+                lossy: typical.lossy,
+                ..none
+            },
+        ),
+        ("typical", typical),
+    ]
+}
+
+/// Pearson correlation of the decoded picture against the source, leaving
+/// out the sync-adjacent tenth at each end of every line.
+fn correlation(source: &[u8], decoded: &[u8]) -> f64 {
+    let margin = WIDTH / 10;
+    let interior = |pixels: &[u8]| -> Vec<f64> {
+        pixels
+            .chunks_exact(WIDTH)
+            .flat_map(|row| row[margin..WIDTH - margin].iter().map(|&v| v as f64))
+            .collect()
+    };
+    let (source, decoded) = (interior(source), interior(decoded));
+    let n = source.len().min(decoded.len());
+    let pairs = || source[..n].iter().zip(&decoded[..n]).map(|(&a, &b)| (a, b));
+    let (ma, mb) = pairs().fold((0.0, 0.0), |(x, y), (a, b)| (x + a / n as f64, y + b / n as f64));
+    let (cov, va, vb) = pairs().fold((0.0, 0.0, 0.0), |(c, x, y), (a, b)| {
+        (c + (a - ma) * (b - mb), x + (a - ma).powi(2), y + (b - mb).powi(2))
+    });
+    cov / (va * vb).sqrt().max(f64::EPSILON)
+}
+
+fn benchmark_degraded_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("degraded_decode");
+    // Coarsened so the texture survives ~400 samples per 512-pixel line
+    let pixels = textured_plane(WIDTH, LINES, |x, y| (x / 4.0, y));
+    let params = DecoderParams::default();
+    let decoder = SstvDecoder::new();
+
+    for (name, tape) in impairments() {
+        let opts = EncodeOptions {
+            tape,
+            ..Default::default()
+        };
+        let audio = encode_image_to_audio_with(&pixels, WIDTH, RATE, params.line_duration_ms, &opts);
+        // Robustness alongside speed: how much of the picture survives
+        let decoded = decoder.decode(&audio, &params, RATE).expect("decode");
+        eprintln!("{name}: correlation {:.3}", correlation(&pixels, &decoded));
+
+        group.throughput(Throughput::Elements(audio.len() as u64));
+        group.bench_with_input(BenchmarkId::new("decode", name), &audio, |b, audio| {
+            b.iter(|| black_box(decoder.decode(black_box(audio), &params, RATE).map(|p| p.len())));
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_degraded_decode);
+criterion_main!(benches);
@@ -6,6 +6,8 @@
 //! nominal ~8.3 ms line period (image signal). Consecutive windows with the
 //! same label are merged into segments.
 
+use std::ops::Range;
+
 use super::compute_spectrum;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -65,24 +67,81 @@ impl Default for ClassifyParams {
 
 /// Classify `samples` into labeled time segments.
 pub fn classify_segments(samples: &[f32], sample_rate: u32, params: &ClassifyParams) -> Vec<Segment> {
-    let window = ((params.window_secs * sample_rate as f64) as usize).max(256);
-    let mut windows: Vec<(f64, SegmentLabel, f32, Option<f32>)> = Vec::new();
+    let mut classifier = SegmentClassifier::new(sample_rate, params.clone());
+    let mut segments = classifier.push(samples);
+    segments.extend(classifier.finish());
+    segments
+}
 
-    let mut start = 0usize;
-    while start + window / 2 <= samples.len() {
-        let end = (start + window).min(samples.len());
-        let chunk = &samples[start..end];
-        let t = start as f64 / sample_rate as f64;
-        let (label, confidence, period) = classify_window(chunk, sample_rate, params);
-        windows.push((t, label, confidence, period));
-        start += window;
+/// Incremental [`classify_segments`] for input that arrives in pieces
+/// (stdin): feed it with [`SegmentClassifier::push`], which returns each
+/// segment once a window with a different label closes it.
+pub struct SegmentClassifier {
+    sample_rate: u32,
+    params: ClassifyParams,
+    /// Analysis window length in samples.
+    window: usize,
+    /// Samples not yet classified; always shorter than one window between
+    /// pushes.
+    pending: Vec<f32>,
+    /// Samples classified so far.
+    consumed: usize,
+    /// The open segment, extended while windows keep its label.
+    current: Option<Segment>,
+}
+
+impl SegmentClassifier {
+    pub fn new(sample_rate: u32, params: ClassifyParams) -> Self {
+        let window = ((params.window_secs * sample_rate as f64) as usize).max(256);
+        Self {
+            sample_rate,
+            params,
+            window,
+            pending: Vec::new(),
+            consumed: 0,
+            current: None,
+        }
     }
 
-    // Merge consecutive same-label windows.
-    let mut segments: Vec<Segment> = Vec::new();
-    let window_secs = window as f64 / sample_rate as f64;
-    for (t, label, confidence, period) in windows {
-        match segments.last_mut() {
+    /// Classify every whole window `samples` completes. Returns the
+    /// segments closed by a label change.
+    pub fn push(&mut self, samples: &[f32]) -> Vec<Segment> {
+        self.pending.extend_from_slice(samples);
+        let mut closed = Vec::new();
+        let mut start = 0;
+        while start + self.window <= self.pending.len() {
+            let chunk = start..start + self.window;
+            closed.extend(self.classify(chunk));
+            start += self.window;
+        }
+        self.pending.drain(..start);
+        closed
+    }
+
+    /// Classify the trailing partial window (if at least half a window
+    /// long) and return the remaining segments, the last one clamped to the
+    /// input length.
+    pub fn finish(mut self) -> Vec<Segment> {
+        let total = self.consumed + self.pending.len();
+        let mut segments = Vec::new();
+        if self.pending.len() >= self.window / 2 {
+            segments.extend(self.classify(0..self.pending.len()));
+        }
+        if let Some(mut last) = self.current {
+            last.end_secs = last.end_secs.min(total as f64 / self.sample_rate as f64);
+            segments.push(last);
+        }
+        segments
+    }
+
+    /// Classify `pending[chunk]` and merge it into the open segment.
+    /// Returns the previous segment when the label changed.
+    fn classify(&mut self, chunk: Range<usize>) -> Option<Segment> {
+        let t = self.consumed as f64 / self.sample_rate as f64;
+        self.consumed += chunk.len();
+        let (label, confidence, period) = classify_window(&self.pending[chunk], self.sample_rate, &self.params);
+        let window_secs = self.window as f64 / self.sample_rate as f64;
+        match &mut self.current {
             Some(seg) if seg.label == label => {
                 let n = ((seg.end_secs - seg.start_secs) / window_secs).round() as f32;
                 seg.confidence = (seg.confidence * n + confidence) / (n + 1.0);
@@ -92,8 +151,9 @@ pub fn classify_segments(samples: &[f32], sample_rate: u32, params: &ClassifyPar
                     seg.period_ms = period;
                 }
                 seg.end_secs = t + window_secs;
+                None
             }
-            _ => segments.push(Segment {
+            _ => self.current.replace(Segment {
                 start_secs: t,
                 end_secs: t + window_secs,
                 label,
@@ -102,10 +162,6 @@ pub fn classify_segments(samples: &[f32], sample_rate: u32, params: &ClassifyPar
             }),
         }
     }
-    if let Some(last) = segments.last_mut() {
-        last.end_secs = last.end_secs.min(samples.len() as f64 / sample_rate as f64);
-    }
-    segments
 }
 
 fn classify_window(chunk: &[f32], sample_rate: u32, params: &ClassifyParams) -> (SegmentLabel, f32, Option<f32>) {
@@ -117,27 +173,14 @@ fn classify_window(chunk: &[f32], sample_rate: u32, params: &ClassifyParams) ->
     }
 
     // Tonality first: a steady tone is also autocorrelation-periodic, so it
-    // must be ruled out before the line-period check. A tone concentrates its
-    // spectral energy in a few bins around one peak; image signal spreads
-    // energy across a harmonic comb at the line rate.
-    const MAX_FFT: usize = 1 << 15;
-    let spectrum = compute_spectrum(&chunk[..chunk.len().min(MAX_FFT)], sample_rate);
-    let powers: Vec<f64> = spectrum.iter().skip(1).map(|&(_, m)| (m * m).max(1e-18)).collect();
+    // must be ruled out before the line-period check.
+    let powers = power_spectrum(chunk, sample_rate);
     if powers.is_empty() {
         return (SegmentLabel::Broadband, 0.5, None);
     }
     let total: f64 = powers.iter().sum();
-    let peak_bin = powers
-        .iter()
-        .enumerate()
-        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
-        .map(|(i, _)| i)
-        .unwrap_or(0);
-    let lo = peak_bin.saturating_sub(2);
-    let hi = (peak_bin + 3).min(powers.len());
-    let peak_energy: f64 = powers[lo..hi].iter().sum();
-    let peak_ratio = (peak_energy / total) as f32;
-    if peak_ratio > 0.6 {
+    let peak_ratio = spectral_peak_ratio(&powers);
+    if peak_ratio > TONE_PEAK_RATIO {
         return (SegmentLabel::Tone, peak_ratio.clamp(0.5, 1.0), None);
     }
 
@@ -160,6 +203,37 @@ fn classify_window(chunk: &[f32], sample_rate: u32, params: &ClassifyParams) ->
     (SegmentLabel::Broadband, flatness.clamp(0.3, 1.0), None)
 }
 
+/// A window whose [`spectral_peak_ratio`] exceeds this is a steady tone.
+pub(super) const TONE_PEAK_RATIO: f32 = 0.6;
+
+/// Power spectrum (DC bin dropped, floored above zero) of at most the first
+/// 2^15 samples of `chunk`.
+pub(super) fn power_spectrum(chunk: &[f32], sample_rate: u32) -> Vec<f64> {
+    const MAX_FFT: usize = 1 << 15;
+    let spectrum = compute_spectrum(&chunk[..chunk.len().min(MAX_FFT)], sample_rate);
+    spectrum.iter().skip(1).map(|&(_, m)| (m * m).max(1e-18)).collect()
+}
+
+/// Fraction of the total power within two bins of the strongest bin. A tone
+/// concentrates its energy in a few bins around one peak; image signal
+/// spreads energy across a harmonic comb at the line rate.
+pub(super) fn spectral_peak_ratio(powers: &[f64]) -> f32 {
+    let total: f64 = powers.iter().sum();
+    if total <= 0.0 {
+        return 0.0;
+    }
+    let peak_bin = powers
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let lo = peak_bin.saturating_sub(2);
+    let hi = (peak_bin + 3).min(powers.len());
+    let peak_energy: f64 = powers[lo..hi].iter().sum();
+    (peak_energy / total) as f32
+}
+
 /// Strongest normalized autocorrelation in the lag range, computed over a
 /// bounded prefix of the chunk for cost control.
 fn autocorr_peak(chunk: &[f32], lag_min: usize, lag_max: usize) -> Option<(usize, f32)> {
@@ -254,4 +328,22 @@ mod tests {
         assert_eq!(segments[0].label, SegmentLabel::Silence);
         assert_eq!(segments[1].label, SegmentLabel::Tone);
     }
+
+    #[test]
+    fn pushing_in_pieces_matches_the_whole_buffer() {
+        let mut samples = vec![0.0f32; 30_000];
+        samples.extend(generate_sine_wave(440.0, 0.7, 48_000, 0.5));
+        samples.extend(generate_white_noise(0.4, 48_000, 0.5));
+        let params = ClassifyParams::default();
+        let whole = classify_segments(&samples, 48_000, &params);
+
+        let mut classifier = SegmentClassifier::new(48_000, params);
+        let mut pieces = Vec::new();
+        for piece in samples.chunks(7_001) {
+            pieces.extend(classifier.push(piece));
+        }
+        pieces.extend(classifier.finish());
+        assert_eq!(format!("{pieces:?}"), format!("{whole:?}"));
+        assert!(whole.len() >= 3, "{whole:?}");
+    }
 }
@@ -0,0 +1,334 @@
+//! Content masks for decoded frames.
+//!
+//! A segmented frame is rarely all picture: the first and last lines are
+//! leader and tone residue, dropouts leave flat bands, and the sync
+//! spike/dip bleeds into the first columns of every line. Statistics that
+//! assume every pixel is image — percentile contrast bounds, plane
+//! registration — get dragged by that junk. This module classifies rows
+//! from per-row statistics and sync-lock status, trims edge columns, and
+//! hands back a [`ContentMask`] those passes restrict themselves to.
+
+use std::ops::Range;
+
+#[derive(Debug, Clone)]
+pub struct ContentParams {
+    /// A row whose standard deviation is below this fraction of the median
+    /// row's is flat (blank leader, dropout, tone).
+    pub flat_std_frac: f32,
+    /// A row (or column) whose mean is further than this many median
+    /// absolute deviations from the median is an outlier.
+    pub outlier_mads: f32,
+    /// Interior runs of junk rows shorter than this stay content: a few
+    /// dark or noisy lines inside a picture are picture.
+    pub min_band_rows: usize,
+    /// Most of the width that may be trimmed from each side as edge junk.
+    pub max_column_trim: f32,
+}
+
+impl Default for ContentParams {
+    fn default() -> Self {
+        Self {
+            flat_std_frac: 0.15,
+            outlier_mads: 5.0,
+            min_band_rows: 8,
+            max_column_trim: 0.25,
+        }
+    }
+}
+
+/// Which parts of a `width`-wide level plane are picture content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentMask {
+    /// One flag per row; `true` is content.
+    pub rows: Vec<bool>,
+    /// Content columns, shared by every row.
+    pub columns: Range<usize>,
+}
+
+/// Below these sizes there are too few rows or columns for robust
+/// statistics; detection keeps every pixel.
+const MIN_ROWS: usize = 16;
+const MIN_COLUMNS: usize = 16;
+/// Detection that keeps fewer rows than this fraction has most likely
+/// misread a dark or low-contrast picture as junk; fall back instead.
+const MIN_CONTENT_FRAC: f32 = 0.25;
+
+impl ContentMask {
+    /// Every pixel is content.
+    pub fn full(height: usize, width: usize) -> Self {
+        Self {
+            rows: vec![true; height],
+            columns: 0..width,
+        }
+    }
+
+    /// Fixed-fraction guess used when detection has nothing to go on: the
+    /// central 60% of rows (edges are leader junk) and columns from 1/5 to
+    /// 9/10 of the width (the left fifth carries sync residue).
+    pub fn central(height: usize, width: usize) -> Self {
+        let (lo, hi) = (height / 5, height - height / 5);
+        Self {
+            rows: (0..height).map(|i| (lo..hi).contains(&i)).collect(),
+            columns: width / 5..(width * 9 / 10).max(width / 5 + 1).min(width),
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_content(&self, row: usize) -> bool {
+        self.rows.get(row).copied().unwrap_or(false)
+    }
+
+    pub fn content_rows(&self) -> usize {
+        self.rows.iter().filter(|&&r| r).count()
+    }
+
+    /// Masked values of a row-major plane. Rows past the mask are excluded.
+    pub fn values<'a>(&'a self, levels: &'a [f32], width: usize) -> impl Iterator<Item = f32> + 'a {
+        levels
+            .chunks_exact(width.max(1))
+            .zip(&self.rows)
+            .filter(|(_, &keep)| keep)
+            .flat_map(move |(row, _)| {
+                row[self.columns.start.min(row.len())..self.columns.end.min(row.len())]
+                    .iter()
+                    .copied()
+            })
+    }
+}
+
+/// Detect the content region of a decoded frame.
+///
+/// `line_locked` is the per-line sync-lock status from
+/// [`crate::sstv::SstvDecoder::decode_lines`]; unlocked lines at the frame
+/// edges are trimmed along with flat or outlier rows. Pass `None` (or a
+/// track with no locks at all, as fixed-period slicing produces) to judge
+/// by statistics alone. Interior junk is masked only in runs of at least
+/// `min_band_rows`.
+pub fn detect_content(levels: &[f32], width: usize, line_locked: Option<&[bool]>, params: &ContentParams) -> ContentMask {
+    let width = width.max(1);
+    let height = levels.len() / width;
+    let fallback = ContentMask::central(height, width);
+    if height < MIN_ROWS || width < MIN_COLUMNS {
+        return ContentMask::full(height, width);
+    }
+
+    // Row statistics over the provisional central columns, so sync residue
+    // doesn't dominate every row's mean.
+    let probe = fallback.columns.clone();
+    let (means, stds): (Vec<f32>, Vec<f32>) = levels.chunks_exact(width).map(|row| mean_std(&row[probe.clone()])).unzip();
+
+    let median_std = median(&stds);
+    let (median_mean, mad) = robust_center(&means);
+
+    let junk: Vec<bool> = means
+        .iter()
+        .zip(&stds)
+        .map(|(&m, &s)| s < median_std * params.flat_std_frac || (m - median_mean).abs() > params.outlier_mads * mad)
+        .collect();
+
+    // Lock status only means something when the track locked somewhere.
+    let unlocked = |row: usize| match line_locked {
+        Some(locks) if locks.iter().any(|&l| l) => !locks.get(row).copied().unwrap_or(false),
+        _ => false,
+    };
+
+    let mut rows = vec![true; height];
+    // Edge bands: leader and trailing residue, however short.
+    let mut top = 0;
+    while top < height && (junk[top] || unlocked(top)) {
+        rows[top] = false;
+        top += 1;
+    }
+    let mut bottom = height;
+    while bottom > top && (junk[bottom - 1] || unlocked(bottom - 1)) {
+        bottom -= 1;
+        rows[bottom] = false;
+    }
+    // Interior bands: only long runs of statistical junk.
+    let mut i = top;
+    while i < bottom {
+        if !junk[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bottom && junk[i] {
+            i += 1;
+        }
+        if i - start >= params.min_band_rows {
+            rows[start..i].fill(false);
+        }
+    }
+
+    let content = rows.iter().filter(|&&r| r).count();
+    if (content as f32) < height as f32 * MIN_CONTENT_FRAC {
+        tracing::debug!(content, height, "Content detection kept too few rows; using central mask");
+        return fallback;
+    }
+
+    let columns = content_columns(levels, width, &rows, params);
+    ContentMask { rows, columns }
+}
+
+/// Trim edge columns whose mean over content rows is an outlier or whose
+/// variation is flat: sync spike/dip residue and line-end overshoot.
+fn content_columns(levels: &[f32], width: usize, rows: &[bool], params: &ContentParams) -> Range<usize> {
+    let mut sums = vec![0.0f64; width];
+    let mut squares = vec![0.0f64; width];
+    let mut n = 0usize;
+    for row in levels
+        .chunks_exact(width)
+        .zip(rows)
+        .filter(|(_, &keep)| keep)
+        .map(|(row, _)| row)
+    {
+        for (x, &v) in row.iter().enumerate() {
+            sums[x] += v as f64;
+            squares[x] += (v as f64) * (v as f64);
+        }
+        n += 1;
+    }
+    if n == 0 {
+        return 0..width;
+    }
+    let means: Vec<f32> = sums.iter().map(|s| (s / n as f64) as f32).collect();
+    let stds: Vec<f32> = sums
+        .iter()
+        .zip(&squares)
+        .map(|(s, q)| {
+            let mean = s / n as f64;
+            ((q / n as f64 - mean * mean).max(0.0).sqrt()) as f32
+        })
+        .collect();
+
+    let median_std = median(&stds);
+    let (median_mean, mad) = robust_center(&means);
+    let junk =
+        |x: usize| stds[x] < median_std * params.flat_std_frac || (means[x] - median_mean).abs() > params.outlier_mads * mad;
+
+    let max_trim = (width as f32 * params.max_column_trim) as usize;
+    let mut left = 0;
+    while left < max_trim && junk(left) {
+        left += 1;
+    }
+    let mut right = width;
+    while width - right < max_trim && right > left + 1 && junk(right - 1) {
+        right -= 1;
+    }
+    left..right
+}
+
+fn mean_std(values: &[f32]) -> (f32, f32) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    let var = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / n;
+    (mean, var.sqrt())
+}
+
+fn median(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable_by(f32::total_cmp);
+    sorted[sorted.len() / 2]
+}
+
+/// Median and median absolute deviation of `values`. A MAD of zero
+/// (uniform values) would flag any deviation at all, so it is floored
+/// against the 5-95% spread.
+fn robust_center(values: &[f32]) -> (f32, f32) {
+    let center = median(values);
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable_by(f32::total_cmp);
+    let spread = match sorted.len() {
+        0 => 0.0,
+        n => sorted[n * 95 / 100] - sorted[n * 5 / 100],
+    };
+    let deviations: Vec<f32> = values.iter().map(|v| (v - center).abs()).collect();
+    (center, median(&deviations).max(spread * 0.02).max(f32::EPSILON))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 64x64 plane: textured picture, 6 blank leader rows on top, a bright
+    /// 10-row dropout band in the middle, 3 tone rows at the bottom, and an
+    /// 8-column sync-residue stripe on the left.
+    fn junky_frame() -> Vec<f32> {
+        let (w, h) = (64usize, 64usize);
+        let mut levels = Vec::with_capacity(w * h);
+        for y in 0..h {
+            for x in 0..w {
+                let picture = 0.3 + 0.2 * (((x * 7 + y * 13) % 17) as f32 / 17.0);
+                let v = match (y, x) {
+                    (0..=5, _) => 0.05,
+                    (61.., _) => 0.95 + 0.01 * ((x % 2) as f32),
+                    (30..=39, _) => 0.9,
+                    (_, 0..=7) => -0.6,
+                    _ => picture,
+                };
+                levels.push(v);
+            }
+        }
+        levels
+    }
+
+    #[test]
+    fn masks_leader_bands_and_sync_residue() {
+        let levels = junky_frame();
+        let mask = detect_content(&levels, 64, None, &ContentParams::default());
+        for y in 0..64 {
+            let expected = !matches!(y, 0..=5 | 30..=39 | 61..);
+            assert_eq!(mask.is_content(y), expected, "row {y}");
+        }
+        assert_eq!(mask.columns, 8..64);
+    }
+
+    #[test]
+    fn short_dark_runs_stay_content_and_unlocked_edges_trim() {
+        let mut levels = junky_frame();
+        // Three near-black lines inside the picture: content, not a band.
+        levels[20 * 64..23 * 64].fill(0.0);
+        let mut locked = vec![true; 64];
+        // The tracker coasted over two picture lines after the leader.
+        locked[6] = false;
+        locked[7] = false;
+        let mask = detect_content(&levels, 64, Some(&locked), &ContentParams::default());
+        assert!(!mask.is_content(6) && !mask.is_content(7));
+        assert!(mask.is_content(8));
+        assert!((20..23).all(|y| mask.is_content(y)), "short dark run masked");
+
+        // All-unlocked (fixed-period slicing) carries no information.
+        let unlocked = vec![false; 64];
+        let mask = detect_content(&levels, 64, Some(&unlocked), &ContentParams::default());
+        assert!(mask.is_content(6));
+    }
+
+    #[test]
+    fn uniform_and_tiny_planes_keep_everything() {
+        let flat = vec![0.5; 64 * 64];
+        let mask = detect_content(&flat, 64, None, &ContentParams::default());
+        assert_eq!(mask.content_rows(), 64);
+        assert_eq!(mask.columns, 0..64);
+
+        let tiny = vec![0.1; 4 * 8];
+        assert_eq!(
+            detect_content(&tiny, 4, None, &ContentParams::default()),
+            ContentMask::full(8, 4)
+        );
+
+        let mask = ContentMask::central(10, 10);
+        assert_eq!(
+            mask.values(&(0..100).map(|v| v as f32).collect::<Vec<_>>(), 10).count(),
+            6 * 7
+        );
+    }
+}
@@ -1,21 +1,33 @@
 //! Signal analysis and diagnostics: one-shot spectra, spectrograms, rolling
-//! statistics, segment classification, and scan-line sync detection.
+//! statistics, segment classification, scan-line sync detection, time-base
+//! (wow/flutter) correction, playback-speed detection, and content masking
+//! of decoded frames.
 //!
 //! Everything here is pure library code; the CLI subcommands and the GUI
 //! diagnostics panel are thin shims over these functions.
 
 pub mod classify;
+pub mod content;
 mod font;
 pub mod segment;
 pub mod spectrogram;
+pub mod speed;
 pub mod stats;
 pub mod sync;
+pub mod timebase;
 
-pub use classify::{classify_segments, ClassifyParams, Segment, SegmentLabel};
-pub use segment::{find_image_bounds, ImageBounds, SegmentImagesParams};
+pub use classify::{classify_segments, ClassifyParams, Segment, SegmentClassifier, SegmentLabel};
+pub use content::{detect_content, ContentMask, ContentParams};
+pub use segment::{find_image_bounds, find_image_bounds_blocks, find_image_bounds_in, ImageBounds, SegmentImagesParams};
 pub use spectrogram::{compute_spectrogram, render_spectrogram, Spectrogram, SpectrogramParams};
+pub use speed::{estimate_line_period, estimate_speed, SpeedEstimate, SpeedParams, SPEED_FACTOR_RANGE};
 pub use stats::{compute_stats, rolling_stats, SignalStats};
-pub use sync::{detect_line_syncs, interval_summary, IntervalSummary, SyncParams};
+pub use sync::{
+    detect_line_syncs, detect_line_syncs_block, detect_line_syncs_in, interval_summary, refine_line_start, sync_block_layout,
+    track_line_syncs_block, track_line_syncs_with_lock, IntervalSummary, LineSync, SyncParams, SyncState, SyncTrack,
+    TrackFallback,
+};
+pub use timebase::{correct_time_base, fit_time_base, TimeBase, WowFlutter, WOW_FLUTTER_SPLIT_HZ};
 
 use realfft::RealFftPlanner;
 
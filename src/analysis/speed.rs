@@ -0,0 +1,298 @@
+//! Playback-speed detection.
+//!
+//! Some rips of the record run fast: a 2× transfer puts the line syncs
+//! ~4.16 ms apart instead of ~8.32 ms, and everything keyed to the nominal
+//! line duration — the sync search spacing, segmentation, the fixed-period
+//! fallback, playback pitch — is off by the same factor. This module
+//! measures the line period the file actually has, from autocorrelation over
+//! short probes spread across a channel, refines it with the sync-interval
+//! mean at that period, and reports the implied speed factor for
+//! [`crate::sstv::DecoderParams::speed_factor`].
+
+use std::ops::RangeInclusive;
+
+use num_complex::Complex;
+use realfft::RealFftPlanner;
+
+use super::classify::{power_spectrum, spectral_peak_ratio, TONE_PEAK_RATIO};
+use super::sync::{detect_line_syncs, SyncParams};
+use crate::audio::WaveformChannel;
+use crate::error::Result;
+use crate::sample_source::SampleSource;
+
+/// Speed factors the detector searches and the decoder accepts.
+pub const SPEED_FACTOR_RANGE: RangeInclusive<f32> = 0.25..=4.0;
+
+/// Every multiple of the line period correlates as well as the period
+/// itself; the fundamental is the shortest lag whose peak comes within this
+/// fraction of the strongest one.
+const FUNDAMENTAL_RATIO: f32 = 0.85;
+/// Sync intervals within this fraction of the autocorrelation period count
+/// as line cadence when refining.
+const CADENCE_TOLERANCE: f64 = 0.1;
+/// A probe is image signal only if at least this fraction of its sync
+/// intervals are line cadence (music and noise trigger erratically).
+const MIN_CADENCE_FRAC: f32 = 0.6;
+/// Probes whose period is within this fraction of the median agree.
+const AGREEMENT: f32 = 0.03;
+
+#[derive(Debug, Clone)]
+pub struct SpeedParams {
+    /// Nominal record line duration in ms; the factor is relative to this.
+    pub nominal_line_ms: f32,
+    /// Length of each probe window in seconds.
+    pub probe_secs: f64,
+    /// Number of probes, spread evenly across the channel.
+    pub probes: usize,
+    /// Minimum normalized autocorrelation at the line period for a probe to
+    /// count as image signal (same bar as segment classification).
+    pub min_corr: f32,
+    /// Factors within this distance of 1.0 are reported as exactly 1.0:
+    /// nominal rips measure a percent or so off from drift alone, and
+    /// resampling playback for that would only detune it.
+    pub unity_tolerance: f32,
+}
+
+impl Default for SpeedParams {
+    fn default() -> Self {
+        Self {
+            nominal_line_ms: 8.32,
+            probe_secs: 2.0,
+            probes: 24,
+            min_corr: 0.35,
+            unity_tolerance: 0.02,
+        }
+    }
+}
+
+/// Measured line period of a file and the speed factor it implies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedEstimate {
+    /// Mean line period over the agreeing probes, ms.
+    pub line_ms: f32,
+    /// Nominal over measured period: 2.0 for a rip running at double speed,
+    /// 1.0 for a nominal one.
+    pub factor: f32,
+    /// Probes whose period agreed with the median.
+    pub agreeing: usize,
+    /// Probes that found a line cadence at all.
+    pub periodic: usize,
+}
+
+impl SpeedEstimate {
+    pub fn is_nominal(&self) -> bool {
+        self.factor == 1.0
+    }
+}
+
+impl std::fmt::Display for SpeedEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "speed ×{:.3} (line {:.3} ms, {}/{} probes agree)",
+            self.factor, self.line_ms, self.agreeing, self.periodic
+        )
+    }
+}
+
+/// Line period of one window in ms, with the normalized autocorrelation at
+/// that lag. `None` when the window is silent or a steady tone, or has no
+/// coherent line cadence within [`SPEED_FACTOR_RANGE`] of the nominal
+/// period.
+pub fn estimate_line_period(samples: &[f32], sample_rate: u32, params: &SpeedParams) -> Option<(f32, f32)> {
+    let nominal = params.nominal_line_ms as f64 / 1000.0 * sample_rate as f64;
+    let lag_min = ((nominal / *SPEED_FACTOR_RANGE.end() as f64) as usize).max(8);
+    let lag_max = (nominal / *SPEED_FACTOR_RANGE.start() as f64).ceil() as usize;
+    if samples.len() < lag_max * 3 {
+        return None;
+    }
+
+    // A tone correlates at every multiple of its own period.
+    let powers = power_spectrum(samples, sample_rate);
+    if powers.is_empty() || spectral_peak_ratio(&powers) > TONE_PEAK_RATIO {
+        return None;
+    }
+
+    let acf = autocorrelation(samples, lag_max + 1)?;
+    let peaks: Vec<(usize, f32)> = (lag_min..lag_max)
+        .filter(|&lag| acf[lag] > acf[lag - 1] && acf[lag] >= acf[lag + 1])
+        .map(|lag| (lag, acf[lag]))
+        .collect();
+    let best = peaks.iter().map(|&(_, c)| c).fold(f32::MIN, f32::max);
+    if best < params.min_corr {
+        return None;
+    }
+    let (coarse, corr) = peaks.into_iter().find(|&(_, c)| c >= best * FUNDAMENTAL_RATIO)?;
+
+    // Refine to sub-sample precision from the syncs at that cadence, and
+    // reject windows whose syncs do not actually keep it.
+    let sync_params = SyncParams {
+        expected_line_ms: (coarse as f64 / sample_rate as f64 * 1000.0) as f32,
+        ..SyncParams::default()
+    };
+    let positions = detect_line_syncs(samples, sample_rate, &sync_params);
+    let intervals = positions.len().saturating_sub(1);
+    let cadence: Vec<f64> = positions
+        .windows(2)
+        .map(|w| (w[1] - w[0]) as f64)
+        .filter(|&iv| (iv - coarse as f64).abs() <= coarse as f64 * CADENCE_TOLERANCE)
+        .collect();
+    if cadence.is_empty() || (cadence.len() as f32) < intervals as f32 * MIN_CADENCE_FRAC {
+        return None;
+    }
+    let period = cadence.iter().sum::<f64>() / cadence.len() as f64;
+    Some(((period / sample_rate as f64 * 1000.0) as f32, corr))
+}
+
+/// Estimate the speed factor of one channel of `source` from
+/// [`SpeedParams::probes`] windows spread across it. `None` when too few
+/// probes find a line cadence, or they disagree about it.
+///
+/// # Errors
+///
+/// Propagates read failures from the source.
+pub fn estimate_speed(
+    source: &dyn SampleSource,
+    channel: WaveformChannel,
+    params: &SpeedParams,
+) -> Result<Option<SpeedEstimate>> {
+    let sample_rate = source.sample_rate();
+    let probe_len = ((params.probe_secs * sample_rate as f64) as usize).max(1);
+    let len = source.len();
+    let probes = if len > probe_len { params.probes.max(1) } else { 1 };
+
+    let mut periods = Vec::new();
+    for i in 0..probes {
+        let start = ((len.saturating_sub(probe_len)) as f64 * (i as f64 + 0.5) / probes as f64) as usize;
+        let window = source.read(channel, start..start + probe_len)?;
+        if let Some((line_ms, _)) = estimate_line_period(&window, sample_rate, params) {
+            periods.push(line_ms);
+        }
+    }
+    Ok(summarize(periods, params))
+}
+
+/// Combine per-probe periods: the agreeing majority around the median wins.
+fn summarize(mut periods: Vec<f32>, params: &SpeedParams) -> Option<SpeedEstimate> {
+    if periods.is_empty() {
+        return None;
+    }
+    periods.sort_unstable_by(f32::total_cmp);
+    let median = periods[periods.len() / 2];
+    let agreeing: Vec<f32> = periods
+        .iter()
+        .copied()
+        .filter(|p| (p - median).abs() <= median * AGREEMENT)
+        .collect();
+    if agreeing.len() * 2 <= periods.len() && periods.len() > 1 {
+        tracing::debug!(?periods, "Speed probes disagree; no estimate");
+        return None;
+    }
+    let line_ms = agreeing.iter().sum::<f32>() / agreeing.len() as f32;
+    let mut factor = (params.nominal_line_ms / line_ms).clamp(*SPEED_FACTOR_RANGE.start(), *SPEED_FACTOR_RANGE.end());
+    if (factor - 1.0).abs() <= params.unity_tolerance {
+        factor = 1.0;
+    }
+    Some(SpeedEstimate {
+        line_ms,
+        factor,
+        agreeing: agreeing.len(),
+        periodic: periods.len(),
+    })
+}
+
+/// Normalized (unbiased) autocorrelation of the mean-removed signal for lags
+/// `0..max_lag`, via zero-padded FFT. `None` for a constant signal.
+fn autocorrelation(samples: &[f32], max_lag: usize) -> Option<Vec<f32>> {
+    let n = samples.len();
+    let size = (2 * n).next_power_of_two();
+    let mut planner = RealFftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(size);
+    let inverse = planner.plan_fft_inverse(size);
+
+    let mean = samples.iter().sum::<f32>() / n as f32;
+    let mut buffer = vec![0.0f32; size];
+    for (dst, &s) in buffer.iter_mut().zip(samples) {
+        *dst = s - mean;
+    }
+    let mut spectrum = forward.make_output_vec();
+    forward.process(&mut buffer, &mut spectrum).ok()?;
+    for bin in spectrum.iter_mut() {
+        *bin = Complex::new(bin.norm_sqr(), 0.0);
+    }
+    inverse.process(&mut spectrum, &mut buffer).ok()?;
+
+    let energy = buffer[0];
+    if energy <= 0.0 {
+        return None;
+    }
+    Some(
+        (0..max_lag.min(n))
+            .map(|lag| buffer[lag] / energy * n as f32 / (n - lag) as f32)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::WavReader;
+    use crate::test_fixtures::{encode_image_to_audio, generate_sine_wave, generate_white_noise, textured_plane};
+
+    const RATE: u32 = 48_000;
+
+    /// Two textured frames separated by silence, encoded at `line_ms`.
+    fn rip(line_ms: f32) -> WavReader {
+        let image = textured_plane(64, 240, |x, y| (x, y));
+        let gap = vec![0.0f32; RATE as usize / 10];
+        let mut audio = gap.clone();
+        for _ in 0..2 {
+            audio.extend(encode_image_to_audio(&image, 64, RATE, line_ms));
+            audio.extend(&gap);
+        }
+        let samples: std::sync::Arc<[f32]> = audio.into();
+        WavReader {
+            channel_data: vec![samples],
+            sample_rate: RATE,
+            channels: 1,
+            format: Default::default(),
+        }
+    }
+
+    fn probe_params() -> SpeedParams {
+        SpeedParams {
+            probe_secs: 0.5,
+            probes: 6,
+            ..SpeedParams::default()
+        }
+    }
+
+    #[test]
+    fn detects_double_speed_rip() {
+        let estimate = estimate_speed(&rip(4.16), WaveformChannel::Left, &probe_params())
+            .unwrap()
+            .expect("estimate");
+        assert!((estimate.line_ms - 4.16).abs() < 0.02, "{estimate}");
+        assert!((estimate.factor - 2.0).abs() < 0.01, "{estimate}");
+        assert!(estimate.agreeing * 2 > estimate.periodic);
+    }
+
+    #[test]
+    fn nominal_rip_is_unity_and_tones_and_noise_have_no_period() {
+        let estimate = estimate_speed(&rip(8.32), WaveformChannel::Left, &probe_params())
+            .unwrap()
+            .expect("estimate");
+        assert!(estimate.is_nominal(), "{estimate}");
+
+        let params = SpeedParams::default();
+        assert_eq!(
+            estimate_line_period(&generate_sine_wave(1200.0, 0.5, RATE, 0.5), RATE, &params),
+            None
+        );
+        assert_eq!(
+            estimate_line_period(&generate_white_noise(0.5, RATE, 0.5), RATE, &params),
+            None
+        );
+        assert_eq!(estimate_line_period(&vec![0.0; RATE as usize], RATE, &params), None);
+    }
+}
@@ -6,9 +6,19 @@
 //! the line start. This module implements that approach: peak picking with a
 //! minimum-distance constraint, then a forward search for the local minimum.
 
-#[derive(Debug, Clone)]
+use std::ops::Range;
+
+use crate::audio::WaveformChannel;
+use crate::error::Result;
+use crate::sample_source::{read_blocks, Block, SampleSource};
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct SyncParams {
-    /// Nominal line duration in milliseconds (Voyager: ~8.32 ms).
+    /// Nominal line duration in milliseconds (Voyager: ~8.32 ms). Not part
+    /// of a preset's sync table: decoders set it from the line duration and
+    /// speed factor.
+    #[serde(skip)]
     pub expected_line_ms: f32,
     /// Peak threshold as a fraction of the robust signal maximum.
     pub peak_height: f32,
@@ -87,6 +97,76 @@ fn falling_edge_min(samples: &[f32], peak: usize, edge_search: usize) -> usize {
     min_idx
 }
 
+/// Block length for [`detect_line_syncs_in`], in seconds. Long enough that
+/// each block's robust-maximum threshold matches the whole-file one on
+/// steady material; short enough to bound memory on multi-hour rips.
+const SOURCE_BLOCK_SECS: f64 = 60.0;
+
+/// [`detect_line_syncs`] over `range` of one channel of a [`SampleSource`],
+/// read block by block so the channel never has to be resident. Positions
+/// are relative to `range.start`.
+///
+/// Blocks overlap by two line periods and each keeps only the positions in
+/// the frames it owns, so a sync straddling a block boundary is reported
+/// once. The peak threshold is per block, which only differs from the
+/// whole-buffer result where the signal level changes within a minute.
+///
+/// # Errors
+///
+/// Propagates read failures from the source.
+pub fn detect_line_syncs_in(
+    source: &dyn SampleSource,
+    channel: WaveformChannel,
+    range: Range<usize>,
+    params: &SyncParams,
+) -> Result<Vec<usize>> {
+    let block_secs = SOURCE_BLOCK_SECS * source.sample_rate() as f64;
+    detect_line_syncs_blocked(source, channel, range, params, block_secs as usize)
+}
+
+fn detect_line_syncs_blocked(
+    source: &dyn SampleSource,
+    channel: WaveformChannel,
+    range: Range<usize>,
+    params: &SyncParams,
+    block_len: usize,
+) -> Result<Vec<usize>> {
+    let sample_rate = source.sample_rate();
+    let (block_len, margin) = block_layout(sample_rate, params, block_len);
+    let mut positions = Vec::new();
+    for block in read_blocks(source, channel, range.clone(), block_len, margin) {
+        let block = block?;
+        positions.extend(
+            detect_line_syncs_block(&block, sample_rate, params)
+                .into_iter()
+                .map(|p| p - range.start),
+        );
+    }
+    Ok(positions)
+}
+
+/// Block length and context margin for block-by-block sync detection, as
+/// [`detect_line_syncs_in`] walks a source. One-pass inputs (stdin) use
+/// the same layout so they find the same syncs.
+pub fn sync_block_layout(sample_rate: u32, params: &SyncParams) -> (usize, usize) {
+    block_layout(sample_rate, params, (SOURCE_BLOCK_SECS * sample_rate as f64) as usize)
+}
+
+fn block_layout(sample_rate: u32, params: &SyncParams, block_len: usize) -> (usize, usize) {
+    let period = (params.expected_line_ms / 1000.0 * sample_rate as f32) as usize;
+    (block_len.max(period * 4), period * 2)
+}
+
+/// [`detect_line_syncs`] over one block of a blocked walk: the syncs in the
+/// block's owned frames, as absolute frame indices.
+pub fn detect_line_syncs_block(block: &Block, sample_rate: u32, params: &SyncParams) -> Vec<usize> {
+    detect_line_syncs(&block.samples, sample_rate, params)
+        .into_iter()
+        .map(|p| block.offset + p)
+        .filter(|p| block.owned.contains(p))
+        .collect()
+}
+
 /// Tracker tuning constants. The detector's analogous knobs live on
 /// [`SyncParams`]; these stay module-level because no caller tunes them yet.
 ///
@@ -106,6 +186,13 @@ const PERIOD_EMA_ALPHA: f64 = 0.05;
 const SWING_ANCHOR_FACTOR: f32 = 0.6;
 /// Accepted re-anchor intervals must lie within this fraction of the period.
 const TRACK_INTERVAL_TOL: f64 = 0.06;
+/// Half-width in samples of the step-matched filter that times each falling
+/// edge to a fraction of a sample: the mean of this many samples before a
+/// point minus the mean of as many after it. Short enough to sit inside the
+/// record's spike and dip even on a double-speed rip.
+const EDGE_TAPS: usize = 3;
+/// Sub-sample line starts are multiples of one over this.
+const EDGE_FRACTION_STEPS: f64 = 1024.0;
 
 /// Track scan-line sync positions with a predictive lock.
 ///
@@ -118,49 +205,376 @@ const TRACK_INTERVAL_TOL: f64 = 0.06;
 /// detector's first coherent position, then walks the buffer line by line,
 /// slowly adapting the period estimate (slant) from accepted peaks.
 pub fn track_line_syncs(samples: &[f32], sample_rate: u32, params: &SyncParams) -> Vec<usize> {
+    track_line_syncs_with_lock(samples, sample_rate, params).positions
+}
+
+/// How a sync position was placed, or for a decoded line, the weaker of
+/// how its two ends were. Variants are ordered weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SyncState {
+    /// Fixed-period slice with no sync behind it (lines only).
+    Fixed,
+    /// No credible sync near the prediction: placed on the prediction.
+    Coasted,
+    /// Raw detector hit, from a track that fell back to the detections.
+    Detected,
+    /// Re-anchored on a sync-like swing below the detector threshold.
+    Weak,
+    /// Re-anchored on a full-threshold swing, or the track's seed.
+    Locked,
+}
+
+impl SyncState {
+    pub const ALL: [SyncState; 5] = [
+        SyncState::Locked,
+        SyncState::Weak,
+        SyncState::Detected,
+        SyncState::Coasted,
+        SyncState::Fixed,
+    ];
+
+    /// Placed on an actual sync event. Raw detections count: each one is an
+    /// actual peak.
+    pub fn is_anchored(self) -> bool {
+        matches!(self, SyncState::Locked | SyncState::Weak | SyncState::Detected)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SyncState::Fixed => "fixed",
+            SyncState::Coasted => "coasted",
+            SyncState::Detected => "detected",
+            SyncState::Weak => "weak",
+            SyncState::Locked => "locked",
+        }
+    }
+}
+
+impl std::fmt::Display for SyncState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.label())
+    }
+}
+
+/// Why [`track_line_syncs_with_lock`] handed back raw detections instead
+/// of a predictive track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackFallback {
+    /// Fewer than two detections, or a silent buffer: nothing to seed from.
+    NoSeed,
+    /// The track mostly coasted: only `locked` of `positions` positions
+    /// re-anchored on a full-threshold swing (a quarter are needed).
+    TooFewLocks { locked: usize, positions: usize },
+}
+
+impl std::fmt::Display for TrackFallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrackFallback::NoSeed => f.write_str("no sync cadence to seed from"),
+            TrackFallback::TooFewLocks { locked, positions } => {
+                write!(f, "only {locked} of {positions} positions locked")
+            }
+        }
+    }
+}
+
+/// Sync positions from [`track_line_syncs_with_lock`] with per-position
+/// diagnostics, all parallel to `positions`.
+#[derive(Debug, Clone, Default)]
+pub struct SyncTrack {
+    pub positions: Vec<usize>,
+    /// Line starts to a fraction of a sample ([`refine_line_start`]);
+    /// `positions` rounds them.
+    pub starts: Vec<f64>,
+    pub states: Vec<SyncState>,
+    /// Spike-to-dip swing at each position over the detector threshold
+    /// (1.0 = threshold). Coasted positions keep the swing of the rejected
+    /// candidate; zero on a silent buffer.
+    pub swings: Vec<f32>,
+    /// Period estimate in samples after each position: the tracker's
+    /// adapted cadence, or for raw detections the median interval.
+    pub periods: Vec<f64>,
+    /// Set when the tracker gave up and `positions` are raw detections.
+    pub fallback: Option<TrackFallback>,
+}
+
+impl SyncTrack {
+    /// Raw detections standing in for a track, with why.
+    fn detected(samples: &[f32], positions: Vec<usize>, sample_rate: u32, params: &SyncParams, fallback: TrackFallback) -> Self {
+        let nominal = (params.expected_line_ms / 1000.0 * sample_rate as f32) as f64;
+        let period = interval_summary(&positions, sample_rate).map_or(nominal, |s| s.median_samples);
+        let threshold = percentile_abs(samples, 0.999) * params.peak_height;
+        let edge_search = ((period * TRACK_EDGE_FRAC) as usize).max(4);
+        let swings = positions
+            .iter()
+            .map(|&p| swing_ratio(swing_before(samples, p, edge_search), threshold))
+            .collect();
+        Self {
+            starts: positions
+                .iter()
+                .map(|&p| refine_line_start(samples, p, edge_search))
+                .collect(),
+            states: vec![SyncState::Detected; positions.len()],
+            periods: vec![period; positions.len()],
+            swings,
+            positions,
+            fallback: Some(fallback),
+        }
+    }
+
+    /// Whether each position was anchored on a sync event.
+    pub fn anchored(&self, index: usize) -> bool {
+        self.states[index].is_anchored()
+    }
+
+    /// Positions in each state, in [`SyncState::ALL`] order.
+    pub fn tally(&self) -> [(SyncState, usize); 5] {
+        SyncState::ALL.map(|state| (state, self.states.iter().filter(|&&s| s == state).count()))
+    }
+
+    /// Diagnostics of the line from position `index` to the next one.
+    pub fn line(&self, index: usize) -> LineSync {
+        LineSync {
+            state: self.states[index].min(self.states[index + 1]),
+            swing: self.swings[index + 1],
+            period: self.periods[index + 1],
+        }
+    }
+}
+
+/// Sync diagnostics of one decoded scan line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineSync {
+    /// The weaker of how the line's two ends were placed.
+    pub state: SyncState,
+    /// Swing of the sync ending the line over the detector threshold; zero
+    /// under fixed-period slicing.
+    pub swing: f32,
+    /// Period estimate at the line's end, in samples.
+    pub period: f64,
+}
+
+impl LineSync {
+    /// A fixed-period slice of `period` samples.
+    pub fn fixed(period: f64) -> Self {
+        Self {
+            state: SyncState::Fixed,
+            swing: 0.0,
+            period,
+        }
+    }
+
+    /// Both ends anchored on a sync event.
+    pub fn is_locked(&self) -> bool {
+        self.state.is_anchored()
+    }
+}
+
+/// [`track_line_syncs`] with per-position diagnostics. Lines between
+/// coasted positions are where the decoder was guessing — leader, dropouts,
+/// boundary junk — which content masking downstream wants to know.
+pub fn track_line_syncs_with_lock(samples: &[f32], sample_rate: u32, params: &SyncParams) -> SyncTrack {
     let detected = detect_line_syncs(samples, sample_rate, params);
-    let Some(summary) = interval_summary(&detected, sample_rate) else {
-        return detected;
+    let Some(lock) = SyncLock::acquire(samples, &detected, sample_rate, params) else {
+        return SyncTrack::detected(samples, detected, sample_rate, params, TrackFallback::NoSeed);
+    };
+
+    let mut track = SyncTrack {
+        positions: vec![lock.seed],
+        starts: vec![lock.start],
+        states: vec![SyncState::Locked],
+        swings: vec![swing_ratio(
+            swing_before(samples, lock.seed, lock.edge_search),
+            lock.threshold,
+        )],
+        periods: vec![lock.period],
+        fallback: None,
+    };
+    let mut pos = lock.start;
+    let mut period = lock.period;
+    let mut locked = 0usize;
+    while let Some(step) = lock.step(samples, pos, &mut period) {
+        locked += usize::from(step.state == SyncState::Locked);
+        track.positions.push(step.next.round() as usize);
+        track.starts.push(step.next);
+        track.states.push(step.state);
+        track.swings.push(step.swing);
+        track.periods.push(period);
+        pos = step.next;
+    }
+
+    // A track that mostly coasted never found real line structure (music,
+    // noise): hand back the raw detections so the caller's cadence check
+    // can reject sync lock and fall through to fixed-period slicing.
+    let positions = track.positions.len();
+    if locked < positions / 4 {
+        let fallback = TrackFallback::TooFewLocks { locked, positions };
+        return SyncTrack::detected(samples, detected, sample_rate, params, fallback);
+    }
+    track
+}
+
+/// [`track_line_syncs_with_lock`] over one block of a blocked walk: the
+/// track's positions in the block's owned frames, as absolute frame
+/// indices. Each block seeds its own track.
+pub fn track_line_syncs_block(block: &Block, sample_rate: u32, params: &SyncParams) -> SyncTrack {
+    let track = track_line_syncs_with_lock(&block.samples, sample_rate, params);
+    let mut owned = SyncTrack {
+        fallback: track.fallback,
+        ..SyncTrack::default()
+    };
+    for (i, &p) in track.positions.iter().enumerate() {
+        if block.owned.contains(&(block.offset + p)) {
+            owned.positions.push(block.offset + p);
+            owned.starts.push(block.offset as f64 + track.starts[i]);
+            owned.states.push(track.states[i]);
+            owned.swings.push(track.swings[i]);
+            owned.periods.push(track.periods[i]);
+        }
+    }
+    owned
+}
+
+/// Largest spike-to-dip drop into the line start at `pos` from the
+/// `edge_search` samples before it.
+fn swing_before(samples: &[f32], pos: usize, edge_search: usize) -> f32 {
+    let Some(&dip) = samples.get(pos) else {
+        return 0.0;
     };
+    let peak = samples[pos.saturating_sub(edge_search)..=pos]
+        .iter()
+        .copied()
+        .fold(dip, f32::max);
+    peak - dip
+}
 
-    let nominal = (params.expected_line_ms / 1000.0 * sample_rate as f32) as f64;
-    // Start from the detected cadence when it is plausibly the line cadence.
-    // When it is not — weak-sync lines get swallowed by the detector's
-    // min-spacing dedup, doubling the apparent median — trust the nominal
-    // period and let per-line locking prove itself (the lock-fraction guard
-    // below rejects the result if it can't).
-    let mut period = if (summary.median_samples - nominal).abs() / nominal < 0.3 {
-        summary.median_samples
+/// Sub-sample line start for the falling edge bottoming out at `bottom`:
+/// the peak of a step-matched filter ([`EDGE_TAPS`] samples either side)
+/// over the edge from the spike maximum within `edge_search` samples
+/// before, parabolically interpolated. A one-sample edge lands exactly on
+/// its first low sample, as the integer search does; a resampled or
+/// band-limited edge lands between samples.
+pub fn refine_line_start(samples: &[f32], bottom: usize, edge_search: usize) -> f64 {
+    if bottom < EDGE_TAPS || bottom + EDGE_TAPS > samples.len() {
+        return bottom as f64;
+    }
+    let lo = bottom.saturating_sub(edge_search).max(EDGE_TAPS);
+    let peak = (lo..=bottom)
+        .max_by(|&a, &b| samples[a].partial_cmp(&samples[b]).unwrap_or(std::cmp::Ordering::Equal))
+        .unwrap_or(bottom);
+    if peak >= bottom {
+        return bottom as f64;
+    }
+    let step = |j: usize| {
+        let before: f32 = samples[j - EDGE_TAPS..j].iter().sum();
+        let after: f32 = samples[j..j + EDGE_TAPS].iter().sum();
+        (before - after) as f64
+    };
+    // Strongest step on the edge, first on ties (a flat dip)
+    let hi = (bottom + 1).min(samples.len() - EDGE_TAPS);
+    let best = (peak + 1..=hi).fold(peak + 1, |best, j| if step(j) > step(best) { j } else { best });
+    if best <= EDGE_TAPS || best + EDGE_TAPS >= samples.len() {
+        return best as f64;
+    }
+    let (l, c, r) = (step(best - 1), step(best), step(best + 1));
+    let curve = l - 2.0 * c + r;
+    let offset = if curve < 0.0 {
+        (0.5 * (l - r) / curve).clamp(-0.5, 0.5)
     } else {
-        nominal
+        0.0
     };
+    // In 1/1024 samples: finer than anything downstream resolves, and exact
+    // under the whole-sample shifts between a stream's buffers and the file
+    best as f64 + (offset * EDGE_FRACTION_STEPS).round() / EDGE_FRACTION_STEPS
+}
 
-    let robust_max = percentile_abs(samples, 0.999);
-    if robust_max <= 0.0 {
-        return detected;
+fn swing_ratio(swing: f32, threshold: f32) -> f32 {
+    if threshold > 0.0 {
+        swing / threshold
+    } else {
+        0.0
     }
-    let threshold = robust_max * params.peak_height;
-    let search = ((period * TRACK_SEARCH_FRAC) as usize).max(2);
-    let edge_search = ((period * TRACK_EDGE_FRAC) as usize).max(4);
-
-    // Seed at the first detected sync that starts a coherent pair.
-    let seed = detected
-        .windows(2)
-        .find(|w| ((w[1] - w[0]) as f64 - period).abs() / period < 0.1)
-        .map(|w| w[0])
-        .unwrap_or(detected[0]);
-
-    let mut positions = vec![seed];
-    let mut pos = seed as f64;
-    let mut locked = 0usize;
-    loop {
-        let predict = pos + period;
-        if predict + period > samples.len() as f64 {
-            break;
+}
+
+/// Predictive-lock state seeded from a buffer's raw detections: where the
+/// track starts and the cadence, threshold and search windows it walks
+/// with. Shared by [`track_line_syncs_with_lock`] and the incremental
+/// [`crate::streaming::StreamingDecoder`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SyncLock {
+    /// First tracked position, in the acquisition buffer.
+    pub seed: usize,
+    /// `seed` to a fraction of a sample.
+    pub start: f64,
+    /// Starting period estimate in samples.
+    pub period: f64,
+    threshold: f32,
+    search: usize,
+    edge_search: usize,
+}
+
+/// One tracker step: the next line start and how it was found.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TrackStep {
+    pub next: f64,
+    /// [`SyncState::Locked`], [`SyncState::Weak`] or [`SyncState::Coasted`].
+    pub state: SyncState,
+    /// Swing of the best candidate over the threshold.
+    pub swing: f32,
+}
+
+impl SyncLock {
+    /// Seed a lock from `detected` ([`detect_line_syncs`] over `samples`).
+    /// `None` without two detections or any signal.
+    pub fn acquire(samples: &[f32], detected: &[usize], sample_rate: u32, params: &SyncParams) -> Option<Self> {
+        let summary = interval_summary(detected, sample_rate)?;
+
+        let nominal = (params.expected_line_ms / 1000.0 * sample_rate as f32) as f64;
+        // Start from the detected cadence when it is plausibly the line cadence.
+        // When it is not — weak-sync lines get swallowed by the detector's
+        // min-spacing dedup, doubling the apparent median — trust the nominal
+        // period and let per-line locking prove itself (the lock-fraction guard
+        // in the caller rejects the result if it can't).
+        let period = if (summary.median_samples - nominal).abs() / nominal < 0.3 {
+            summary.median_samples
+        } else {
+            nominal
+        };
+
+        let robust_max = percentile_abs(samples, 0.999);
+        if robust_max <= 0.0 {
+            return None;
+        }
+
+        // Seed at the first detected sync that starts a coherent pair.
+        let seed = detected
+            .windows(2)
+            .find(|w| ((w[1] - w[0]) as f64 - period).abs() / period < 0.1)
+            .map(|w| w[0])
+            .unwrap_or(detected[0]);
+
+        let edge_search = ((period * TRACK_EDGE_FRAC) as usize).max(4);
+        Some(Self {
+            seed,
+            start: refine_line_start(samples, seed, edge_search),
+            period,
+            threshold: robust_max * params.peak_height,
+            search: ((period * TRACK_SEARCH_FRAC) as usize).max(2),
+            edge_search,
+        })
+    }
+
+    /// Find the line start after the one at `pos` (both in `samples`),
+    /// adapting `period` on a re-anchor. `None` until `samples` holds a
+    /// whole period past the prediction.
+    pub fn step(&self, samples: &[f32], pos: f64, period: &mut f64) -> Option<TrackStep> {
+        let predict = pos + *period;
+        if predict + *period > samples.len() as f64 {
+            return None;
         }
         let center = predict.round() as usize;
-        let lo = center.saturating_sub(search);
-        let hi = (center + search).min(samples.len() - 1);
+        let lo = center.saturating_sub(self.search);
+        let hi = (center + self.search).min(samples.len() - 1);
 
         // Always take the window maximum as the spike candidate — real sync
         // spikes persist even in dark lines, just attenuated, and the tight
@@ -179,35 +593,36 @@ pub fn track_line_syncs(samples: &[f32], sample_rate: u32, params: &SyncParams)
             .expect("window is non-empty");
 
         // Falling edge after the peak marks the true line start.
-        let min_idx = falling_edge_min(samples, peak, edge_search);
+        let min_idx = falling_edge_min(samples, peak, self.edge_search);
         // Re-anchor only on a sync-like event: a genuine spike-to-dip swing
         // (attenuated syncs in dark lines still swing harder than content
         // noise) at a plausible interval. Anchoring on anything weaker
         // randomizes line starts in dark regions; coasting there is what
         // keeps the image coherent.
         let swing = samples[peak] - samples[min_idx];
-        let interval = min_idx as f64 - pos;
-        let next = if swing > threshold * SWING_ANCHOR_FACTOR && (interval - period).abs() / period < TRACK_INTERVAL_TOL {
-            period = period * (1.0 - PERIOD_EMA_ALPHA) + interval * PERIOD_EMA_ALPHA;
-            if swing > threshold {
-                locked += 1;
-            }
-            min_idx as f64
-        } else {
+        let next = refine_line_start(samples, min_idx, self.edge_search);
+        let interval = next - pos;
+        let anchored = swing > self.threshold * SWING_ANCHOR_FACTOR && (interval - *period).abs() / *period < TRACK_INTERVAL_TOL;
+        let ratio = swing_ratio(swing, self.threshold);
+        if !anchored {
             // No credible sync (dark line, dropout, boundary junk): coast.
-            predict
-        };
-        positions.push(next.round() as usize);
-        pos = next;
-    }
-
-    // A track that mostly coasted never found real line structure (music,
-    // noise): hand back the raw detections so the caller's cadence check
-    // can reject sync lock and fall through to fixed-period slicing.
-    if locked < positions.len() / 4 {
-        return detected;
+            return Some(TrackStep {
+                next: predict,
+                state: SyncState::Coasted,
+                swing: ratio,
+            });
+        }
+        *period = *period * (1.0 - PERIOD_EMA_ALPHA) + interval * PERIOD_EMA_ALPHA;
+        Some(TrackStep {
+            next,
+            state: if swing > self.threshold {
+                SyncState::Locked
+            } else {
+                SyncState::Weak
+            },
+            swing: ratio,
+        })
     }
-    positions
 }
 
 /// Summary of intervals between consecutive sync positions.
@@ -311,6 +726,38 @@ mod tests {
         assert!((summary.median_ms - 8.33).abs() < 0.1);
     }
 
+    #[test]
+    fn refined_line_starts_follow_the_edge_between_samples() {
+        // Content, a spike from sample 14, and an area-sampled step down to
+        // the dip at `edge`
+        let line = |edge: f64| -> Vec<f32> {
+            (0..40)
+                .map(|k| {
+                    let k = k as f64;
+                    if k < 14.0 || k >= edge + 6.0 {
+                        0.3
+                    } else {
+                        (-0.8 + 1.8 * (edge - k).clamp(0.0, 1.0)) as f32
+                    }
+                })
+                .collect()
+        };
+        // A one-sample edge refines to exactly where the integer search puts it
+        let sharp = line(20.0);
+        assert_eq!(refine_line_start(&sharp, 20, 10), 20.0);
+
+        let mut previous = f64::MIN;
+        for tenth in 0..10 {
+            let edge = 20.0 + tenth as f64 / 10.0;
+            let samples = line(edge);
+            let bottom = falling_edge_min(&samples, 15, 10);
+            let refined = refine_line_start(&samples, bottom, 10);
+            assert!((refined - edge).abs() < 0.15, "edge {edge}: refined {refined}");
+            assert!(refined > previous, "edge {edge}: refined {refined} not past {previous}");
+            previous = refined;
+        }
+    }
+
     #[test]
     fn line_start_is_after_peak() {
         let samples = synthetic_lines(10, 400);
@@ -381,6 +828,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn tracker_flags_coasted_lines_as_unanchored() {
+        let period = 400usize;
+        let mut samples = synthetic_lines(60, period);
+        // Flatten lines 30..40: no sync events to anchor on.
+        samples[30 * period..40 * period].fill(0.2);
+
+        let tracked = track_line_syncs_with_lock(&samples, 48_000, &SyncParams::default());
+        assert_eq!(tracked.positions.len(), tracked.states.len());
+        for (i, &p) in tracked.positions.iter().enumerate() {
+            let anchored = tracked.anchored(i);
+            let line = p / period;
+            if (31..40).contains(&line) {
+                assert!(!anchored, "line {line} anchored inside the dropout");
+            } else if !(29..=41).contains(&line) {
+                assert!(anchored, "line {line} coasted on a clean sync");
+            }
+        }
+    }
+
     #[test]
     fn tracker_matches_detector_on_clean_signal() {
         let samples = synthetic_lines(50, 400);
@@ -390,6 +857,76 @@ mod tests {
         assert!(summary.std_samples < 3.0, "jitter {}", summary.std_samples);
     }
 
+    #[test]
+    fn track_reports_states_swings_and_fallbacks() {
+        let period = 400usize;
+        let mut samples = synthetic_lines(60, period);
+        samples[30 * period..40 * period].fill(0.2);
+
+        let track = track_line_syncs_with_lock(&samples, 48_000, &SyncParams::default());
+        assert_eq!(track.fallback, None);
+        assert_eq!(track.states.len(), track.positions.len());
+        assert_eq!(track.swings.len(), track.positions.len());
+        assert_eq!(track.periods.len(), track.positions.len());
+        let tally = track.tally();
+        let count = |state: SyncState| tally.iter().find(|(s, _)| *s == state).unwrap().1;
+        assert!(count(SyncState::Coasted) >= 9, "{tally:?}");
+        assert!(count(SyncState::Locked) >= 45, "{tally:?}");
+        for (i, &p) in track.positions.iter().enumerate() {
+            if track.states[i] == SyncState::Locked {
+                assert!(track.swings[i] > 1.0, "locked sync at {p} swings {}", track.swings[i]);
+            }
+            assert!((track.periods[i] - period as f64).abs() < 4.0);
+        }
+        // A line is as weak as its weaker end
+        let first_coasted = track.states.iter().position(|&s| s == SyncState::Coasted).unwrap();
+        assert_eq!(track.line(first_coasted - 1).state, SyncState::Coasted);
+        assert!(!track.line(first_coasted - 1).is_locked());
+
+        let silent = track_line_syncs_with_lock(&vec![0.0; 48_000], 48_000, &SyncParams::default());
+        assert_eq!(silent.fallback, Some(TrackFallback::NoSeed));
+    }
+
+    #[test]
+    fn block_tracks_keep_owned_positions() {
+        let samples = synthetic_lines(100, 400);
+        let params = SyncParams::default();
+        let block = Block {
+            offset: 10_000,
+            owned: 10_800..30_000,
+            samples: samples.clone(),
+        };
+        let track = track_line_syncs_block(&block, 48_000, &params);
+        let whole = track_line_syncs_with_lock(&samples, 48_000, &params);
+        let expected: Vec<usize> = whole
+            .positions
+            .iter()
+            .map(|p| p + 10_000)
+            .filter(|p| (10_800..30_000).contains(p))
+            .collect();
+        assert_eq!(track.positions, expected);
+        assert_eq!(track.states.len(), expected.len());
+        assert_eq!(track.periods.len(), expected.len());
+    }
+
+    #[test]
+    fn block_scan_matches_whole_buffer_detection() {
+        use crate::audio::WavReader;
+
+        let samples: std::sync::Arc<[f32]> = synthetic_lines(400, 400).into();
+        let reader = WavReader {
+            channel_data: vec![samples.clone()],
+            sample_rate: 48_000,
+            channels: 1,
+            format: Default::default(),
+        };
+        let params = SyncParams::default();
+        let whole = detect_line_syncs(&samples[1000..], 48_000, &params);
+        // Blocks of ~7 lines, so most syncs sit near some block boundary.
+        let blocked = detect_line_syncs_blocked(&reader, WaveformChannel::Left, 1000..samples.len(), &params, 2_777).unwrap();
+        assert_eq!(blocked, whole);
+    }
+
     #[test]
     fn silence_yields_no_syncs() {
         let samples = vec![0.0f32; 48_000];
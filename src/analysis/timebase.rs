@@ -0,0 +1,521 @@
+//! Time-base correction: wow and flutter taken out of the audio before it
+//! is sliced into lines.
+//!
+//! Every scan line of the record lasted the same time when it was cut, so
+//! the tracked sync positions are timing marks that sample the transfer's
+//! speed at the line rate. The predictive tracker
+//! ([`super::sync::track_line_syncs_with_lock`]) follows the period only
+//! through a slow average: lines it coasts over land where that average
+//! predicts, not where the wandering speed put them, and every anchored
+//! line start carries the detector's own jitter. [`fit_time_base`] fits a
+//! smooth position curve through the anchored syncs, [`TimeBase::resample`]
+//! replays the audio along it so every line lasts exactly one period, and
+//! [`TimeBase::flutter`] reports the spectrum of the speed variation it
+//! removed.
+
+use realfft::RealFftPlanner;
+
+use super::sync::{interval_summary, track_line_syncs_with_lock, SyncParams, SyncTrack};
+
+/// Savitzky-Golay half-width in lines for smoothing sync positions
+/// (quadratic, 7 lines): halves the integer-sample jitter of the detector.
+/// At the record's ~120 Hz line rate wow passes whole; flutter near 10 Hz
+/// comes through at about half its depth.
+const SMOOTH_HALF_WIDTH: usize = 3;
+/// Quadratic smoothing weights over the 7-line window, and the matching
+/// first-derivative weights; each set over its denominator.
+const SMOOTH_WEIGHTS: ([f64; 7], f64) = ([-2.0, 3.0, 6.0, 7.0, 6.0, 3.0, -2.0], 21.0);
+const SLOPE_WEIGHTS: ([f64; 7], f64) = ([-3.0, -2.0, -1.0, 0.0, 1.0, 2.0, 3.0], 28.0);
+/// Intervals further than this fraction from the median period are breaks
+/// (frame gaps, seams between separately tracked blocks), not speed variation:
+/// left uncorrected, and the curve is fitted on either side separately.
+/// Real wow and flutter stay well under a percent.
+const BREAK_TOLERANCE: f64 = 0.03;
+/// Fewest tracked lines worth fitting.
+const MIN_LINES: usize = 2 * SMOOTH_HALF_WIDTH + 2;
+/// Speed variation below this rate is wow, above it flutter.
+pub const WOW_FLUTTER_SPLIT_HZ: f64 = 4.0;
+
+/// A fitted mapping between corrected and source sample positions.
+#[derive(Debug, Clone)]
+pub struct TimeBase {
+    /// One knot per tracked line start, from the first anchored one to the
+    /// last.
+    knots: Vec<Knot>,
+    /// Track index of the first knot.
+    first_line: usize,
+    /// Line period of the corrected audio in samples (the mean steady source
+    /// period, so the overall speed is left to the speed factor).
+    period: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Knot {
+    /// Position in the corrected audio.
+    out: f64,
+    /// Smoothed position in the source.
+    src: f64,
+    /// Source samples per line here: the local speed times the period.
+    slope: f64,
+    /// Whether the interval to the next knot is one line of steady cadence
+    /// (corrected to one period) rather than a break (kept as is).
+    smooth: bool,
+}
+
+/// Fit a time base to a sync track. `None` when the track fell back to raw
+/// detections or is too short to fit.
+pub fn fit_time_base(track: &SyncTrack) -> Option<TimeBase> {
+    let n = track.positions.len();
+    if track.fallback.is_some() || n < MIN_LINES {
+        return None;
+    }
+    let anchored: Vec<usize> = (0..n).filter(|&i| track.anchored(i)).collect();
+    if anchored.len() < MIN_LINES {
+        return None;
+    }
+
+    // Coasted positions are only predictions: replace them by linear
+    // interpolation between the anchored ones on either side.
+    let mut positions = track.starts.clone();
+    for pair in anchored.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        for i in a + 1..b {
+            let t = (i - a) as f64 / (b - a) as f64;
+            positions[i] = positions[a] + (positions[b] - positions[a]) * t;
+        }
+    }
+    let (first, last) = (anchored[0], *anchored.last().unwrap());
+    let positions = &positions[first..=last];
+    let n = positions.len();
+
+    let rounded: Vec<usize> = positions.iter().map(|p| p.round() as usize).collect();
+    let median = interval_summary(&rounded, 1)?.median_samples;
+    let smooth: Vec<bool> = positions
+        .windows(2)
+        .map(|w| ((w[1] - w[0]) - median).abs() <= median * BREAK_TOLERANCE)
+        .collect();
+    // The median of integer intervals is off by up to half a sample; the
+    // mean over the steady lines keeps the fraction
+    let steady: Vec<f64> = positions
+        .windows(2)
+        .zip(&smooth)
+        .filter(|(_, &s)| s)
+        .map(|(w, _)| w[1] - w[0])
+        .collect();
+    if steady.is_empty() {
+        return None;
+    }
+    let period = steady.iter().sum::<f64>() / steady.len() as f64;
+
+    // Smooth within runs of steady cadence; near a run's ends fall back to
+    // the raw position and the mean of the neighbouring intervals.
+    let steady_around = |i: usize| {
+        i >= SMOOTH_HALF_WIDTH
+            && i + SMOOTH_HALF_WIDTH < n
+            && smooth[i - SMOOTH_HALF_WIDTH..i + SMOOTH_HALF_WIDTH].iter().all(|&s| s)
+    };
+    let filter = |i: usize, (weights, norm): ([f64; 7], f64)| {
+        weights
+            .iter()
+            .enumerate()
+            .map(|(k, w)| w * positions[i + k - SMOOTH_HALF_WIDTH])
+            .sum::<f64>()
+            / norm
+    };
+    let mut knots = Vec::with_capacity(n);
+    let mut out = positions[0];
+    for i in 0..n {
+        let (src, slope) = if steady_around(i) {
+            (filter(i, SMOOTH_WEIGHTS), filter(i, SLOPE_WEIGHTS))
+        } else {
+            let neighbours: Vec<f64> = [i.checked_sub(1), (i + 1 < n).then_some(i)]
+                .into_iter()
+                .flatten()
+                .filter(|&k| smooth[k])
+                .map(|k| positions[k + 1] - positions[k])
+                .collect();
+            let slope = if neighbours.is_empty() {
+                period
+            } else {
+                neighbours.iter().sum::<f64>() / neighbours.len() as f64
+            };
+            (positions[i], slope)
+        };
+        let steady = i + 1 < n && smooth[i];
+        knots.push(Knot {
+            out,
+            src,
+            slope,
+            smooth: steady,
+        });
+        if i + 1 < n {
+            out += if steady { period } else { positions[i + 1] - positions[i] };
+        }
+    }
+
+    Some(TimeBase {
+        knots,
+        first_line: first,
+        period,
+    })
+}
+
+/// Track the syncs of `samples`, fit a time base and resample onto it.
+/// `None` when there is no usable track (see [`fit_time_base`]).
+pub fn correct_time_base(samples: &[f32], sample_rate: u32, params: &SyncParams) -> Option<(Vec<f32>, TimeBase)> {
+    let track = track_line_syncs_with_lock(samples, sample_rate, params);
+    let time_base = fit_time_base(&track)?;
+    Some((time_base.resample(samples), time_base))
+}
+
+impl TimeBase {
+    /// Line period of the corrected audio, in samples.
+    pub fn period(&self) -> f64 {
+        self.period
+    }
+
+    /// Relative speed at each tracked line start: source samples per line
+    /// over the corrected period (1.0 = steady).
+    pub fn speed(&self) -> Vec<f64> {
+        self.knots.iter().map(|k| k.slope / self.period).collect()
+    }
+
+    /// Source position of corrected position `pos`. Lines of steady cadence
+    /// follow a cubic Hermite curve through the smoothed sync positions and
+    /// slopes; breaks map linearly; outside the track (leader, trailing
+    /// coasted lines) the audio passes through at nominal speed.
+    pub fn to_source(&self, pos: f64) -> f64 {
+        let (first, last) = (self.knots[0], self.knots[self.knots.len() - 1]);
+        if pos <= first.out {
+            return first.src + (pos - first.out);
+        }
+        if pos >= last.out {
+            return last.src + (pos - last.out);
+        }
+        let k = self.knots.partition_point(|knot| knot.out <= pos) - 1;
+        self.map_within(k, pos)
+    }
+
+    /// Corrected position of source position `src`: the inverse of
+    /// [`Self::to_source`], for carrying sync positions over.
+    pub fn to_output(&self, src: f64) -> f64 {
+        let (first, last) = (self.knots[0], self.knots[self.knots.len() - 1]);
+        if src <= first.src {
+            return first.out + (src - first.src);
+        }
+        if src >= last.src {
+            return last.out + (src - last.src);
+        }
+        let k = self.knots.partition_point(|knot| knot.src <= src) - 1;
+        let (a, b) = (self.knots[k], self.knots[k + 1]);
+        // Newton from the linear guess; the curve is nearly a line
+        let mut t = (src - a.src) / (b.src - a.src);
+        if a.smooth {
+            for _ in 0..4 {
+                let (x, dx) = hermite(a, b, t);
+                t = (t - (x - src) / dx.max(f64::EPSILON)).clamp(0.0, 1.0);
+            }
+        }
+        a.out + (b.out - a.out) * t
+    }
+
+    fn map_within(&self, k: usize, pos: f64) -> f64 {
+        let (a, b) = (self.knots[k], self.knots[k + 1]);
+        let t = (pos - a.out) / (b.out - a.out);
+        if a.smooth {
+            hermite(a, b, t).0
+        } else {
+            a.src + (b.src - a.src) * t
+        }
+    }
+
+    /// `track` (the one this was fitted to) carried over to the corrected
+    /// audio. Lines the fit covers start on the fitted curve, coasted ones
+    /// included; positions outside it map through [`Self::to_output`].
+    pub fn carry_track(&self, track: &SyncTrack) -> SyncTrack {
+        let starts: Vec<f64> = (track.starts.iter().enumerate())
+            .map(
+                |(i, &p)| match i.checked_sub(self.first_line).and_then(|k| self.knots.get(k)) {
+                    Some(knot) => knot.out,
+                    None => self.to_output(p).max(0.0),
+                },
+            )
+            .collect();
+        SyncTrack {
+            positions: starts.iter().map(|p| p.round() as usize).collect(),
+            starts,
+            ..track.clone()
+        }
+    }
+
+    /// Replay `samples` (the source this was fitted to) on the corrected
+    /// time base: sample `j` of the result is the source read at
+    /// [`Self::to_source`]`(j)`, Catmull-Rom interpolated.
+    pub fn resample(&self, samples: &[f32]) -> Vec<f32> {
+        let end = samples.len() as f64 - 1.0;
+        let mut out = Vec::with_capacity(samples.len());
+        let mut k = 0usize;
+        loop {
+            let pos = out.len() as f64;
+            while k + 1 < self.knots.len() && self.knots[k + 1].out <= pos {
+                k += 1;
+            }
+            let inside = pos >= self.knots[0].out && k + 1 < self.knots.len();
+            let x = if inside {
+                self.map_within(k, pos)
+            } else {
+                self.to_source(pos)
+            };
+            if x > end {
+                break;
+            }
+            out.push(catmull_rom(samples, x.max(0.0)));
+        }
+        out
+    }
+
+    /// Spectrum of the speed variation at the line rate, for a source at
+    /// `sample_rate`.
+    pub fn flutter(&self, sample_rate: u32) -> WowFlutter {
+        let line_rate = sample_rate as f64 / self.period;
+        let speed = self.speed();
+        let n = speed.len();
+        let mean = speed.iter().sum::<f64>() / n as f64;
+
+        let window: Vec<f64> = (0..n)
+            .map(|i| 0.5 - 0.5 * (std::f64::consts::TAU * i as f64 / (n - 1).max(1) as f64).cos())
+            .collect();
+        let size = n.next_power_of_two() * 2;
+        let fft = RealFftPlanner::<f64>::new().plan_fft_forward(size);
+        let mut input = fft.make_input_vec();
+        for (dst, (s, w)) in input.iter_mut().zip(speed.iter().zip(&window)) {
+            *dst = (s - mean) * w;
+        }
+        let mut bins = fft.make_output_vec();
+        fft.process(&mut input, &mut bins).expect("FFT buffers sized by the plan");
+
+        let (sum_w, sum_w2) = (window.iter().sum::<f64>(), window.iter().map(|w| w * w).sum::<f64>());
+        let hz = |k: usize| k as f64 * line_rate / size as f64;
+        // Peak deviation for display; band power by Parseval over the window
+        let spectrum: Vec<(f64, f64)> = bins
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(k, c)| (hz(k), 2.0 * c.norm() / sum_w))
+            .collect();
+        let band_rms = |keep: &dyn Fn(f64) -> bool| {
+            let power: f64 = bins
+                .iter()
+                .enumerate()
+                .skip(1)
+                .filter(|&(k, _)| keep(hz(k)))
+                .map(|(_, c)| c.norm_sqr())
+                .sum();
+            (2.0 * power / (size as f64 * sum_w2)).sqrt()
+        };
+        let (peak_hz, peak) = spectrum
+            .iter()
+            .copied()
+            .fold((0.0, 0.0), |best, bin| if bin.1 > best.1 { bin } else { best });
+
+        WowFlutter {
+            line_rate_hz: line_rate,
+            wow_rms: band_rms(&|f| f < WOW_FLUTTER_SPLIT_HZ),
+            flutter_rms: band_rms(&|f| f >= WOW_FLUTTER_SPLIT_HZ),
+            peak_hz,
+            peak,
+            spectrum,
+        }
+    }
+}
+
+/// Source position between knots `a` and `b` at `t` in `[0, 1]`, and its
+/// derivative in `t`. One line per unit `t`, so the slopes are the tangents
+/// as they are.
+fn hermite(a: Knot, b: Knot, t: f64) -> (f64, f64) {
+    let (t2, t3) = (t * t, t * t * t);
+    let x = (2.0 * t3 - 3.0 * t2 + 1.0) * a.src
+        + (t3 - 2.0 * t2 + t) * a.slope
+        + (-2.0 * t3 + 3.0 * t2) * b.src
+        + (t3 - t2) * b.slope;
+    let dx = (6.0 * t2 - 6.0 * t) * (a.src - b.src) + (3.0 * t2 - 4.0 * t + 1.0) * a.slope + (3.0 * t2 - 2.0 * t) * b.slope;
+    (x, dx)
+}
+
+/// Catmull-Rom interpolation of `samples` at fractional index `x`, edges
+/// clamped.
+fn catmull_rom(samples: &[f32], x: f64) -> f32 {
+    let i = x.floor() as usize;
+    let t = (x - i as f64) as f32;
+    let at = |k: isize| samples[(i as isize + k).clamp(0, samples.len() as isize - 1) as usize];
+    let (p0, p1, p2, p3) = (at(-1), at(0), at(1), at(2));
+    p1 + 0.5 * t * (p2 - p0 + t * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3 + t * (3.0 * (p1 - p2) + p3 - p0)))
+}
+
+/// Speed variation found by [`TimeBase::flutter`], as fractions of the
+/// nominal speed (0.001 = 0.1%).
+#[derive(Debug, Clone)]
+pub struct WowFlutter {
+    /// Rate the speed is sampled at: one value per line.
+    pub line_rate_hz: f64,
+    /// RMS deviation below [`WOW_FLUTTER_SPLIT_HZ`].
+    pub wow_rms: f64,
+    /// RMS deviation from [`WOW_FLUTTER_SPLIT_HZ`] up to half the line rate.
+    pub flutter_rms: f64,
+    /// Strongest component and its peak deviation.
+    pub peak_hz: f64,
+    pub peak: f64,
+    /// `(frequency Hz, peak deviation)` per bin, DC excluded.
+    pub spectrum: Vec<(f64, f64)>,
+}
+
+impl std::fmt::Display for WowFlutter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "wow {:.3}% rms, flutter {:.3}% rms, strongest {:.3}% at {:.2} Hz",
+            self.wow_rms * 100.0,
+            self.flutter_rms * 100.0,
+            self.peak * 100.0,
+            self.peak_hz
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::sync::SyncState;
+    use crate::test_fixtures::{degrade, encode_image_to_audio, textured_plane, TapeDegradation};
+
+    const RATE: u32 = 48_000;
+    const LINE_MS: f32 = 8.32;
+
+    fn sync_params() -> SyncParams {
+        SyncParams {
+            expected_line_ms: LINE_MS,
+            ..SyncParams::default()
+        }
+    }
+
+    /// A 400-line textured picture played with sinusoidal wow and flutter.
+    fn wobbly(wow: f32, flutter: f32) -> Vec<f32> {
+        let image = textured_plane(128, 400, |x, y| (x, y));
+        let clean = encode_image_to_audio(&image, 128, RATE, LINE_MS);
+        let tape = TapeDegradation {
+            seed: 5,
+            wow_depth: wow,
+            wow_hz: 1.5,
+            flutter_depth: flutter,
+            flutter_hz: 9.0,
+            ..Default::default()
+        };
+        degrade(&clean, RATE, &tape)
+    }
+
+    /// Spread of the line intervals in samples, timed at the midpoint
+    /// crossing of each sync's falling edge: the tracker's line starts sit
+    /// anywhere on the flat dip after it.
+    fn edge_interval_spread(samples: &[f32]) -> f64 {
+        let track = track_line_syncs_with_lock(samples, RATE, &sync_params());
+        let edges: Vec<f64> = track
+            .positions
+            .iter()
+            .filter_map(|&p| {
+                let j = (1..=p).rev().take(40).find(|&j| samples[j - 1] >= 0.1)?;
+                let (a, b) = (samples[j - 1], samples[j]);
+                Some(j as f64 - 1.0 + (a - 0.1) as f64 / (a - b) as f64)
+            })
+            .collect();
+        let intervals: Vec<f64> = edges.windows(2).map(|w| w[1] - w[0]).collect();
+        let mean = intervals.iter().sum::<f64>() / intervals.len() as f64;
+        (intervals.iter().map(|i| (i - mean).powi(2)).sum::<f64>() / intervals.len() as f64).sqrt()
+    }
+
+    fn locked_track(positions: Vec<usize>) -> SyncTrack {
+        let n = positions.len();
+        SyncTrack {
+            starts: positions.iter().map(|&p| p as f64).collect(),
+            positions,
+            states: vec![SyncState::Locked; n],
+            swings: vec![1.0; n],
+            periods: vec![400.0; n],
+            fallback: None,
+        }
+    }
+
+    #[test]
+    fn steady_cadence_fits_a_flat_speed_and_resamples_unchanged() {
+        let audio = wobbly(0.0, 0.0);
+        let track = track_line_syncs_with_lock(&audio, RATE, &sync_params());
+        let time_base = fit_time_base(&track).expect("fit");
+        assert!(time_base.speed().iter().all(|s| (s - 1.0).abs() < 0.002));
+        let corrected = time_base.resample(&audio);
+        assert!(corrected.len().abs_diff(audio.len()) < 400);
+        let flutter = time_base.flutter(RATE);
+        assert!(flutter.wow_rms < 0.000_5 && flutter.flutter_rms < 0.001, "{flutter}");
+    }
+
+    #[test]
+    fn resampling_evens_out_line_intervals_under_wow_and_flutter() {
+        let audio = wobbly(0.01, 0.004);
+        let (corrected, time_base) = correct_time_base(&audio, RATE, &sync_params()).expect("fit");
+        let (before, after) = (edge_interval_spread(&audio), edge_interval_spread(&corrected));
+        assert!(after < before / 3.0, "interval spread {before:.2} -> {after:.2} samples");
+
+        // The spectrum finds both tones at about their depth
+        let flutter = time_base.flutter(RATE);
+        let near = |hz: f64| {
+            flutter
+                .spectrum
+                .iter()
+                .filter(|(f, _)| (f - hz).abs() < 0.6)
+                .map(|&(_, a)| a)
+                .fold(0.0, f64::max)
+        };
+        assert!((near(1.5) - 0.01).abs() < 0.003, "wow peak {:.4} ({flutter})", near(1.5));
+        assert!(near(9.0) > 0.001, "flutter peak {:.4} ({flutter})", near(9.0));
+        assert!(flutter.wow_rms > flutter.flutter_rms, "{flutter}");
+        assert!((flutter.peak_hz - 1.5).abs() < 0.6, "{flutter}");
+    }
+
+    #[test]
+    fn frame_gaps_are_kept_and_map_back_to_the_source() {
+        // Two runs of 400-sample lines, 1234 samples apart
+        let positions: Vec<usize> = (0..60).map(|i| 100 + i * 400 + if i >= 30 { 1_234 } else { 0 }).collect();
+        let time_base = fit_time_base(&locked_track(positions.clone())).expect("fit");
+        assert!((time_base.period() - 400.0).abs() < 1e-9);
+        assert!(time_base.speed().iter().all(|s| (s - 1.0).abs() < 1e-9));
+        for pos in [
+            0.0,
+            150.0,
+            positions[29] as f64 + 700.0,
+            positions[45] as f64 + 33.0,
+            29_000.0,
+        ] {
+            assert!(
+                (time_base.to_source(pos) - pos).abs() < 1e-6,
+                "{pos} -> {}",
+                time_base.to_source(pos)
+            );
+        }
+    }
+
+    #[test]
+    fn to_output_inverts_to_source() {
+        let audio = wobbly(0.01, 0.004);
+        let track = track_line_syncs_with_lock(&audio, RATE, &sync_params());
+        let time_base = fit_time_base(&track).expect("fit");
+        for pos in (0..audio.len()).step_by(997) {
+            let src = time_base.to_source(pos as f64);
+            assert!((time_base.to_output(src) - pos as f64).abs() < 1e-6, "{pos} -> {src}");
+        }
+    }
+
+    #[test]
+    fn unusable_tracks_do_not_fit() {
+        let silence = vec![0.0f32; RATE as usize];
+        assert!(correct_time_base(&silence, RATE, &sync_params()).is_none());
+        assert!(fit_time_base(&SyncTrack::default()).is_none());
+    }
+}
@@ -8,32 +8,52 @@ use rodio::mixer::Mixer;
 #[cfg(feature = "audio_playback")]
 use rodio::{OutputStream, OutputStreamBuilder, Sink};
 
-use crate::audio::{WavReader, WaveformChannel};
+use crate::analysis::{estimate_speed, LineSync, SpeedEstimate, SpeedParams};
+use crate::audio::WaveformChannel;
 #[cfg(feature = "audio_playback")]
 use crate::audio_state::AudioError;
 use crate::audio_state::AudioPlaybackState;
+use crate::backend::{self, Decoder, Param};
+use crate::codec;
 use crate::config::AppConfig;
 use crate::error::VoyagerError;
+use crate::fm_sstv::SstvMode;
 use crate::metrics::AppMetrics;
-use crate::pipeline::PipelineResult;
+use crate::pipeline::{DecodingPipeline, PipelineResult, RecordFrames};
+use crate::preset::{Preset, DEFAULT_PRESET};
+use crate::project::{params_for_frame, Bookmark, FrameOverride, Project};
+use crate::sample_source::{open_source, SampleSource};
 #[cfg(feature = "audio_playback")]
-use crate::services::audio::AudioBufferSource;
+use crate::services::audio::{AudioBufferSource, ChunkedSource};
 use crate::services::batch::{BatchProgressMsg, BatchRunner};
-use crate::services::decoder::{DecodeOrchestrator, DecodeResult};
+use crate::services::decoder::{DecodeOrchestrator, DecodeResult, LiveDecodeSession};
 use crate::sstv::{DecoderMode, DecoderParams, SstvDecoder};
+use crate::streaming::LiveImage;
 use crate::ui::batch::BatchPanel;
+use crate::ui::catalog::CatalogPanel;
+use crate::ui::compare::{CompareInput, ComparePanel};
 use crate::ui::controls::{ControlAction, ControlsPanel};
+use crate::ui::gutter;
+use crate::ui::image_view::{self, ImageView};
 use crate::ui::spectrum::SpectrumPanel;
 use crate::ui::theme;
 use crate::ui::waveform::WaveformPanel;
-use crate::utils::format_duration;
+use crate::utils::{format_duration, format_timecode};
+
+/// How far ahead "next sync" scans a streamed file when the cached markers
+/// have not arrived yet, in seconds.
+const NEXT_SYNC_LOOKAHEAD_SECS: f64 = 30.0;
+
+/// Scan lines kept in the image grown by streaming live decode; older lines
+/// scroll off the top.
+const LIVE_IMAGE_LINES: usize = 1024;
 
 pub struct VoyagerApp {
     // Configuration
     config: AppConfig,
 
-    // Audio data
-    wav_reader: Option<WavReader>,
+    // Audio data: resident or streamed from disk, per `config.input`
+    audio_source: Option<Arc<dyn SampleSource>>,
     video_decoder: SstvDecoder,
     image_texture: Option<TextureHandle>,
     params: DecoderParams,
@@ -46,6 +66,34 @@ pub struct VoyagerApp {
     /// take seconds on real Golden Record audio, so it must not block the UI;
     /// replacing the receiver cancels delivery from a stale scan.
     sync_scan_rx: Option<std::sync::mpsc::Receiver<Vec<usize>>>,
+    /// Channel segmented into image frames for frame-decoding backends
+    /// (frame triplet).
+    /// A full pass over the channel, so it runs in the background and only
+    /// while that mode is selected (on load, channel switch or mode switch).
+    record_frames: Option<Arc<RecordFrames>>,
+    record_frames_rx: Option<std::sync::mpsc::Receiver<Option<RecordFrames>>>,
+    /// Playback-speed estimate for the loaded file, measured in the
+    /// background on load (`decoder.auto_speed`) and applied to
+    /// `params.speed_factor` when it arrives.
+    speed_estimate: Option<SpeedEstimate>,
+    speed_rx: Option<std::sync::mpsc::Receiver<Option<SpeedEstimate>>>,
+    /// Name of the preset last chosen in the controls panel, which is marked
+    /// modified once `params` drift from it. Also the settings the batch
+    /// runner decodes with.
+    selected_preset: String,
+
+    // Session (saved to and restored from project files)
+    /// Path of the loaded audio file
+    wav_path: Option<std::path::PathBuf>,
+    /// Project file last opened or saved, offered again on the next save
+    project_path: Option<std::path::PathBuf>,
+    /// Bookmarks, in position order
+    bookmarks: Vec<Bookmark>,
+    /// Per-frame setting overrides for frame-triplet decodes
+    frame_overrides: Vec<FrameOverride>,
+    /// (generation, frame) of the last frame-triplet live decode, so playback
+    /// does not re-decode the same three frames every interval.
+    last_frame_request: Option<(u64, usize)>,
 
     // Audio playback state
     audio_state: AudioPlaybackState,
@@ -74,6 +122,11 @@ pub struct VoyagerApp {
     /// Last decode error surfaced, to avoid re-raising the identical error
     /// every live-decode interval (which would defeat the dismiss button).
     last_decode_error: Option<String>,
+    /// Streaming live decode for backends with `capabilities.streaming`,
+    /// and the image it grows during playback. Restarted on a seek, input
+    /// or settings change.
+    live_session: Option<LiveDecodeSession>,
+    live_image: Option<LiveImage>,
 
     // Metrics and errors
     metrics: AppMetrics,
@@ -83,10 +136,17 @@ pub struct VoyagerApp {
     // Signal Analysis
     spectrum_panel: SpectrumPanel,
     waveform_panel: WaveformPanel,
+    image_view: ImageView,
 
     // Batch Processing
     batch_panel: BatchPanel,
     batch_runner: BatchRunner,
+
+    // Catalog browser
+    catalog_panel: CatalogPanel,
+
+    // Reference comparison
+    compare_panel: ComparePanel,
 }
 
 impl Default for VoyagerApp {
@@ -115,7 +175,7 @@ impl Default for VoyagerApp {
 
         Self {
             config: config.clone(),
-            wav_reader: None,
+            audio_source: None,
             video_decoder: SstvDecoder::new(),
             image_texture: None,
             params,
@@ -123,6 +183,16 @@ impl Default for VoyagerApp {
             selected_channel: WaveformChannel::Left,
             sync_positions: Vec::new(),
             sync_scan_rx: None,
+            record_frames: None,
+            record_frames_rx: None,
+            speed_estimate: None,
+            speed_rx: None,
+            selected_preset: DEFAULT_PRESET.to_string(),
+            wav_path: None,
+            project_path: None,
+            bookmarks: Vec::new(),
+            frame_overrides: Vec::new(),
+            last_frame_request: None,
             audio_state: AudioPlaybackState::Uninitialized,
             #[cfg(feature = "audio_playback")]
             audio_stream: None,
@@ -140,31 +210,49 @@ impl Default for VoyagerApp {
             decode_worker: DecodeOrchestrator::new(),
             decode_generation: 0,
             last_decode_error: None,
+            live_session: None,
+            live_image: None,
             metrics: AppMetrics::new(),
             error_message: None,
             frame_start: None,
             spectrum_panel: SpectrumPanel::default(),
             waveform_panel: WaveformPanel::default(),
+            image_view: ImageView::default(),
             batch_panel: BatchPanel::default(),
             batch_runner: BatchRunner::default(),
+            catalog_panel: CatalogPanel::default(),
+            compare_panel: ComparePanel::default(),
         }
     }
 }
 
 impl VoyagerApp {
     fn handle_load_wav(&mut self) {
-        if let Some(path) = rfd::FileDialog::new().add_filter("WAV", &["wav"]).pick_file() {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Audio", codec::AUDIO_EXTENSIONS)
+            .pick_file()
+        {
             self.load_wav_from_path(&path);
         }
     }
 
-    /// Load a WAV file directly by path (shared by the file dialog and the
-    /// `--load` startup flag).
-    pub fn load_wav_from_path(&mut self, path: &std::path::Path) {
-        match WavReader::from_file(path) {
-            Ok(reader) => {
-                tracing::info!(path = %path.display(), "WAV file loaded successfully");
-                self.wav_reader = Some(reader);
+    /// Load a WAV file directly by path (shared by the file dialog, project
+    /// open and the `--load` startup flag). Starts a fresh session: bookmarks
+    /// and frame overrides belong to the previous file. Returns whether the
+    /// file loaded.
+    pub fn load_wav_from_path(&mut self, path: &std::path::Path) -> bool {
+        match open_source(path, self.config.input.resident_limit_bytes()) {
+            Ok(source) => {
+                tracing::info!(path = %path.display(), "Audio file loaded successfully");
+                // A channel picked for a wider file may not exist in this one
+                if !self.selected_channel.is_available(source.channels()) {
+                    self.selected_channel = WaveformChannel::Left;
+                }
+                self.audio_source = Some(Arc::clone(&source));
+                self.wav_path = Some(path.to_path_buf());
+                self.project_path = None;
+                self.bookmarks.clear();
+                self.frame_overrides.clear();
                 self.image_texture = None;
                 self.last_decoded = None;
                 // In-flight worker results now belong to the previous input
@@ -173,6 +261,10 @@ impl VoyagerApp {
                 // Pointer-keyed caches must not survive a buffer swap (ABA)
                 self.waveform_panel.invalidate();
                 self.spectrum_panel.invalidate();
+                self.catalog_panel.invalidate();
+                self.compare_panel.invalidate();
+                self.waveform_panel
+                    .build_envelope(source, path.to_path_buf(), self.config.ui.persist_waveform_envelope);
                 // Update audio state to Ready when WAV is loaded
                 self.audio_state = AudioPlaybackState::Ready;
                 // Clear any previous error and reset decode position
@@ -180,15 +272,22 @@ impl VoyagerApp {
                 self.last_decode_position = 0;
                 // Cache sync markers once per load (not per frame)
                 self.refresh_sync_positions();
+                self.refresh_speed_estimate();
+                self.refresh_record_frames();
+                true
             }
             Err(e) => {
-                tracing::error!(path = %path.display(), error = %e, "Failed to load WAV file");
+                tracing::error!(path = %path.display(), error = %e, "Failed to load audio file");
                 // Keep any previously loaded file fully usable — only the new
                 // load failed. Reset state only when nothing was loaded.
-                if self.wav_reader.is_none() {
+                if self.audio_source.is_none() {
                     self.audio_state = AudioPlaybackState::Uninitialized;
                     self.sync_positions.clear();
                     self.sync_scan_rx = None;
+                    self.record_frames = None;
+                    self.record_frames_rx = None;
+                    self.speed_estimate = None;
+                    self.speed_rx = None;
                 }
 
                 // Extract user-friendly error message
@@ -196,10 +295,139 @@ impl VoyagerApp {
                     VoyagerError::Audio(audio_err) => audio_err.user_message(),
                     _ => format!("Failed to load audio file: {}", e),
                 });
+                false
             }
         }
     }
 
+    fn handle_open_project(&mut self, ctx: &egui::Context) {
+        if let Some(path) = rfd::FileDialog::new().add_filter("Voyager project", &["toml"]).pick_file() {
+            self.open_project(ctx, &path);
+        }
+    }
+
+    /// Restore a saved session: load its audio, then its channel, settings,
+    /// frame bounds, bookmarks, overrides and playhead (shared by the file
+    /// dialog and the `--project` startup flag).
+    pub fn open_project(&mut self, ctx: &egui::Context, path: &std::path::Path) {
+        let project = match Project::load(path) {
+            Ok(project) => project,
+            Err(e) => {
+                tracing::error!(path = %path.display(), error = %e, "Failed to open project");
+                self.error_message = Some(format!("Could not open project: {e}"));
+                return;
+            }
+        };
+
+        // The scans started by the load (sync markers, speed estimate,
+        // segmentation) run on the project's channel, settings and preset
+        let previous = (self.selected_channel, self.params, self.selected_preset.clone());
+        self.selected_channel = project.channel;
+        self.params = project.params;
+        if !project.preset.is_empty() {
+            self.selected_preset = project.preset;
+        }
+        if !self.load_wav_from_path(&project.wav_path) {
+            (self.selected_channel, self.params, self.selected_preset) = previous;
+            return;
+        }
+        let Some(len) = self.audio_source.as_ref().map(|s| s.len()) else {
+            return;
+        };
+
+        // The project records the speed factor, which the load's speed
+        // estimate reset; keep it over a new estimate
+        self.params.speed_factor = project.params.speed_factor;
+        self.speed_rx = None;
+        self.speed_estimate = None;
+        // Saved bounds spare the full-channel segmentation pass, as long as
+        // they still fit the file
+        if !project.bounds.is_empty() && project.bounds.iter().all(|b| b.end_sample <= len) {
+            self.record_frames = Some(Arc::new(RecordFrames::from_bounds(project.channel, project.bounds)));
+            self.record_frames_rx = None;
+            self.last_frame_request = None;
+        } else {
+            self.refresh_record_frames();
+        }
+        self.bookmarks = project.bookmarks;
+        self.frame_overrides = project.overrides;
+        self.project_path = Some(path.to_path_buf());
+        tracing::info!(path = %path.display(), "Project opened");
+        self.seek_to(ctx, project.position_samples.min(len.saturating_sub(1)));
+    }
+
+    /// The current session as a project, `None` with no file loaded.
+    fn current_project(&self) -> Option<Project> {
+        let wav_path = self.wav_path.clone()?;
+        // Only bounds for the selected channel describe this session
+        let bounds = match &self.record_frames {
+            Some(frames) if frames.channel() == self.selected_channel => frames.bounds().to_vec(),
+            _ => Vec::new(),
+        };
+        Some(Project {
+            channel: self.selected_channel,
+            position_samples: self.current_position_samples,
+            preset: self.selected_preset.clone(),
+            params: self.params,
+            bounds,
+            bookmarks: self.bookmarks.clone(),
+            overrides: self.frame_overrides.clone(),
+            ..Project::new(wav_path)
+        })
+    }
+
+    /// Save the session via a save dialog, defaulting to the last project
+    /// path or `<stem>.voyager.toml` beside the audio.
+    fn handle_save_project(&mut self) {
+        let Some(project) = self.current_project() else {
+            self.error_message = Some("No file loaded to save a project for".to_string());
+            return;
+        };
+        let default_path = self
+            .project_path
+            .clone()
+            .unwrap_or_else(|| Project::default_path_for(&project.wav_path));
+        let mut dialog = rfd::FileDialog::new().add_filter("Voyager project", &["toml"]);
+        if let Some(dir) = default_path.parent() {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(name) = default_path.file_name() {
+            dialog = dialog.set_file_name(name.to_string_lossy());
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+
+        match project.save(&path) {
+            Ok(()) => {
+                tracing::info!(path = %path.display(), "Project saved");
+                self.project_path = Some(path);
+                self.error_message = None;
+            }
+            Err(e) => {
+                tracing::error!(path = %path.display(), error = %e, "Failed to save project");
+                self.error_message = Some(format!("Project save failed: {e}"));
+            }
+        }
+    }
+
+    /// Bookmark the playhead, keeping the list in position order.
+    fn add_bookmark(&mut self) {
+        let position = self.current_position_samples;
+        if self.bookmarks.iter().any(|b| b.position_samples == position) {
+            return;
+        }
+        let index = self.bookmarks.partition_point(|b| b.position_samples < position);
+        let label = format!("Bookmark {}", self.bookmarks.len() + 1);
+        self.bookmarks.insert(
+            index,
+            Bookmark {
+                position_samples: position,
+                label,
+            },
+        );
+    }
+
     /// Apply one batch-progress message to panel state.
     fn apply_batch_message(&mut self, msg: BatchProgressMsg, ctx: &egui::Context) {
         match msg {
@@ -222,34 +450,74 @@ impl VoyagerApp {
         }
     }
 
+    /// Decode on demand: with a frame-decoding backend the frame under the
+    /// playhead (with its color siblings), otherwise the whole selected
+    /// channel when it is resident or one window from the playhead when the
+    /// file is streamed or the backend decodes from the playhead (SSTV).
     fn handle_decode(&mut self, ctx: &egui::Context) {
-        if let Some(reader) = &self.wav_reader {
-            let samples = reader.get_samples(self.selected_channel);
-
-            // Clear any previous errors
-            self.error_message = None;
-
-            // Perform decode with error handling using unified pipeline
-            let pipeline = crate::pipeline::DecodingPipeline::new();
-            match pipeline.process(samples, &self.params, reader.sample_rate) {
-                Ok(result) => {
-                    tracing::info!(pixels = result.pixels.len(), "Decode completed successfully");
-                    let img = result.to_egui_image();
-                    self.image_texture = Some(ctx.load_texture("decoded", img, Default::default()));
-                    self.last_decoded = Some(result);
-                }
-                Err(e) => {
-                    tracing::error!(error = %e, "Decode failed");
-                    self.error_message = Some(format!("Decode failed: {}", e));
-                    self.image_texture = None;
-                    self.last_decoded = None;
-                }
+        let Some(source) = self.audio_source.clone() else {
+            self.error_message = Some("No audio file loaded".to_string());
+            return;
+        };
+
+        // Clear any previous errors
+        self.error_message = None;
+
+        // Perform decode with error handling using unified pipeline
+        let pipeline = DecodingPipeline::new();
+        let decoded = if self.backend().info().capabilities.frames {
+            match &self.record_frames {
+                Some(frames) => pipeline.process_frame_at(source.as_ref(), frames, self.current_position_samples, &self.params),
+                None => Err(anyhow::anyhow!(
+                    "Record is still being segmented into frames; try again shortly"
+                )),
             }
         } else {
-            self.error_message = Some("No audio file loaded".to_string());
+            self.decode_channel(&pipeline, source.as_ref())
+        };
+        match decoded {
+            Ok(result) => {
+                tracing::info!(pixels = result.pixels.len(), "Decode completed successfully");
+                let img = result.to_egui_image();
+                self.image_texture = Some(ctx.load_texture("decoded", img, image_view::TEXTURE_OPTIONS));
+                self.last_decoded = Some(result);
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Decode failed");
+                self.error_message = Some(format!("Decode failed: {}", e));
+                self.image_texture = None;
+                self.last_decoded = None;
+            }
         }
     }
 
+    /// Whole selected channel when resident; one decode window from the
+    /// playhead for streamed files, which are never loaded in full, and for
+    /// backends that decode from the playhead (SSTV: the next transmission,
+    /// not the first in the file).
+    fn decode_channel(&self, pipeline: &DecodingPipeline, source: &dyn SampleSource) -> anyhow::Result<PipelineResult> {
+        let resident = source
+            .resident(self.selected_channel)
+            .filter(|_| !self.backend().info().capabilities.from_playhead);
+        let (samples, start): (Arc<[f32]>, usize) = match resident {
+            Some(samples) => (samples, 0),
+            None => {
+                let window = (self.params.window_secs() * source.sample_rate() as f64) as usize;
+                let start = self.current_position_samples;
+                let samples = source.read(self.selected_channel, start..start.saturating_add(window))?;
+                (samples.into(), start)
+            }
+        };
+        Ok(pipeline
+            .process(&samples, &self.params, source.sample_rate())?
+            .offset_lines(start))
+    }
+
+    /// Backend for the selected decoder mode.
+    fn backend(&self) -> &'static dyn Decoder {
+        backend::for_mode(self.params.mode)
+    }
+
     /// Restart the decode worker after a crash or timeout, recording metrics.
     fn restart_worker(&mut self) {
         self.decode_worker.restart();
@@ -265,23 +533,26 @@ impl VoyagerApp {
         self.sync_positions.clear();
         self.sync_scan_rx = None;
 
-        let Some(reader) = &self.wav_reader else {
+        let Some(source) = &self.audio_source else {
             return;
         };
-        let samples: Arc<[f32]> = match self.selected_channel {
-            WaveformChannel::Left => Arc::clone(&reader.left_channel),
-            WaveformChannel::Right => Arc::clone(&reader.right_channel),
-        };
-        if samples.is_empty() {
+        if source.is_empty() {
             return;
         }
-        let sample_rate = reader.sample_rate;
+        let source = Arc::clone(source);
+        let channel = self.selected_channel;
 
         let (tx, rx) = std::sync::mpsc::channel();
         std::thread::spawn(move || {
             let start = Instant::now();
             let decoder = SstvDecoder::new();
-            let positions = decoder.find_tone_regions(&samples, sample_rate);
+            let positions = match source.resident(channel) {
+                Some(samples) => decoder.find_tone_regions(&samples, source.sample_rate()),
+                None => decoder.find_tone_regions_in(source.as_ref(), channel).unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "Background sync scan failed");
+                    Vec::new()
+                }),
+            };
             tracing::info!(
                 count = positions.len(),
                 elapsed_ms = start.elapsed().as_millis() as u64,
@@ -293,6 +564,254 @@ impl VoyagerApp {
         self.sync_scan_rx = Some(rx);
     }
 
+    /// Segment the selected channel into image frames in the background, for
+    /// frame-decoding backends. A no-op (beyond dropping stale frames) for
+    /// the others; selecting such a backend calls it again.
+    fn refresh_record_frames(&mut self) {
+        self.record_frames = None;
+        self.record_frames_rx = None;
+        self.last_frame_request = None;
+
+        if !self.backend().info().capabilities.frames {
+            return;
+        }
+        let Some(source) = &self.audio_source else {
+            return;
+        };
+        if source.is_empty() {
+            return;
+        }
+        let source = Arc::clone(source);
+        let channel = self.selected_channel;
+        let params = self.params;
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let start = Instant::now();
+            let frames = match RecordFrames::segment(source.as_ref(), channel, &params) {
+                Ok(frames) => {
+                    tracing::info!(
+                        frames = frames.bounds().len(),
+                        catalog_aligned = frames.is_catalog_aligned(),
+                        elapsed_ms = start.elapsed().as_millis() as u64,
+                        "Background record segmentation completed"
+                    );
+                    Some(frames)
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Background record segmentation failed");
+                    None
+                }
+            };
+            // Receiver may have been replaced by a newer run; ignore failure.
+            let _ = tx.send(frames);
+        });
+        self.record_frames_rx = Some(rx);
+    }
+
+    /// Measure the loaded file's playback speed in the background when
+    /// `decoder.auto_speed` is on and the selected preset does not fix the
+    /// factor. Starts from 1.0 so a new file never inherits the previous
+    /// file's factor; the estimate is applied in `update()`.
+    fn refresh_speed_estimate(&mut self) {
+        self.speed_estimate = None;
+        self.speed_rx = None;
+
+        let fixed = self
+            .config
+            .preset(&self.selected_preset)
+            .is_some_and(|p| p.speed_factor.is_some());
+        if !self.config.decoder.auto_speed || fixed {
+            return;
+        }
+        self.params.speed_factor = 1.0;
+        let Some(source) = &self.audio_source else {
+            return;
+        };
+        if source.is_empty() {
+            return;
+        }
+        let source = Arc::clone(source);
+        let channel = self.selected_channel;
+        let params = SpeedParams {
+            nominal_line_ms: self.params.line_duration_ms,
+            ..SpeedParams::default()
+        };
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let start = Instant::now();
+            let estimate = estimate_speed(source.as_ref(), channel, &params).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Background speed estimate failed");
+                None
+            });
+            tracing::info!(
+                estimate = ?estimate,
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "Background speed estimate completed"
+            );
+            // Receiver may have been replaced by a newer load; ignore failure.
+            let _ = tx.send(estimate);
+        });
+        self.speed_rx = Some(rx);
+    }
+
+    /// Switch to a new speed factor. Frame bounds were segmented and the
+    /// playing sink built for the old one, so both are redone.
+    fn apply_speed_factor(&mut self, factor: f32) {
+        self.params.speed_factor = factor;
+        self.refresh_record_frames();
+        self.catalog_panel.invalidate();
+        #[cfg(feature = "audio_playback")]
+        self.restart_audio_from_current_position();
+        #[cfg(not(feature = "audio_playback"))]
+        if self.playback_start_time.is_some() {
+            self.playback_start_time = Some(Instant::now());
+            self.playback_start_position = self.current_position_samples;
+        }
+    }
+
+    /// Load a preset's settings and channel. Frame bounds, the speed
+    /// estimate, sync markers (on a channel change) and the playing sink all
+    /// depend on them, so each is redone. Also applies `--preset` on startup.
+    pub fn apply_preset(&mut self, preset: &Preset) {
+        tracing::info!(preset = %preset.name, "Applying decoder preset");
+        self.selected_preset = preset.name.clone();
+        preset.apply(&mut self.params);
+        let channel = match &self.audio_source {
+            Some(source) if !preset.channel.is_available(source.channels()) => WaveformChannel::Left,
+            _ => preset.channel,
+        };
+        let channel_changed = self.selected_channel != channel;
+        self.selected_channel = channel;
+        if channel_changed {
+            self.refresh_sync_positions();
+        }
+        self.refresh_speed_estimate();
+        self.apply_speed_factor(self.params.speed_factor);
+        // In-flight decode results used the old settings
+        self.decode_generation += 1;
+    }
+
+    /// Switch the image channel (channel selector, catalog jump). Sync
+    /// markers, frame bounds, in-flight decodes and the playing sink all
+    /// belong to the old channel, so each is redone.
+    fn select_channel(&mut self, channel: WaveformChannel) {
+        if self.selected_channel == channel {
+            return;
+        }
+        self.selected_channel = channel;
+        // Sync markers are channel-specific; rescan once.
+        self.refresh_sync_positions();
+        self.refresh_record_frames();
+        // In-flight decode results are for the old channel
+        self.decode_generation += 1;
+        // The sink was built from the old channel's buffer; rebuild so
+        // audio matches the decode.
+        #[cfg(feature = "audio_playback")]
+        self.restart_audio_from_current_position();
+    }
+
+    /// Move the playhead to `position` (waveform click, bookmark, project
+    /// open, catalog jump): playback follows from there and the position is
+    /// decoded.
+    fn seek_to(&mut self, ctx: &egui::Context, position: usize) {
+        self.current_position_samples = position;
+
+        #[cfg(feature = "audio_playback")]
+        self.restart_audio_from_current_position();
+
+        #[cfg(not(feature = "audio_playback"))]
+        if self.audio_state.is_playing() {
+            self.playback_start_time = Some(Instant::now());
+            self.playback_start_position = self.current_position_samples;
+        }
+
+        // Trigger decode on manual seek
+        self.decode_at_position(ctx, self.current_position_samples);
+        self.last_decode_position = self.current_position_samples;
+    }
+
+    /// Per-frame override editor for the frame group led by `lead`. Edits
+    /// re-decode the frame once they settle.
+    fn draw_frame_override(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, lead: usize) {
+        let mut own = self.frame_overrides.iter().any(|o| o.frame == lead);
+        let mut changed = false;
+        if ui.checkbox(&mut own, format!("Own settings for frame {lead}")).changed() {
+            changed = true;
+            if own {
+                self.frame_overrides.push(FrameOverride {
+                    frame: lead,
+                    invert: Some(self.params.invert),
+                    gamma: Some(self.params.gamma),
+                    sync_lock: Some(self.params.sync_lock),
+                });
+            } else {
+                self.frame_overrides.retain(|o| o.frame != lead);
+            }
+        }
+        if let Some(frame_override) = self.frame_overrides.iter_mut().find(|o| o.frame == lead) {
+            let mut effective = self.params;
+            frame_override.apply(&mut effective);
+            ui.indent("frame_override", |ui| {
+                changed |= ui.checkbox(&mut effective.invert, "Invert").changed();
+                changed |= ui.checkbox(&mut effective.sync_lock, "Sync lock").changed();
+                let gamma = ui.add(egui::Slider::new(&mut effective.gamma, 0.2..=3.0).text("Gamma"));
+                changed |= gamma.drag_stopped() || (gamma.changed() && !gamma.dragged());
+            });
+            frame_override.invert = Some(effective.invert);
+            frame_override.gamma = Some(effective.gamma);
+            frame_override.sync_lock = Some(effective.sync_lock);
+        }
+        if changed {
+            // The cached request for this frame used the old settings
+            self.decode_generation += 1;
+            self.decode_at_position(ctx, self.current_position_samples);
+        }
+    }
+
+    /// Bookmark list: click a timecode to seek there; labels are editable.
+    fn draw_bookmarks(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        if self.bookmarks.is_empty() {
+            ui.label(
+                egui::RichText::new("🔖 in the transport bar marks the playhead")
+                    .size(12.0)
+                    .color(theme::TEXT_MUTED),
+            );
+            return;
+        }
+        let rate = self.audio_source.as_ref().map_or(1, |s| s.sample_rate().max(1)) as f64;
+        let mut seek = None;
+        let mut remove = None;
+        egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+            for (index, bookmark) in self.bookmarks.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let timecode = format_timecode(bookmark.position_samples as f64 / rate);
+                    if ui.button(egui::RichText::new(timecode).monospace()).clicked() {
+                        seek = Some(bookmark.position_samples);
+                    }
+                    ui.add(egui::TextEdit::singleline(&mut bookmark.label).desired_width(90.0));
+                    if ui.small_button("✕").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+        });
+        if let Some(index) = remove {
+            self.bookmarks.remove(index);
+        }
+        if let Some(position) = seek {
+            self.seek_to(ctx, position);
+        }
+    }
+
+    /// Lead frame of the frame group under the playhead, once segmented.
+    fn current_frame_lead(&self) -> Option<usize> {
+        let frames = self.record_frames.as_ref()?;
+        let index = frames.frame_at(self.current_position_samples)?;
+        Some(frames.group(index).lead())
+    }
+
     /// Export the last decoded image as a PNG via a save dialog.
     fn handle_export(&mut self) {
         let Some(result) = &self.last_decoded else {
@@ -377,7 +896,7 @@ impl VoyagerApp {
                 }
                 AudioPlaybackState::Ready => {
                     // Start fresh playback
-                    if self.wav_reader.is_none() {
+                    if self.audio_source.is_none() {
                         self.error_message = Some("No audio file loaded".to_string());
                         return;
                     }
@@ -393,6 +912,7 @@ impl VoyagerApp {
 
                     // Create sink with the mixer
                     let sink = Sink::connect_new(stream);
+                    sink.set_speed(self.playback_speed());
                     if let Some(source) = self.make_buffer_source_from_current_position() {
                         sink.append(source);
                         sink.play();
@@ -443,13 +963,14 @@ impl VoyagerApp {
         }
 
         // Reset playback state
-        if self.wav_reader.is_some() {
+        if self.audio_source.is_some() {
             self.audio_state = AudioPlaybackState::Ready;
         } else {
             self.audio_state = AudioPlaybackState::Uninitialized;
         }
         self.current_position_samples = 0;
         self.last_decode_position = 0;
+        self.live_session = None;
         #[cfg(feature = "audio_playback")]
         {
             self.playback_base_samples = 0;
@@ -474,7 +995,7 @@ impl VoyagerApp {
     ///
     /// **NEW approach (async via worker thread):**
     /// ```ignore
-    /// let request = DecodeRequest { samples: Arc::clone(&buffer), ... };
+    /// let request = DecodeRequest { source: Arc::clone(&source), ... };
     /// decode_tx.send(request);  // Returns immediately (microseconds)
     /// // Worker processes in background, UI stays at 60fps
     /// // Result arrives via decode_rx, polled in update()
@@ -485,31 +1006,25 @@ impl VoyagerApp {
     /// - UI frame time: <16ms (previously spiked to 100-500ms during decode)
     /// - Responsiveness: Immediate (no blocking operations)
     fn decode_at_position(&mut self, _ctx: &egui::Context, position: usize) {
-        if let Some(reader) = &self.wav_reader {
-            // Get shared reference to samples (Arc enables zero-copy sharing with worker)
-            let samples: Arc<[f32]> = match self.selected_channel {
-                WaveformChannel::Left => Arc::clone(&reader.left_channel),
-                WaveformChannel::Right => Arc::clone(&reader.right_channel),
-            };
-
+        if let Some(source) = &self.audio_source {
             // Bounds check - ensure we have samples and position is valid
-            if samples.is_empty() {
+            if source.is_empty() {
                 tracing::warn!("No samples available for decoding");
                 return;
             }
 
-            if position >= samples.len() {
+            if position >= source.len() {
                 tracing::warn!(
                     position = position,
-                    samples_len = samples.len(),
+                    samples_len = source.len(),
                     "Decode position out of bounds"
                 );
                 return;
             }
 
             // Ensure we have enough samples for a meaningful decode window
-            let min_window_samples = (reader.sample_rate as f64 * 0.1) as usize; // 100ms minimum
-            let remaining_samples = samples.len() - position;
+            let min_window_samples = (source.sample_rate() as f64 * 0.1) as usize; // 100ms minimum
+            let remaining_samples = source.len() - position;
             if remaining_samples < min_window_samples {
                 tracing::debug!(
                     remaining = remaining_samples,
@@ -519,32 +1034,156 @@ impl VoyagerApp {
                 return;
             }
 
-            let sample_rate = reader.sample_rate;
+            // Frame-triplet mode decodes whole frames: wait for segmentation,
+            // and skip the request while the playhead stays in one frame
+            let mut params = self.params;
+            let frames = if self.backend().info().capabilities.frames {
+                let Some(frames) = &self.record_frames else {
+                    tracing::debug!("Record frames not ready, skipping frame-triplet decode");
+                    return;
+                };
+                let frame = frames.frame_at(position);
+                let request = frame.map(|frame| (self.decode_generation, frame));
+                if request.is_some() && request == self.last_frame_request {
+                    return;
+                }
+                self.last_frame_request = request;
+                if let Some(frame) = frame {
+                    params = params_for_frame(&self.params, &self.frame_overrides, frames.group(frame).lead());
+                }
+                Some(Arc::clone(frames))
+            } else {
+                None
+            };
+
+            // The worker shares the source (Arc) and reads only its window
             self.decode_worker.request(
-                samples,
+                Arc::clone(source),
+                self.selected_channel,
                 position,
-                self.params,
-                sample_rate,
+                params,
+                frames,
                 self.config.worker.max_queue_size,
                 self.decode_generation,
             );
         }
     }
 
+    /// Feed playback up to `position` to the streaming live decode, first
+    /// starting a session (and a fresh image) when playback no longer
+    /// continues the current one: a seek, a jump, or new input or settings.
+    fn advance_live_decode(&mut self, position: usize) {
+        let Some(source) = &self.audio_source else {
+            return;
+        };
+        let max_gap = (self.params.decode_window_secs * source.sample_rate() as f64) as usize;
+        let continues = self.live_session.as_ref().is_some_and(|session| {
+            session.generation() == self.decode_generation
+                && *session.params() == self.params
+                && session.continues(position, max_gap)
+        });
+        if !continues {
+            match LiveDecodeSession::start(
+                Arc::clone(source),
+                self.selected_channel,
+                position,
+                self.params,
+                self.decode_generation,
+            ) {
+                Ok(session) => {
+                    self.live_session = Some(session);
+                    self.live_image = Some(LiveImage::new(self.params.effective_width(), LIVE_IMAGE_LINES));
+                }
+                Err(e) => {
+                    self.live_session = None;
+                    self.report_decode_error(&e.to_string());
+                    return;
+                }
+            }
+        }
+        if let Some(session) = &mut self.live_session {
+            if !session.advance(position) {
+                tracing::warn!("Live decode worker has terminated");
+                self.live_session = None;
+            }
+        }
+    }
+
+    /// Append lines from the streaming live decode to the live image and
+    /// show it.
+    fn poll_live_decode(&mut self, ctx: &egui::Context) {
+        let Some(session) = &self.live_session else {
+            return;
+        };
+        if session.generation() != self.decode_generation {
+            self.live_session = None;
+            return;
+        }
+        let mut grew = false;
+        for result in session.poll() {
+            let pixel_count = result.lines.len() * self.params.effective_width();
+            self.metrics
+                .record_decode(result.decode_duration, pixel_count, result.error.is_none());
+            if let Some(err_msg) = result.error {
+                self.live_session = None;
+                self.report_decode_error(&err_msg);
+                break;
+            }
+            if let Some(image) = &mut self.live_image {
+                for line in &result.lines {
+                    image.push(line);
+                }
+                grew |= !result.lines.is_empty();
+            }
+        }
+        if let Some(res) = self
+            .live_image
+            .as_ref()
+            .filter(|_| grew)
+            .and_then(|image| image.to_result(&self.params))
+        {
+            self.last_decode_error = None;
+            let img = res.to_egui_image();
+            self.image_texture = Some(ctx.load_texture("decoded_realtime", img, image_view::TEXTURE_OPTIONS));
+            self.last_decoded = Some(res);
+        }
+    }
+
+    /// Surface a decode failure, unless it repeats the last one: live
+    /// decode retries every interval and would otherwise make the dismiss
+    /// button useless.
+    fn report_decode_error(&mut self, err_msg: &str) {
+        let msg = format!("Decode failed: {}", err_msg);
+        if self.last_decode_error.as_deref() != Some(msg.as_str()) {
+            self.error_message = Some(msg.clone());
+            self.last_decode_error = Some(msg);
+        }
+    }
+
+    /// Per-row sync diagnostics of the displayed image, when its rows are
+    /// scan lines.
+    fn last_decoded_line_sync(&self) -> Option<&[LineSync]> {
+        let result = self.last_decoded.as_ref()?;
+        result
+            .line_sync
+            .as_deref()
+            .filter(|sync| sync.len() == result.height as usize)
+    }
+
     fn seek_to_next_sync(&mut self) {
-        if let Some(reader) = &self.wav_reader {
-            let samples = reader.get_samples(self.selected_channel);
+        if let Some(source) = &self.audio_source {
+            let total_samples = source.len();
 
-            if samples.is_empty() {
+            if total_samples == 0 {
                 tracing::warn!("No samples available for sync detection");
                 return;
             }
 
             // Ensure current position is within bounds
-            if self.current_position_samples >= samples.len() {
+            if self.current_position_samples >= total_samples {
                 tracing::warn!(
                     position = self.current_position_samples,
-                    samples_len = samples.len(),
+                    samples_len = total_samples,
                     "Current position out of bounds, resetting to start"
                 );
                 self.current_position_samples = 0;
@@ -553,16 +1192,27 @@ impl VoyagerApp {
             // Prefer the cached sync markers (the same ones drawn on the
             // waveform) so the jump matches what the user sees and costs
             // nothing; fall back to a live scan only while the background
-            // marker scan hasn't finished yet.
-            let min_jump = self.current_position_samples + (reader.sample_rate as usize / 100); // skip the marker we're on
+            // marker scan hasn't finished yet. Streamed files scan a bounded
+            // look-ahead window rather than the rest of the file.
+            let sample_rate = source.sample_rate();
+            let min_jump = self.current_position_samples + (sample_rate as usize / 100); // skip the marker we're on
             let next_sync = self.sync_positions.iter().copied().find(|&p| p > min_jump).or_else(|| {
-                self.video_decoder
-                    .find_next_tone_region(samples, self.current_position_samples, reader.sample_rate)
+                let position = self.current_position_samples;
+                match source.resident(self.selected_channel) {
+                    Some(samples) => self.video_decoder.find_next_tone_region(&samples, position, sample_rate),
+                    None => {
+                        let lookahead = (NEXT_SYNC_LOOKAHEAD_SECS * sample_rate as f64) as usize;
+                        let window = source.read(self.selected_channel, position..position + lookahead).ok()?;
+                        self.video_decoder
+                            .find_next_tone_region(&window, 0, sample_rate)
+                            .map(|p| position + p)
+                    }
+                }
             });
 
             if let Some(sync_position) = next_sync {
                 // Validate sync position
-                if sync_position < samples.len() {
+                if sync_position < total_samples {
                     self.current_position_samples = sync_position;
                     tracing::info!(sync_position, "Seeking to next sync");
 
@@ -578,7 +1228,7 @@ impl VoyagerApp {
                 } else {
                     tracing::warn!(
                         sync_position = sync_position,
-                        samples_len = samples.len(),
+                        samples_len = total_samples,
                         "Sync position out of bounds, ignoring"
                     );
                 }
@@ -589,36 +1239,38 @@ impl VoyagerApp {
     }
 
     #[cfg(feature = "audio_playback")]
-    /// Create an AudioBufferSource from the current position in the selected channel (zero-copy)
-    fn make_buffer_source_from_current_position(&self) -> Option<AudioBufferSource> {
-        let reader = self.wav_reader.as_ref()?;
-
-        // Get the Arc buffer for the selected channel
-        let buffer = match self.selected_channel {
-            WaveformChannel::Left => Arc::clone(&reader.left_channel),
-            WaveformChannel::Right => Arc::clone(&reader.right_channel),
-        };
+    /// Create a playback source from the current position in the selected
+    /// channel: zero-copy over the resident buffer, or chunked disk reads for
+    /// streamed files.
+    fn make_buffer_source_from_current_position(&self) -> Option<Box<dyn rodio::Source + Send>> {
+        let source = self.audio_source.as_ref()?;
 
-        if self.current_position_samples >= buffer.len() {
+        if self.current_position_samples >= source.len() {
             return None;
         }
 
         // Use Arc + offset instead of cloning - zero-copy seek!
-        // AudioBufferSource::new validates parameters and returns Result
-        AudioBufferSource::new(
-            buffer,
-            self.current_position_samples,
-            reader.sample_rate,
-            1, // Mono playback (we've already selected a channel)
-        )
-        .inspect_err(|e| {
-            tracing::error!(
-                error = %e,
-                offset = self.current_position_samples,
-                "Failed to create AudioBufferSource"
-            );
-        })
-        .ok()
+        // Both constructors validate parameters and return Result
+        let playback: Result<Box<dyn rodio::Source + Send>, _> = match source.resident(self.selected_channel) {
+            Some(buffer) => AudioBufferSource::new(
+                buffer,
+                self.current_position_samples,
+                source.sample_rate(),
+                1, // Mono playback (we've already selected a channel)
+            )
+            .map(|s| Box::new(s) as Box<dyn rodio::Source + Send>),
+            None => ChunkedSource::new(Arc::clone(source), self.selected_channel, self.current_position_samples)
+                .map(|s| Box::new(s) as Box<dyn rodio::Source + Send>),
+        };
+        playback
+            .inspect_err(|e| {
+                tracing::error!(
+                    error = %e,
+                    offset = self.current_position_samples,
+                    "Failed to create playback source"
+                );
+            })
+            .ok()
     }
 
     #[cfg(feature = "audio_playback")]
@@ -652,6 +1304,7 @@ impl VoyagerApp {
 
         // Create new sink with source from current position
         let sink = Sink::connect_new(stream);
+        sink.set_speed(self.playback_speed());
         if let Some(source) = self.make_buffer_source_from_current_position() {
             sink.append(source);
             sink.play();
@@ -664,15 +1317,23 @@ impl VoyagerApp {
 }
 
 impl VoyagerApp {
+    /// Rate at which playback advances through the source: the inverse of
+    /// the speed factor, so a 2x rip plays at half speed and the record's
+    /// own pitch.
+    fn playback_speed(&self) -> f32 {
+        1.0 / self.params.speed_factor
+    }
+
     /// Current playhead in samples, anchored to the audio device clock.
     #[cfg(feature = "audio_playback")]
     fn live_position(&self) -> Option<usize> {
         let sink = self.audio_sink.as_ref()?;
-        let reader = self.wav_reader.as_ref()?;
+        let source = self.audio_source.as_ref()?;
         Some(crate::services::playback::position_samples(
             self.playback_base_samples,
             sink.get_pos(),
-            reader.sample_rate,
+            source.sample_rate(),
+            sink.speed(),
         ))
     }
 
@@ -680,8 +1341,8 @@ impl VoyagerApp {
     #[cfg(not(feature = "audio_playback"))]
     fn live_position(&self) -> Option<usize> {
         let start_time = self.playback_start_time?;
-        let reader = self.wav_reader.as_ref()?;
-        let samples_elapsed = (start_time.elapsed().as_secs_f32() * reader.sample_rate as f32) as usize;
+        let source = self.audio_source.as_ref()?;
+        let samples_elapsed = (start_time.elapsed().as_secs_f32() * self.playback_speed() * source.sample_rate() as f32) as usize;
         Some(self.playback_start_position + samples_elapsed)
     }
 }
@@ -697,7 +1358,11 @@ impl eframe::App for VoyagerApp {
                 Some(output_dir) => {
                     let queue = self.batch_panel.queue.clone();
                     let mode = self.batch_panel.selected_mode;
-                    let cancel_flag = self.batch_runner.start(queue, output_dir, mode);
+                    let mut preset = self.config.preset(&self.selected_preset).unwrap_or_default();
+                    if let Some(channel) = self.batch_panel.selected_channel {
+                        preset.channel = channel;
+                    }
+                    let cancel_flag = self.batch_runner.start(queue, output_dir, mode, preset);
                     self.batch_panel.cancel_flag = Some(cancel_flag);
                     ctx.request_repaint();
                 }
@@ -760,6 +1425,52 @@ impl eframe::App for VoyagerApp {
             }
         }
 
+        // Collect the background speed estimate and apply its factor
+        if let Some(rx) = &self.speed_rx {
+            match rx.try_recv() {
+                Ok(estimate) => {
+                    self.speed_rx = None;
+                    if let Some(estimate) = estimate {
+                        if estimate.factor != self.params.speed_factor {
+                            tracing::info!(%estimate, "Compensating playback speed");
+                            self.apply_speed_factor(estimate.factor);
+                        }
+                    }
+                    self.speed_estimate = estimate;
+                    ctx.request_repaint();
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    ctx.request_repaint_after(Duration::from_millis(200));
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    tracing::warn!("Speed estimate thread exited without a result");
+                    self.speed_rx = None;
+                }
+            }
+        }
+
+        // Collect background record segmentation, then show the frame under
+        // the playhead
+        if let Some(rx) = &self.record_frames_rx {
+            match rx.try_recv() {
+                Ok(frames) => {
+                    self.record_frames = frames.map(Arc::new);
+                    self.record_frames_rx = None;
+                    if self.record_frames.is_some() {
+                        self.decode_at_position(ctx, self.current_position_samples);
+                    }
+                    ctx.request_repaint();
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    ctx.request_repaint_after(Duration::from_millis(200));
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    tracing::warn!("Record segmentation thread exited without a result");
+                    self.record_frames_rx = None;
+                }
+            }
+        }
+
         // Poll for decode results from background worker (non-blocking)
         for decode_result in self.decode_worker.poll() {
             let DecodeResult {
@@ -799,23 +1510,19 @@ impl eframe::App for VoyagerApp {
                 continue;
             }
 
-            // Handle error or update texture. Identical consecutive errors are
-            // not re-raised — live decode retries every interval and would
-            // otherwise make the dismiss button useless.
+            // Handle error or update texture.
             if let Some(err_msg) = error {
-                let msg = format!("Decode failed: {}", err_msg);
-                if self.last_decode_error.as_deref() != Some(msg.as_str()) {
-                    self.error_message = Some(msg.clone());
-                    self.last_decode_error = Some(msg);
-                }
+                self.report_decode_error(&err_msg);
             } else if let Some(res) = pipeline_result {
                 self.last_decode_error = None;
                 let img = res.to_egui_image();
-                self.image_texture = Some(ctx.load_texture("decoded_realtime", img, Default::default()));
+                self.image_texture = Some(ctx.load_texture("decoded_realtime", img, image_view::TEXTURE_OPTIONS));
                 self.last_decoded = Some(res);
             }
         }
 
+        self.poll_live_decode(ctx);
+
         // Update playback position if playing. The position comes from the
         // audio device clock (sink.get_pos), not a UI-frame timer, so it
         // cannot drift under frame-rate jitter; the live decode window is
@@ -831,8 +1538,8 @@ impl eframe::App for VoyagerApp {
 
             if let (Some(new_position), Some(total_samples), Some(sample_rate)) = (
                 self.live_position(),
-                self.wav_reader.as_ref().map(|r| r.left_channel.len()),
-                self.wav_reader.as_ref().map(|r| r.sample_rate),
+                self.audio_source.as_ref().map(|s| s.len()),
+                self.audio_source.as_ref().map(|s| s.sample_rate()),
             ) {
                 if new_position >= total_samples || sink_drained {
                     // Reached end of audio, stop playback
@@ -846,8 +1553,14 @@ impl eframe::App for VoyagerApp {
                         (sample_rate as f32 * (self.config.worker.decode_interval_ms as f32 / 1000.0)) as usize;
                     let position_change = new_position.abs_diff(self.last_decode_position);
 
-                    if position_change >= decode_threshold_samples {
-                        self.decode_at_position(ctx, new_position);
+                    // Some backends' windows are minutes of audio (a whole
+                    // SSTV transmission): those decode on demand only.
+                    if position_change >= decode_threshold_samples && self.backend().info().capabilities.live {
+                        if self.backend().info().capabilities.streaming {
+                            self.advance_live_decode(new_position);
+                        } else {
+                            self.decode_at_position(ctx, new_position);
+                        }
                         self.last_decode_position = new_position;
                     }
                 }
@@ -862,6 +1575,30 @@ impl eframe::App for VoyagerApp {
         // Draw Batch Panel (floating window)
         self.batch_panel.draw(ctx);
 
+        // Catalog browser (floating window): scans once opened on a file
+        if let Some(source) = &self.audio_source {
+            self.catalog_panel
+                .ensure_scan(source, &self.params, self.record_frames.as_ref());
+        }
+        if let Some((channel, position)) = self
+            .catalog_panel
+            .draw(ctx, (self.selected_channel, self.current_position_samples))
+        {
+            self.select_channel(channel);
+            self.seek_to(ctx, position);
+        }
+
+        // Reference comparison (floating window)
+        self.compare_panel.draw(
+            ctx,
+            CompareInput {
+                source: self.audio_source.as_ref(),
+                channel: self.selected_channel,
+                params: &self.params,
+                frames: self.record_frames.as_ref(),
+            },
+        );
+
         // --- Header bar ---
         egui::TopBottomPanel::top("header_bar")
             .frame(theme::strip_frame())
@@ -886,13 +1623,10 @@ impl eframe::App for VoyagerApp {
         egui::TopBottomPanel::top("transport_bar")
             .frame(theme::strip_frame())
             .show(ctx, |ui| {
-                let (current_secs, total_secs) = match &self.wav_reader {
-                    Some(reader) => {
-                        let rate = reader.sample_rate.max(1) as f64;
-                        (
-                            self.current_position_samples as f64 / rate,
-                            reader.left_channel.len() as f64 / rate,
-                        )
+                let (current_secs, total_secs) = match &self.audio_source {
+                    Some(source) => {
+                        let rate = source.sample_rate().max(1) as f64;
+                        (self.current_position_samples as f64 / rate, source.len() as f64 / rate)
                     }
                     None => (0.0, 0.0),
                 };
@@ -900,15 +1634,18 @@ impl eframe::App for VoyagerApp {
                 if let Some(action) = ControlsPanel::draw(
                     ui,
                     self.audio_state.is_playing(),
-                    self.wav_reader.is_some(),
+                    self.audio_source.is_some(),
                     current_secs,
                     total_secs,
                 ) {
                     match action {
                         ControlAction::OpenWav => self.handle_load_wav(),
+                        ControlAction::OpenProject => self.handle_open_project(ctx),
+                        ControlAction::SaveProject => self.handle_save_project(),
                         ControlAction::TogglePlayback => self.toggle_playback(),
                         ControlAction::StopPlayback => self.stop_playback(),
                         ControlAction::SeekToNextSync => self.seek_to_next_sync(),
+                        ControlAction::AddBookmark => self.add_bookmark(),
                     }
                 }
             });
@@ -925,26 +1662,13 @@ impl eframe::App for VoyagerApp {
                 theme::section_label(ui, "Waveform");
                 if let Some(new_pos) = self.waveform_panel.draw(
                     ui,
-                    &self.wav_reader,
+                    self.audio_source.as_deref(),
                     self.selected_channel,
                     self.current_position_samples,
                     &mut self.waveform_hover_position,
                     &self.sync_positions,
                 ) {
-                    self.current_position_samples = new_pos;
-
-                    #[cfg(feature = "audio_playback")]
-                    self.restart_audio_from_current_position();
-
-                    #[cfg(not(feature = "audio_playback"))]
-                    if self.audio_state.is_playing() {
-                        self.playback_start_time = Some(Instant::now());
-                        self.playback_start_position = self.current_position_samples;
-                    }
-
-                    // Trigger decode on manual seek
-                    self.decode_at_position(ctx, self.current_position_samples);
-                    self.last_decode_position = self.current_position_samples;
+                    self.seek_to(ctx, new_pos);
                 }
             });
 
@@ -982,14 +1706,14 @@ impl eframe::App for VoyagerApp {
                             .color(theme::TEXT_MUTED),
                     );
 
-                    if let Some(reader) = &self.wav_reader {
+                    if let Some(source) = &self.audio_source {
                         ui.separator();
-                        let duration_secs = reader.left_channel.len() as f32 / reader.sample_rate as f32;
+                        let duration_secs = source.len() as f32 / source.sample_rate() as f32;
                         ui.label(
                             egui::RichText::new(format!(
                                 "{} Hz · {} · {}",
-                                reader.sample_rate,
-                                if reader.channels == 1 { "mono" } else { "stereo" },
+                                source.sample_rate(),
+                                if source.channels() == 1 { "mono" } else { "stereo" },
                                 format_duration(duration_secs)
                             ))
                             .size(12.0)
@@ -1044,9 +1768,12 @@ impl eframe::App for VoyagerApp {
                         });
                     });
                     if self.spectrum_panel.visible {
-                        peak =
-                            self.spectrum_panel
-                                .draw(ui, &self.wav_reader, self.current_position_samples, self.selected_channel);
+                        peak = self.spectrum_panel.draw(
+                            ui,
+                            self.audio_source.as_deref(),
+                            self.current_position_samples,
+                            self.selected_channel,
+                        );
                     }
                 });
 
@@ -1056,10 +1783,19 @@ impl eframe::App for VoyagerApp {
                     ui.set_min_width(ui.available_width());
                     theme::section_label(ui, "Signal");
                     ui.add_space(2.0);
-                    if let Some(reader) = &self.wav_reader {
-                        let duration_secs = reader.left_channel.len() as f32 / reader.sample_rate as f32;
-                        theme::key_value(ui, "Sample rate", &format!("{} Hz", reader.sample_rate));
-                        theme::key_value(ui, "Channels", if reader.channels == 1 { "mono" } else { "stereo" });
+                    if let Some(source) = &self.audio_source {
+                        let duration_secs = source.len() as f32 / source.sample_rate() as f32;
+                        theme::key_value(ui, "Format", &source.format().to_string());
+                        theme::key_value(ui, "Sample rate", &format!("{} Hz", source.sample_rate()));
+                        let channels = match source.channels() {
+                            1 => "mono".to_string(),
+                            2 => "stereo".to_string(),
+                            n => n.to_string(),
+                        };
+                        theme::key_value(ui, "Channels", &channels);
+                        if source.resident(WaveformChannel::Left).is_none() {
+                            theme::key_value(ui, "Access", "streamed");
+                        }
                         theme::key_value(ui, "Duration", &format_duration(duration_secs));
                         let sync_marks = if self.sync_scan_rx.is_some() {
                             "scanning…".to_string()
@@ -1067,6 +1803,35 @@ impl eframe::App for VoyagerApp {
                             self.sync_positions.len().to_string()
                         };
                         theme::key_value(ui, "Sync marks", &sync_marks);
+                        let speed = match (&self.speed_estimate, self.speed_rx.is_some()) {
+                            (_, true) => "measuring…".to_string(),
+                            (Some(estimate), false) if estimate.factor == self.params.speed_factor => {
+                                format!("×{:.3} ({:.2} ms lines)", estimate.factor, estimate.line_ms)
+                            }
+                            _ => format!("×{:.3}", self.params.speed_factor),
+                        };
+                        theme::key_value(ui, "Speed", &speed);
+                        if let Some(mode) = self.last_decoded.as_ref().and_then(|r| r.sstv_mode) {
+                            theme::key_value(ui, "SSTV mode", mode.name());
+                        }
+                        if self.backend().info().capabilities.frames {
+                            let frames = match (&self.record_frames, self.record_frames_rx.is_some()) {
+                                (_, true) => "segmenting…".to_string(),
+                                (Some(frames), false) if frames.is_catalog_aligned() => {
+                                    format!("{} (catalog)", frames.bounds().len())
+                                }
+                                (Some(frames), false) => format!("{} (no catalog match)", frames.bounds().len()),
+                                (None, false) => "unavailable".to_string(),
+                            };
+                            theme::key_value(ui, "Frames", &frames);
+                            if let Some(reg) = self.last_decoded.as_ref().and_then(|r| r.registration.as_ref()) {
+                                for (plane, reg) in [("Green reg", &reg.green), ("Blue reg", &reg.blue)] {
+                                    let t = reg.transform;
+                                    let value = format!("{:+.1},{:+.1} {:+.2}° ×{:.3}", t.dx, t.dy, t.rotation_deg, t.scale);
+                                    theme::key_value(ui, plane, &value);
+                                }
+                            }
+                        }
                         if let Some((freq, mag)) = peak {
                             theme::key_value(ui, "Dominant", &format!("{:.0} Hz ({:.1})", freq, mag));
                         }
@@ -1095,56 +1860,150 @@ impl eframe::App for VoyagerApp {
                         .num_columns(2)
                         .spacing([10.0, 8.0])
                         .show(ui, |ui| {
-                            ui.label(egui::RichText::new("Line (ms)").size(12.0).color(theme::TEXT_MUTED));
-                            ui.add(
-                                egui::DragValue::new(&mut self.params.line_duration_ms)
-                                    .range(1..=100)
-                                    .speed(0.01),
-                            );
-                            ui.end_row();
-
-                            ui.label(egui::RichText::new("Gamma").size(12.0).color(theme::TEXT_MUTED));
-                            ui.add(egui::Slider::new(&mut self.params.gamma, 0.2..=3.0));
+                            ui.label(egui::RichText::new("Preset").size(12.0).color(theme::TEXT_MUTED));
+                            let presets = self.config.presets();
+                            let current = presets.iter().find(|p| p.name == self.selected_preset);
+                            let label = match current {
+                                Some(preset) if preset.matches(&self.params) => preset.name.clone(),
+                                _ => format!("{} (modified)", self.selected_preset),
+                            };
+                            let mut chosen = None;
+                            egui::ComboBox::from_id_salt("preset_combo")
+                                .selected_text(label)
+                                .show_ui(ui, |ui| {
+                                    for preset in &presets {
+                                        if ui
+                                            .selectable_label(preset.name == self.selected_preset, &preset.name)
+                                            .clicked()
+                                        {
+                                            chosen = Some(preset);
+                                        }
+                                    }
+                                });
+                            if let Some(preset) = chosen {
+                                self.apply_preset(preset);
+                            }
                             ui.end_row();
 
                             ui.label(egui::RichText::new("Mode").size(12.0).color(theme::TEXT_MUTED));
+                            let previous_mode = self.params.mode;
                             egui::ComboBox::from_id_salt("decode_mode_combo")
-                                .selected_text(match self.params.mode {
-                                    DecoderMode::Grayscale => "Grayscale",
-                                    DecoderMode::PseudoColor => "PseudoColor",
-                                })
+                                .selected_text(self.backend().info().name)
                                 .show_ui(ui, |ui| {
-                                    ui.selectable_value(&mut self.params.mode, DecoderMode::Grayscale, "Grayscale");
-                                    ui.selectable_value(&mut self.params.mode, DecoderMode::PseudoColor, "PseudoColor");
+                                    for backend in backend::backends() {
+                                        let info = backend.info();
+                                        ui.selectable_value(&mut self.params.mode, info.mode, info.name)
+                                            .on_hover_text(info.description);
+                                    }
                                 });
+                            if self.params.mode != previous_mode
+                                && self.backend().info().capabilities.frames
+                                && self.record_frames.is_none()
+                                && self.record_frames_rx.is_none()
+                            {
+                                self.refresh_record_frames();
+                            }
                             ui.end_row();
 
+                            // Only the parameters the selected backend reads
+                            let info = self.backend().info();
+                            let label = |ui: &mut egui::Ui, param: Param| {
+                                ui.label(egui::RichText::new(param.label()).size(12.0).color(theme::TEXT_MUTED));
+                            };
+                            let range = |param: Param| param.range().expect("numeric parameter");
+
+                            if info.uses(Param::LineDuration) {
+                                label(ui, Param::LineDuration);
+                                ui.add(
+                                    egui::DragValue::new(&mut self.params.line_duration_ms)
+                                        .range(range(Param::LineDuration))
+                                        .speed(0.01),
+                                );
+                                ui.end_row();
+                            }
+
+                            if info.uses(Param::SpeedFactor) {
+                                label(ui, Param::SpeedFactor);
+                                let speed = ui.add(
+                                    egui::DragValue::new(&mut self.params.speed_factor)
+                                        .range(range(Param::SpeedFactor))
+                                        .speed(0.005)
+                                        .max_decimals(3),
+                                );
+                                // Rebuild once the drag settles, not every
+                                // frame of it: applying resegments the record.
+                                if speed.drag_stopped() || (speed.changed() && !speed.dragged()) {
+                                    self.apply_speed_factor(self.params.speed_factor);
+                                }
+                                ui.end_row();
+                            }
+
+                            if info.uses(Param::Gamma) {
+                                label(ui, Param::Gamma);
+                                ui.add(egui::Slider::new(&mut self.params.gamma, range(Param::Gamma)));
+                                ui.end_row();
+                            }
+
+                            if info.uses(Param::SstvMode) {
+                                label(ui, Param::SstvMode);
+                                egui::ComboBox::from_id_salt("sstv_mode_combo")
+                                    .selected_text(self.params.sstv_mode.map_or("Auto (VIS)", |mode| mode.name()))
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(&mut self.params.sstv_mode, None, "Auto (VIS)");
+                                        for mode in SstvMode::ALL {
+                                            ui.selectable_value(&mut self.params.sstv_mode, Some(mode), mode.name());
+                                        }
+                                    });
+                                ui.end_row();
+                            }
+
                             ui.label(egui::RichText::new("Channel").size(12.0).color(theme::TEXT_MUTED));
                             let previous_channel = self.selected_channel;
+                            let file_channels = self.audio_source.as_ref().map_or(2, |source| source.channels());
                             egui::ComboBox::from_id_salt("channel_combo")
-                                .selected_text(match self.selected_channel {
-                                    WaveformChannel::Left => "Left",
-                                    WaveformChannel::Right => "Right",
-                                })
+                                .selected_text(self.selected_channel.label())
                                 .show_ui(ui, |ui| {
-                                    ui.selectable_value(&mut self.selected_channel, WaveformChannel::Left, "Left");
-                                    ui.selectable_value(&mut self.selected_channel, WaveformChannel::Right, "Right");
+                                    for channel in WaveformChannel::available(file_channels) {
+                                        ui.selectable_value(&mut self.selected_channel, channel, channel.label());
+                                    }
                                 });
                             if self.selected_channel != previous_channel {
-                                // Sync markers are channel-specific; rescan once.
-                                self.refresh_sync_positions();
-                                // In-flight decode results are for the old channel
-                                self.decode_generation += 1;
-                                // The sink was built from the old channel's
-                                // buffer; rebuild so audio matches the decode.
-                                #[cfg(feature = "audio_playback")]
-                                self.restart_audio_from_current_position();
+                                let channel = std::mem::replace(&mut self.selected_channel, previous_channel);
+                                self.select_channel(channel);
                             }
                             ui.end_row();
                         });
 
-                    ui.checkbox(&mut self.params.invert, "Invert");
-                    ui.checkbox(&mut self.params.sync_lock, "Sync lock");
+                    let info = self.backend().info();
+                    if info.uses(Param::Invert) {
+                        ui.checkbox(&mut self.params.invert, Param::Invert.label());
+                    }
+                    if info.uses(Param::SyncLock) {
+                        ui.checkbox(&mut self.params.sync_lock, Param::SyncLock.label());
+                    }
+                    if info.uses(Param::TimeBase) {
+                        ui.add_enabled(
+                            self.params.sync_lock,
+                            egui::Checkbox::new(&mut self.params.time_base, Param::TimeBase.label()),
+                        )
+                        .on_hover_text("Resample onto the line syncs' time base before slicing (whole-window decodes)");
+                    }
+
+                    if self.backend().info().capabilities.frames {
+                        if let Some(lead) = self.current_frame_lead() {
+                            ui.separator();
+                            self.draw_frame_override(ui, ctx, lead);
+                        }
+                    }
+                });
+
+                ui.add_space(8.0);
+
+                theme::panel_frame().show(ui, |ui| {
+                    ui.set_min_width(ui.available_width());
+                    theme::section_label(ui, "Bookmarks");
+                    ui.add_space(2.0);
+                    self.draw_bookmarks(ui, ctx);
                 });
 
                 ui.add_space(8.0);
@@ -1163,6 +2022,20 @@ impl eframe::App for VoyagerApp {
                     if ui.button("Batch…").clicked() {
                         self.batch_panel.visible = !self.batch_panel.visible;
                     }
+                    if ui
+                        .add_enabled(self.audio_source.is_some(), egui::Button::new("Catalog…"))
+                        .on_hover_text("Thumbnails of every frame; click one to jump there")
+                        .clicked()
+                    {
+                        self.catalog_panel.visible = !self.catalog_panel.visible;
+                    }
+                    if ui
+                        .button("Compare…")
+                        .on_hover_text("Score decoded frames against a directory of reference decodes")
+                        .clicked()
+                    {
+                        self.compare_panel.visible = !self.compare_panel.visible;
+                    }
                 });
             });
 
@@ -1182,9 +2055,28 @@ impl eframe::App for VoyagerApp {
                             if ui.button("Decode").clicked() {
                                 self.handle_decode(ctx);
                             }
+                            let pixels_per_point = ctx.pixels_per_point();
+                            if ui
+                                .selectable_label(self.image_view.is_actual_size(pixels_per_point), "1:1")
+                                .on_hover_text("One image pixel per screen pixel")
+                                .clicked()
+                            {
+                                self.image_view.actual_size(pixels_per_point);
+                            }
+                            if ui
+                                .selectable_label(self.image_view.is_fit(), "Fit")
+                                .on_hover_text("Fit the image width; scroll to zoom, drag to pan")
+                                .clicked()
+                            {
+                                self.image_view.fit();
+                            }
                             let lines = self.last_decoded.as_ref().map(|r| r.height).unwrap_or(0);
+                            let locked = self
+                                .last_decoded_line_sync()
+                                .map(|sync| format!(" · {:.0}% locked", gutter::locked_fraction(sync) * 100.0))
+                                .unwrap_or_default();
                             ui.label(
-                                egui::RichText::new(format!("Lines: {}", lines))
+                                egui::RichText::new(format!("Lines: {}{}", lines, locked))
                                     .size(12.0)
                                     .monospace()
                                     .color(theme::TEXT_BRIGHT),
@@ -1193,20 +2085,28 @@ impl eframe::App for VoyagerApp {
                     });
                     ui.add_space(4.0);
 
-                    egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
-                        if let Some(texture) = &self.image_texture {
-                            ui.add(egui::Image::new(texture).max_width(ui.available_width()));
-                        } else {
+                    let mut seek = None;
+                    match (&self.image_texture, &self.last_decoded) {
+                        (Some(texture), Some(result)) => {
+                            let sample_rate = self.audio_source.as_ref().map(|s| s.sample_rate());
+                            seek = self
+                                .image_view
+                                .show(ui, texture, result, self.current_position_samples, sample_rate);
+                        }
+                        _ => {
                             ui.centered_and_justified(|ui| {
                                 ui.label(
                                     egui::RichText::new(
-                                        "No image decoded yet — load a WAV and press Decode, or play to decode live",
+                                        "No image decoded yet — load a recording and press Decode, or play to decode live",
                                     )
                                     .color(theme::TEXT_MUTED),
                                 );
                             });
                         }
-                    });
+                    }
+                    if let Some(position) = seek {
+                        self.seek_to(ctx, position);
+                    }
                 });
             });
     }
@@ -1,11 +1,16 @@
+use std::borrow::Cow;
+use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use hound::{SampleFormat, WavReader as HoundReader, WavSpec};
 
+use crate::codec::{self, AudioFormat, Container};
 use crate::error::{AudioError, Result};
+use crate::sample_source::{SampleSource, StreamingWavReader};
 
-/// WAV file reader with normalized `f32` samples and zero-copy buffer sharing.
+/// Audio file reader with normalized `f32` samples and zero-copy buffer sharing.
 ///
 /// # Architecture Decision: Arc&lt;[f32]&gt; vs Vec&lt;f32&gt;
 ///
@@ -26,28 +31,31 @@ use crate::error::{AudioError, Result};
 /// # Sample Normalization
 ///
 /// All samples are normalized to f32 in the nominal range `[-1.0, 1.0]`.
-/// Supported encodings: IEEE float32 (the format of the Golden Record rips)
-/// and integer PCM up to 32 bits.
+/// WAV supports IEEE float32 (the format of the Golden Record rips) and
+/// integer PCM up to 32 bits, read with `hound`; RF64 and Wave64 files over
+/// 4 GB are read through [`StreamingWavReader`]. FLAC, AIFF/AIFC, Ogg Vorbis
+/// and MP3 go through [`crate::codec`]; the container is recognized from the
+/// file header, not the extension.
 pub struct WavReader {
-    /// Left channel samples (or mono channel for mono files).
-    /// Shared via Arc for zero-copy playback.
-    pub left_channel: Arc<[f32]>,
-    /// Right channel samples (duplicated from left for mono files).
-    /// Shared via Arc for zero-copy playback.
-    pub right_channel: Arc<[f32]>,
+    /// One buffer per channel of the file, in file order (a single buffer
+    /// for mono). Shared via Arc for zero-copy playback.
+    pub channel_data: Vec<Arc<[f32]>>,
     /// Original sample rate in Hz (e.g., 44100, 48000).
     pub sample_rate: u32,
-    /// Number of channels in the original file (1 for mono, 2 for stereo).
+    /// Number of channels in the original file.
     pub channels: u16,
+    /// Container and codec the samples were decoded from.
+    pub format: AudioFormat,
 }
 
 impl WavReader {
-    /// Load and decode a WAV file with comprehensive error handling.
+    /// Load and decode an audio file with comprehensive error handling.
     ///
     /// # Errors
     ///
-    /// Returns [`AudioError::LoadFailed`] if the file cannot be opened or read.
-    /// Returns [`AudioError::UnsupportedChannels`] for files with > 2 channels.
+    /// Returns [`AudioError::LoadFailed`] if a WAV file cannot be opened or read,
+    /// and [`AudioError::DecodeFailed`] for other formats.
+    /// Returns [`AudioError::UnsupportedChannels`] for files declaring no channels.
     /// Returns [`AudioError::InvalidSampleRate`] for sample rates outside 8kHz-192kHz.
     /// Returns [`AudioError::EmptyFile`] if the file contains no audio samples.
     ///
@@ -57,14 +65,14 @@ impl WavReader {
     /// use voyager_explorer::audio::WavReader;
     ///
     /// let reader = WavReader::from_file("audio.wav")?;
-    /// println!("Loaded {} samples at {} Hz", reader.left_channel.len(), reader.sample_rate);
+    /// println!("Loaded {} channel(s) at {} Hz", reader.channels, reader.sample_rate);
     /// # Ok::<(), voyager_explorer::error::VoyagerError>(())
     /// ```
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         Self::load(path.as_ref(), None, None)
     }
 
-    /// Load only a time window of a WAV file, seeking past the leading frames.
+    /// Load only a time window of a file, seeking past the leading frames.
     ///
     /// `start_secs` is clamped to the file length; `duration_secs` may run past
     /// the end of the file (the result is simply shorter). This keeps the
@@ -78,7 +86,7 @@ impl WavReader {
         Self::load(path.as_ref(), Some(start_secs), Some(duration_secs))
     }
 
-    /// Load a WAV file from `start_secs` to the end of the file.
+    /// Load a file from `start_secs` to the end of the file.
     ///
     /// # Errors
     ///
@@ -88,6 +96,78 @@ impl WavReader {
     }
 
     fn load(path: &Path, start_secs: Option<f64>, duration_secs: Option<f64>) -> Result<Self> {
+        // Unrecognized headers go to hound too, for its error message.
+        match Container::sniff(path) {
+            Ok(Some(Container::Wav) | None) | Err(_) => Self::load_wav(path, start_secs, duration_secs),
+            Ok(Some(Container::Rf64 | Container::Wave64)) => Self::load_streamed(path, start_secs, duration_secs),
+            Ok(Some(_)) => Self::load_decoded(path, start_secs, duration_secs),
+        }
+    }
+
+    /// Read the window through [`StreamingWavReader`], which addresses frames
+    /// with 64-bit offsets: RF64/Wave64 files, and starts past hound's seek
+    /// range.
+    fn load_streamed(path: &Path, start_secs: Option<f64>, duration_secs: Option<f64>) -> Result<Self> {
+        let source = StreamingWavReader::open(path)?;
+        let rate = source.sample_rate() as f64;
+        let first = start_secs.map_or(0, |s| (s.max(0.0) * rate) as usize);
+        let last = duration_secs.map_or(source.len(), |d| first.saturating_add((d.max(0.0) * rate) as usize));
+
+        let channel_data = (0..source.channels())
+            .map(|index| Ok(source.read(WaveformChannel::index(index), first..last)?.into()))
+            .collect::<Result<Vec<Arc<[f32]>>>>()?;
+        if channel_data[0].is_empty() {
+            return Err(AudioError::EmptyFile {
+                path: path.to_path_buf(),
+            }
+            .into());
+        }
+
+        tracing::info!(
+            path = %path.display(),
+            sample_rate = source.sample_rate(),
+            channels = source.channels(),
+            format = %source.format(),
+            samples = channel_data[0].len(),
+            "Successfully loaded WAV window"
+        );
+
+        Ok(Self {
+            channel_data,
+            sample_rate: source.sample_rate(),
+            channels: source.channels(),
+            format: source.format(),
+        })
+    }
+
+    fn load_decoded(path: &Path, start_secs: Option<f64>, duration_secs: Option<f64>) -> Result<Self> {
+        let decoded = codec::decode_file(path, start_secs, duration_secs)?;
+        if decoded.samples.is_empty() {
+            return Err(AudioError::EmptyFile {
+                path: path.to_path_buf(),
+            }
+            .into());
+        }
+        let channel_data = split_channels(decoded.samples, decoded.channels);
+
+        tracing::info!(
+            path = %path.display(),
+            sample_rate = decoded.sample_rate,
+            channels = decoded.channels,
+            format = %decoded.format,
+            samples = channel_data[0].len(),
+            "Successfully decoded audio file"
+        );
+
+        Ok(Self {
+            channel_data,
+            sample_rate: decoded.sample_rate,
+            channels: decoded.channels,
+            format: decoded.format,
+        })
+    }
+
+    fn load_wav(path: &Path, start_secs: Option<f64>, duration_secs: Option<f64>) -> Result<Self> {
         let path_buf = path.to_path_buf();
 
         let mut reader = HoundReader::open(path).map_err(|source| AudioError::LoadFailed {
@@ -104,7 +184,7 @@ impl WavReader {
         }
 
         // Validate channel count
-        if spec.channels != 1 && spec.channels != 2 {
+        if spec.channels == 0 {
             return Err(AudioError::UnsupportedChannels { channels: spec.channels }.into());
         }
 
@@ -112,9 +192,10 @@ impl WavReader {
             let start_frame_wide = (start.max(0.0) * spec.sample_rate as f64) as u64;
             // hound's seek multiplies the frame index by the channel count in
             // u32 internally; an out-of-range start would overflow there and
-            // silently land at the wrong position. Reject it instead.
+            // silently land at the wrong position. Read those with 64-bit
+            // offsets instead.
             if start_frame_wide > (u32::MAX / spec.channels as u32) as u64 {
-                return Err(AudioError::SeekOutOfRange { start_secs: start }.into());
+                return Self::load_streamed(path, start_secs, duration_secs);
             }
             reader
                 .seek(start_frame_wide as u32)
@@ -135,26 +216,7 @@ impl WavReader {
             return Err(AudioError::EmptyFile { path: path_buf }.into());
         }
 
-        let (left_channel, right_channel) = match spec.channels {
-            1 => {
-                // Mono: duplicate to both channels (convert to Arc)
-                let arc_samples: Arc<[f32]> = samples.into();
-                (Arc::clone(&arc_samples), arc_samples)
-            }
-            2 => {
-                // Stereo: split interleaved samples
-                let mut left = Vec::with_capacity(samples.len() / 2);
-                let mut right = Vec::with_capacity(samples.len() / 2);
-
-                for chunk in samples.chunks_exact(2) {
-                    left.push(chunk[0]);
-                    right.push(chunk[1]);
-                }
-
-                (left.into(), right.into())
-            }
-            _ => unreachable!(),
-        };
+        let channel_data = split_channels(samples, spec.channels);
 
         tracing::info!(
             path = %path.display(),
@@ -162,26 +224,42 @@ impl WavReader {
             channels = spec.channels,
             format = ?spec.sample_format,
             bits = spec.bits_per_sample,
-            samples = left_channel.len(),
+            samples = channel_data[0].len(),
             "Successfully loaded WAV file"
         );
 
         Ok(Self {
-            left_channel,
-            right_channel,
+            channel_data,
             sample_rate: spec.sample_rate,
             channels: spec.channels,
+            format: AudioFormat::wav(Container::Wav, &spec),
         })
     }
 
-    pub fn get_samples(&self, channel: WaveformChannel) -> &[f32] {
-        match channel {
-            WaveformChannel::Left => &self.left_channel,
-            WaveformChannel::Right => &self.right_channel,
+    /// Samples of `channel`: borrowed for a physical channel, mixed for
+    /// mid/side.
+    pub fn get_samples(&self, channel: WaveformChannel) -> Cow<'_, [f32]> {
+        match channel.physical(self.channels) {
+            Some(index) => Cow::Borrowed(&self.channel_data[index]),
+            None => {
+                let right = WaveformChannel::Right.physical(self.channels).unwrap_or(0);
+                Cow::Owned(channel.mix(&self.channel_data[0], &self.channel_data[right]))
+            }
         }
     }
 }
 
+/// Deinterleave samples into one buffer per channel.
+fn split_channels(samples: Vec<f32>, channels: u16) -> Vec<Arc<[f32]>> {
+    if channels == 1 {
+        return vec![samples.into()];
+    }
+    let channels = channels as usize;
+    (0..channels)
+        .map(|c| samples.iter().skip(c).step_by(channels).copied().collect())
+        .collect()
+}
+
 /// Decode up to `max_samples` interleaved samples as normalized f32, honoring
 /// the file's sample format and bit depth.
 ///
@@ -246,10 +324,139 @@ where
     out
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// The signal to analyze: one channel of the file, or a mix of the first
+/// two.
+///
+/// Serialized (presets, projects) and parsed (`--channel`) as `left`,
+/// `right`, `mid`, `side`, or a 1-based channel number for captures with
+/// more than two channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum WaveformChannel {
+    /// First channel (the only one of a mono file).
     Left,
+    /// Second channel; mono files read their only channel.
     Right,
+    /// Channel by zero-based index, for the third channel onwards. Build
+    /// with [`WaveformChannel::index`] so the first two stay `Left`/`Right`.
+    Index(u16),
+    /// (L + R) / 2: what the two heads share, with uncorrelated noise
+    /// halved.
+    Mid,
+    /// (L − R) / 2: the difference between the heads.
+    Side,
+}
+
+impl WaveformChannel {
+    /// The channel at zero-based `index`.
+    pub fn index(index: u16) -> Self {
+        match index {
+            0 => Self::Left,
+            1 => Self::Right,
+            n => Self::Index(n),
+        }
+    }
+
+    /// Buffer index of this channel in a file with `channels` channels,
+    /// clamped to the last one (so `Right` of a mono file is its only
+    /// channel). `None` for the derived mid/side mixes.
+    pub fn physical(self, channels: u16) -> Option<usize> {
+        let last = channels.max(1) as usize - 1;
+        match self {
+            Self::Left => Some(0),
+            Self::Right => Some(1.min(last)),
+            Self::Index(n) => Some((n as usize).min(last)),
+            Self::Mid | Self::Side => None,
+        }
+    }
+
+    /// Whether this channel exists in a file with `channels` channels
+    /// (mid/side need two).
+    pub fn is_available(self, channels: u16) -> bool {
+        match self {
+            Self::Left => true,
+            Self::Right => true,
+            Self::Index(n) => n < channels,
+            Self::Mid | Self::Side => channels >= 2,
+        }
+    }
+
+    /// Every channel selectable for a file with `channels` channels: each
+    /// physical channel, then mid and side.
+    pub fn available(channels: u16) -> Vec<Self> {
+        let mut list: Vec<Self> = (0..channels.max(1)).map(Self::index).collect();
+        if channels >= 2 {
+            list.extend([Self::Mid, Self::Side]);
+        }
+        list
+    }
+
+    /// Mix `left` and `right` for a derived channel; physical channels
+    /// return `left` unchanged.
+    pub fn mix(self, left: &[f32], right: &[f32]) -> Vec<f32> {
+        let combine: fn(f32, f32) -> f32 = match self {
+            Self::Mid => |l, r| (l + r) * 0.5,
+            Self::Side => |l, r| (l - r) * 0.5,
+            _ => return left.to_vec(),
+        };
+        left.iter().zip(right).map(|(&l, &r)| combine(l, r)).collect()
+    }
+
+    /// Name for selectors and status text.
+    pub fn label(self) -> String {
+        match self {
+            Self::Left => "Left".to_string(),
+            Self::Right => "Right".to_string(),
+            Self::Index(n) => format!("Ch {}", n + 1),
+            Self::Mid => "Mid (L+R)".to_string(),
+            Self::Side => "Side (L−R)".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for WaveformChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+            Self::Index(n) => write!(f, "{}", n + 1),
+            Self::Mid => write!(f, "mid"),
+            Self::Side => write!(f, "side"),
+        }
+    }
+}
+
+impl FromStr for WaveformChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "left" | "l" => Ok(Self::Left),
+            "right" | "r" => Ok(Self::Right),
+            "mid" | "m" => Ok(Self::Mid),
+            "side" | "s" => Ok(Self::Side),
+            other => match other.parse::<u16>() {
+                Ok(n) if n >= 1 => Ok(Self::index(n - 1)),
+                _ => Err(format!(
+                    "unknown channel {s:?} (expected left, right, mid, side or a channel number from 1)"
+                )),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for WaveformChannel {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<WaveformChannel> for String {
+    fn from(channel: WaveformChannel) -> Self {
+        channel.to_string()
+    }
 }
 
 #[cfg(test)]
@@ -304,14 +511,20 @@ mod tests {
 
         assert_eq!(reader.sample_rate, 44100);
         assert_eq!(reader.channels, 1);
-        assert_eq!(reader.left_channel.len(), 5);
-        assert_eq!(reader.right_channel.len(), 5);
+        assert_eq!(reader.get_samples(WaveformChannel::Left).len(), 5);
+        assert_eq!(reader.get_samples(WaveformChannel::Right).len(), 5);
 
         // Check that samples are normalized to f32
         let expected_normalized: Vec<f32> = test_samples.iter().map(|&s| s as f32 / i16::MAX as f32).collect();
 
-        assert_eq!(reader.left_channel.as_ref(), expected_normalized.as_slice());
-        assert_eq!(reader.right_channel.as_ref(), expected_normalized.as_slice());
+        assert_eq!(
+            reader.get_samples(WaveformChannel::Left).as_ref(),
+            expected_normalized.as_slice()
+        );
+        assert_eq!(
+            reader.get_samples(WaveformChannel::Right).as_ref(),
+            expected_normalized.as_slice()
+        );
         // Mono duplicated to both channels
     }
 
@@ -324,15 +537,15 @@ mod tests {
 
         assert_eq!(reader.sample_rate, 48000);
         assert_eq!(reader.channels, 2);
-        assert_eq!(reader.left_channel.len(), 3);
-        assert_eq!(reader.right_channel.len(), 3);
+        assert_eq!(reader.get_samples(WaveformChannel::Left).len(), 3);
+        assert_eq!(reader.get_samples(WaveformChannel::Right).len(), 3);
 
         // Check that left and right channels are separated correctly
         let expected_left = vec![1000.0 / i16::MAX as f32, 3000.0 / i16::MAX as f32, 5000.0 / i16::MAX as f32];
         let expected_right = vec![2000.0 / i16::MAX as f32, 4000.0 / i16::MAX as f32, 6000.0 / i16::MAX as f32];
 
-        assert_eq!(reader.left_channel.as_ref(), expected_left.as_slice());
-        assert_eq!(reader.right_channel.as_ref(), expected_right.as_slice());
+        assert_eq!(reader.get_samples(WaveformChannel::Left).as_ref(), expected_left.as_slice());
+        assert_eq!(reader.get_samples(WaveformChannel::Right).as_ref(), expected_right.as_slice());
     }
 
     #[test]
@@ -349,6 +562,46 @@ mod tests {
         assert_ne!(left_samples, right_samples); // Should be different for stereo
     }
 
+    #[test]
+    fn four_channel_wav_selects_each_channel() {
+        let samples = [0.1_f32, 0.2, 0.3, 0.4, -0.1, -0.2, -0.3, -0.4];
+        let temp_file = create_f32_wav(&samples, 48000, 4);
+
+        let reader = WavReader::from_file(temp_file.path()).unwrap();
+
+        assert_eq!(reader.channels, 4);
+        assert_eq!(reader.get_samples(WaveformChannel::Left).as_ref(), &[0.1, -0.1]);
+        assert_eq!(reader.get_samples(WaveformChannel::Right).as_ref(), &[0.2, -0.2]);
+        assert_eq!(reader.get_samples(WaveformChannel::index(2)).as_ref(), &[0.3, -0.3]);
+        assert_eq!(reader.get_samples(WaveformChannel::index(3)).as_ref(), &[0.4, -0.4]);
+        assert!(!WaveformChannel::index(4).is_available(reader.channels));
+    }
+
+    #[test]
+    fn mid_and_side_mix_the_first_two_channels() {
+        let samples = [0.5_f32, 0.25, 0.0, -0.5];
+        let temp_file = create_f32_wav(&samples, 48000, 2);
+
+        let reader = WavReader::from_file(temp_file.path()).unwrap();
+
+        assert_eq!(reader.get_samples(WaveformChannel::Mid).as_ref(), &[0.375, -0.25]);
+        assert_eq!(reader.get_samples(WaveformChannel::Side).as_ref(), &[0.125, 0.25]);
+        assert!(!WaveformChannel::Mid.is_available(1));
+    }
+
+    #[test]
+    fn channel_names_round_trip() {
+        for channel in WaveformChannel::available(4) {
+            assert_eq!(channel.to_string().parse::<WaveformChannel>(), Ok(channel));
+        }
+        assert_eq!("3".parse::<WaveformChannel>(), Ok(WaveformChannel::Index(2)));
+        // Numbers 1 and 2 name the same channels as left/right
+        assert_eq!("2".parse::<WaveformChannel>(), Ok(WaveformChannel::Right));
+        assert_eq!("M".parse::<WaveformChannel>(), Ok(WaveformChannel::Mid));
+        assert!("0".parse::<WaveformChannel>().is_err());
+        assert!("centre".parse::<WaveformChannel>().is_err());
+    }
+
     #[test]
     fn test_invalid_wav_file() {
         let result = WavReader::from_file("nonexistent_file.wav");
@@ -379,7 +632,7 @@ mod tests {
         let reader = WavReader::from_file(temp_file.path()).unwrap();
 
         assert_eq!(reader.sample_rate, 48000);
-        assert_eq!(reader.left_channel.as_ref(), samples.as_slice());
+        assert_eq!(reader.get_samples(WaveformChannel::Left).as_ref(), samples.as_slice());
     }
 
     #[test]
@@ -391,8 +644,8 @@ mod tests {
         let reader = WavReader::from_file(temp_file.path()).unwrap();
 
         assert_eq!(reader.sample_rate, 384_000);
-        assert_eq!(reader.left_channel.as_ref(), &[0.1, 0.2, 0.3]);
-        assert_eq!(reader.right_channel.as_ref(), &[-0.1, -0.2, -0.3]);
+        assert_eq!(reader.get_samples(WaveformChannel::Left).as_ref(), &[0.1, 0.2, 0.3]);
+        assert_eq!(reader.get_samples(WaveformChannel::Right).as_ref(), &[-0.1, -0.2, -0.3]);
     }
 
     #[test]
@@ -404,14 +657,59 @@ mod tests {
 
         // Window: start at 0.5 s, take 0.25 s => 2000 samples starting at index 4000
         let reader = WavReader::from_file_range(temp_file.path(), 0.5, 0.25).unwrap();
-        assert_eq!(reader.left_channel.len(), (rate as f64 * 0.25) as usize);
-        assert_eq!(reader.left_channel[0], samples[(rate as f64 * 0.5) as usize]);
+        assert_eq!(reader.get_samples(WaveformChannel::Left).len(), (rate as f64 * 0.25) as usize);
+        assert_eq!(
+            reader.get_samples(WaveformChannel::Left)[0],
+            samples[(rate as f64 * 0.5) as usize]
+        );
 
         // Duration past EOF is clamped to what exists
         let reader = WavReader::from_file_range(temp_file.path(), 1.5, 10.0).unwrap();
-        assert_eq!(reader.left_channel.len(), (rate as f64 * 0.5) as usize);
+        assert_eq!(reader.get_samples(WaveformChannel::Left).len(), (rate as f64 * 0.5) as usize);
 
         // Window entirely past EOF is an empty-file error
         assert!(WavReader::from_file_range(temp_file.path(), 5.0, 1.0).is_err());
     }
+
+    #[test]
+    fn flac_and_aiff_decode_like_wav() {
+        use crate::codec::{Codec, Container};
+        use crate::test_fixtures::{create_test_aiff_file, create_test_flac_file, create_test_wav_file};
+
+        // 8 kHz stereo, 1.5 s: several FLAC frames, so the window has to seek
+        let rate = 8000u32;
+        let samples: Vec<f32> = (0..rate as usize * 3).map(|i| ((i % 400) as f32 / 200.0) - 1.0).collect();
+        let wav = WavReader::from_file_range(create_test_wav_file(&samples, rate, 2).path(), 0.7, 0.5).unwrap();
+
+        let cases = [
+            (create_test_flac_file(&samples, rate, 2), Container::Flac, Codec::Flac),
+            (create_test_aiff_file(&samples, rate, 2), Container::Aiff, Codec::Pcm),
+        ];
+        for (file, container, codec) in cases {
+            let reader = WavReader::from_file_range(file.path(), 0.7, 0.5).unwrap();
+            assert_eq!(reader.sample_rate, rate);
+            assert_eq!(reader.channels, 2);
+            assert_eq!(reader.format.container, container);
+            assert_eq!(reader.format.codec, codec);
+            assert_eq!(reader.format.bits_per_sample, Some(16));
+            assert_eq!(
+                reader.get_samples(WaveformChannel::Left).len(),
+                wav.get_samples(WaveformChannel::Left).len(),
+                "{container:?}"
+            );
+            for channel in [WaveformChannel::Left, WaveformChannel::Right] {
+                let max_err = reader
+                    .get_samples(channel)
+                    .iter()
+                    .zip(wav.get_samples(channel).iter())
+                    .map(|(a, b)| (a - b).abs())
+                    .fold(0.0f32, f32::max);
+                assert!(max_err < 1e-3, "{container:?} differs from WAV by {max_err}");
+            }
+
+            let whole = WavReader::from_file(file.path()).unwrap();
+            assert_eq!(whole.get_samples(WaveformChannel::Left).len(), samples.len() / 2);
+            assert!(WavReader::from_file_range(file.path(), 5.0, 1.0).is_err());
+        }
+    }
 }
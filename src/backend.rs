@@ -0,0 +1,581 @@
+//! Pluggable decoding backends.
+//!
+//! Every [`DecoderMode`] is served by one [`Decoder`]: signal levels in,
+//! image out, plus the metadata (capabilities, parameter schema) that the
+//! GUI, CLI and batch runner read instead of matching on the mode.
+//! [`backends`] lists them in display order; [`for_mode`] and [`by_id`]
+//! look one up. A new demodulator is a new `Decoder` impl, a `DecoderMode`
+//! variant to persist the choice, and an entry in the registry.
+
+use std::ops::{Range, RangeInclusive};
+
+use anyhow::{Context, Result};
+
+use crate::analysis::{detect_content, ContentMask, ContentParams, SPEED_FACTOR_RANGE};
+use crate::fm_sstv::FmSstvDecoder;
+use crate::pipeline::{composite_triplet_masked, FrameGroup, PipelineResult, RecordFrames};
+use crate::sample_source::SampleSource;
+use crate::sstv::{
+    normalize_levels, percentile_bounds, percentile_bounds_masked, DecodedLines, DecoderMode, DecoderParams, SstvDecoder,
+};
+
+/// A decoding backend.
+pub trait Decoder: Send + Sync {
+    fn info(&self) -> &'static BackendInfo;
+
+    /// Decode a window of signal levels (samples in [-1, 1]) into an image.
+    fn decode(&self, samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Result<PipelineResult>;
+
+    /// Decode frame `index` of a segmented record. By default the frame's
+    /// samples are decoded as one window; backends with
+    /// [`Capabilities::frames`] decode frame groups instead.
+    fn decode_frame(
+        &self,
+        source: &dyn SampleSource,
+        frames: &RecordFrames,
+        index: usize,
+        params: &DecoderParams,
+    ) -> Result<PipelineResult> {
+        let bounds = frames
+            .bounds()
+            .get(index)
+            .with_context(|| format!("Frame {index} out of range ({} frames)", frames.bounds().len()))?;
+        let samples = source.read(frames.channel(), bounds.start_sample..bounds.end_sample)?;
+        self.decode(&samples, params, source.sample_rate())
+    }
+
+    /// Separate pictures within a whole channel, for batch output: `None`
+    /// when the channel decodes as one image, otherwise one sample range per
+    /// picture (possibly none).
+    fn pictures(&self, _samples: &[f32], _params: &DecoderParams, _sample_rate: u32) -> Option<Vec<Range<usize>>> {
+        None
+    }
+}
+
+/// Static description of a backend.
+#[derive(Debug)]
+pub struct BackendInfo {
+    /// Mode persisted in configs, presets and projects.
+    pub mode: DecoderMode,
+    /// Command-line name (`--mode <id>`), also used in batch file names.
+    pub id: &'static str,
+    /// Display name.
+    pub name: &'static str,
+    pub description: &'static str,
+    pub capabilities: Capabilities,
+    /// Decoder parameters the backend reads; the rest are ignored.
+    pub params: &'static [Param],
+}
+
+impl BackendInfo {
+    pub fn uses(&self, param: Param) -> bool {
+        self.params.contains(&param)
+    }
+}
+
+/// What a backend can do, for callers deciding how to drive it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// May produce RGB output (3 bytes per pixel); a result's own
+    /// [`PipelineResult::mode`] says which it is.
+    pub color: bool,
+    /// Decodes whole frames of a segmented record
+    /// ([`Decoder::decode_frame`]), not arbitrary windows.
+    pub frames: bool,
+    /// Timing follows the record's scan-line cadence, so the playback speed
+    /// factor applies and is worth measuring per file.
+    pub line_cadence: bool,
+    /// Cheap enough to re-decode every live-decode interval during playback.
+    pub live: bool,
+    /// Output is the grayscale line stack [`crate::streaming::StreamingDecoder`]
+    /// produces, so live playback can grow the image line by line instead of
+    /// re-decoding windows.
+    pub streaming: bool,
+    /// Decodes a window from the playhead even when the whole channel is
+    /// resident.
+    pub from_playhead: bool,
+}
+
+/// A tunable in [`DecoderParams`] that a backend may read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Param {
+    LineDuration,
+    SpeedFactor,
+    Width,
+    Invert,
+    Gamma,
+    SyncLock,
+    TimeBase,
+    SstvMode,
+}
+
+/// How a [`Param`] is edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    /// A value within [`Param::range`].
+    Number,
+    Toggle,
+    /// One of a fixed set of named values.
+    Choice,
+}
+
+impl Param {
+    /// Field name in [`DecoderParams`] (and in config/preset tables).
+    pub fn key(&self) -> &'static str {
+        match self {
+            Param::LineDuration => "line_duration_ms",
+            Param::SpeedFactor => "speed_factor",
+            Param::Width => "width",
+            Param::Invert => "invert",
+            Param::Gamma => "gamma",
+            Param::SyncLock => "sync_lock",
+            Param::TimeBase => "time_base",
+            Param::SstvMode => "sstv_mode",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Param::LineDuration => "Line (ms)",
+            Param::SpeedFactor => "Speed ×",
+            Param::Width => "Width",
+            Param::Invert => "Invert",
+            Param::Gamma => "Gamma",
+            Param::SyncLock => "Sync lock",
+            Param::TimeBase => "Wow/flutter fix",
+            Param::SstvMode => "SSTV mode",
+        }
+    }
+
+    pub fn kind(&self) -> ParamKind {
+        match self {
+            Param::LineDuration | Param::SpeedFactor | Param::Width | Param::Gamma => ParamKind::Number,
+            Param::Invert | Param::SyncLock | Param::TimeBase => ParamKind::Toggle,
+            Param::SstvMode => ParamKind::Choice,
+        }
+    }
+
+    /// Editable range of a [`ParamKind::Number`] parameter.
+    pub fn range(&self) -> Option<RangeInclusive<f32>> {
+        match self {
+            Param::LineDuration => Some(1.0..=100.0),
+            Param::SpeedFactor => Some(SPEED_FACTOR_RANGE),
+            Param::Width => Some(1.0..=4096.0),
+            Param::Gamma => Some(0.2..=3.0),
+            Param::Invert | Param::SyncLock | Param::TimeBase | Param::SstvMode => None,
+        }
+    }
+}
+
+/// Parameters of the baseband (record) backends.
+const BASEBAND_PARAMS: &[Param] = &[
+    Param::LineDuration,
+    Param::SpeedFactor,
+    Param::Width,
+    Param::Invert,
+    Param::Gamma,
+    Param::SyncLock,
+    Param::TimeBase,
+];
+
+static BACKENDS: [&dyn Decoder; 4] = [&GrayscaleBackend, &PseudoColorBackend, &FrameTripletBackend, &FmSstvBackend];
+
+/// Every registered backend, in display order.
+pub fn backends() -> &'static [&'static dyn Decoder] {
+    &BACKENDS
+}
+
+/// The backend serving `mode`.
+pub fn for_mode(mode: DecoderMode) -> &'static dyn Decoder {
+    *BACKENDS
+        .iter()
+        .find(|backend| backend.info().mode == mode)
+        .expect("every decoder mode has a registered backend")
+}
+
+/// The backend with command-line name `id`.
+pub fn by_id(id: &str) -> Option<&'static dyn Decoder> {
+    BACKENDS.iter().copied().find(|backend| backend.info().id == id)
+}
+
+/// Baseband decoding with a percentile contrast stretch; see
+/// [`SstvDecoder::decode`].
+pub struct GrayscaleBackend;
+
+static GRAYSCALE_INFO: BackendInfo = BackendInfo {
+    mode: DecoderMode::Grayscale,
+    id: "grayscale",
+    name: "Grayscale",
+    description: "Record baseband video: one scan line per sync, levels to gray",
+    capabilities: Capabilities {
+        color: false,
+        frames: false,
+        line_cadence: true,
+        live: true,
+        streaming: true,
+        from_playhead: false,
+    },
+    params: BASEBAND_PARAMS,
+};
+
+impl Decoder for GrayscaleBackend {
+    fn info(&self) -> &'static BackendInfo {
+        &GRAYSCALE_INFO
+    }
+
+    fn decode(&self, samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Result<PipelineResult> {
+        let lines = SstvDecoder::new()
+            .decode_lines(samples, params, sample_rate)
+            .context("Failed to decode audio")?;
+        let (lo, hi) = percentile_bounds(&lines.levels, 0.01, 0.99);
+        let pixels = normalize_levels(&lines.levels, lo, hi, params.invert, params.gamma);
+        let mut result = gray_result(pixels, params.effective_width(), DecoderMode::Grayscale)?;
+        result.line_sync = Some(lines.sync);
+        result.line_spans = Some(lines.spans);
+        result.levels = Some(lines.levels);
+        Ok(result)
+    }
+}
+
+/// Legacy color: successive decoded lines taken as R, G, B.
+pub struct PseudoColorBackend;
+
+static PSEUDO_COLOR_INFO: BackendInfo = BackendInfo {
+    mode: DecoderMode::PseudoColor,
+    id: "pseudo-color",
+    name: "PseudoColor",
+    description: "Baseband lines grouped in threes as R, G, B (legacy model)",
+    capabilities: Capabilities {
+        color: true,
+        frames: false,
+        line_cadence: true,
+        live: true,
+        streaming: false,
+        from_playhead: false,
+    },
+    params: BASEBAND_PARAMS,
+};
+
+impl Decoder for PseudoColorBackend {
+    fn info(&self) -> &'static BackendInfo {
+        &PSEUDO_COLOR_INFO
+    }
+
+    fn decode(&self, samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Result<PipelineResult> {
+        let decoder = SstvDecoder::new();
+        let lines = decoder
+            .decode_lines(samples, params, sample_rate)
+            .context("Failed to decode audio")?;
+        if lines.skipped > 0 {
+            // Dropped lines shift the positional R/G/B grouping for
+            // everything after the gap; the mode has no way to recover
+            // phase, so at least say so.
+            tracing::warn!(
+                skipped = lines.skipped,
+                "PseudoColor: sync gaps broke line continuity; RGB channel grouping may be shifted"
+            );
+        }
+        let (lo, hi) = percentile_bounds(&lines.levels, 0.01, 0.99);
+        let gray = normalize_levels(&lines.levels, lo, hi, params.invert, params.gamma);
+        let width = params.effective_width();
+        let num_lines = gray.len() / width;
+        let num_color_lines = num_lines / 3;
+        // Warn if we're discarding incomplete color lines (not divisible by 3)
+        if !num_lines.is_multiple_of(3) {
+            tracing::warn!(
+                num_lines,
+                discarded_lines = num_lines % 3,
+                "PseudoColor: incomplete color lines detected, truncating to {} complete color lines",
+                num_color_lines
+            );
+        }
+        anyhow::ensure!(num_color_lines > 0, "Decoded pixels empty");
+
+        let mut pixels = Vec::with_capacity(num_color_lines * width * 3);
+        for rgb in gray.chunks_exact(width * 3) {
+            let (r, rest) = rgb.split_at(width);
+            let (g, b) = rest.split_at(width);
+            for x in 0..width {
+                pixels.extend_from_slice(&[r[x], g[x], b[x]]);
+            }
+        }
+        // Each color row spans its red, green and blue scan lines
+        let spans = lines.spans.chunks_exact(3).map(|rgb| rgb[0].start..rgb[2].end).collect();
+        Ok(PipelineResult {
+            pixels,
+            width: width as u32,
+            height: num_color_lines as u32,
+            mode: DecoderMode::PseudoColor,
+            registration: None,
+            sstv_mode: None,
+            line_sync: None,
+            line_spans: Some(spans),
+            levels: None,
+        })
+    }
+}
+
+/// Voyager color: whole frames of a segmented record, color triplets
+/// composited from their registered R/G/B planes.
+pub struct FrameTripletBackend;
+
+static FRAME_TRIPLET_INFO: BackendInfo = BackendInfo {
+    mode: DecoderMode::FrameTriplet,
+    id: "frame-triplet",
+    name: "Frame triplet",
+    description: "Whole record frames; color triplets composited from registered planes",
+    capabilities: Capabilities {
+        color: true,
+        frames: true,
+        line_cadence: true,
+        live: true,
+        streaming: false,
+        from_playhead: false,
+    },
+    params: BASEBAND_PARAMS,
+};
+
+impl Decoder for FrameTripletBackend {
+    fn info(&self) -> &'static BackendInfo {
+        &FRAME_TRIPLET_INFO
+    }
+
+    fn decode(&self, _samples: &[f32], _params: &DecoderParams, _sample_rate: u32) -> Result<PipelineResult> {
+        anyhow::bail!("FrameTriplet decodes whole frames of a segmented record; use decode_frame")
+    }
+
+    /// A color frame reads and composites all three members of its triplet
+    /// (RGB result, joint contrast bounds, registered planes), a grayscale
+    /// frame decodes alone (Grayscale result, its own bounds).
+    fn decode_frame(
+        &self,
+        source: &dyn SampleSource,
+        frames: &RecordFrames,
+        index: usize,
+        params: &DecoderParams,
+    ) -> Result<PipelineResult> {
+        let decoder = SstvDecoder::new();
+        let width = params.effective_width();
+        let content_params = ContentParams::default();
+        // Lines with their spans made absolute, and the frame's content mask
+        let levels_of = |idx: usize| -> Result<(DecodedLines, ContentMask)> {
+            let b = frames
+                .bounds()
+                .get(idx)
+                .with_context(|| format!("Frame {idx} out of range ({} frames)", frames.bounds().len()))?;
+            let samples = source.read(frames.channel(), b.start_sample..b.end_sample)?;
+            let mut lines = decoder
+                .decode_lines(&samples, params, source.sample_rate())
+                .with_context(|| format!("Failed to decode frame {idx}"))?;
+            for span in &mut lines.spans {
+                *span = span.start + b.start_sample..span.end + b.start_sample;
+            }
+            anyhow::ensure!(lines.levels.len() >= width, "Frame {idx} decoded no complete lines");
+            let mask = detect_content(&lines.levels, width, Some(&lines.locked), &content_params);
+            tracing::debug!(
+                frame = idx,
+                content_rows = mask.content_rows(),
+                rows = mask.height(),
+                columns = ?mask.columns,
+                "Frame content mask"
+            );
+            Ok((lines, mask))
+        };
+
+        match frames.group(index) {
+            FrameGroup::Single(idx) => {
+                let (lines, mask) = levels_of(idx)?;
+                let mut levels = lines.levels;
+                let (lo, hi) = percentile_bounds_masked(&levels, width, &mask, 0.01, 0.99);
+                let height = (levels.len() / width) as u32;
+                levels.truncate(height as usize * width);
+                let pixels = normalize_levels(&levels, lo, hi, params.invert, params.gamma);
+                Ok(PipelineResult {
+                    pixels,
+                    width: width as u32,
+                    height,
+                    mode: DecoderMode::Grayscale,
+                    registration: None,
+                    sstv_mode: None,
+                    line_sync: Some(lines.sync),
+                    line_spans: Some(lines.spans),
+                    levels: Some(levels),
+                })
+            }
+            FrameGroup::Triplet([r, g, b]) => {
+                let ((red, red_mask), (grn, grn_mask), (blu, blu_mask)) = (levels_of(r)?, levels_of(g)?, levels_of(b)?);
+                let composite = composite_triplet_masked(
+                    [&red.levels, &grn.levels, &blu.levels],
+                    [&red_mask, &grn_mask, &blu_mask],
+                    width,
+                    params.invert,
+                    params.gamma,
+                )?;
+                tracing::info!(triplet = ?[r, g, b], registration = %composite.registration, "Composited color triplet");
+                let rgb = composite.image.to_rgb8();
+                Ok(PipelineResult {
+                    width: rgb.width(),
+                    height: rgb.height(),
+                    pixels: rgb.into_raw(),
+                    mode: DecoderMode::FrameTriplet,
+                    registration: Some(composite.registration),
+                    sstv_mode: None,
+                    line_sync: None,
+                    line_spans: None,
+                    levels: None,
+                })
+            }
+        }
+    }
+}
+
+/// Amateur FM SSTV; see [`crate::fm_sstv`].
+pub struct FmSstvBackend;
+
+static FM_SSTV_INFO: BackendInfo = BackendInfo {
+    mode: DecoderMode::Sstv,
+    id: "sstv",
+    name: "SSTV (FM)",
+    description: "Amateur FM SSTV: Martin, Scottie, Robot and PD, mode from the VIS header",
+    capabilities: Capabilities {
+        color: true,
+        frames: false,
+        line_cadence: false,
+        live: false,
+        streaming: false,
+        from_playhead: true,
+    },
+    params: &[Param::SyncLock, Param::SstvMode],
+};
+
+impl Decoder for FmSstvBackend {
+    fn info(&self) -> &'static BackendInfo {
+        &FM_SSTV_INFO
+    }
+
+    fn decode(&self, samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Result<PipelineResult> {
+        let image = FmSstvDecoder::new()
+            .decode(samples, params, sample_rate)
+            .context("Failed to decode SSTV transmission")?;
+        Ok(PipelineResult {
+            pixels: image.pixels,
+            width: image.width as u32,
+            height: image.height as u32,
+            mode: DecoderMode::Sstv,
+            registration: None,
+            sstv_mode: Some(image.mode),
+            line_sync: None,
+            line_spans: None,
+            levels: None,
+        })
+    }
+
+    /// One picture per VIS header, running to the next one. With no header,
+    /// an explicitly selected mode takes the whole channel as one picture.
+    fn pictures(&self, samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Option<Vec<Range<usize>>> {
+        let starts: Vec<usize> = FmSstvDecoder::new()
+            .find_transmissions(samples, sample_rate)
+            .iter()
+            .map(|header| header.start)
+            .collect();
+        if starts.is_empty() {
+            let whole = params.sstv_mode.map(|_| 0..samples.len());
+            return Some(whole.into_iter().collect());
+        }
+        Some(
+            starts
+                .iter()
+                .enumerate()
+                .map(|(i, &start)| start..starts.get(i + 1).copied().unwrap_or(samples.len()))
+                .collect(),
+        )
+    }
+}
+
+/// Wrap grayscale pixels as a result, `width` per row.
+fn gray_result(pixels: Vec<u8>, width: usize, mode: DecoderMode) -> Result<PipelineResult> {
+    // Detect empty pixel data immediately, fail before computing dimensions
+    anyhow::ensure!(!pixels.is_empty(), "Decoded pixels empty");
+    anyhow::ensure!(
+        pixels.len().is_multiple_of(width),
+        "Pixel buffer length ({}) not evenly divisible by row size ({width}) for mode {mode:?}",
+        pixels.len()
+    );
+    Ok(PipelineResult {
+        height: (pixels.len() / width) as u32,
+        pixels,
+        width: width as u32,
+        mode,
+        registration: None,
+        sstv_mode: None,
+        line_sync: None,
+        line_spans: None,
+        levels: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_mode_has_one_backend() {
+        for mode in [
+            DecoderMode::Grayscale,
+            DecoderMode::PseudoColor,
+            DecoderMode::FrameTriplet,
+            DecoderMode::Sstv,
+        ] {
+            let matching = backends().iter().filter(|b| b.info().mode == mode).count();
+            assert_eq!(matching, 1, "{mode:?}");
+            let info = for_mode(mode).info();
+            assert_eq!(by_id(info.id).map(|b| b.info().mode), Some(mode));
+        }
+        assert!(by_id("wefax").is_none());
+    }
+
+    #[test]
+    fn pseudo_color_packs_successive_lines_as_rgb() {
+        let params = DecoderParams {
+            line_duration_ms: 1.0,
+            mode: DecoderMode::PseudoColor,
+            ..Default::default()
+        };
+        // One sample per line: R=high, G=low, B=high -> (255, 0, 255)
+        let result = for_mode(DecoderMode::PseudoColor)
+            .decode(&[1.0, 0.0, 1.0], &params, 1000)
+            .expect("PseudoColor decode should succeed");
+
+        assert_eq!((result.width, result.height), (512, 1));
+        assert_eq!(result.pixels.len(), 512 * 3);
+        for chunk in result.pixels.chunks_exact(3) {
+            assert_eq!(chunk, [255, 0, 255]);
+        }
+    }
+
+    #[test]
+    fn window_backends_report_their_output() {
+        let params = DecoderParams {
+            line_duration_ms: 1.0,
+            width: 4,
+            sync_lock: false,
+            ..Default::default()
+        };
+        let samples: Vec<f32> = (0..30).map(|i| (i % 7) as f32 / 7.0).collect();
+        let gray = for_mode(DecoderMode::Grayscale).decode(&samples, &params, 1000).unwrap();
+        assert_eq!((gray.width, gray.height, gray.pixels.len()), (4, 30, 120));
+        let sync = gray.line_sync.as_deref().expect("grayscale rows are scan lines");
+        assert_eq!(sync.len(), 30);
+        assert!(sync.iter().all(|line| line.state == crate::analysis::SyncState::Fixed));
+        assert_eq!(gray.row_span(2), Some(2..3));
+        assert_eq!(
+            gray.level_at(3, 2),
+            Some(2.0 / 7.0),
+            "levels keep the pre-normalization value"
+        );
+        assert_eq!(gray.level_at(4, 2), None);
+        let pseudo = for_mode(DecoderMode::PseudoColor).decode(&samples, &params, 1000).unwrap();
+        assert_eq!(pseudo.row_span(1), Some(3..6), "a color row spans its three scan lines");
+        assert!(for_mode(DecoderMode::FrameTriplet).decode(&samples, &params, 1000).is_err());
+    }
+}
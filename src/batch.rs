@@ -1,10 +1,15 @@
 use std::fs;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 
-use crate::audio::WavReader;
-use crate::pipeline::DecodingPipeline;
+use crate::analysis::{estimate_speed, SpeedParams};
+use crate::audio::{WavReader, WaveformChannel};
+use crate::backend::{self, Decoder};
+use crate::pipeline::{DecodingPipeline, FrameGroup, RecordFrames};
+use crate::preset::Preset;
+use crate::sample_source::SampleSource;
 use crate::sstv::{DecoderMode, DecoderParams};
 
 #[derive(Debug)]
@@ -12,6 +17,8 @@ pub struct BatchArgs {
     pub input_pattern: String,
     pub output_dir: PathBuf,
     pub mode: DecoderMode,
+    /// Decode settings and channel applied to every file
+    pub preset: Preset,
 }
 
 pub fn run_batch_processing(args: BatchArgs) -> Result<()> {
@@ -19,6 +26,7 @@ pub fn run_batch_processing(args: BatchArgs) -> Result<()> {
     tracing::info!("Input pattern: {}", args.input_pattern);
     tracing::info!("Output directory: {:?}", args.output_dir);
     tracing::info!("Mode: {:?}", args.mode);
+    tracing::info!("Preset: {}", args.preset.name);
 
     // Create output directory if it doesn't exist
     fs::create_dir_all(&args.output_dir).context("Failed to create output directory")?;
@@ -37,15 +45,11 @@ pub fn run_batch_processing(args: BatchArgs) -> Result<()> {
     tracing::info!("Found {} files to process", paths.len());
 
     let pipeline = DecodingPipeline::new();
-    let params = DecoderParams {
-        mode: args.mode,
-        ..DecoderParams::default()
-    };
 
     for path in paths {
         tracing::info!("Processing file: {:?}", path);
 
-        match process_file(&path, &args.output_dir, &pipeline, &params) {
+        match process_file(&path, &args.output_dir, &pipeline, &args.preset, args.mode) {
             Ok(_) => tracing::info!("Successfully processed {:?}", path),
             Err(e) => tracing::error!("Failed to process {:?}: {}", path, e),
         }
@@ -55,12 +59,38 @@ pub fn run_batch_processing(args: BatchArgs) -> Result<()> {
     Ok(())
 }
 
-fn process_file(input_path: &Path, output_dir: &Path, pipeline: &DecodingPipeline, params: &DecoderParams) -> Result<()> {
+fn process_file(
+    input_path: &Path,
+    output_dir: &Path,
+    pipeline: &DecodingPipeline,
+    preset: &Preset,
+    mode: DecoderMode,
+) -> Result<()> {
     // Load WAV file
-    let reader = WavReader::from_file(input_path).context("Failed to load WAV file")?;
+    let reader = WavReader::from_file(input_path).context("Failed to load audio file")?;
+    reader.require_channel(preset.channel)?;
+    let mut params = DecoderParams {
+        mode,
+        ..preset.decoder_params()
+    };
+    let backend = backend::for_mode(mode);
+    let capabilities = backend.info().capabilities;
+    // Rips differ in transfer speed; unless the preset fixes it, each file
+    // is measured on its own (where the backend follows the line cadence).
+    if preset.speed_factor.is_none() && capabilities.line_cadence {
+        params.speed_factor = detect_speed_factor(&reader, input_path, preset.channel, &params)?;
+    }
+    let params = &params;
+
+    if capabilities.frames {
+        return process_record_frames(&reader, input_path, output_dir, pipeline, preset.channel, params);
+    }
 
-    // Use left channel by default for batch processing
-    let samples = reader.get_samples(crate::audio::WaveformChannel::Left);
+    let samples = reader.get_samples(preset.channel);
+    let samples = &*samples;
+    if let Some(pictures) = backend.pictures(samples, params, reader.sample_rate) {
+        return process_pictures(samples, &pictures, &reader, input_path, output_dir, backend, params);
+    }
 
     // Decode
     let result = pipeline
@@ -87,6 +117,108 @@ fn process_file(input_path: &Path, output_dir: &Path, pipeline: &DecodingPipelin
     Ok(())
 }
 
+/// Speed factor of one file from its line cadence (1.0 when none is found).
+fn detect_speed_factor(reader: &WavReader, input_path: &Path, channel: WaveformChannel, params: &DecoderParams) -> Result<f32> {
+    let speed_params = SpeedParams {
+        nominal_line_ms: params.line_duration_ms,
+        ..SpeedParams::default()
+    };
+    let estimate = estimate_speed(reader, channel, &speed_params)?;
+    match estimate {
+        Some(estimate) if !estimate.is_nominal() => {
+            tracing::info!("{:?}: {estimate}", input_path);
+            Ok(estimate.factor)
+        }
+        _ => Ok(1.0),
+    }
+}
+
+/// Frame-triplet batch output: segment the channel into frames and
+/// write `<stem>_frame_NNN.png` per grayscale frame and
+/// `<stem>_color_NNN-MMM.png` per composited color triplet.
+fn process_record_frames(
+    reader: &WavReader,
+    input_path: &Path,
+    output_dir: &Path,
+    pipeline: &DecodingPipeline,
+    channel: WaveformChannel,
+    params: &DecoderParams,
+) -> Result<()> {
+    let file_stem = input_path
+        .file_stem()
+        .ok_or_else(|| anyhow::anyhow!("Invalid filename: {:?} has no stem", input_path))?
+        .to_string_lossy();
+
+    let frames = RecordFrames::segment(reader, channel, params)?;
+    if frames.bounds().is_empty() {
+        tracing::warn!("No image frames found in {:?}", input_path);
+        return Ok(());
+    }
+
+    for group in frames.groups() {
+        let (index, name) = match group {
+            FrameGroup::Single(idx) => (idx, format!("{file_stem}_frame_{idx:03}")),
+            FrameGroup::Triplet(members) => {
+                let (first, last) = (members.iter().min().unwrap_or(&0), members.iter().max().unwrap_or(&0));
+                (members[0], format!("{file_stem}_color_{first:03}-{last:03}"))
+            }
+        };
+        let result = match pipeline.process_frame(reader, &frames, index, params) {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::warn!("Skipping {name} in {:?}: {e:#}", input_path);
+                continue;
+            }
+        };
+        let image_buffer = result.to_dynamic_image().context("Failed to convert pixel data to image")?;
+        let output_path = unique_output_path(output_dir, std::ffi::OsStr::new(&name));
+        image_buffer.save(&output_path).context("Failed to save image")?;
+    }
+
+    Ok(())
+}
+
+/// Multi-picture batch output, for backends that find separate pictures
+/// in a channel ([`Decoder::pictures`]): one `<stem>_<backend id>_NNN.png`
+/// per picture (SSTV: one per transmission).
+fn process_pictures(
+    samples: &[f32],
+    pictures: &[Range<usize>],
+    reader: &WavReader,
+    input_path: &Path,
+    output_dir: &Path,
+    backend: &dyn Decoder,
+    params: &DecoderParams,
+) -> Result<()> {
+    let file_stem = input_path
+        .file_stem()
+        .ok_or_else(|| anyhow::anyhow!("Invalid filename: {:?} has no stem", input_path))?
+        .to_string_lossy();
+    if pictures.is_empty() {
+        tracing::warn!("No {} pictures found in {:?}", backend.info().name, input_path);
+        return Ok(());
+    }
+
+    for (index, range) in pictures.iter().enumerate() {
+        let name = format!("{file_stem}_{}_{index:03}", backend.info().id);
+        let result = match backend.decode(&samples[range.clone()], params, reader.sample_rate) {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::warn!("Skipping {name} in {:?}: {e:#}", input_path);
+                continue;
+            }
+        };
+        if let Some(mode) = result.sstv_mode {
+            tracing::info!("{name}: {mode}, {} lines", result.height);
+        }
+        let image_buffer = result.to_dynamic_image().context("Failed to convert pixel data to image")?;
+        let output_path = unique_output_path(output_dir, std::ffi::OsStr::new(&name));
+        image_buffer.save(&output_path).context("Failed to save image")?;
+    }
+
+    Ok(())
+}
+
 /// Pick a non-colliding output path: `<stem>.png`, then `<stem>_1.png`,
 /// `<stem>_2.png`, ... so batch runs never silently overwrite earlier
 /// outputs (including same-stem inputs from different directories).
@@ -106,9 +238,9 @@ fn unique_output_path(output_dir: &Path, file_stem: &std::ffi::OsStr) -> PathBuf
     }
 }
 
-pub fn process_single_file(path: &Path, output_dir: &Path, params: &DecoderParams) -> Result<()> {
+pub fn process_single_file(path: &Path, output_dir: &Path, preset: &Preset, mode: DecoderMode) -> Result<()> {
     let pipeline = DecodingPipeline::new();
-    process_file(path, output_dir, &pipeline, params)
+    process_file(path, output_dir, &pipeline, preset, mode)
 }
 
 #[cfg(test)]
@@ -131,4 +263,26 @@ mod tests {
         let third = unique_output_path(dir.path(), stem);
         assert_eq!(third, dir.path().join("frame_2.png"));
     }
+
+    #[test]
+    fn test_sstv_batch_writes_one_image_per_transmission() {
+        use crate::fm_sstv::SstvMode;
+        use crate::test_fixtures::{create_test_wav_file, encode_sstv};
+
+        const RATE: u32 = 8_000;
+        let gray = vec![128u8; 320 * 240 * 3];
+        let mut audio = encode_sstv(SstvMode::Robot36, &gray, RATE);
+        audio.extend(vec![0.0; RATE as usize / 2]);
+        audio.extend(encode_sstv(SstvMode::Robot36, &gray, RATE));
+        let wav = create_test_wav_file(&audio, RATE, 1);
+        let out = tempfile::tempdir().unwrap();
+
+        process_single_file(wav.path(), out.path(), &Preset::default(), DecoderMode::Sstv).unwrap();
+
+        let stem = wav.path().file_stem().unwrap().to_string_lossy().into_owned();
+        for index in 0..2 {
+            let image = image::open(out.path().join(format!("{stem}_sstv_{index:03}.png"))).unwrap();
+            assert_eq!((image.width(), image.height()), (320, 240));
+        }
+    }
 }
@@ -37,11 +37,13 @@ pub struct CatalogEntry {
 /// Number of image frames on each stereo channel.
 pub const FRAMES_PER_CHANNEL: usize = 78;
 
-/// The reference frame sequence for a channel.
+/// The reference frame sequence for a channel. Only the right channel of
+/// the record has its own sequence; indexed and mid/side channels are
+/// matched against the left one.
 pub fn channel_catalog(channel: WaveformChannel) -> &'static [CatalogEntry; FRAMES_PER_CHANNEL] {
     match channel {
-        WaveformChannel::Left => &LEFT,
         WaveformChannel::Right => &RIGHT,
+        _ => &LEFT,
     }
 }
 
@@ -1,26 +1,50 @@
 //! Diagnostics CLI: scriptable analysis and decode commands for iterating on
 //! the decoder against real record audio without the GUI.
 
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
 use clap::Subcommand;
 
 use crate::analysis::{
-    classify_segments, compute_stats, detect_line_syncs, find_image_bounds, interval_summary, rolling_stats, ClassifyParams,
-    SegmentImagesParams, SignalStats, SpectrogramParams, SyncParams,
+    compute_stats, detect_line_syncs_block, estimate_speed, find_image_bounds_blocks, find_image_bounds_in, fit_time_base,
+    interval_summary, rolling_stats, sync_block_layout, track_line_syncs_block, ClassifyParams, Segment, SegmentClassifier,
+    SegmentImagesParams, SignalStats, SpectrogramParams, SpeedParams, SyncParams, SyncState, SyncTrack, SPEED_FACTOR_RANGE,
 };
-use crate::audio::{WavReader, WaveformChannel};
-use crate::pipeline::DecodingPipeline;
+use crate::audio::WaveformChannel;
+use crate::backend;
+use crate::codec::Container;
+use crate::compare::{compare_reference, find_references, Baseline, Orientation, Similarity, Tolerance};
+use crate::config::AppConfig;
+use crate::error::AudioError;
+use crate::fm_sstv::SstvMode;
+use crate::golden::{run_manifest, CaseOutcome, GoldenOptions};
+use crate::pcm::{is_stdin, PcmEncoding, PcmStream, RawPcmFormat};
+use crate::pipeline::{DecodingPipeline, FrameGroup, RecordFrames};
+use crate::preset::{Preset, DEFAULT_PRESET};
+use crate::riff::FloatWavWriter;
+use crate::sample_source::{open_source, read_blocks, Block, SampleSource, StreamingWavReader};
 use crate::sstv::{DecoderMode, DecoderParams};
 
+/// Frames per read/write when carving.
+const CARVE_BLOCK_FRAMES: usize = 1 << 20;
+
+/// Frames per read when classifying.
+const CLASSIFY_BLOCK_FRAMES: usize = 1 << 18;
+
 #[derive(Subcommand)]
 pub enum DiagnosticsCommand {
     /// Decode a time window of a WAV file to a PNG image
     Decode {
+        /// Audio file, or `-` for raw PCM on stdin (with --rate)
         #[arg(short, long)]
         input: PathBuf,
+        #[command(flatten)]
+        raw: RawArgs,
         /// Start offset in seconds
         #[arg(short, long, default_value_t = 0.0)]
         start: f64,
@@ -30,27 +54,51 @@ pub enum DiagnosticsCommand {
         /// Output PNG path
         #[arg(short, long)]
         out: PathBuf,
-        /// Channel for stereo files
-        #[arg(short, long, value_enum, default_value_t = ChannelArg::Left)]
-        channel: ChannelArg,
-        /// Image width in pixels
-        #[arg(long, default_value_t = 512)]
-        width: u32,
-        /// Scan line duration in milliseconds
-        #[arg(long, default_value_t = 8.32)]
-        line_ms: f32,
-        /// Invert brightness polarity (rip-dependent)
-        #[arg(long, default_value_t = false)]
+        /// Channel: left, right, mid, side or a number from 1 [default: preset's]
+        #[arg(short, long)]
+        channel: Option<WaveformChannel>,
+        /// Image width in pixels [default: preset's, 512]
+        #[arg(long)]
+        width: Option<u32>,
+        /// Scan line duration in milliseconds [default: preset's, 8.32]
+        #[arg(long)]
+        line_ms: Option<f32>,
+        /// Playback-speed factor of the rip (2 for a double-speed rip), or
+        /// "auto" to measure it from the line cadence [default: preset's,
+        /// auto]
+        #[arg(long)]
+        speed: Option<SpeedArg>,
+        /// Invert brightness polarity (rip-dependent) [default: preset's]
+        #[arg(long, overrides_with = "no_invert")]
         invert: bool,
-        /// Gamma applied after normalization
-        #[arg(long, default_value_t = 1.0)]
-        gamma: f32,
+        /// Keep brightness polarity as recorded, overriding a preset's invert
+        #[arg(long)]
+        no_invert: bool,
+        /// Gamma applied after normalization [default: preset's, 1.0]
+        #[arg(long)]
+        gamma: Option<f32>,
+        /// Align each line to its sync pulse [default: preset's, on]
+        #[arg(long, overrides_with = "no_sync_lock")]
+        sync_lock: bool,
         /// Disable per-line sync alignment (fixed-period slicing instead)
-        #[arg(long, default_value_t = false)]
+        #[arg(long)]
         no_sync_lock: bool,
-        /// Decoder mode
-        #[arg(long, value_enum, default_value_t = CliMode::Grayscale)]
-        mode: CliMode,
+        /// Correct wow and flutter from the line-sync timing before slicing
+        /// (sync-locked decodes) [default: preset's, off]
+        #[arg(long, overrides_with = "no_time_base")]
+        time_base: bool,
+        /// Skip wow/flutter correction, overriding a preset's time_base
+        #[arg(long)]
+        no_time_base: bool,
+        /// Decoding backend (see `backends`). frame-triplet segments the
+        /// whole channel and decodes the frame at --start, composited with its
+        /// color siblings (--duration is ignored)
+        #[arg(long, default_value = "grayscale", value_parser = mode_parser())]
+        mode: DecoderMode,
+        /// Amateur SSTV mode for --mode sstv ("martin-m1", "scottie-s2",
+        /// "robot36", "pd120", ...) [default: read from the VIS header]
+        #[arg(long)]
+        sstv_mode: Option<SstvMode>,
         /// Rotate output 90° clockwise (Voyager lines are vertical scans)
         #[arg(long, default_value_t = false)]
         rotate: bool,
@@ -63,14 +111,16 @@ pub enum DiagnosticsCommand {
     Spectrogram {
         #[arg(short, long)]
         input: PathBuf,
+        #[command(flatten)]
+        raw: RawArgs,
         #[arg(short, long, default_value_t = 0.0)]
         start: f64,
         #[arg(short, long)]
         duration: Option<f64>,
         #[arg(short, long)]
         out: PathBuf,
-        #[arg(short, long, value_enum, default_value_t = ChannelArg::Left)]
-        channel: ChannelArg,
+        #[arg(short, long)]
+        channel: Option<WaveformChannel>,
         /// Upper frequency bound to display, Hz
         #[arg(long)]
         fmax: Option<f32>,
@@ -89,45 +139,79 @@ pub enum DiagnosticsCommand {
     Syncs {
         #[arg(short, long)]
         input: PathBuf,
+        #[command(flatten)]
+        raw: RawArgs,
         #[arg(short, long, default_value_t = 0.0)]
         start: f64,
         #[arg(short, long)]
         duration: Option<f64>,
-        #[arg(short, long, value_enum, default_value_t = ChannelArg::Left)]
-        channel: ChannelArg,
-        /// Expected line duration in milliseconds
-        #[arg(long, default_value_t = 8.32)]
-        line_ms: f32,
-        /// Peak threshold as a fraction of the robust maximum
-        #[arg(long, default_value_t = 0.45)]
-        peak_height: f32,
-        /// Print every position instead of only the summary
+        #[arg(short, long)]
+        channel: Option<WaveformChannel>,
+        /// Expected line duration in milliseconds [default: preset's, 8.32]
+        #[arg(long)]
+        line_ms: Option<f32>,
+        /// Peak threshold as a fraction of the robust maximum [default:
+        /// preset's, 0.45]
+        #[arg(long)]
+        peak_height: Option<f32>,
+        /// Also print the decoder's predictive track, one row per line:
+        /// lock state, swing over the threshold and period estimate
         #[arg(long, default_value_t = false)]
         verbose: bool,
+        /// Also fit the speed curve to the predictive track and print the
+        /// wow/flutter summary and spectrum
+        #[arg(long, default_value_t = false)]
+        flutter: bool,
+    },
+
+    /// Measure the line period across a file and the playback-speed factor
+    /// it implies (e.g. 2 for a rip running at double speed)
+    Speed {
+        #[arg(short, long)]
+        input: PathBuf,
+        #[command(flatten)]
+        raw: RawArgs,
+        #[arg(short, long)]
+        channel: Option<WaveformChannel>,
+        /// Nominal record line duration in milliseconds [default: preset's,
+        /// 8.32]
+        #[arg(long)]
+        line_ms: Option<f32>,
+        /// Number of probe windows spread across the file
+        #[arg(long, default_value_t = 24)]
+        probes: usize,
     },
 
     /// Classify a file into silence / tone / image-periodic / broadband segments
     Classify {
         #[arg(short, long)]
         input: PathBuf,
+        #[command(flatten)]
+        raw: RawArgs,
         #[arg(short, long, default_value_t = 0.0)]
         start: f64,
         #[arg(short, long)]
         duration: Option<f64>,
-        #[arg(short, long, value_enum, default_value_t = ChannelArg::Left)]
-        channel: ChannelArg,
+        #[arg(short, long)]
+        channel: Option<WaveformChannel>,
     },
 
-    /// Print signal statistics for a time window
+    /// List the decoding backends (`--mode` values) with their
+    /// capabilities and the decoder parameters each reads
+    Backends,
+
+    /// Print the file format and signal statistics for a time window
     Stats {
         #[arg(short, long)]
         input: PathBuf,
+        #[command(flatten)]
+        raw: RawArgs,
         #[arg(short, long, default_value_t = 0.0)]
         start: f64,
         #[arg(short, long)]
         duration: Option<f64>,
-        #[arg(short, long, value_enum, default_value_t = ChannelArg::Left)]
-        channel: ChannelArg,
+        #[arg(short, long)]
+        channel: Option<WaveformChannel>,
         /// Also print rolling stats with this window length in seconds
         #[arg(long)]
         rolling: Option<f64>,
@@ -138,24 +222,33 @@ pub enum DiagnosticsCommand {
     Segment {
         #[arg(short, long)]
         input: PathBuf,
+        #[command(flatten)]
+        raw: RawArgs,
         #[arg(short, long, default_value_t = 0.0)]
         start: f64,
         #[arg(short, long)]
         duration: Option<f64>,
-        #[arg(short, long, value_enum, default_value_t = ChannelArg::Left)]
-        channel: ChannelArg,
-        /// Expected line duration in milliseconds
-        #[arg(long, default_value_t = 8.32)]
-        line_ms: f32,
+        #[arg(short, long)]
+        channel: Option<WaveformChannel>,
+        /// Expected line duration in milliseconds [default: preset's, 8.32]
+        #[arg(long)]
+        line_ms: Option<f32>,
+        /// Playback-speed factor of the rip, or "auto" to measure it
+        /// [default: preset's, auto]
+        #[arg(long)]
+        speed: Option<SpeedArg>,
         /// Cadence-break threshold as a multiple of the median sync interval
-        #[arg(long, default_value_t = 1.5)]
-        gap_factor: f32,
-        /// Minimum scan lines for a run to count as an image
-        #[arg(long, default_value_t = 200)]
-        min_lines: usize,
+        /// [default: preset's, 1.5]
+        #[arg(long)]
+        gap_factor: Option<f32>,
+        /// Minimum scan lines for a run to count as an image [default:
+        /// preset's, 200]
+        #[arg(long)]
+        min_lines: Option<usize>,
         /// Nominal lines per image (used for the confidence column)
-        #[arg(long, default_value_t = 600)]
-        expected_lines: usize,
+        /// [default: preset's, 600]
+        #[arg(long)]
+        expected_lines: Option<usize>,
         /// Keep runs that classify as steady tone (lead-in/calibration tone)
         #[arg(long, default_value_t = false)]
         keep_tones: bool,
@@ -167,15 +260,21 @@ pub enum DiagnosticsCommand {
         /// candidate count to match the published 78 frames per channel)
         #[arg(long, default_value_t = false)]
         color: bool,
-        /// Image width in pixels (decode)
-        #[arg(long, default_value_t = 512)]
-        width: u32,
-        /// Invert brightness polarity (decode, rip-dependent)
-        #[arg(long, default_value_t = false)]
+        /// Image width in pixels (decode) [default: preset's, 512]
+        #[arg(long)]
+        width: Option<u32>,
+        /// Invert brightness polarity (decode, rip-dependent) [default:
+        /// preset's]
+        #[arg(long, overrides_with = "no_invert")]
         invert: bool,
-        /// Gamma applied after normalization (decode)
-        #[arg(long, default_value_t = 1.0)]
-        gamma: f32,
+        /// Keep brightness polarity as recorded (decode), overriding a
+        /// preset's invert
+        #[arg(long)]
+        no_invert: bool,
+        /// Gamma applied after normalization (decode) [default: preset's,
+        /// 1.0]
+        #[arg(long)]
+        gamma: Option<f32>,
         /// Rotate output 90° clockwise (decode)
         #[arg(long, default_value_t = false)]
         rotate: bool,
@@ -184,97 +283,480 @@ pub enum DiagnosticsCommand {
         flip: bool,
     },
 
-    /// Cut a time window out of a WAV file into a new (mono) WAV file
+    /// Score decoded frames against reference decodes: each image in the
+    /// reference directory named by catalog index (`image_012_….png`,
+    /// `color_009-011_….png`, as `segment --decode-dir --color` writes) is
+    /// aligned to its decoded frame and scored by SSIM, PSNR and
+    /// correlation. Fails when a score regresses against --baseline
+    Compare {
+        #[arg(short, long)]
+        input: PathBuf,
+        #[command(flatten)]
+        raw: RawArgs,
+        /// Directory of reference images for the channel
+        #[arg(short, long)]
+        references: PathBuf,
+        #[arg(short, long)]
+        channel: Option<WaveformChannel>,
+        /// Scan line duration in milliseconds [default: preset's, 8.32]
+        #[arg(long)]
+        line_ms: Option<f32>,
+        /// Playback-speed factor of the rip, or "auto" to measure it
+        /// [default: preset's, auto]
+        #[arg(long)]
+        speed: Option<SpeedArg>,
+        /// Image width in pixels [default: preset's, 512]
+        #[arg(long)]
+        width: Option<u32>,
+        /// Invert brightness polarity (rip-dependent) [default: preset's]
+        #[arg(long, overrides_with = "no_invert")]
+        invert: bool,
+        /// Keep brightness polarity as recorded, overriding a preset's invert
+        #[arg(long)]
+        no_invert: bool,
+        /// Gamma applied after normalization [default: preset's, 1.0]
+        #[arg(long)]
+        gamma: Option<f32>,
+        /// Rotate decodes 90° clockwise to match the references
+        #[arg(long, default_value_t = false)]
+        rotate: bool,
+        /// Mirror decodes horizontally to match the references
+        #[arg(long, default_value_t = false)]
+        flip: bool,
+        /// Scores of an accepted run (from --save-baseline) to flag
+        /// regressions against
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+        /// Write this run's scores as a baseline
+        #[arg(long)]
+        save_baseline: Option<PathBuf>,
+        /// SSIM drop below the baseline that counts as a regression
+        #[arg(long, default_value_t = Tolerance::default().ssim)]
+        ssim_tolerance: f64,
+        /// PSNR drop below the baseline, dB, that counts as a regression
+        #[arg(long, default_value_t = Tolerance::default().psnr_db)]
+        psnr_tolerance: f64,
+        /// Correlation drop below the baseline that counts as a regression
+        #[arg(long, default_value_t = Tolerance::default().correlation)]
+        corr_tolerance: f64,
+    },
+
+    /// Decoder regression check: decode every case of a golden manifest and
+    /// compare each with its baseline image (see `golden.rs` for the
+    /// format). Fails when a case falls below its SSIM tolerance, writing
+    /// actual/expected/diff images to --diff-dir
+    Golden {
+        /// Golden manifest (TOML)
+        #[arg(short, long, default_value = "tests/golden/manifest.toml")]
+        manifest: PathBuf,
+        /// Rewrite the baselines from the current decoder instead of
+        /// checking against them
+        #[arg(long, default_value_t = false)]
+        bless: bool,
+        /// Where failing cases write their images
+        #[arg(long, default_value = "target/golden-diffs")]
+        diff_dir: PathBuf,
+        /// Run only the case with this name
+        #[arg(long)]
+        case: Option<String>,
+    },
+
+    /// Cut a time window out of an audio file into a new (mono) float WAV,
+    /// RF64 or Wave64 file
     Carve {
         #[arg(short, long)]
         input: PathBuf,
+        #[command(flatten)]
+        raw: RawArgs,
         #[arg(short, long, default_value_t = 0.0)]
         start: f64,
         #[arg(short, long)]
         duration: f64,
         #[arg(short, long)]
         out: PathBuf,
-        #[arg(short, long, value_enum, default_value_t = ChannelArg::Left)]
-        channel: ChannelArg,
+        #[arg(short, long)]
+        channel: Option<WaveformChannel>,
+        /// Output container [default: from the --out extension: .w64 is
+        /// Wave64, .rf64 is RF64, anything else WAV, which switches to RF64
+        /// past 4 GB]
+        #[arg(long, value_enum)]
+        container: Option<ContainerArg>,
     },
 }
 
+/// Headerless PCM input: `--rate` reads the input as raw interleaved
+/// samples, and is required to read stdin (`-`).
+#[derive(clap::Args, Clone, Copy, Debug)]
+pub struct RawArgs {
+    /// Read the input as headerless interleaved PCM at this sample rate, Hz
+    /// (required for `-`, stdin)
+    #[arg(long)]
+    rate: Option<u32>,
+    /// Channel count of raw PCM input
+    #[arg(long, default_value_t = 1, requires = "rate")]
+    channels: u16,
+    /// Sample encoding of raw PCM input (little-endian)
+    #[arg(long, value_enum, default_value = "s16le", requires = "rate")]
+    format: EncodingArg,
+}
+
+impl RawArgs {
+    fn format(&self) -> Option<RawPcmFormat> {
+        self.rate.map(|sample_rate| RawPcmFormat {
+            sample_rate,
+            channels: self.channels,
+            encoding: self.format.into(),
+        })
+    }
+}
+
 #[derive(clap::ValueEnum, Clone, Copy, Debug)]
-pub enum ChannelArg {
-    Left,
-    Right,
+pub enum EncodingArg {
+    U8,
+    S16le,
+    S24le,
+    S32le,
+    F32le,
 }
 
-impl From<ChannelArg> for WaveformChannel {
-    fn from(val: ChannelArg) -> Self {
+impl From<EncodingArg> for PcmEncoding {
+    fn from(val: EncodingArg) -> Self {
         match val {
-            ChannelArg::Left => WaveformChannel::Left,
-            ChannelArg::Right => WaveformChannel::Right,
+            EncodingArg::U8 => PcmEncoding::U8,
+            EncodingArg::S16le => PcmEncoding::S16,
+            EncodingArg::S24le => PcmEncoding::S24,
+            EncodingArg::S32le => PcmEncoding::S32,
+            EncodingArg::F32le => PcmEncoding::F32,
         }
     }
 }
 
 #[derive(clap::ValueEnum, Clone, Copy, Debug)]
-pub enum CliMode {
-    Grayscale,
-    PseudoColor,
+pub enum ContainerArg {
+    Wav,
+    Rf64,
+    W64,
 }
 
-impl From<CliMode> for DecoderMode {
-    fn from(val: CliMode) -> Self {
+impl From<ContainerArg> for Container {
+    fn from(val: ContainerArg) -> Self {
         match val {
-            CliMode::Grayscale => DecoderMode::Grayscale,
-            CliMode::PseudoColor => DecoderMode::PseudoColor,
+            ContainerArg::Wav => Container::Wav,
+            ContainerArg::Rf64 => Container::Rf64,
+            ContainerArg::W64 => Container::Wave64,
         }
     }
 }
 
-/// Load the requested window/channel of a WAV file. Returns the shared
-/// channel buffer without copying it.
-fn load_window(input: &PathBuf, start: f64, duration: Option<f64>, channel: ChannelArg) -> Result<(Arc<[f32]>, u32)> {
-    let reader = match duration {
-        Some(d) => WavReader::from_file_range(input, start, d),
-        None if start > 0.0 => WavReader::from_file_start(input, start),
-        None => WavReader::from_file(input),
+impl ContainerArg {
+    fn for_path(path: &std::path::Path) -> Self {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("w64") => Self::W64,
+            Some("rf64") => Self::Rf64,
+            _ => Self::Wav,
+        }
     }
-    .with_context(|| format!("loading {}", input.display()))?;
-    let samples = match WaveformChannel::from(channel) {
-        WaveformChannel::Left => Arc::clone(&reader.left_channel),
-        WaveformChannel::Right => Arc::clone(&reader.right_channel),
+}
+
+/// `--speed`: a fixed playback-speed factor, or `auto` to measure it.
+#[derive(Clone, Copy, Debug)]
+pub enum SpeedArg {
+    Auto,
+    Factor(f32),
+}
+
+impl FromStr for SpeedArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(SpeedArg::Auto);
+        }
+        let factor: f32 = s.parse().map_err(|_| format!("expected \"auto\" or a factor, got {s:?}"))?;
+        if !SPEED_FACTOR_RANGE.contains(&factor) {
+            return Err(format!("speed factor {factor} out of range 0.25-4"));
+        }
+        Ok(SpeedArg::Factor(factor))
+    }
+}
+
+/// `--mode` values: the ids of the registered decoding backends.
+pub fn mode_parser() -> impl TypedValueParser<Value = DecoderMode> {
+    let ids = backend::backends()
+        .iter()
+        .map(|b| PossibleValue::new(b.info().id).help(b.info().description));
+    PossibleValuesParser::new(ids).map(|id| backend::by_id(&id).expect("parser only accepts registered ids").info().mode)
+}
+
+/// Open the input for ranged reads (WAV and raw PCM streamed from disk,
+/// other formats decoded), check that it has `channel`, and resolve the
+/// `--start`/`--duration` window to a frame range (`None` duration runs to
+/// the end of the input). Stdin is read once, so only the window is kept:
+/// its frame range starts at 0.
+fn open_window(
+    input: &PathBuf,
+    raw: &RawArgs,
+    start: f64,
+    duration: Option<f64>,
+    channel: WaveformChannel,
+) -> Result<(Arc<dyn SampleSource>, Range<usize>)> {
+    let loading = || format!("loading {}", input.display());
+    let source: Arc<dyn SampleSource> = match (raw.format(), is_stdin(input)) {
+        (None, true) => anyhow::bail!("reading stdin needs --rate (and --channels/--format) to describe the raw PCM"),
+        (None, false) => open_source(input, 0).with_context(loading)?,
+        (Some(format), false) => Arc::new(StreamingWavReader::open_raw(input, format).with_context(loading)?),
+        (Some(format), true) => {
+            let stream = open_stream(input, format, start, duration)?;
+            let source = stream.into_resident().with_context(loading)?;
+            source.require_channel(channel)?;
+            let len = SampleSource::len(&source);
+            return Ok((Arc::new(source), 0..len));
+        }
+    };
+    source.require_channel(channel)?;
+    let rate = source.sample_rate() as f64;
+    let first = (start.max(0.0) * rate) as usize;
+    let last = match duration {
+        Some(d) => first.saturating_add((d.max(0.0) * rate) as usize),
+        None => source.len(),
     };
-    Ok((samples, reader.sample_rate))
+    let range = first..last.min(source.len());
+    Ok((source, range))
+}
+
+/// Open raw PCM as a one-pass stream positioned at `start` and limited to
+/// `duration`.
+fn open_stream(input: &Path, format: RawPcmFormat, start: f64, duration: Option<f64>) -> Result<PcmStream> {
+    let mut stream = PcmStream::open(input, format).with_context(|| format!("loading {}", input.display()))?;
+    let rate = format.sample_rate as f64;
+    stream.skip((start.max(0.0) * rate) as usize)?;
+    if let Some(d) = duration {
+        stream.limit((d.max(0.0) * rate) as usize);
+    }
+    Ok(stream)
+}
+
+/// The `--start`/`--duration` window of the input for commands that walk it
+/// once, front to back: ranged reads of a file, or stdin as it arrives, so
+/// piped input is never buffered whole.
+enum Walk {
+    Source(Arc<dyn SampleSource>, Range<usize>),
+    Stream(PcmStream),
 }
 
-pub fn run(command: DiagnosticsCommand) -> Result<()> {
+impl Walk {
+    fn open(input: &PathBuf, raw: &RawArgs, start: f64, duration: Option<f64>, channel: WaveformChannel) -> Result<Self> {
+        match raw.format() {
+            Some(format) if is_stdin(input) => {
+                let stream = open_stream(input, format, start, duration)?;
+                anyhow::ensure!(
+                    channel.is_available(format.channels),
+                    AudioError::ChannelNotPresent {
+                        channel: channel.to_string(),
+                        channels: format.channels,
+                    }
+                );
+                Ok(Self::Stream(stream))
+            }
+            _ => {
+                let (source, range) = open_window(input, raw, start, duration, channel)?;
+                Ok(Self::Source(source, range))
+            }
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        match self {
+            Self::Source(source, _) => source.sample_rate(),
+            Self::Stream(stream) => stream.sample_rate(),
+        }
+    }
+
+    /// Blocks of `channel` as [`read_blocks`] lays them out, and the frame
+    /// the walk starts at (block offsets count from the start of the input).
+    fn blocks(self, channel: WaveformChannel, block_len: usize, margin: usize) -> (Blocks, usize) {
+        match self {
+            Self::Source(source, range) => {
+                let origin = range.start;
+                (Box::new(read_blocks(source, channel, range, block_len, margin)), origin)
+            }
+            Self::Stream(stream) => {
+                let origin = stream.position();
+                (Box::new(stream.blocks(channel, block_len, margin)), origin)
+            }
+        }
+    }
+}
+
+type Blocks = Box<dyn Iterator<Item = crate::error::Result<Block>>>;
+
+/// Read the requested window/channel of an audio file. For WAV only that
+/// range is read from disk, so windows into multi-gigabyte rips stay cheap.
+fn load_window(
+    input: &PathBuf,
+    raw: &RawArgs,
+    start: f64,
+    duration: Option<f64>,
+    channel: WaveformChannel,
+) -> Result<(Vec<f32>, u32)> {
+    let (source, range) = open_window(input, raw, start, duration, channel)?;
+    let samples = source.read(channel, range)?;
+    if samples.is_empty() {
+        return Err(AudioError::EmptyFile { path: input.clone() }.into());
+    }
+    Ok((samples, source.sample_rate()))
+}
+
+/// Look up `--preset` among the built-in and config-file presets (the
+/// default preset when omitted). A config file that fails to load or
+/// validate falls back to the built-ins, as the GUI does.
+pub fn resolve_preset(name: Option<&str>) -> Result<Preset> {
+    let config = AppConfig::load_or_default(AppConfig::default_path());
+    let config = match config.validate() {
+        Ok(()) => config,
+        Err(e) => {
+            tracing::warn!("Ignoring invalid config: {e}");
+            AppConfig::default()
+        }
+    };
+    let name = name.unwrap_or(DEFAULT_PRESET);
+    config.preset(name).with_context(|| {
+        let known: Vec<String> = config.presets().into_iter().map(|p| format!("\"{}\"", p.name)).collect();
+        format!("unknown preset \"{name}\" (available: {})", known.join(", "))
+    })
+}
+
+/// Resolve `--speed` to a factor: an explicit value, else the preset's fixed
+/// factor, else measured. Measuring probes the whole channel and reports a
+/// non-unity result, since it changes every timing downstream.
+fn resolve_speed(
+    speed: Option<SpeedArg>,
+    preset: &Preset,
+    source: &dyn SampleSource,
+    channel: WaveformChannel,
+    line_ms: f32,
+) -> Result<f32> {
+    let speed = speed.unwrap_or(match preset.speed_factor {
+        Some(factor) => SpeedArg::Factor(factor),
+        None => SpeedArg::Auto,
+    });
+    match speed {
+        SpeedArg::Factor(factor) => Ok(factor),
+        SpeedArg::Auto => {
+            let params = SpeedParams {
+                nominal_line_ms: line_ms,
+                ..SpeedParams::default()
+            };
+            let estimate = estimate_speed(source, channel, &params)?;
+            match estimate {
+                Some(estimate) if !estimate.is_nominal() => {
+                    println!("detected {estimate}");
+                    Ok(estimate.factor)
+                }
+                _ => Ok(1.0),
+            }
+        }
+    }
+}
+
+/// `--speed` for stdin, which cannot be probed ahead: `auto` keeps the
+/// nominal speed.
+fn stream_speed(speed: Option<SpeedArg>, preset: &Preset) -> f32 {
+    match speed.or(preset.speed_factor.map(SpeedArg::Factor)) {
+        Some(SpeedArg::Factor(factor)) => factor,
+        _ => {
+            tracing::warn!("cannot measure the speed factor of stdin; assuming 1 (pass --speed to set it)");
+            1.0
+        }
+    }
+}
+
+/// The setting given by a `--flag`/`--no-flag` pair (the later one wins),
+/// or `None` to fall back to the preset.
+fn flag_pair(on: bool, off: bool) -> Option<bool> {
+    (on || off).then_some(on)
+}
+
+/// Run one diagnostics command. Settings not given on the command line come
+/// from `preset`.
+pub fn run(command: DiagnosticsCommand, preset: &Preset) -> Result<()> {
     match command {
         DiagnosticsCommand::Decode {
             input,
+            raw,
             start,
             duration,
             out,
             channel,
             width,
             line_ms,
+            speed,
             invert,
+            no_invert,
             gamma,
+            sync_lock,
             no_sync_lock,
+            time_base,
+            no_time_base,
             mode,
+            sstv_mode,
             rotate,
             flip,
         } => {
-            let (samples, sample_rate) = load_window(&input, start, duration, channel)?;
-            let params = DecoderParams {
-                line_duration_ms: line_ms,
-                invert,
-                gamma,
-                sync_lock: !no_sync_lock,
-                mode: mode.into(),
-                width,
-                ..DecoderParams::default()
+            let channel = channel.unwrap_or(preset.channel);
+            let (source, range) = open_window(&input, &raw, start, duration, channel)?;
+            let mut params = DecoderParams {
+                line_duration_ms: line_ms.unwrap_or(preset.line_duration_ms),
+                invert: flag_pair(invert, no_invert).unwrap_or(preset.invert),
+                gamma: gamma.unwrap_or(preset.gamma),
+                sync_lock: flag_pair(sync_lock, no_sync_lock).unwrap_or(preset.sync_lock),
+                time_base: flag_pair(time_base, no_time_base).unwrap_or(preset.time_base),
+                mode,
+                width: width.unwrap_or(preset.width),
+                sstv_mode,
+                ..preset.decoder_params()
             };
-            let result = DecodingPipeline::new()
-                .process(&samples, &params, sample_rate)
-                .context("decode failed")?;
+            let capabilities = backend::for_mode(mode).info().capabilities;
+            // Only line-cadence backends have a speed factor to measure
+            if capabilities.line_cadence {
+                params.speed_factor = resolve_speed(speed, preset, &*source, channel, params.line_duration_ms)?;
+            }
+            let result = if capabilities.frames {
+                let frames = RecordFrames::segment(&*source, channel, &params)?;
+                let index = frames
+                    .frame_at(range.start)
+                    .with_context(|| format!("no image frame at {start:.3}s"))?;
+                match frames.group(index) {
+                    FrameGroup::Single(idx) => println!("frame {idx} of {} (grayscale)", frames.bounds().len()),
+                    FrameGroup::Triplet([r, g, b]) => {
+                        println!("frame {index} of {}: color triplet R={r} G={g} B={b}", frames.bounds().len())
+                    }
+                }
+                DecodingPipeline::new()
+                    .process_frame(&*source, &frames, index, &params)
+                    .context("decode failed")?
+            } else {
+                let samples = source.read(channel, range)?;
+                if samples.is_empty() {
+                    return Err(AudioError::EmptyFile { path: input.clone() }.into());
+                }
+                DecodingPipeline::new()
+                    .process(&samples, &params, source.sample_rate())
+                    .context("decode failed")?
+            };
+            if let Some(mode) = result.sstv_mode {
+                println!("SSTV mode {mode}");
+            }
+            if let Some(registration) = &result.registration {
+                println!("registration vs red:");
+                println!("  green {}", registration.green);
+                println!("  blue  {}", registration.blue);
+            }
             let mut img = result.to_dynamic_image().context("building image")?;
             if rotate {
                 img = img.rotate90();
@@ -294,6 +776,7 @@ pub fn run(command: DiagnosticsCommand) -> Result<()> {
 
         DiagnosticsCommand::Spectrogram {
             input,
+            raw,
             start,
             duration,
             out,
@@ -303,7 +786,8 @@ pub fn run(command: DiagnosticsCommand) -> Result<()> {
             fft_size,
             plot_width,
         } => {
-            let (samples, sample_rate) = load_window(&input, start, duration, channel)?;
+            let channel = channel.unwrap_or(preset.channel);
+            let (samples, sample_rate) = load_window(&input, &raw, start, duration, channel)?;
             let params = SpectrogramParams {
                 fft_size,
                 hop: (fft_size / 4).max(1),
@@ -325,29 +809,93 @@ pub fn run(command: DiagnosticsCommand) -> Result<()> {
 
         DiagnosticsCommand::Syncs {
             input,
+            raw,
             start,
             duration,
             channel,
             line_ms,
             peak_height,
             verbose,
+            flutter,
         } => {
-            let (samples, sample_rate) = load_window(&input, start, duration, channel)?;
+            let channel = channel.unwrap_or(preset.channel);
+            let walk = Walk::open(&input, &raw, start, duration, channel)?;
+            let sample_rate = walk.sample_rate();
+            // An explicit --line-ms is the interval expected in the file; the
+            // preset's is nominal, scaled by its speed factor when it fixes one.
             let params = SyncParams {
-                expected_line_ms: line_ms,
-                peak_height,
-                ..SyncParams::default()
+                expected_line_ms: line_ms.unwrap_or_else(|| preset.decoder_params().effective_line_ms()),
+                peak_height: peak_height.unwrap_or(preset.sync.peak_height),
+                ..preset.sync
             };
-            let positions = detect_line_syncs(&samples, sample_rate, &params);
-            println!("{} sync positions detected", positions.len());
+            // Detected block by block. Verbose rows are the decoder's
+            // predictive track of each block, printed as it is found.
+            let (block_len, margin) = sync_block_layout(sample_rate, &params);
+            let (blocks, origin) = walk.blocks(channel, block_len, margin);
             if verbose {
-                println!("{:>12} {:>12} {:>10}", "sample", "abs_secs", "delta");
-                let mut prev: Option<usize> = None;
-                for &p in &positions {
-                    let delta = prev.map(|q| format!("{}", p - q)).unwrap_or_else(|| "-".into());
-                    println!("{:>12} {:>12.4} {:>10}", p, start + p as f64 / sample_rate as f64, delta);
-                    prev = Some(p);
+                println!(
+                    "{:>12} {:>12} {:>10} {:>9} {:>7} {:>10}",
+                    "sample", "abs_secs", "delta", "state", "swing", "period_ms"
+                );
+            }
+            let secs = |p: usize| start + p as f64 / sample_rate as f64;
+            let mut positions: Vec<usize> = Vec::new();
+            let mut tracked: Vec<usize> = Vec::new();
+            let mut tally = SyncState::ALL.map(|state| (state, 0usize));
+            // Every block's track end to end, for the speed fit
+            let mut whole = SyncTrack::default();
+            for block in blocks {
+                let block = block?;
+                positions.extend(
+                    detect_line_syncs_block(&block, sample_rate, &params)
+                        .into_iter()
+                        .map(|p| p - origin),
+                );
+                if !verbose && !flutter {
+                    continue;
                 }
+                let track = track_line_syncs_block(&block, sample_rate, &params);
+                if flutter && track.fallback.is_none() {
+                    whole.positions.extend(track.positions.iter().map(|p| p - origin));
+                    whole.starts.extend(track.starts.iter().map(|p| p - origin as f64));
+                    whole.states.extend(&track.states);
+                    whole.swings.extend(&track.swings);
+                    whole.periods.extend(&track.periods);
+                }
+                if !verbose {
+                    continue;
+                }
+                if let Some(reason) = track.fallback {
+                    println!(
+                        "# from {:.3}s: tracking fell back to raw detections ({reason})",
+                        secs(block.owned.start - origin)
+                    );
+                }
+                for (i, p) in track.positions.iter().map(|p| p - origin).enumerate() {
+                    let delta = tracked.last().map(|q| format!("{}", p - q)).unwrap_or_else(|| "-".into());
+                    println!(
+                        "{:>12} {:>12.4} {:>10} {:>9} {:>7.2} {:>10.4}",
+                        p,
+                        secs(p),
+                        delta,
+                        track.states[i],
+                        track.swings[i],
+                        track.periods[i] / sample_rate as f64 * 1000.0
+                    );
+                    tracked.push(p);
+                }
+                for (entry, (_, count)) in tally.iter_mut().zip(track.tally()) {
+                    entry.1 += count;
+                }
+            }
+            println!("{} sync positions detected", positions.len());
+            if verbose {
+                let counts: Vec<String> = tally
+                    .iter()
+                    .filter(|(_, count)| *count > 0)
+                    .map(|(state, count)| format!("{count} {state}"))
+                    .collect();
+                println!("{} tracked: {}", tracked.len(), counts.join(", "));
             }
             if let Some(summary) = interval_summary(&positions, sample_rate) {
                 println!(
@@ -389,21 +937,69 @@ pub fn run(command: DiagnosticsCommand) -> Result<()> {
                     println!("  {label:>9}: {count}");
                 }
             }
+            if flutter {
+                match fit_time_base(&whole) {
+                    Some(time_base) => {
+                        let report = time_base.flutter(sample_rate);
+                        println!("speed variation: {report}");
+                        // Components within 20 dB of the strongest
+                        println!("{:>10} {:>10}", "hz", "peak_%");
+                        for (hz, peak) in report.spectrum.iter().filter(|(_, peak)| *peak >= report.peak * 0.1) {
+                            println!("{hz:>10.3} {:>10.4}", peak * 100.0);
+                        }
+                    }
+                    None => println!("speed variation: no usable sync track"),
+                }
+            }
+        }
+
+        DiagnosticsCommand::Speed {
+            input,
+            raw,
+            channel,
+            line_ms,
+            probes,
+        } => {
+            let channel = channel.unwrap_or(preset.channel);
+            let (source, _) = open_window(&input, &raw, 0.0, None, channel)?;
+            let params = SpeedParams {
+                nominal_line_ms: line_ms.unwrap_or(preset.line_duration_ms),
+                probes,
+                ..SpeedParams::default()
+            };
+            match estimate_speed(&*source, channel, &params)? {
+                Some(estimate) => {
+                    println!("{estimate}");
+                    if !estimate.is_nominal() {
+                        println!(
+                            "decode/segment apply this with --speed auto (the default) or --speed {:.3}",
+                            estimate.factor
+                        );
+                    }
+                }
+                None => println!("no consistent line cadence found in {probes} probes"),
+            }
         }
 
         DiagnosticsCommand::Classify {
             input,
+            raw,
             start,
             duration,
             channel,
         } => {
-            let (samples, sample_rate) = load_window(&input, start, duration, channel)?;
-            let segments = classify_segments(&samples, sample_rate, &ClassifyParams::default());
+            let channel = channel.unwrap_or(preset.channel);
+            let walk = Walk::open(&input, &raw, start, duration, channel)?;
+            let sample_rate = walk.sample_rate();
+            let params = ClassifyParams {
+                expected_line_ms: preset.decoder_params().effective_line_ms(),
+                ..ClassifyParams::default()
+            };
             println!(
                 "{:>10} {:>10} {:>16} {:>6} {:>10}",
                 "start_s", "end_s", "label", "conf", "period_ms"
             );
-            for seg in segments {
+            let print_segment = |seg: Segment| {
                 println!(
                     "{:>10.3} {:>10.3} {:>16} {:>6.2} {:>10}",
                     start + seg.start_secs,
@@ -412,18 +1008,62 @@ pub fn run(command: DiagnosticsCommand) -> Result<()> {
                     seg.confidence,
                     seg.period_ms.map(|p| format!("{p:.3}")).unwrap_or_else(|| "-".into()),
                 );
+            };
+            // Each segment prints as soon as a label change closes it
+            let mut classifier = SegmentClassifier::new(sample_rate, params);
+            let (blocks, _) = walk.blocks(channel, CLASSIFY_BLOCK_FRAMES, 0);
+            for block in blocks {
+                classifier.push(&block?.samples).into_iter().for_each(print_segment);
+            }
+            classifier.finish().into_iter().for_each(print_segment);
+        }
+
+        DiagnosticsCommand::Backends => {
+            for backend in backend::backends() {
+                let info = backend.info();
+                println!("{:<14} {} - {}", info.id, info.name, info.description);
+                let caps = info.capabilities;
+                let flags: Vec<&str> = [
+                    (caps.color, "color"),
+                    (caps.frames, "segmented frames"),
+                    (caps.line_cadence, "line cadence"),
+                    (caps.live, "live"),
+                    (caps.streaming, "streaming"),
+                    (caps.from_playhead, "from playhead"),
+                ]
+                .into_iter()
+                .filter_map(|(set, name)| set.then_some(name))
+                .collect();
+                println!("  capabilities: {}", flags.join(", "));
+                let params: Vec<String> = info
+                    .params
+                    .iter()
+                    .map(|param| match param.range() {
+                        Some(range) => format!("{} ({}-{})", param.key(), range.start(), range.end()),
+                        None => param.key().to_string(),
+                    })
+                    .collect();
+                println!("  parameters: {}", params.join(", "));
             }
         }
 
         DiagnosticsCommand::Stats {
             input,
+            raw,
             start,
             duration,
             channel,
             rolling,
         } => {
-            let (samples, sample_rate) = load_window(&input, start, duration, channel)?;
+            let channel = channel.unwrap_or(preset.channel);
+            let (source, range) = open_window(&input, &raw, start, duration, channel)?;
+            let samples = source.read(channel, range)?;
+            if samples.is_empty() {
+                return Err(AudioError::EmptyFile { path: input }.into());
+            }
+            let sample_rate = source.sample_rate();
             let stats = compute_stats(&samples, sample_rate);
+            println!("format={} channels={}", source.format(), source.channels());
             println!(
                 "samples={} rate={} duration={:.3}s",
                 samples.len(),
@@ -441,10 +1081,12 @@ pub fn run(command: DiagnosticsCommand) -> Result<()> {
 
         DiagnosticsCommand::Segment {
             input,
+            raw,
             start,
             duration,
             channel,
             line_ms,
+            speed,
             gap_factor,
             min_lines,
             expected_lines,
@@ -453,23 +1095,45 @@ pub fn run(command: DiagnosticsCommand) -> Result<()> {
             color,
             width,
             invert,
+            no_invert,
             gamma,
             rotate,
             flip,
         } => {
-            let (samples, sample_rate) = load_window(&input, start, duration, channel)?;
+            let channel = channel.unwrap_or(preset.channel);
+            let walk = Walk::open(&input, &raw, start, duration, channel)?;
+            if decode_dir.is_some() && matches!(walk, Walk::Stream(_)) {
+                anyhow::bail!("--decode-dir reads each image back, which stdin cannot do; save the stream to a file first");
+            }
+            let sample_rate = walk.sample_rate();
+            let line_ms = line_ms.unwrap_or(preset.line_duration_ms);
+            let speed_factor = match &walk {
+                Walk::Source(source, _) => resolve_speed(speed, preset, &**source, channel, line_ms)?,
+                Walk::Stream(_) => stream_speed(speed, preset),
+            };
             let params = SegmentImagesParams {
                 sync: SyncParams {
-                    expected_line_ms: line_ms,
-                    ..SyncParams::default()
+                    expected_line_ms: line_ms / speed_factor,
+                    ..preset.sync
                 },
-                gap_factor,
-                min_lines,
-                expected_lines,
-                filter_tones: !keep_tones,
-                ..SegmentImagesParams::default()
+                gap_factor: gap_factor.unwrap_or(preset.segment.gap_factor),
+                min_lines: min_lines.unwrap_or(preset.segment.min_lines),
+                expected_lines: expected_lines.unwrap_or(preset.segment.expected_lines),
+                filter_tones: preset.segment.filter_tones && !keep_tones,
+                ..preset.segment
+            };
+            let (bounds, window) = match walk {
+                Walk::Source(source, range) => (
+                    find_image_bounds_in(&*source, channel, range.clone(), &params)?,
+                    Some((source, range)),
+                ),
+                Walk::Stream(stream) => {
+                    let (block_len, margin) = sync_block_layout(sample_rate, &params.sync);
+                    let origin = stream.position();
+                    let blocks = stream.blocks(channel, block_len, margin);
+                    (find_image_bounds_blocks(blocks, origin, sample_rate, &params)?, None)
+                }
             };
-            let bounds = find_image_bounds(&samples, sample_rate, &params);
             println!("{} image candidates", bounds.len());
             println!(
                 "{:>4} {:>10} {:>10} {:>8} {:>7} {:>10} {:>6}",
@@ -487,21 +1151,24 @@ pub fn run(command: DiagnosticsCommand) -> Result<()> {
                 );
             }
 
-            if let Some(dir) = decode_dir {
+            if let (Some(dir), Some((source, range))) = (decode_dir, window) {
                 std::fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+                let invert = flag_pair(invert, no_invert).unwrap_or(preset.invert);
+                let gamma = gamma.unwrap_or(preset.gamma);
                 let decode_params = DecoderParams {
                     line_duration_ms: line_ms,
+                    speed_factor,
                     invert,
                     gamma,
                     sync_lock: true,
                     mode: DecoderMode::Grayscale,
-                    width,
-                    ..DecoderParams::default()
+                    width: width.unwrap_or(preset.width),
+                    ..preset.decoder_params()
                 };
 
                 let catalog = if color {
                     if bounds.len() == crate::catalog::FRAMES_PER_CHANNEL {
-                        Some(crate::catalog::channel_catalog(channel.into()))
+                        Some(crate::catalog::channel_catalog(channel))
                     } else {
                         tracing::warn!(
                             "--color: {} candidates != {} catalog frames; falling back to plain naming",
@@ -527,33 +1194,43 @@ pub fn run(command: DiagnosticsCommand) -> Result<()> {
                 // Triplet members keep their raw levels for the joint-bounds
                 // composite pass, so each frame is decoded exactly once.
                 let triplets = match catalog {
-                    Some(_) => crate::catalog::color_triplets(channel.into()),
+                    Some(_) => crate::catalog::color_triplets(channel),
                     None => Vec::new(),
                 };
 
                 let decoder = crate::sstv::SstvDecoder::new();
                 let plane_width = decode_params.effective_width();
-                // Dense by frame index; only triplet members keep their levels.
-                let mut member_levels: Vec<Option<Vec<f32>>> = vec![None; bounds.len()];
+                let content_params = crate::analysis::ContentParams::default();
+                // Dense by frame index; only triplet members keep their levels
+                // (with their content masks).
+                let mut member_levels: Vec<Option<(Vec<f32>, crate::analysis::ContentMask)>> = vec![None; bounds.len()];
                 for (idx, b) in bounds.iter().enumerate() {
-                    let window = &samples[b.start_sample..b.end_sample];
-                    let levels = match decoder.decode_levels(window, &decode_params, sample_rate) {
-                        Ok(levels) => levels,
+                    let window = source.read(channel, range.start + b.start_sample..range.start + b.end_sample)?;
+                    let lines = match decoder.decode_lines(&window, &decode_params, sample_rate) {
+                        Ok(lines) => lines,
                         Err(e) => {
                             tracing::warn!("image {idx} at {:.3}s failed to decode: {e:#}", start + b.start_secs);
                             continue;
                         }
                     };
-                    // The standalone PNG keeps per-frame contrast bounds.
-                    let (lo, hi) = crate::sstv::percentile_bounds(&levels, 0.01, 0.99);
+                    let levels = lines.levels;
+                    let mask = crate::analysis::detect_content(&levels, plane_width, Some(&lines.locked), &content_params);
+                    // The standalone PNG keeps per-frame contrast bounds, over
+                    // its content only.
+                    let (lo, hi) = crate::sstv::percentile_bounds_masked(&levels, plane_width, &mask, 0.01, 0.99);
                     let frame = crate::pipeline::PipelineResult {
                         pixels: crate::sstv::normalize_levels(&levels, lo, hi, invert, gamma),
                         width: plane_width as u32,
                         height: (levels.len() / plane_width) as u32,
                         mode: DecoderMode::Grayscale,
+                        registration: None,
+                        sstv_mode: None,
+                        line_sync: None,
+                        line_spans: None,
+                        levels: None,
                     };
                     if triplets.iter().any(|t| t.contains(&idx)) {
-                        member_levels[idx] = Some(levels);
+                        member_levels[idx] = Some((levels, mask));
                     }
                     let img = orient(frame.to_dynamic_image().context("building image")?);
                     let name = match catalog {
@@ -568,52 +1245,232 @@ pub fn run(command: DiagnosticsCommand) -> Result<()> {
                 if let Some(cat) = catalog {
                     for triplet in triplets {
                         let [r, _, bl] = triplet;
-                        let planes: Vec<&[f32]> = triplet.iter().filter_map(|&idx| member_levels[idx].as_deref()).collect();
-                        let [pr, pg, pb] = planes.as_slice() else {
+                        let planes: Vec<&(Vec<f32>, crate::analysis::ContentMask)> =
+                            triplet.iter().filter_map(|&idx| member_levels[idx].as_ref()).collect();
+                        let [(pr, mr), (pg, mg), (pb, mb)] = planes.as_slice() else {
                             tracing::warn!("triplet {r}-{bl}: missing decoded frame, skipping composite");
                             continue;
                         };
-                        let img = match crate::pipeline::composite_triplet_levels([pr, pg, pb], plane_width, invert, gamma) {
-                            Ok(img) => orient(img),
+                        let composite =
+                            crate::pipeline::composite_triplet_masked([pr, pg, pb], [mr, mg, mb], plane_width, invert, gamma);
+                        let composite = match composite {
+                            Ok(composite) => composite,
                             Err(e) => {
                                 tracing::warn!("triplet {r}-{bl}: composite failed: {e:#}");
                                 continue;
                             }
                         };
+                        let img = orient(composite.image);
                         let (first, last) = (triplet.iter().min().unwrap(), triplet.iter().max().unwrap());
                         let path = dir.join(format!("color_{first:03}-{last:03}_{}.png", slugify(cat[*first].label)));
                         img.save(&path).with_context(|| format!("writing {}", path.display()))?;
                         println!("  [color {first:03}-{last:03}] -> {}", path.display());
+                        println!("      green {}", composite.registration.green);
+                        println!("      blue  {}", composite.registration.blue);
+                    }
+                }
+            }
+        }
+
+        DiagnosticsCommand::Compare {
+            input,
+            raw,
+            references,
+            channel,
+            line_ms,
+            speed,
+            width,
+            invert,
+            no_invert,
+            gamma,
+            rotate,
+            flip,
+            baseline,
+            save_baseline,
+            ssim_tolerance,
+            psnr_tolerance,
+            corr_tolerance,
+        } => {
+            let channel = channel.unwrap_or(preset.channel);
+            let reference_dir = references;
+            let references = find_references(&reference_dir)?;
+            anyhow::ensure!(
+                !references.is_empty(),
+                "no reference images named by catalog index in {}",
+                reference_dir.display()
+            );
+            let (source, _) = open_window(&input, &raw, 0.0, None, channel)?;
+            let mut params = DecoderParams {
+                line_duration_ms: line_ms.unwrap_or(preset.line_duration_ms),
+                invert: flag_pair(invert, no_invert).unwrap_or(preset.invert),
+                gamma: gamma.unwrap_or(preset.gamma),
+                width: width.unwrap_or(preset.width),
+                ..preset.decoder_params()
+            };
+            params.speed_factor = resolve_speed(speed, preset, &*source, channel, params.line_duration_ms)?;
+            let frames = RecordFrames::segment(&*source, channel, &params)?;
+            let catalog = frames.is_catalog_aligned().then(|| crate::catalog::channel_catalog(channel));
+            match catalog {
+                Some(_) => println!("{} frames on {}", frames.bounds().len(), channel.label()),
+                None => println!(
+                    "{} frames on {} (catalog has {}; indices may not match the references, composites are skipped)",
+                    frames.bounds().len(),
+                    channel.label(),
+                    crate::catalog::FRAMES_PER_CHANNEL
+                ),
+            }
+            let baseline_scores = baseline.as_deref().map(Baseline::load).transpose()?;
+            let tolerance = Tolerance {
+                ssim: ssim_tolerance,
+                psnr_db: psnr_tolerance,
+                correlation: corr_tolerance,
+            };
+            let orientation = Orientation { rotate, flip };
+
+            println!(
+                "{:<10} {:>6} {:>8} {:>6} {:>7}  {:<10} label",
+                "key", "ssim", "psnr_db", "corr", "d_ssim", "flag"
+            );
+            let mut scores = Vec::new();
+            let mut regressions = 0usize;
+            for reference in &references {
+                let label = catalog
+                    .and_then(|cat| cat.get(reference.key.frame()))
+                    .map_or("", |entry| entry.label.split(',').next().unwrap_or(entry.label));
+                let previous = baseline_scores.as_ref().and_then(|b| b.get(reference.key));
+                match compare_reference(&*source, &frames, reference, &params, orientation) {
+                    Ok(comparison) => {
+                        let score = comparison.similarity;
+                        scores.push((reference.key, score));
+                        let delta = previous.map_or_else(String::new, |p| format!("{:+.3}", score.ssim - p.ssim));
+                        let worse = previous.map(|p| tolerance.regressions(&score, p)).unwrap_or_default();
+                        let flag = if worse.is_empty() {
+                            String::new()
+                        } else {
+                            regressions += 1;
+                            format!("REGR {}", worse.join(","))
+                        };
+                        println!(
+                            "{:<10} {:>6.3} {:>8.2} {:>6.3} {delta:>7}  {flag:<10} {label}",
+                            reference.key.to_string(),
+                            score.ssim,
+                            score.psnr_db,
+                            score.correlation,
+                        );
+                    }
+                    Err(e) => {
+                        // A frame that compared before and no longer does
+                        // is a regression too
+                        let flag = if previous.is_some() {
+                            regressions += 1;
+                            "REGR failed"
+                        } else {
+                            "failed"
+                        };
+                        println!(
+                            "{:<10} {:>6} {:>8} {:>6} {:>7}  {flag:<10} {e:#}",
+                            reference.key.to_string(),
+                            "-",
+                            "-",
+                            "-",
+                            ""
+                        );
                     }
                 }
             }
+
+            if !scores.is_empty() {
+                let mean =
+                    |metric: fn(&Similarity) -> f64| scores.iter().map(|(_, s)| metric(s)).sum::<f64>() / scores.len() as f64;
+                println!(
+                    "compared {} of {} references: mean ssim {:.3}, psnr {:.2} dB, corr {:.3}",
+                    scores.len(),
+                    references.len(),
+                    mean(|s| s.ssim),
+                    mean(|s| s.psnr_db),
+                    mean(|s| s.correlation)
+                );
+            }
+            if let Some(path) = save_baseline {
+                Baseline::from_scores(scores).save(&path)?;
+                println!("baseline -> {}", path.display());
+            }
+            if regressions > 0 {
+                anyhow::bail!("{regressions} reference(s) regressed against the baseline");
+            }
+        }
+
+        DiagnosticsCommand::Golden {
+            manifest,
+            bless,
+            diff_dir,
+            case,
+        } => {
+            let options = GoldenOptions {
+                bless,
+                diff_dir,
+                only: case,
+            };
+            let reports = run_manifest(&manifest, &options)?;
+            for report in &reports {
+                match &report.outcome {
+                    CaseOutcome::Passed { ssim } => println!("ok      {:<32} ssim {ssim:.4}", report.name),
+                    CaseOutcome::Blessed { path } => println!("blessed {:<32} -> {}", report.name, path.display()),
+                    CaseOutcome::Skipped { reason } => println!("skipped {:<32} {reason}", report.name),
+                    CaseOutcome::Failed { reason, diff, .. } => {
+                        println!("FAILED  {:<32} {reason}", report.name);
+                        if let Some(dir) = diff {
+                            println!("        images in {}", dir.display());
+                        }
+                    }
+                }
+            }
+            let failed = reports.iter().filter(|r| r.is_failure()).count();
+            println!(
+                "{} cases: {failed} failed, {} skipped",
+                reports.len(),
+                reports
+                    .iter()
+                    .filter(|r| matches!(r.outcome, CaseOutcome::Skipped { .. }))
+                    .count()
+            );
+            if failed > 0 {
+                anyhow::bail!("{failed} golden case(s) failed");
+            }
         }
 
         DiagnosticsCommand::Carve {
             input,
+            raw,
             start,
             duration,
             out,
             channel,
+            container,
         } => {
-            let (samples, sample_rate) = load_window(&input, start, Some(duration), channel)?;
-            let spec = hound::WavSpec {
-                channels: 1,
-                sample_rate,
-                bits_per_sample: 32,
-                sample_format: hound::SampleFormat::Float,
-            };
-            let mut writer = hound::WavWriter::create(&out, spec).with_context(|| format!("creating {}", out.display()))?;
-            for &s in samples.iter() {
-                writer.write_sample(s)?;
+            let channel = channel.unwrap_or(preset.channel);
+            let container = container.unwrap_or_else(|| ContainerArg::for_path(&out));
+            let (source, range) = open_window(&input, &raw, start, Some(duration), channel)?;
+            if range.is_empty() {
+                return Err(AudioError::EmptyFile { path: input }.into());
             }
-            writer.finalize()?;
+            let sample_rate = source.sample_rate();
+            let frames = range.len();
+            // Copied in blocks, so carving gigabytes stays within a fixed
+            // amount of memory.
+            let mut writer = FloatWavWriter::create(&out, container.into(), 1, sample_rate)
+                .with_context(|| format!("creating {}", out.display()))?;
+            for block in read_blocks(&*source, channel, range, CARVE_BLOCK_FRAMES, 0) {
+                writer.write(&block?.samples)?;
+            }
+            let written = writer.finalize()?;
             println!(
-                "carved {:.3}s ({} samples @ {} Hz) from {start:.3}s -> {}",
-                samples.len() as f64 / sample_rate as f64,
-                samples.len(),
+                "carved {:.3}s ({} samples @ {} Hz) from {start:.3}s -> {} ({})",
+                frames as f64 / sample_rate as f64,
+                frames,
                 sample_rate,
-                out.display()
+                out.display(),
+                written.name()
             );
         }
     }
@@ -0,0 +1,446 @@
+//! Container sniffing and non-WAV decoding.
+//!
+//! WAV (including RF64 and Wave64, see [`crate::riff`]) stays on `hound` and
+//! [`crate::sample_source::StreamingWavReader`] for ranged reads from disk. Everything else people actually share the
+//! record in (archival FLAC rips, AIFF/AIFC from Mac tooling, Ogg Vorbis and
+//! MP3 copies) is decoded here with `symphonia`, honoring the same
+//! start/duration window as [`crate::audio::WavReader::from_file_range`].
+
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use hound::{SampleFormat, WavSpec};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{self, CodecParameters, CodecType, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::Time;
+
+use crate::error::{AudioError, Result};
+
+/// File extensions offered by the open dialogs and matched by batch globs.
+pub const AUDIO_EXTENSIONS: &[&str] = &["wav", "rf64", "w64", "flac", "aif", "aiff", "aifc", "ogg", "oga", "mp3"];
+
+/// Audio container, identified from the file's leading bytes rather than
+/// its extension (rips are frequently misnamed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Container {
+    #[default]
+    Wav,
+    /// RF64/BW64: RIFF layout with 64-bit sizes, for files over 4 GB.
+    Rf64,
+    /// Sony Wave64: GUID chunk ids and 64-bit sizes.
+    Wave64,
+    Flac,
+    Aiff,
+    Aifc,
+    Ogg,
+    Mp3,
+    /// Headerless interleaved PCM (see [`crate::pcm`]); never sniffed, the
+    /// layout comes from the caller.
+    Raw,
+}
+
+impl Container {
+    /// Identify the container of `path` from its header. `None` when the
+    /// signature is not recognized; the decoder's own probe gets the final
+    /// say for those.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error when the file cannot be opened.
+    pub fn sniff(path: &Path) -> std::io::Result<Option<Self>> {
+        let mut header = [0u8; 12];
+        let mut file = File::open(path)?;
+        let mut len = 0;
+        while len < header.len() {
+            match file.read(&mut header[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        Ok(Self::from_header(&header[..len]))
+    }
+
+    fn from_header(header: &[u8]) -> Option<Self> {
+        if crate::riff::is_w64(header) {
+            return Some(Self::Wave64);
+        }
+        let at = |range: std::ops::Range<usize>| header.get(range);
+        match (at(0..4), at(8..12)) {
+            (Some(b"RIFF"), Some(b"WAVE")) => Some(Self::Wav),
+            (Some(b"RF64" | b"BW64"), Some(b"WAVE")) => Some(Self::Rf64),
+            (Some(b"FORM"), Some(b"AIFF")) => Some(Self::Aiff),
+            (Some(b"FORM"), Some(b"AIFC")) => Some(Self::Aifc),
+            (Some(b"fLaC"), _) => Some(Self::Flac),
+            (Some(b"OggS"), _) => Some(Self::Ogg),
+            _ => match header {
+                [b'I', b'D', b'3', ..] => Some(Self::Mp3),
+                // MPEG audio frame sync (11 set bits)
+                [0xFF, b, ..] if b & 0xE0 == 0xE0 => Some(Self::Mp3),
+                _ => None,
+            },
+        }
+    }
+
+    /// WAVE-family containers, read by [`crate::sample_source::StreamingWavReader`].
+    pub fn is_wave(self) -> bool {
+        matches!(self, Self::Wav | Self::Rf64 | Self::Wave64)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Wav => "WAV",
+            Self::Rf64 => "RF64",
+            Self::Wave64 => "Wave64",
+            Self::Flac => "FLAC",
+            Self::Aiff => "AIFF",
+            Self::Aifc => "AIFC",
+            Self::Ogg => "Ogg",
+            Self::Mp3 => "MP3",
+            Self::Raw => "Raw",
+        }
+    }
+}
+
+/// Sample encoding inside the container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+    #[default]
+    Pcm,
+    Float,
+    Flac,
+    Vorbis,
+    /// MPEG-1/2 audio layer I, II or III.
+    Mpeg(u8),
+}
+
+impl Codec {
+    fn from_symphonia(codec: CodecType) -> Option<Self> {
+        Some(match codec {
+            codecs::CODEC_TYPE_FLAC => Self::Flac,
+            codecs::CODEC_TYPE_VORBIS => Self::Vorbis,
+            codecs::CODEC_TYPE_MP1 => Self::Mpeg(1),
+            codecs::CODEC_TYPE_MP2 => Self::Mpeg(2),
+            codecs::CODEC_TYPE_MP3 => Self::Mpeg(3),
+            other => {
+                let short_name = symphonia::default::get_codecs().get_codec(other)?.short_name;
+                match short_name.strip_prefix("pcm_") {
+                    Some(kind) if kind.starts_with('f') => Self::Float,
+                    Some(_) => Self::Pcm,
+                    None => return None,
+                }
+            }
+        })
+    }
+
+    pub fn is_lossy(self) -> bool {
+        matches!(self, Self::Vorbis | Self::Mpeg(_))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pcm => "PCM",
+            Self::Float => "float",
+            Self::Flac => "FLAC",
+            Self::Vorbis => "Vorbis",
+            Self::Mpeg(1) => "MPEG layer I",
+            Self::Mpeg(2) => "MPEG layer II",
+            Self::Mpeg(_) => "MP3",
+        }
+    }
+}
+
+/// Container and codec details of a loaded file, for the signal panel and
+/// `stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AudioFormat {
+    pub container: Container,
+    pub codec: Codec,
+    /// Stored bit depth; `None` for lossy codecs, which have none.
+    pub bits_per_sample: Option<u32>,
+}
+
+impl AudioFormat {
+    pub fn wav(container: Container, spec: &WavSpec) -> Self {
+        Self {
+            container,
+            codec: match spec.sample_format {
+                SampleFormat::Float => Codec::Float,
+                SampleFormat::Int => Codec::Pcm,
+            },
+            bits_per_sample: Some(spec.bits_per_sample as u32),
+        }
+    }
+
+    pub fn is_lossy(&self) -> bool {
+        self.codec.is_lossy()
+    }
+}
+
+impl fmt::Display for AudioFormat {
+    /// e.g. "WAV · float 32-bit", "FLAC 24-bit", "Ogg · Vorbis (lossy)".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let container = self.container.name();
+        let codec = self.codec.name();
+        if codec == container {
+            write!(f, "{container}")?;
+        } else {
+            write!(f, "{container} · {codec}")?;
+        }
+        if let Some(bits) = self.bits_per_sample {
+            write!(f, " {bits}-bit")?;
+        }
+        if self.is_lossy() {
+            write!(f, " (lossy)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Interleaved samples of a decoded window.
+pub(crate) struct Decoded {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub format: AudioFormat,
+}
+
+/// Decode a non-WAV file to interleaved, normalized `f32`, keeping only the
+/// frames from `start_secs` for `duration_secs` (both optional, as in
+/// [`crate::audio::WavReader::from_file_range`]).
+///
+/// Seekable containers jump close to the window start and discard the
+/// remainder frame-accurately; others decode from the top. Corrupt packets
+/// become silence, with a warning, instead of failing the load.
+pub(crate) fn decode_file(path: &Path, start_secs: Option<f64>, duration_secs: Option<f64>) -> Result<Decoded> {
+    let decode_failed = |source: SymphoniaError| AudioError::DecodeFailed {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let file = File::open(path).map_err(|e| decode_failed(SymphoniaError::IoError(e)))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+    let format_options = FormatOptions {
+        // Trim encoder delay/padding so lossy files keep the source timing.
+        enable_gapless: true,
+        ..Default::default()
+    };
+    let probed = symphonia::default::get_probe()
+        .format(&hint, stream, &format_options, &MetadataOptions::default())
+        .map_err(decode_failed)?;
+    let mut reader = probed.format;
+
+    let track = reader
+        .default_track()
+        .filter(|t| t.codec_params.codec != codecs::CODEC_TYPE_NULL)
+        .ok_or_else(|| decode_failed(SymphoniaError::Unsupported("no audio track")))?;
+    let track_id = track.id;
+    let params: CodecParameters = track.codec_params.clone();
+
+    let sample_rate = params
+        .sample_rate
+        .ok_or_else(|| decode_failed(SymphoniaError::Unsupported("unknown sample rate")))?;
+    if sample_rate < 8000 {
+        return Err(AudioError::InvalidSampleRate { rate: sample_rate }.into());
+    }
+    if let Some(channels) = params.channels {
+        let count = channels.count() as u16;
+        if count == 0 {
+            return Err(AudioError::UnsupportedChannels { channels: count }.into());
+        }
+    }
+
+    let codec = Codec::from_symphonia(params.codec).unwrap_or(Codec::Pcm);
+    let format = AudioFormat {
+        container: Container::sniff(path).ok().flatten().unwrap_or(match codec {
+            Codec::Flac => Container::Flac,
+            Codec::Vorbis => Container::Ogg,
+            Codec::Mpeg(_) => Container::Mp3,
+            _ => Container::Aiff,
+        }),
+        codec,
+        bits_per_sample: params.bits_per_sample.filter(|_| !codec.is_lossy()),
+    };
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&params, &DecoderOptions::default())
+        .map_err(decode_failed)?;
+
+    let start_frame = start_secs.map_or(0, |s| (s.max(0.0) * sample_rate as f64) as u64);
+    let end_frame = duration_secs.map_or(u64::MAX, |d| {
+        start_frame.saturating_add((d.max(0.0) * sample_rate as f64) as u64)
+    });
+    if start_frame > 0 {
+        let time = Time::from(start_frame as f64 / sample_rate as f64);
+        let seek = reader.seek(
+            SeekMode::Accurate,
+            SeekTo::Time {
+                time,
+                track_id: Some(track_id),
+            },
+        );
+        match seek {
+            Ok(_) => decoder.reset(),
+            Err(SymphoniaError::SeekError(symphonia::core::errors::SeekErrorKind::OutOfRange)) => {
+                return Err(AudioError::EmptyFile {
+                    path: path.to_path_buf(),
+                }
+                .into());
+            }
+            Err(e) => {
+                // Unseekable streams: decode from the top and discard.
+                tracing::debug!(error = %e, "Seek failed, decoding from the start");
+            }
+        }
+    }
+
+    // Packet timestamps are in the track time base; convert to frames.
+    let to_frames = |ts: u64| match params.time_base {
+        Some(tb) => (ts as u128 * sample_rate as u128 * tb.numer as u128 / tb.denom as u128) as u64,
+        None => ts,
+    };
+
+    let mut samples = Vec::new();
+    let mut channels = params.channels.map(|c| c.count() as u16);
+    let mut dropped_packets = 0usize;
+    // Frame the next kept sample belongs to
+    let mut expected = start_frame;
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+
+    loop {
+        let packet = match reader.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            // Chained Ogg streams with a new header: keep what we have.
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(decode_failed(e).into()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        // Each packet's own timestamp, so a dropped packet leaves a silent
+        // gap rather than shifting everything after it
+        let first = to_frames(packet.ts());
+        if first >= end_frame {
+            break;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(e)) => {
+                dropped_packets += 1;
+                tracing::debug!(error = e, "Dropping corrupt packet");
+                continue;
+            }
+            Err(e) => return Err(decode_failed(e).into()),
+        };
+
+        let spec = *decoded.spec();
+        let count = spec.channels.count() as u16;
+        if count == 0 {
+            return Err(AudioError::UnsupportedChannels { channels: count }.into());
+        }
+        channels.get_or_insert(count);
+        let frames = decoded.frames() as u64;
+        let buf = match &mut buffer {
+            Some(buf) if buf.capacity() >= decoded.capacity() * count as usize => buf,
+            _ => buffer.insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+        };
+        buf.copy_interleaved_ref(decoded);
+
+        // Keep the part of this packet inside [start_frame, end_frame),
+        // after silence for any dropped packets before it.
+        let c = count as usize;
+        let gap = first.min(end_frame).saturating_sub(expected);
+        samples.resize(samples.len() + gap as usize * c, 0.0);
+        expected += gap;
+        let keep_from = expected.saturating_sub(first).min(frames);
+        let keep_to = end_frame.saturating_sub(first).min(frames);
+        if keep_from < keep_to {
+            samples.extend_from_slice(&buf.samples()[keep_from as usize * c..keep_to as usize * c]);
+            expected = first + keep_to;
+        }
+    }
+
+    if dropped_packets > 0 {
+        tracing::warn!("Dropped {} corrupt packet(s) while decoding", dropped_packets);
+    }
+
+    Ok(Decoded {
+        samples,
+        sample_rate,
+        channels: channels.unwrap_or(1),
+        format,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::create_corrupt_flac_file;
+
+    #[test]
+    fn sniffs_containers_from_their_signatures() {
+        assert_eq!(Container::from_header(b"RIFF\0\0\0\0WAVE"), Some(Container::Wav));
+        assert_eq!(Container::from_header(b"RF64\xff\xff\xff\xffWAVE"), Some(Container::Rf64));
+        assert_eq!(
+            Container::from_header(b"riff.\x91\xcf\x11\xa5\xd6(\xdb"),
+            Some(Container::Wave64)
+        );
+        assert_eq!(Container::from_header(b"FORM\0\0\0\0AIFC"), Some(Container::Aifc));
+        assert_eq!(Container::from_header(b"fLaC\0\0\0\x22"), Some(Container::Flac));
+        assert_eq!(Container::from_header(b"ID3\x04"), Some(Container::Mp3));
+        assert_eq!(Container::from_header(&[0xFF, 0xFB, 0x90, 0x00]), Some(Container::Mp3));
+        assert_eq!(Container::from_header(b"RIFF\0\0\0\0AVI "), None);
+        assert_eq!(Container::from_header(b""), None);
+    }
+
+    #[test]
+    fn format_labels_name_container_codec_and_depth() {
+        let flac = AudioFormat {
+            container: Container::Flac,
+            codec: Codec::Flac,
+            bits_per_sample: Some(24),
+        };
+        assert_eq!(flac.to_string(), "FLAC 24-bit");
+        let vorbis = AudioFormat {
+            container: Container::Ogg,
+            codec: Codec::Vorbis,
+            bits_per_sample: None,
+        };
+        assert_eq!(vorbis.to_string(), "Ogg · Vorbis (lossy)");
+        assert!(!AudioFormat::default().is_lossy());
+    }
+
+    #[test]
+    fn corrupt_packets_keep_later_packets_on_their_timestamps() {
+        const BLOCK: usize = 4096;
+        // Each sample holds its own frame index; the second packet is bad
+        let ramp: Vec<f32> = (0..6 * BLOCK).map(|n| (n as f32 + 0.5) / i16::MAX as f32).collect();
+        let file = create_corrupt_flac_file(&ramp, 48_000, 1, &[1]);
+        let path = file.path();
+        let frame_of = |sample: f32| (sample * 32768.0).round() as usize;
+
+        // The dropped packet is silence; every other sample keeps its index
+        let head = decode_file(path, None, Some(0.25)).unwrap();
+        assert_eq!(head.samples.len(), 12_000);
+        assert!(head.samples[BLOCK..2 * BLOCK].iter().all(|&s| s == 0.0));
+        for k in (0..BLOCK).chain(2 * BLOCK..12_000) {
+            assert_eq!(frame_of(head.samples[k]), k);
+        }
+
+        let window = decode_file(path, Some(0.25), Some(0.0625)).unwrap();
+        assert_eq!(window.samples.len(), 3000);
+        assert_eq!(frame_of(window.samples[0]), 12_000);
+    }
+}
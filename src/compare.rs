@@ -0,0 +1,835 @@
+//! Reference comparison: scores decoded frames against a directory of
+//! published reference decodes, for the Gate 2 side-by-side review.
+//!
+//! References are keyed by catalog index from their file names, the same
+//! names `segment --decode-dir --color` writes: `image_012_….png` (or any
+//! name whose first number is the index) is frame 12 of the channel, and
+//! `color_009-011_….png` is the color composite of the triplet starting at
+//! frame 9. Each decoded frame is aligned to its reference by a scale/crop
+//! search (references are cropped and resized differently from our 512 px
+//! decodes), then scored by SSIM, PSNR and correlation on luma. Scores can
+//! be saved as a baseline and later runs flagged where they fall below it.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use image::imageops::FilterType;
+use image::{DynamicImage, GrayImage};
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::{DecodingPipeline, FrameGroup, RecordFrames};
+use crate::sample_source::SampleSource;
+use crate::sstv::{DecoderMode, DecoderParams};
+
+/// Image file extensions read as references.
+const REFERENCE_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// Width the decoded frame is reduced to for the coarse alignment search.
+const COARSE_WIDTH: u32 = 64;
+
+/// Width of the refinement pass around the coarse match.
+const FINE_WIDTH: u32 = 160;
+
+/// Smallest share of the decoded frame, per axis, a reference may cover.
+/// Decodes carry blank margins and sync edges the references crop away, but
+/// never most of the frame.
+const MIN_COVER: f64 = 0.5;
+
+/// Coverage step of the coarse search.
+const COVER_STEP: f64 = 0.05;
+
+/// PSNR reported for identical images, so scores stay finite in baselines
+/// and differences.
+pub const PSNR_CAP_DB: f64 = 100.0;
+
+/// What a reference image shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ReferenceKey {
+    /// Frame `index` of the channel, decoded alone.
+    Frame(usize),
+    /// Color composite of the triplet whose first frame (the blue plane,
+    /// in record order) is `index`.
+    Composite(usize),
+}
+
+impl ReferenceKey {
+    /// Key from a file stem: `color…` names a composite by its first
+    /// number, anything else a frame. `None` without a number.
+    pub fn parse(stem: &str) -> Option<Self> {
+        let digits: String = stem
+            .chars()
+            .skip_while(|c| !c.is_ascii_digit())
+            .take_while(char::is_ascii_digit)
+            .collect();
+        let index = digits.parse().ok()?;
+        if stem.to_ascii_lowercase().starts_with("color") {
+            Some(Self::Composite(index))
+        } else {
+            Some(Self::Frame(index))
+        }
+    }
+
+    /// Frame the key decodes from.
+    pub fn frame(&self) -> usize {
+        match *self {
+            Self::Frame(index) | Self::Composite(index) => index,
+        }
+    }
+}
+
+impl fmt::Display for ReferenceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Frame(index) => write!(f, "image_{index:03}"),
+            Self::Composite(index) => write!(f, "color_{index:03}"),
+        }
+    }
+}
+
+impl TryFrom<String> for ReferenceKey {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value).ok_or_else(|| format!("no catalog index in reference key {value:?}"))
+    }
+}
+
+impl From<ReferenceKey> for String {
+    fn from(key: ReferenceKey) -> Self {
+        key.to_string()
+    }
+}
+
+/// A reference image on disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub key: ReferenceKey,
+    pub path: PathBuf,
+}
+
+/// The reference images in `dir`, in key order. Files without a catalog
+/// index are skipped; of two files with the same key the first by name is
+/// kept.
+pub fn find_references(dir: &Path) -> Result<Vec<Reference>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("reading reference directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| REFERENCE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        })
+        .collect();
+    paths.sort();
+    let mut references = BTreeMap::new();
+    for path in paths {
+        let Some(key) = path.file_stem().and_then(|s| s.to_str()).and_then(ReferenceKey::parse) else {
+            tracing::debug!(path = %path.display(), "No catalog index in reference name; skipped");
+            continue;
+        };
+        if let Some(kept) = references.get(&key) {
+            tracing::warn!(
+                path = %path.display(),
+                kept = %Path::new(kept).display(),
+                "Duplicate reference for {key}; skipped"
+            );
+            continue;
+        }
+        references.insert(key, path);
+    }
+    Ok(references.into_iter().map(|(key, path)| Reference { key, path }).collect())
+}
+
+/// How decoded images are turned to match the references: the same
+/// options as `decode --rotate/--flip` (Voyager lines are vertical scans).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Orientation {
+    /// Rotate 90° clockwise
+    pub rotate: bool,
+    /// Mirror horizontally, after rotating
+    pub flip: bool,
+}
+
+impl Orientation {
+    pub fn apply(&self, mut image: DynamicImage) -> DynamicImage {
+        if self.rotate {
+            image = image.rotate90();
+        }
+        if self.flip {
+            image = image.fliph();
+        }
+        image
+    }
+}
+
+/// Similarity of a decoded frame to its reference.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Similarity {
+    /// Mean structural similarity over 8×8 windows, up to 1
+    pub ssim: f64,
+    /// Peak signal-to-noise ratio, dB (capped at [`PSNR_CAP_DB`])
+    pub psnr_db: f64,
+    /// Pearson correlation of the pixel values, -1 to 1
+    pub correlation: f64,
+}
+
+/// Similarity of two equally sized grayscale images.
+pub fn similarity(a: &GrayImage, b: &GrayImage) -> Similarity {
+    assert_eq!(a.dimensions(), b.dimensions(), "similarity needs equally sized images");
+    let (x, y) = (a.as_raw(), b.as_raw());
+    let n = x.len().max(1) as f64;
+    let mse = x.iter().zip(y).map(|(&p, &q)| (p as f64 - q as f64).powi(2)).sum::<f64>() / n;
+    let psnr_db = if mse > 0.0 {
+        (10.0 * (255.0 * 255.0 / mse).log10()).min(PSNR_CAP_DB)
+    } else {
+        PSNR_CAP_DB
+    };
+    Similarity {
+        ssim: ssim(a, b),
+        psnr_db,
+        correlation: correlation(x, y),
+    }
+}
+
+/// Pearson correlation; 1 when both are flat and equal, 0 when only one is
+/// flat.
+fn correlation(x: &[u8], y: &[u8]) -> f64 {
+    let stats = WindowStats::of(x.iter().zip(y).map(|(&p, &q)| (p as f64, q as f64)));
+    let denominator = (stats.var_x * stats.var_y).sqrt();
+    if denominator > 1e-12 {
+        stats.cov / denominator
+    } else if stats.var_x < 1e-12 && stats.var_y < 1e-12 && (stats.mean_x - stats.mean_y).abs() < 0.5 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// SSIM window side and stride.
+const SSIM_WINDOW: u32 = 8;
+const SSIM_STRIDE: u32 = 4;
+
+/// Mean SSIM (Wang et al., uniform windows) over overlapping windows; an
+/// image smaller than a window is one window.
+fn ssim(a: &GrayImage, b: &GrayImage) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+    let (width, height) = a.dimensions();
+    let (wx, wy) = (SSIM_WINDOW.min(width), SSIM_WINDOW.min(height));
+    let mut total = 0.0;
+    let mut windows = 0usize;
+    for y0 in (0..=height.saturating_sub(wy)).step_by(SSIM_STRIDE as usize) {
+        for x0 in (0..=width.saturating_sub(wx)).step_by(SSIM_STRIDE as usize) {
+            let pixels = (y0..y0 + wy)
+                .flat_map(|y| (x0..x0 + wx).map(move |x| (x, y)))
+                .map(|(x, y)| (a.get_pixel(x, y)[0] as f64, b.get_pixel(x, y)[0] as f64));
+            let s = WindowStats::of(pixels);
+            total += ((2.0 * s.mean_x * s.mean_y + C1) * (2.0 * s.cov + C2))
+                / ((s.mean_x.powi(2) + s.mean_y.powi(2) + C1) * (s.var_x + s.var_y + C2));
+            windows += 1;
+        }
+    }
+    if windows == 0 {
+        return 0.0;
+    }
+    total / windows as f64
+}
+
+/// Means, variances and covariance of paired values.
+struct WindowStats {
+    mean_x: f64,
+    mean_y: f64,
+    var_x: f64,
+    var_y: f64,
+    cov: f64,
+}
+
+impl WindowStats {
+    fn of(pairs: impl Iterator<Item = (f64, f64)>) -> Self {
+        let (mut n, mut sx, mut sy, mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        for (x, y) in pairs {
+            n += 1.0;
+            sx += x;
+            sy += y;
+            sxx += x * x;
+            syy += y * y;
+            sxy += x * y;
+        }
+        if n == 0.0 {
+            return Self {
+                mean_x: 0.0,
+                mean_y: 0.0,
+                var_x: 0.0,
+                var_y: 0.0,
+                cov: 0.0,
+            };
+        }
+        let (mean_x, mean_y) = (sx / n, sy / n);
+        Self {
+            mean_x,
+            mean_y,
+            var_x: (sxx / n - mean_x * mean_x).max(0.0),
+            var_y: (syy / n - mean_y * mean_y).max(0.0),
+            cov: sxy / n - mean_x * mean_y,
+        }
+    }
+}
+
+/// Part of the decoded image the reference shows, in decoded pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crop {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Crop {
+    /// The cropped part of `image`, resized to `width`×`height`.
+    pub fn extract(&self, image: &GrayImage, width: u32, height: u32) -> GrayImage {
+        let view = image::imageops::crop_imm(image, self.x, self.y, self.width, self.height).to_image();
+        image::imageops::resize(&view, width, height, FilterType::Triangle)
+    }
+}
+
+/// A candidate placement during the search, in the search image's pixels.
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    score: f64,
+}
+
+/// Find where `reference` lies in `decoded`: the crop, at any per-axis
+/// scale covering at least half the frame, whose resized content
+/// correlates best with the reference. A coarse search over every
+/// coverage and offset is refined edge by edge at a finer scale.
+pub fn align(decoded: &GrayImage, reference: &GrayImage) -> Crop {
+    let full = Crop {
+        x: 0,
+        y: 0,
+        width: decoded.width(),
+        height: decoded.height(),
+    };
+    if decoded.width() < 4 || decoded.height() < 4 || reference.width() == 0 || reference.height() == 0 {
+        return full;
+    }
+
+    let coarse = shrink(decoded, COARSE_WIDTH);
+    let covers: Vec<f64> = (0..)
+        .map(|step| MIN_COVER + step as f64 * COVER_STEP)
+        .take_while(|cover| *cover <= 1.0 + 1e-9)
+        .collect();
+    let mut sizes = Vec::new();
+    for &cover_x in &covers {
+        for &cover_y in &covers {
+            sizes.push((
+                (coarse.width() as f64 * cover_x).round() as u32,
+                (coarse.height() as f64 * cover_y).round() as u32,
+            ));
+        }
+    }
+    let Some(best) = best_placement(&coarse, reference, &sizes) else {
+        return full;
+    };
+
+    // Refine by coordinate descent on the finer image, from the coarse
+    // match scaled up
+    let fine = shrink(decoded, FINE_WIDTH);
+    let (kx, ky) = (
+        fine.width() as f64 / coarse.width() as f64,
+        fine.height() as f64 / coarse.height() as f64,
+    );
+    let start = Placement {
+        x: (best.x as f64 * kx).round() as u32,
+        y: (best.y as f64 * ky).round() as u32,
+        width: ((best.width as f64 * kx).round() as u32).min(fine.width()),
+        height: ((best.height as f64 * ky).round() as u32).min(fine.height()),
+        score: f64::NEG_INFINITY,
+    };
+    let placement = refine(&fine, reference, start);
+
+    // Back to decoded pixels
+    let (sx, sy) = (
+        decoded.width() as f64 / fine.width() as f64,
+        decoded.height() as f64 / fine.height() as f64,
+    );
+    let x = ((placement.x as f64 * sx).round() as u32).min(decoded.width() - 1);
+    let y = ((placement.y as f64 * sy).round() as u32).min(decoded.height() - 1);
+    Crop {
+        x,
+        y,
+        width: ((placement.width as f64 * sx).round() as u32).clamp(1, decoded.width() - x),
+        height: ((placement.height as f64 * sy).round() as u32).clamp(1, decoded.height() - y),
+    }
+}
+
+/// `image` reduced to `width` (never enlarged), keeping its aspect.
+fn shrink(image: &GrayImage, width: u32) -> GrayImage {
+    if image.width() <= width {
+        return image.clone();
+    }
+    let height = ((image.height() as u64 * width as u64) / image.width() as u64).max(1) as u32;
+    image::imageops::resize(image, width, height, FilterType::Triangle)
+}
+
+/// Best normalized-correlation placement of `reference`, resized to each
+/// of `sizes`, over every offset in `search`.
+fn best_placement(search: &GrayImage, reference: &GrayImage, sizes: &[(u32, u32)]) -> Option<Placement> {
+    let mut best: Option<Placement> = None;
+    for &(width, height) in sizes {
+        let Some(template) = Template::new(search, reference, width, height) else {
+            continue;
+        };
+        for y in 0..=search.height() - height {
+            for x in 0..=search.width() - width {
+                let score = template.score(search, x, y);
+                if best.is_none_or(|b| score > b.score) {
+                    best = Some(Placement {
+                        x,
+                        y,
+                        width,
+                        height,
+                        score,
+                    });
+                }
+            }
+        }
+    }
+    best
+}
+
+/// Move each edge of `start` by 4, 2, then 1 pixels while that raises the
+/// correlation.
+fn refine(search: &GrayImage, reference: &GrayImage, start: Placement) -> Placement {
+    const MAX_MOVES: usize = 64;
+    let score = |p: &Placement| {
+        let fits = p.x + p.width <= search.width() && p.y + p.height <= search.height();
+        fits.then(|| Template::new(search, reference, p.width, p.height))
+            .flatten()
+            .map(|t| t.score(search, p.x, p.y))
+    };
+    let mut best = start;
+    best.score = score(&best).unwrap_or(f64::NEG_INFINITY);
+    for step in [4i64, 2, 1] {
+        for _ in 0..MAX_MOVES {
+            let mut moved = false;
+            for (dx, dy, dw, dh) in [
+                (1, 0, -1, 0),
+                (-1, 0, 1, 0),
+                (0, 0, 1, 0),
+                (0, 0, -1, 0),
+                (0, 1, 0, -1),
+                (0, -1, 0, 1),
+                (0, 0, 0, 1),
+                (0, 0, 0, -1),
+            ] {
+                let shifted = |v: u32, d: i64| u32::try_from(v as i64 + d * step).ok();
+                let (Some(x), Some(y), Some(width), Some(height)) = (
+                    shifted(best.x, dx),
+                    shifted(best.y, dy),
+                    shifted(best.width, dw),
+                    shifted(best.height, dh),
+                ) else {
+                    continue;
+                };
+                let mut candidate = Placement {
+                    x,
+                    y,
+                    width,
+                    height,
+                    score: 0.0,
+                };
+                match score(&candidate) {
+                    Some(s) if s > best.score + 1e-9 => {
+                        candidate.score = s;
+                        best = candidate;
+                        moved = true;
+                    }
+                    _ => {}
+                }
+            }
+            if !moved {
+                break;
+            }
+        }
+    }
+    best
+}
+
+/// The reference resized to a candidate size, zero-mean.
+struct Template {
+    values: Vec<f64>,
+    norm: f64,
+    width: u32,
+    height: u32,
+}
+
+impl Template {
+    /// `None` when the size does not fit `search` or the resized reference
+    /// is flat.
+    fn new(search: &GrayImage, reference: &GrayImage, width: u32, height: u32) -> Option<Self> {
+        if width < 2 || height < 2 || width > search.width() || height > search.height() {
+            return None;
+        }
+        let resized = image::imageops::resize(reference, width, height, FilterType::Triangle);
+        let mean = resized.as_raw().iter().map(|&v| v as f64).sum::<f64>() / resized.len() as f64;
+        let values: Vec<f64> = resized.as_raw().iter().map(|&v| v as f64 - mean).collect();
+        let norm = values.iter().map(|v| v * v).sum::<f64>().sqrt();
+        (norm > 1e-9).then_some(Self {
+            values,
+            norm,
+            width,
+            height,
+        })
+    }
+
+    fn score(&self, image: &GrayImage, x: u32, y: u32) -> f64 {
+        window_correlation(image, x, y, self.width, self.height, &self.values, self.norm)
+    }
+}
+
+/// Correlation of the `width`×`height` window of `image` at (`x`, `y`) with
+/// a zero-mean template of norm `t_norm`.
+fn window_correlation(image: &GrayImage, x: u32, y: u32, width: u32, height: u32, t: &[f64], t_norm: f64) -> f64 {
+    let stride = image.width() as usize;
+    let raw = image.as_raw();
+    let (mut sum, mut sum_sq, mut dot) = (0.0, 0.0, 0.0);
+    for row in 0..height as usize {
+        let start = (y as usize + row) * stride + x as usize;
+        let pixels = &raw[start..start + width as usize];
+        let template = &t[row * width as usize..(row + 1) * width as usize];
+        for (&p, &q) in pixels.iter().zip(template) {
+            let p = p as f64;
+            sum += p;
+            sum_sq += p * p;
+            dot += p * q;
+        }
+    }
+    let n = (width * height) as f64;
+    // The template is zero-mean, so the window's mean drops out of the dot
+    let norm = (sum_sq - sum * sum / n).max(0.0).sqrt();
+    if norm < 1e-9 {
+        return 0.0;
+    }
+    dot / (norm * t_norm)
+}
+
+/// A scored reference, with the aligned images for side-by-side viewing.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub similarity: Similarity,
+    /// Region of the (oriented) decode matched to the reference
+    pub crop: Crop,
+    /// Decoded luma, cropped and resized to the reference
+    pub decoded: GrayImage,
+    /// Reference luma
+    pub reference: GrayImage,
+}
+
+/// Compare `reference` against its frame of `frames`.
+///
+/// A frame key decodes its frame alone (as the frame-triplet backend does
+/// a grayscale frame, with contrast bounds over its content; a triplet
+/// member as its plane alone); a composite key decodes the triplet with
+/// its planes registered. `params` supplies everything but the mode.
+pub fn compare_reference(
+    source: &dyn SampleSource,
+    frames: &RecordFrames,
+    reference: &Reference,
+    params: &DecoderParams,
+    orientation: Orientation,
+) -> Result<Comparison> {
+    let index = reference.key.frame();
+    anyhow::ensure!(
+        index < frames.bounds().len(),
+        "frame {index} out of range ({} frames)",
+        frames.bounds().len()
+    );
+    let mode = match (reference.key, frames.group(index)) {
+        (ReferenceKey::Frame(_), FrameGroup::Single(_)) => DecoderMode::FrameTriplet,
+        (ReferenceKey::Frame(_), FrameGroup::Triplet(_)) => DecoderMode::Grayscale,
+        (ReferenceKey::Composite(_), FrameGroup::Triplet(_)) => DecoderMode::FrameTriplet,
+        (ReferenceKey::Composite(_), FrameGroup::Single(_)) => {
+            anyhow::bail!("frame {index} is not in a color triplet (is the segmentation catalog-aligned?)")
+        }
+    };
+    let params = DecoderParams { mode, ..*params };
+    let result = DecodingPipeline::new()
+        .process_frame(source, frames, index, &params)
+        .with_context(|| format!("decoding frame {index}"))?;
+    let decoded = orientation
+        .apply(result.to_dynamic_image().context("building image")?)
+        .to_luma8();
+    let reference_image = image::open(&reference.path)
+        .with_context(|| format!("reading {}", reference.path.display()))?
+        .to_luma8();
+
+    let crop = align(&decoded, &reference_image);
+    let aligned = crop.extract(&decoded, reference_image.width(), reference_image.height());
+    Ok(Comparison {
+        similarity: similarity(&aligned, &reference_image),
+        crop,
+        decoded: aligned,
+        reference: reference_image,
+    })
+}
+
+/// How far a score may fall below its baseline before it is flagged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub ssim: f64,
+    pub psnr_db: f64,
+    pub correlation: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            ssim: 0.02,
+            psnr_db: 1.0,
+            correlation: 0.02,
+        }
+    }
+}
+
+impl Tolerance {
+    /// Names of the metrics in `current` that fell past tolerance below
+    /// `baseline`.
+    pub fn regressions(&self, current: &Similarity, baseline: &Similarity) -> Vec<&'static str> {
+        let mut worse = Vec::new();
+        if current.ssim < baseline.ssim - self.ssim {
+            worse.push("ssim");
+        }
+        if current.psnr_db < baseline.psnr_db - self.psnr_db {
+            worse.push("psnr");
+        }
+        if current.correlation < baseline.correlation - self.correlation {
+            worse.push("corr");
+        }
+        worse
+    }
+}
+
+/// One baseline score.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub key: ReferenceKey,
+    #[serde(flatten)]
+    pub similarity: Similarity,
+}
+
+/// Saved scores of an accepted run, to flag regressions against.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    #[serde(default)]
+    pub frames: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Baseline from scored keys, in key order.
+    pub fn from_scores(scores: impl IntoIterator<Item = (ReferenceKey, Similarity)>) -> Self {
+        let mut frames: Vec<BaselineEntry> = scores
+            .into_iter()
+            .map(|(key, similarity)| BaselineEntry { key, similarity })
+            .collect();
+        frames.sort_by_key(|entry| entry.key);
+        Self { frames }
+    }
+
+    pub fn get(&self, key: ReferenceKey) -> Option<&Similarity> {
+        self.frames.iter().find(|e| e.key == key).map(|e| &e.similarity)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).with_context(|| format!("reading baseline {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("parsing baseline {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string_pretty(self).context("serializing baseline")?;
+        std::fs::write(path, contents).with_context(|| format!("writing baseline {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Smooth test card with features in both axes, so correlation peaks
+    /// sharply at the right placement.
+    fn card(width: u32, height: u32) -> GrayImage {
+        GrayImage::from_fn(width, height, |x, y| {
+            let (u, v) = (x as f64 / width as f64, y as f64 / height as f64);
+            let value = 128.0 + 60.0 * (7.0 * u).sin() * (5.0 * v).cos() + 50.0 * (u - v);
+            image::Luma([value.clamp(0.0, 255.0) as u8])
+        })
+    }
+
+    #[test]
+    fn reference_keys_parse_from_file_names() {
+        assert_eq!(ReferenceKey::parse("image_012_pulsar_map"), Some(ReferenceKey::Frame(12)));
+        assert_eq!(ReferenceKey::parse("7"), Some(ReferenceKey::Frame(7)));
+        assert_eq!(ReferenceKey::parse("color_009-011_sunset"), Some(ReferenceKey::Composite(9)));
+        assert_eq!(ReferenceKey::parse("notes"), None);
+        for key in [ReferenceKey::Frame(3), ReferenceKey::Composite(42)] {
+            assert_eq!(ReferenceKey::parse(&key.to_string()), Some(key));
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "image_002_b.png",
+            "image_002_a.png",
+            "color_003-005.jpg",
+            "readme.txt",
+            "cover.png",
+        ] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let references = find_references(dir.path()).unwrap();
+        let found: Vec<(ReferenceKey, &str)> = references
+            .iter()
+            .map(|r| (r.key, r.path.file_name().unwrap().to_str().unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (ReferenceKey::Frame(2), "image_002_a.png"),
+                (ReferenceKey::Composite(3), "color_003-005.jpg")
+            ]
+        );
+    }
+
+    #[test]
+    fn similarity_scores_identity_noise_and_inversion() {
+        let a = card(40, 30);
+        let same = similarity(&a, &a);
+        assert!((same.ssim - 1.0).abs() < 1e-9 && (same.correlation - 1.0).abs() < 1e-9);
+        assert_eq!(same.psnr_db, PSNR_CAP_DB);
+
+        let noisy = GrayImage::from_fn(40, 30, |x, y| {
+            let v = a.get_pixel(x, y)[0] as i32 + if (x * 7 + y * 3) % 2 == 0 { 12 } else { -12 };
+            image::Luma([v.clamp(0, 255) as u8])
+        });
+        let noisy = similarity(&noisy, &a);
+        assert!(noisy.ssim < 0.99 && noisy.ssim > 0.5, "ssim {}", noisy.ssim);
+        assert!((noisy.psnr_db - 26.5).abs() < 1.0, "psnr {}", noisy.psnr_db);
+
+        let inverted = GrayImage::from_fn(40, 30, |x, y| image::Luma([255 - a.get_pixel(x, y)[0]]));
+        let inverted = similarity(&inverted, &a);
+        assert!(inverted.correlation < -0.99 && inverted.ssim < 0.0);
+    }
+
+    #[test]
+    fn alignment_finds_the_cropped_scaled_reference() {
+        // The decode has margins around the picture and a different aspect
+        let decoded_picture = card(180, 240);
+        let mut decoded = GrayImage::new(240, 300);
+        image::imageops::replace(&mut decoded, &decoded_picture, 40, 25);
+        let reference = image::imageops::resize(&decoded_picture, 120, 120, FilterType::Triangle);
+
+        let crop = align(&decoded, &reference);
+        let error = [
+            crop.x as i64 - 40,
+            crop.y as i64 - 25,
+            crop.width as i64 - 180,
+            crop.height as i64 - 240,
+        ];
+        assert!(error.iter().all(|e| e.abs() <= 4), "crop {crop:?}");
+        let aligned = crop.extract(&decoded, 120, 120);
+        assert!(similarity(&aligned, &reference).ssim > 0.9);
+    }
+
+    #[test]
+    fn decoded_frames_score_against_their_source_picture() {
+        use crate::analysis::ImageBounds;
+        use crate::audio::{WavReader, WaveformChannel};
+        use crate::test_fixtures::{create_test_wav_file, encode_image_to_audio};
+
+        const RATE: u32 = 16_000;
+        const LINE_MS: f32 = 8.32;
+        let picture = card(128, 200);
+        let audio = encode_image_to_audio(picture.as_raw(), 128, RATE, LINE_MS);
+        let wav = create_test_wav_file(&audio, RATE, 1);
+        let source = WavReader::from_file(wav.path()).unwrap();
+        let frames = RecordFrames::from_bounds(
+            WaveformChannel::Left,
+            vec![ImageBounds {
+                start_sample: 0,
+                end_sample: audio.len(),
+                start_secs: 0.0,
+                end_secs: audio.len() as f64 / RATE as f64,
+                line_count: 200,
+                median_interval_samples: 0.0,
+                confidence: 1.0,
+            }],
+        );
+        let dir = tempfile::tempdir().unwrap();
+        // A published reference is smaller than our decode
+        let path = dir.path().join("image_000_card.png");
+        image::imageops::resize(&picture, 96, 150, FilterType::Triangle)
+            .save(&path)
+            .unwrap();
+        let params = DecoderParams {
+            line_duration_ms: LINE_MS,
+            ..DecoderParams::default()
+        };
+
+        let reference = &find_references(dir.path()).unwrap()[0];
+        let comparison = compare_reference(&source, &frames, reference, &params, Orientation::default()).unwrap();
+        assert_eq!(comparison.decoded.dimensions(), (96, 150));
+        let score = comparison.similarity;
+        assert!(score.ssim > 0.8 && score.correlation > 0.95, "{score:?}");
+
+        // Decoded with the wrong polarity the best crop still scores worse
+        let inverted = DecoderParams { invert: true, ..params };
+        let worse = compare_reference(&source, &frames, reference, &inverted, Orientation::default()).unwrap();
+        assert!(
+            worse.similarity.ssim < score.ssim - 0.2 && worse.similarity.correlation < score.correlation - 0.2,
+            "{:?}",
+            worse.similarity
+        );
+
+        // Composites need a triplet; frames past the end fail
+        let composite = Reference {
+            key: ReferenceKey::Composite(0),
+            path: path.clone(),
+        };
+        assert!(compare_reference(&source, &frames, &composite, &params, Orientation::default()).is_err());
+        let missing = Reference {
+            key: ReferenceKey::Frame(1),
+            path,
+        };
+        assert!(compare_reference(&source, &frames, &missing, &params, Orientation::default()).is_err());
+    }
+
+    #[test]
+    fn baselines_round_trip_and_flag_regressions() {
+        let score = |ssim, psnr_db, correlation| Similarity {
+            ssim,
+            psnr_db,
+            correlation,
+        };
+        let baseline = Baseline::from_scores([
+            (ReferenceKey::Composite(9), score(0.8, 20.0, 0.9)),
+            (ReferenceKey::Frame(1), score(0.7, 18.0, 0.85)),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.toml");
+        baseline.save(&path).unwrap();
+        let loaded = Baseline::load(&path).unwrap();
+        assert_eq!(loaded, baseline);
+        assert_eq!(loaded.frames[0].key, ReferenceKey::Frame(1));
+
+        let tolerance = Tolerance::default();
+        let old = loaded.get(ReferenceKey::Composite(9)).unwrap();
+        assert!(tolerance.regressions(&score(0.79, 19.5, 0.95), old).is_empty());
+        assert_eq!(tolerance.regressions(&score(0.7, 18.0, 0.9), old), ["ssim", "psnr"]);
+        assert!(loaded.get(ReferenceKey::Frame(9)).is_none());
+    }
+}
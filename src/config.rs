@@ -7,6 +7,7 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{ConfigError, Result};
+use crate::preset::{merge_presets, validate_presets, Preset};
 
 /// Top-level application configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -22,11 +23,18 @@ pub struct AppConfig {
     #[cfg(feature = "audio_playback")]
     pub audio: AudioConfig,
 
+    /// Input file handling
+    pub input: InputConfig,
+
     /// Worker thread configuration
     pub worker: WorkerConfig,
 
     /// Metrics configuration
     pub metrics: MetricsConfig,
+
+    /// User decoder presets (`[[presets]]` tables), added to the built-in
+    /// ones; a preset named like a built-in replaces it
+    pub presets: Vec<Preset>,
 }
 
 /// SSTV decoder configuration
@@ -36,6 +44,10 @@ pub struct DecoderConfig {
     /// Default line duration in milliseconds (1.0-100.0)
     pub default_line_duration_ms: f32,
 
+    /// Measure each loaded file's playback-speed factor (e.g. a 2x rip) and
+    /// compensate decoding, segmentation and playback for it
+    pub auto_speed: bool,
+
     /// Default brightness polarity inversion (rip-dependent)
     pub default_invert: bool,
 
@@ -68,6 +80,10 @@ pub struct UiConfig {
     /// Waveform display height in pixels
     pub waveform_height: f32,
 
+    /// Save the waveform envelope pyramid next to the WAV
+    /// (`<name>.wav.envelope`) so reopening a large rip skips the rebuild
+    pub persist_waveform_envelope: bool,
+
     /// Enable debug panel
     pub show_debug_panel: bool,
 
@@ -90,6 +106,15 @@ pub struct AudioConfig {
     pub default_volume: f32,
 }
 
+/// Input file handling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputConfig {
+    /// Files larger than this (MiB) are streamed from disk instead of being
+    /// decoded into memory
+    pub resident_limit_mb: u64,
+}
+
 /// Worker thread configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -136,6 +161,7 @@ impl Default for DecoderConfig {
     fn default() -> Self {
         Self {
             default_line_duration_ms: 8.32,
+            auto_speed: true,
             default_invert: false,
             default_gamma: 1.0,
             decode_window_secs: 2.0,
@@ -152,6 +178,7 @@ impl Default for UiConfig {
             image_width: 512,
             max_image_height: 16384,
             waveform_height: 200.0,
+            persist_waveform_envelope: false,
             show_debug_panel: cfg!(debug_assertions),
             target_fps: 60,
         }
@@ -169,6 +196,19 @@ impl Default for AudioConfig {
     }
 }
 
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self { resident_limit_mb: 512 }
+    }
+}
+
+impl InputConfig {
+    /// Resident-load limit in bytes, for [`crate::sample_source::open_source`].
+    pub fn resident_limit_bytes(&self) -> u64 {
+        self.resident_limit_mb.saturating_mul(1024 * 1024)
+    }
+}
+
 impl Default for WorkerConfig {
     fn default() -> Self {
         Self {
@@ -247,6 +287,16 @@ impl AppConfig {
         config_dir.join("config.toml")
     }
 
+    /// Built-in and user presets, in selection order.
+    pub fn presets(&self) -> Vec<Preset> {
+        merge_presets(&self.presets)
+    }
+
+    /// Look up a preset by name (built-in or user).
+    pub fn preset(&self, name: &str) -> Option<Preset> {
+        self.presets().into_iter().find(|p| p.name == name)
+    }
+
     /// Validate configuration values
     pub fn validate(&self) -> Result<(), ConfigError> {
         // Validate decoder config
@@ -308,6 +358,8 @@ impl AppConfig {
             });
         }
 
+        validate_presets(&self.presets)?;
+
         Ok(())
     }
 }
@@ -359,6 +411,30 @@ mod tests {
         let _deserialized: AppConfig = toml::from_str(&toml_str).expect("Should deserialize");
     }
 
+    #[test]
+    fn test_user_presets_from_toml() {
+        let toml_str = r#"
+[[presets]]
+name = "cassette"
+invert = true
+channel = "right"
+
+[[presets]]
+name = "384k master"
+gamma = 1.2
+"#;
+        let config: AppConfig = toml::from_str(toml_str).expect("Should deserialize");
+        config.validate().expect("Presets should be valid");
+        assert_eq!(config.presets.len(), 2);
+        assert!(config.preset("cassette").expect("user preset").invert);
+        assert_eq!(config.preset("384k master").expect("overridden builtin").gamma, 1.2);
+        assert!(config.preset("2x rip").is_some());
+        assert!(config.preset("missing").is_none());
+
+        let unnamed: AppConfig = toml::from_str("[[presets]]\ngamma = 1.2\n").expect("Should deserialize");
+        assert!(unnamed.validate().is_err());
+    }
+
     #[test]
     fn test_validation_line_duration() {
         let mut config = AppConfig::default();
@@ -0,0 +1,418 @@
+//! Multi-resolution min/max envelope of a [`SampleSource`], for drawing the
+//! waveform strip at any zoom without touching more than a screenful of
+//! data per frame.
+//!
+//! Level 0 holds one (min, max) pair per [`BASE_BIN`] frames; each level
+//! above merges [`LEVEL_FACTOR`] bins of the one below, up to a single bin
+//! for the whole file. Drawing a view picks the coarsest level whose bins
+//! are still narrower than a pixel column, so a full-width view of a
+//! two-hour rip folds a few thousand bins instead of half a billion samples.
+//! Views finer than [`BASE_BIN`] frames per column are read straight from
+//! the source.
+//!
+//! Building the pyramid is one sequential pass over the file, done once per
+//! load on a background thread. It can be persisted next to the WAV
+//! ([`sidecar_path`]) so reopening a rip skips the pass; the sidecar records
+//! the WAV's size and modification time and is ignored once either changes.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::audio::WaveformChannel;
+use crate::error::Result;
+use crate::sample_source::{read_blocks, SampleSource};
+
+/// Frames per level-0 bin.
+pub const BASE_BIN: usize = 256;
+
+/// Bins of level `k` merged into one bin of level `k + 1`.
+pub const LEVEL_FACTOR: usize = 4;
+
+/// Frames read per block while building.
+const BUILD_BLOCK_BINS: usize = 16_384;
+
+const SIDECAR_MAGIC: &[u8; 8] = b"VXENV\0\0\x01";
+
+/// Per-channel decimation pyramid. See the module docs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvelopePyramid {
+    len: usize,
+    /// `channels[c][k]` is level `k` of channel `c` (one entry for mono).
+    channels: Vec<Vec<Vec<(f32, f32)>>>,
+}
+
+/// Identity of the WAV a sidecar was built from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint {
+    file_len: u64,
+    mtime_nanos: u128,
+}
+
+impl Fingerprint {
+    fn of(path: &Path) -> std::io::Result<Self> {
+        let meta = std::fs::metadata(path)?;
+        let mtime_nanos = meta.modified()?.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        Ok(Self {
+            file_len: meta.len(),
+            mtime_nanos,
+        })
+    }
+}
+
+impl EnvelopePyramid {
+    /// Build the pyramid in one pass over every channel of `source`.
+    ///
+    /// # Errors
+    ///
+    /// Propagates read failures from the source.
+    pub fn build(source: &dyn SampleSource) -> Result<Self> {
+        let len = source.len();
+        let mut channels = Vec::new();
+        for channel in source_channels(source) {
+            let mut base = Vec::with_capacity(len.div_ceil(BASE_BIN));
+            // Blocks are a multiple of BASE_BIN, so bins never straddle two
+            for block in read_blocks(source, channel, 0..len, BASE_BIN * BUILD_BLOCK_BINS, 0) {
+                base.extend(block?.samples.chunks(BASE_BIN).map(min_max));
+            }
+            channels.push(levels_from_base(base));
+        }
+        Ok(Self { len, channels })
+    }
+
+    /// Load the sidecar for `wav_path` when it matches the file and the
+    /// source, otherwise build from `source`. With `persist`, a fresh build
+    /// is written back; a failed write only costs the next load a rebuild.
+    ///
+    /// # Errors
+    ///
+    /// Propagates read failures from the source during a build.
+    pub fn load_or_build(source: &dyn SampleSource, wav_path: &Path, persist: bool) -> Result<Self> {
+        let sidecar = sidecar_path(wav_path);
+        let fingerprint = Fingerprint::of(wav_path).ok();
+        if persist {
+            if let Some(fingerprint) = fingerprint {
+                match Self::load(&sidecar, fingerprint) {
+                    Ok(pyramid) if pyramid.matches(source) => {
+                        tracing::info!(path = %sidecar.display(), "Loaded waveform envelope sidecar");
+                        return Ok(pyramid);
+                    }
+                    Ok(_) => tracing::info!(path = %sidecar.display(), "Envelope sidecar is stale, rebuilding"),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => tracing::warn!(path = %sidecar.display(), error = %e, "Ignoring unreadable envelope sidecar"),
+                }
+            }
+        }
+
+        let pyramid = Self::build(source)?;
+        if let (true, Some(fingerprint)) = (persist, fingerprint) {
+            if let Err(e) = pyramid.save(&sidecar, fingerprint) {
+                tracing::warn!(path = %sidecar.display(), error = %e, "Failed to write envelope sidecar");
+            }
+        }
+        Ok(pyramid)
+    }
+
+    /// Frames covered.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Per-column (min, max) for `width` columns of `samples_per_px` frames
+    /// starting at frame `start`. Columns past the end are inverted
+    /// (`min > max`) so the caller skips them. Returns `None` when
+    /// `samples_per_px` is finer than [`BASE_BIN`]: read the source directly.
+    pub fn columns(&self, channel: WaveformChannel, start: f64, samples_per_px: f64, width: usize) -> Option<Vec<(f32, f32)>> {
+        if samples_per_px < BASE_BIN as f64 {
+            return None;
+        }
+        let count = self.channels.len() as u16;
+        let (first_levels, second_levels) = match channel.physical(count) {
+            Some(index) => (self.channels.get(index)?, None),
+            None => (
+                self.channels.first()?,
+                Some(self.channels.get(WaveformChannel::Right.physical(count)?)?),
+            ),
+        };
+        let mut level = 0;
+        let mut bin = BASE_BIN;
+        while level + 1 < first_levels.len() && (bin * LEVEL_FACTOR) as f64 <= samples_per_px {
+            level += 1;
+            bin *= LEVEL_FACTOR;
+        }
+        let bins = &first_levels[level];
+        // Mid/side bounds from the two channels' bounds: conservative (the
+        // extremes need not coincide), which is all an overview needs.
+        let right_bins = second_levels.map(|levels| &levels[level]);
+        let bounds = |i: usize| -> (f32, f32) {
+            let (min, max) = bins[i];
+            match (channel, right_bins) {
+                (WaveformChannel::Mid, Some(right)) => ((min + right[i].0) * 0.5, (max + right[i].1) * 0.5),
+                (WaveformChannel::Side, Some(right)) => ((min - right[i].1) * 0.5, (max - right[i].0) * 0.5),
+                _ => (min, max),
+            }
+        };
+
+        let columns = (0..width)
+            .map(|x| {
+                let first = (start + x as f64 * samples_per_px).max(0.0) as usize;
+                let last = ((start + (x + 1) as f64 * samples_per_px).max(0.0) as usize).min(self.len);
+                if first >= last {
+                    return (1.0, -1.0);
+                }
+                let range = (first / bin).min(bins.len())..last.div_ceil(bin).min(bins.len());
+                range
+                    .map(bounds)
+                    .fold((1.0f32, -1.0f32), |(lo, hi), (min, max)| (lo.min(min), hi.max(max)))
+            })
+            .collect();
+        Some(columns)
+    }
+
+    /// Whether this pyramid can describe `source` (same length and layout).
+    fn matches(&self, source: &dyn SampleSource) -> bool {
+        self.len == source.len() && self.channels.len() == source_channels(source).len()
+    }
+
+    fn save(&self, path: &Path, fingerprint: Fingerprint) -> std::io::Result<()> {
+        // Write to a temporary name first so an interrupted save never
+        // leaves a truncated sidecar that looks valid
+        let tmp = path.with_extension("envelope.tmp");
+        {
+            let mut out = BufWriter::new(File::create(&tmp)?);
+            out.write_all(SIDECAR_MAGIC)?;
+            out.write_all(&fingerprint.file_len.to_le_bytes())?;
+            out.write_all(&fingerprint.mtime_nanos.to_le_bytes())?;
+            out.write_all(&(self.len as u64).to_le_bytes())?;
+            out.write_all(&(BASE_BIN as u32).to_le_bytes())?;
+            out.write_all(&(self.channels.len() as u32).to_le_bytes())?;
+            for levels in &self.channels {
+                let base = &levels[0];
+                out.write_all(&(base.len() as u64).to_le_bytes())?;
+                for &(min, max) in base {
+                    out.write_all(&min.to_le_bytes())?;
+                    out.write_all(&max.to_le_bytes())?;
+                }
+            }
+            out.flush()?;
+        }
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Read a sidecar written by [`Self::save`]. Only level 0 is stored; the
+    /// upper levels are cheap to rebuild.
+    fn load(path: &Path, fingerprint: Fingerprint) -> std::io::Result<Self> {
+        let invalid = |what: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, what.to_string());
+        let mut input = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != SIDECAR_MAGIC {
+            return Err(invalid("not an envelope sidecar"));
+        }
+        let stored = Fingerprint {
+            file_len: u64::from_le_bytes(read_array(&mut input)?),
+            mtime_nanos: u128::from_le_bytes(read_array(&mut input)?),
+        };
+        if stored != fingerprint {
+            return Err(invalid("sidecar was built from a different version of the file"));
+        }
+        let len = u64::from_le_bytes(read_array(&mut input)?) as usize;
+        if u32::from_le_bytes(read_array(&mut input)?) as usize != BASE_BIN {
+            return Err(invalid("sidecar bin size mismatch"));
+        }
+        let channel_count = u32::from_le_bytes(read_array(&mut input)?);
+        if !(1..=u16::MAX as u32).contains(&channel_count) {
+            return Err(invalid("sidecar channel count out of range"));
+        }
+
+        let mut channels = Vec::new();
+        for _ in 0..channel_count {
+            let bins = u64::from_le_bytes(read_array(&mut input)?) as usize;
+            if bins != len.div_ceil(BASE_BIN) {
+                return Err(invalid("sidecar bin count mismatch"));
+            }
+            let mut base = Vec::with_capacity(bins);
+            for _ in 0..bins {
+                let min = f32::from_le_bytes(read_array(&mut input)?);
+                let max = f32::from_le_bytes(read_array(&mut input)?);
+                base.push((min, max));
+            }
+            channels.push(levels_from_base(base));
+        }
+        Ok(Self { len, channels })
+    }
+}
+
+/// Sidecar location for `wav_path`: `<name>.wav.envelope` beside it.
+pub fn sidecar_path(wav_path: &Path) -> PathBuf {
+    let mut name = wav_path.as_os_str().to_owned();
+    name.push(".envelope");
+    PathBuf::from(name)
+}
+
+/// The physical channels of `source`; mid/side are derived when drawn.
+fn source_channels(source: &dyn SampleSource) -> Vec<WaveformChannel> {
+    (0..source.channels().max(1)).map(WaveformChannel::index).collect()
+}
+
+/// Stack merged levels on top of `base` until one bin covers everything.
+fn levels_from_base(base: Vec<(f32, f32)>) -> Vec<Vec<(f32, f32)>> {
+    let mut levels = vec![base];
+    while let Some(top) = levels.last().filter(|top| top.len() > 1) {
+        let merged = top
+            .chunks(LEVEL_FACTOR)
+            .map(|group| {
+                group
+                    .iter()
+                    .fold((1.0f32, -1.0f32), |(lo, hi), &(min, max)| (lo.min(min), hi.max(max)))
+            })
+            .collect();
+        levels.push(merged);
+    }
+    levels
+}
+
+/// (min, max) of `samples` clamped to [-1, 1]; `(1.0, -1.0)` (an inverted,
+/// skipped column) when empty.
+pub fn min_max(samples: &[f32]) -> (f32, f32) {
+    samples.iter().fold((1.0f32, -1.0f32), |(lo, hi), s| {
+        let clamped = s.clamp(-1.0, 1.0);
+        (lo.min(clamped), hi.max(clamped))
+    })
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> std::io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    input.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::audio::WavReader;
+
+    fn write_stereo(path: &Path, frames: usize) {
+        write_channels(path, frames, 2);
+    }
+
+    /// Stereo signal, then a loud constant on every channel past the second
+    fn write_channels(path: &Path, frames: usize, channels: u16) {
+        let spec = hound::WavSpec {
+            channels,
+            sample_rate: 8_000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for i in 0..frames {
+            let left = ((i as f32 * 0.013).sin() * (i % 7000) as f32 / 7000.0 * 30_000.0) as i16;
+            writer.write_sample(left).unwrap();
+            writer.write_sample(-left / 2).unwrap();
+            for _ in 2..channels {
+                writer.write_sample(20_000i16).unwrap();
+            }
+        }
+        writer.finalize().unwrap();
+    }
+
+    #[test]
+    fn columns_match_a_direct_scan_at_every_level() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rip.wav");
+        write_stereo(&path, 300_001);
+        let source = WavReader::from_file(&path).unwrap();
+        let pyramid = EnvelopePyramid::build(&source).unwrap();
+
+        for channel in [WaveformChannel::Left, WaveformChannel::Right] {
+            let samples = source.resident(channel).unwrap();
+            for samples_per_px in [256.0, 1_000.0, 4_096.0, 70_000.0] {
+                // Columns aligned to level-0 bins must match a direct scan exactly
+                let start = 512.0;
+                let width = 40;
+                let columns = pyramid.columns(channel, start, samples_per_px, width).unwrap();
+                for (x, &column) in columns.iter().enumerate() {
+                    let first = (start + x as f64 * samples_per_px) as usize;
+                    let last = ((start + (x + 1) as f64 * samples_per_px) as usize).min(samples.len());
+                    if first >= last {
+                        assert!(column.0 > column.1, "column {x} past the end should be skipped");
+                        continue;
+                    }
+                    let exact = min_max(&samples[first..last]);
+                    // Coarser bins may overhang the column edges, so the
+                    // envelope can only ever be wider than the exact one
+                    assert!(column.0 <= exact.0 && column.1 >= exact.1, "column {x} at {samples_per_px}");
+                    if samples_per_px == 256.0 {
+                        assert_eq!(column, exact, "level-0 column {x}");
+                    }
+                }
+            }
+        }
+        assert!(pyramid.columns(WaveformChannel::Left, 0.0, 100.0, 10).is_none());
+    }
+
+    #[test]
+    fn mid_and_side_columns_bound_the_mix() {
+        let dir = TempDir::new().unwrap();
+        // Mid/side mix the first two channels, whatever follows them
+        for channels in [2, 4] {
+            let path = dir.path().join(format!("rip{channels}.wav"));
+            write_channels(&path, 100_000, channels);
+            let source = WavReader::from_file(&path).unwrap();
+            let pyramid = EnvelopePyramid::build(&source).unwrap();
+            assert_mix_bounded(&source, &pyramid);
+        }
+    }
+
+    fn assert_mix_bounded(source: &WavReader, pyramid: &EnvelopePyramid) {
+        for channel in [WaveformChannel::Mid, WaveformChannel::Side] {
+            let samples = source.get_samples(channel);
+            let samples_per_px = 4_096.0;
+            let columns = pyramid.columns(channel, 0.0, samples_per_px, 20).unwrap();
+            for (x, &column) in columns.iter().enumerate() {
+                let first = x * samples_per_px as usize;
+                let exact = min_max(&samples[first..first + samples_per_px as usize]);
+                assert!(column.0 <= exact.0 && column.1 >= exact.1, "{channel} column {x}");
+            }
+        }
+    }
+
+    #[test]
+    fn sidecar_round_trips_and_goes_stale_with_the_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rip.wav");
+        write_stereo(&path, 50_000);
+        let source = WavReader::from_file(&path).unwrap();
+
+        let built = EnvelopePyramid::load_or_build(&source, &path, true).unwrap();
+        let sidecar = sidecar_path(&path);
+        assert!(sidecar.exists());
+        let fingerprint = Fingerprint::of(&path).unwrap();
+        assert_eq!(EnvelopePyramid::load(&sidecar, fingerprint).unwrap(), built);
+
+        // A rewritten file (different length) invalidates the sidecar
+        write_stereo(&path, 60_000);
+        assert!(EnvelopePyramid::load(&sidecar, Fingerprint::of(&path).unwrap()).is_err());
+        let source = WavReader::from_file(&path).unwrap();
+        let rebuilt = EnvelopePyramid::load_or_build(&source, &path, true).unwrap();
+        assert_eq!(rebuilt.len(), 60_000);
+    }
+
+    #[test]
+    fn without_persist_no_sidecar_is_written() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("clip.wav");
+        write_stereo(&path, 10_000);
+        let source = WavReader::from_file(&path).unwrap();
+        EnvelopePyramid::load_or_build(&source, &path, false).unwrap();
+        assert!(!sidecar_path(&path).exists());
+    }
+}
@@ -22,6 +22,10 @@ pub enum VoyagerError {
     #[error("Configuration error: {0}")]
     Config(#[from] ConfigError),
 
+    /// Project file errors
+    #[error("Project error: {0}")]
+    Project(#[from] ProjectError),
+
     /// I/O errors
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -33,15 +37,24 @@ pub enum AudioError {
     #[error("Failed to load WAV file '{path}': {source}")]
     LoadFailed { path: PathBuf, source: hound::Error },
 
+    #[error("Failed to decode audio file '{path}': {source}")]
+    DecodeFailed {
+        path: PathBuf,
+        source: symphonia::core::errors::Error,
+    },
+
+    #[error("Failed to read samples from '{path}': {source}")]
+    ReadFailed { path: PathBuf, source: std::io::Error },
+
     #[error("Invalid sample rate: {rate} Hz (must be at least 8 kHz)")]
     InvalidSampleRate { rate: u32 },
 
-    #[error("Seek offset {start_secs:.1}s exceeds the seekable range for this file")]
-    SeekOutOfRange { start_secs: f64 },
-
-    #[error("Unsupported channel count: {channels} (only mono/stereo supported)")]
+    #[error("Unsupported channel count: {channels}")]
     UnsupportedChannels { channels: u16 },
 
+    #[error("Channel {channel} not present in a {channels}-channel file")]
+    ChannelNotPresent { channel: String, channels: u16 },
+
     #[error("Empty audio file: {path}")]
     EmptyFile { path: PathBuf },
 
@@ -66,6 +79,9 @@ pub enum DecoderError {
 
     #[error("Insufficient samples for decoding: needed {needed}, got {actual}")]
     InsufficientSamples { needed: usize, actual: usize },
+
+    #[error("No SSTV VIS header found; select the SSTV mode explicitly")]
+    NoVisHeader,
 }
 
 /// Configuration errors
@@ -87,6 +103,25 @@ pub enum ConfigError {
     SerializationFailed { source: toml::ser::Error },
 }
 
+/// Project (session) file errors
+#[derive(Error, Debug)]
+pub enum ProjectError {
+    #[error("Failed to read project file '{path}': {source}")]
+    LoadFailed { path: Box<PathBuf>, source: std::io::Error },
+
+    #[error("Invalid project format in '{path}': {source}")]
+    InvalidFormat { path: Box<PathBuf>, source: toml::de::Error },
+
+    #[error("Unsupported project version {version} (this build reads up to {supported})")]
+    UnsupportedVersion { version: u32, supported: u32 },
+
+    #[error("Failed to save project to '{path}': {source}")]
+    SaveFailed { path: Box<PathBuf>, source: std::io::Error },
+
+    #[error("Project serialization failed: {source}")]
+    SerializationFailed { source: toml::ser::Error },
+}
+
 /// Result type alias for Voyager operations
 pub type Result<T, E = VoyagerError> = std::result::Result<T, E>;
 
@@ -105,15 +140,21 @@ impl AudioError {
             AudioError::LoadFailed { path, .. } => {
                 format!("Could not open audio file '{}'", path.display())
             }
+            AudioError::DecodeFailed { path, .. } => {
+                format!("Could not decode audio file '{}'", path.display())
+            }
+            AudioError::ReadFailed { path, .. } => {
+                format!("Could not read audio from '{}'", path.display())
+            }
             AudioError::InvalidSampleRate { rate } => {
                 format!("Audio file has unsupported sample rate: {} Hz", rate)
             }
-            AudioError::SeekOutOfRange { start_secs } => {
-                format!("Start offset {start_secs:.1}s is beyond the seekable range of this file")
-            }
             AudioError::UnsupportedChannels { channels } => {
                 format!("Audio file has unsupported {} channels", channels)
             }
+            AudioError::ChannelNotPresent { channel, channels } => {
+                format!("Channel {} is not in this {}-channel file", channel, channels)
+            }
             AudioError::EmptyFile { path } => {
                 format!("Audio file '{}' is empty", path.display())
             }
@@ -0,0 +1,884 @@
+//! Amateur-radio SSTV: frequency-modulated slow-scan television.
+//!
+//! The record's images are baseband video ([`crate::sstv::SstvDecoder`]);
+//! amateur SSTV instead carries luminance as frequency, 1500 Hz black to
+//! 2300 Hz white, with 1200 Hz line syncs and a VIS (vertical interval
+//! signaling) header naming the mode ahead of each picture. Decoding
+//! demodulates the audio to an instantaneous-frequency track, reads the VIS
+//! code, locks lines with the shared sync tracker
+//! ([`crate::analysis::sync::track_line_syncs_with_lock`]) and resamples
+//! every color scan with the baseband decoder's line resampler.
+//!
+//! Supported modes: Martin M1/M2, Scottie S1/S2, Robot 36/72 and PD50-PD290.
+
+use std::f64::consts::TAU;
+use std::fmt;
+use std::str::FromStr;
+
+use num_complex::Complex;
+
+use crate::analysis::sync::{interval_summary, track_line_syncs_with_lock, SyncParams};
+use crate::error::{DecoderError, Result, VoyagerError};
+use crate::sstv::{resample_line, DecoderParams};
+
+/// Black level, Hz.
+pub const BLACK_HZ: f32 = 1500.0;
+/// White level, Hz.
+pub const WHITE_HZ: f32 = 2300.0;
+/// Line sync pulses and the VIS start, stop and break tones, Hz.
+pub const SYNC_HZ: f32 = 1200.0;
+/// VIS leader tone, Hz. Also the demodulator's mixing frequency.
+pub const LEADER_HZ: f32 = 1900.0;
+/// VIS data bit 1, Hz.
+pub const VIS_ONE_HZ: f32 = 1100.0;
+/// VIS data bit 0, Hz.
+pub const VIS_ZERO_HZ: f32 = 1300.0;
+/// Length of each VIS leader tone, ms.
+pub const VIS_LEADER_MS: f64 = 300.0;
+/// 1200 Hz break between the two leader tones, ms.
+pub const VIS_BREAK_MS: f64 = 10.0;
+/// Length of every VIS bit (start, 7 data, parity, stop), ms.
+pub const VIS_BIT_MS: f64 = 30.0;
+/// A whole VIS header: two leaders around the break, then ten bits.
+pub const VIS_HEADER_SECS: f64 = (2.0 * VIS_LEADER_MS + VIS_BREAK_MS + 10.0 * VIS_BIT_MS) / 1000.0;
+
+/// Lowest sample rate accepted, matching the audio loader's floor.
+const MIN_SAMPLE_RATE: u32 = 8000;
+/// Shortest leader run accepted ahead of a VIS code, ms. Well under the
+/// nominal 300 ms so a dropout inside the leader does not hide the header.
+const MIN_LEADER_MS: usize = 150;
+/// Tolerance on the leader tone, Hz.
+const LEADER_TOL_HZ: f32 = 100.0;
+/// Tolerance on the VIS start and stop bits, Hz.
+const VIS_SYNC_TOL_HZ: f32 = 60.0;
+/// Frequencies at or below this read fully as sync in the sync trace; the
+/// trace fades to zero 100 Hz above it, short of the black level.
+const SYNC_EDGE_HZ: f32 = 1300.0;
+/// How far a line's last scan may run past the end of the audio, ms.
+const SCAN_OVERRUN_MS: f64 = 2.0;
+/// Tracked sync cadence must match the mode's line period this closely
+/// before it replaces the nominal timing.
+const CADENCE_TOL: f64 = 0.05;
+
+/// The supported amateur SSTV modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SstvMode {
+    MartinM1,
+    MartinM2,
+    ScottieS1,
+    ScottieS2,
+    Robot36,
+    Robot72,
+    Pd50,
+    Pd90,
+    Pd120,
+    Pd160,
+    Pd180,
+    Pd240,
+    Pd290,
+}
+
+impl SstvMode {
+    pub const ALL: [SstvMode; 13] = [
+        SstvMode::MartinM1,
+        SstvMode::MartinM2,
+        SstvMode::ScottieS1,
+        SstvMode::ScottieS2,
+        SstvMode::Robot36,
+        SstvMode::Robot72,
+        SstvMode::Pd50,
+        SstvMode::Pd90,
+        SstvMode::Pd120,
+        SstvMode::Pd160,
+        SstvMode::Pd180,
+        SstvMode::Pd240,
+        SstvMode::Pd290,
+    ];
+
+    /// Display name, as radio software labels the mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SstvMode::MartinM1 => "Martin M1",
+            SstvMode::MartinM2 => "Martin M2",
+            SstvMode::ScottieS1 => "Scottie S1",
+            SstvMode::ScottieS2 => "Scottie S2",
+            SstvMode::Robot36 => "Robot 36",
+            SstvMode::Robot72 => "Robot 72",
+            SstvMode::Pd50 => "PD50",
+            SstvMode::Pd90 => "PD90",
+            SstvMode::Pd120 => "PD120",
+            SstvMode::Pd160 => "PD160",
+            SstvMode::Pd180 => "PD180",
+            SstvMode::Pd240 => "PD240",
+            SstvMode::Pd290 => "PD290",
+        }
+    }
+
+    /// 7-bit VIS code announcing the mode.
+    pub fn vis_code(&self) -> u8 {
+        match self {
+            SstvMode::MartinM1 => 44,
+            SstvMode::MartinM2 => 40,
+            SstvMode::ScottieS1 => 60,
+            SstvMode::ScottieS2 => 56,
+            SstvMode::Robot36 => 8,
+            SstvMode::Robot72 => 12,
+            SstvMode::Pd50 => 93,
+            SstvMode::Pd90 => 99,
+            SstvMode::Pd120 => 95,
+            SstvMode::Pd160 => 98,
+            SstvMode::Pd180 => 96,
+            SstvMode::Pd240 => 97,
+            SstvMode::Pd290 => 94,
+        }
+    }
+
+    pub fn from_vis(code: u8) -> Option<SstvMode> {
+        Self::ALL.into_iter().find(|mode| mode.vis_code() == code)
+    }
+
+    /// Image size and line timing.
+    pub fn spec(&self) -> ModeSpec {
+        match self {
+            SstvMode::MartinM1 => martin(146.432),
+            SstvMode::MartinM2 => martin(73.216),
+            SstvMode::ScottieS1 => scottie(138.24),
+            SstvMode::ScottieS2 => scottie(88.064),
+            SstvMode::Robot36 => ModeSpec {
+                width: 320,
+                height: 240,
+                line_ms: 150.0,
+                sync_ms: 9.0,
+                first_sync_end_ms: 9.0,
+                // porch 3, Y 88, separator 4.5, porch 1.5, chroma 44
+                layout: Layout::AlternatingChroma {
+                    luma: Scan::new(3.0, 88.0),
+                    separator: Scan::new(91.0, 4.5),
+                    chroma: Scan::new(97.0, 44.0),
+                },
+            },
+            SstvMode::Robot72 => ModeSpec {
+                width: 320,
+                height: 240,
+                line_ms: 300.0,
+                sync_ms: 9.0,
+                first_sync_end_ms: 9.0,
+                // porch 3, Y 138, then separator 4.5 + porch 1.5 ahead of
+                // each 69 ms chroma scan
+                layout: Layout::Ycc {
+                    luma: Scan::new(3.0, 138.0),
+                    red_diff: Scan::new(147.0, 69.0),
+                    blue_diff: Scan::new(222.0, 69.0),
+                },
+            },
+            SstvMode::Pd50 => pd(91.52, 320, 256),
+            SstvMode::Pd90 => pd(170.24, 320, 256),
+            SstvMode::Pd120 => pd(121.6, 640, 496),
+            SstvMode::Pd160 => pd(195.584, 512, 400),
+            SstvMode::Pd180 => pd(183.04, 640, 496),
+            SstvMode::Pd240 => pd(244.48, 640, 496),
+            SstvMode::Pd290 => pd(228.8, 800, 616),
+        }
+    }
+}
+
+impl fmt::Display for SstvMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SstvMode {
+    type Err = String;
+
+    /// Accepts the display name or the config spelling, ignoring case,
+    /// spaces and dashes ("Martin M1", "martin-m1", "pd120").
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let key = |name: &str| -> String {
+            name.chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .map(|c| c.to_ascii_lowercase())
+                .collect()
+        };
+        let wanted = key(s);
+        Self::ALL.into_iter().find(|mode| key(mode.name()) == wanted).ok_or_else(|| {
+            let known: Vec<&str> = Self::ALL.iter().map(|mode| mode.name()).collect();
+            format!("unknown SSTV mode \"{s}\" (known: {})", known.join(", "))
+        })
+    }
+}
+
+/// One scan within a line, timed from the end of the line's sync pulse.
+/// Negative offsets precede the sync (Scottie sends green and blue first).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scan {
+    pub offset_ms: f64,
+    pub len_ms: f64,
+}
+
+impl Scan {
+    const fn new(offset_ms: f64, len_ms: f64) -> Self {
+        Self { offset_ms, len_ms }
+    }
+}
+
+/// How the scans of one sync period make up image rows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    /// One RGB row per sync (Martin, Scottie).
+    Rgb { red: Scan, green: Scan, blue: Scan },
+    /// One row per sync: luma plus one chroma scan, R-Y and B-Y on alternate
+    /// lines, told apart by the separator tone before it (Robot 36).
+    AlternatingChroma { luma: Scan, separator: Scan, chroma: Scan },
+    /// One row per sync from luma, R-Y and B-Y scans (Robot 72).
+    Ycc { luma: Scan, red_diff: Scan, blue_diff: Scan },
+    /// Two rows per sync sharing one chroma pair (PD).
+    DualLuma {
+        luma: [Scan; 2],
+        red_diff: Scan,
+        blue_diff: Scan,
+    },
+}
+
+impl Layout {
+    /// Every scan of one sync period.
+    pub fn scans(&self) -> Vec<Scan> {
+        match *self {
+            Layout::Rgb { red, green, blue } => vec![red, green, blue],
+            Layout::AlternatingChroma { luma, separator, chroma } => vec![luma, separator, chroma],
+            Layout::Ycc {
+                luma,
+                red_diff,
+                blue_diff,
+            } => vec![luma, red_diff, blue_diff],
+            Layout::DualLuma {
+                luma,
+                red_diff,
+                blue_diff,
+            } => vec![luma[0], luma[1], red_diff, blue_diff],
+        }
+    }
+}
+
+/// Image size and line timing of an [`SstvMode`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModeSpec {
+    pub width: usize,
+    pub height: usize,
+    /// Sync-to-sync period, ms.
+    pub line_ms: f64,
+    pub sync_ms: f64,
+    /// From the end of the VIS header to the end of the first regular line
+    /// sync, ms. Past `sync_ms` only for Scottie, whose first line opens
+    /// with green and blue after a starting sync.
+    pub first_sync_end_ms: f64,
+    pub layout: Layout,
+}
+
+impl ModeSpec {
+    pub fn rows_per_sync(&self) -> usize {
+        match self.layout {
+            Layout::DualLuma { .. } => 2,
+            _ => 1,
+        }
+    }
+
+    /// Sync periods in a full picture.
+    pub fn sync_lines(&self) -> usize {
+        self.height / self.rows_per_sync()
+    }
+
+    /// Length of a full picture after its VIS header, in seconds: up to the
+    /// end of the last line's final scan.
+    pub fn duration_secs(&self) -> f64 {
+        let last_scan_end = self
+            .layout
+            .scans()
+            .iter()
+            .map(|scan| scan.offset_ms + scan.len_ms)
+            .fold(f64::MIN, f64::max);
+        (self.first_sync_end_ms + (self.sync_lines() - 1) as f64 * self.line_ms + last_scan_end) / 1000.0
+    }
+}
+
+/// Martin: sync 4.862 ms, then green, blue and red scans, each behind a
+/// 0.572 ms porch/separator, with a trailing separator.
+fn martin(scan_ms: f64) -> ModeSpec {
+    const SYNC: f64 = 4.862;
+    const GAP: f64 = 0.572;
+    ModeSpec {
+        width: 320,
+        height: 256,
+        line_ms: SYNC + 4.0 * GAP + 3.0 * scan_ms,
+        sync_ms: SYNC,
+        first_sync_end_ms: SYNC,
+        layout: Layout::Rgb {
+            green: Scan::new(GAP, scan_ms),
+            blue: Scan::new(2.0 * GAP + scan_ms, scan_ms),
+            red: Scan::new(3.0 * GAP + 2.0 * scan_ms, scan_ms),
+        },
+    }
+}
+
+/// Scottie: separator, green, separator, blue, 9 ms sync, porch, red, all
+/// gaps 1.5 ms. A starting sync precedes the first line's green.
+fn scottie(scan_ms: f64) -> ModeSpec {
+    const SYNC: f64 = 9.0;
+    const GAP: f64 = 1.5;
+    ModeSpec {
+        width: 320,
+        height: 256,
+        line_ms: SYNC + 3.0 * GAP + 3.0 * scan_ms,
+        sync_ms: SYNC,
+        first_sync_end_ms: SYNC + 2.0 * GAP + 2.0 * scan_ms + SYNC,
+        layout: Layout::Rgb {
+            red: Scan::new(GAP, scan_ms),
+            green: Scan::new(-(SYNC + 2.0 * scan_ms + GAP), scan_ms),
+            blue: Scan::new(-(SYNC + scan_ms), scan_ms),
+        },
+    }
+}
+
+/// PD: 20 ms sync, 2.08 ms porch, then Y (even row), R-Y, B-Y, Y (odd row).
+fn pd(scan_ms: f64, width: usize, height: usize) -> ModeSpec {
+    const SYNC: f64 = 20.0;
+    const PORCH: f64 = 2.08;
+    ModeSpec {
+        width,
+        height,
+        line_ms: SYNC + PORCH + 4.0 * scan_ms,
+        sync_ms: SYNC,
+        first_sync_end_ms: SYNC,
+        layout: Layout::DualLuma {
+            luma: [Scan::new(PORCH, scan_ms), Scan::new(PORCH + 3.0 * scan_ms, scan_ms)],
+            red_diff: Scan::new(PORCH + scan_ms, scan_ms),
+            blue_diff: Scan::new(PORCH + 2.0 * scan_ms, scan_ms),
+        },
+    }
+}
+
+/// A VIS header found in the audio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VisHeader {
+    pub mode: SstvMode,
+    pub code: u8,
+    /// Start of the leader tone ahead of the code, in samples.
+    pub start: usize,
+    /// First sample after the stop bit: where the picture begins.
+    pub end: usize,
+}
+
+/// Instantaneous frequency of `samples`, in Hz, one value per sample.
+///
+/// Quadrature demodulation: the signal is mixed down around the leader
+/// tone, low-passed by two cascaded moving averages whose first null sits
+/// on the mixing image (the band mirrored to about -3800 Hz), and the phase
+/// step between consecutive baseband samples gives the frequency offset.
+/// The output is shifted back by the filters' group delay so it lines up
+/// with the input.
+pub fn demodulate(samples: &[f32], sample_rate: u32) -> Vec<f32> {
+    let n = samples.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let rate = sample_rate as f64;
+    let step = TAU * LEADER_HZ as f64 / rate;
+    let mut baseband: Vec<Complex<f32>> = samples
+        .iter()
+        .enumerate()
+        .map(|(k, &s)| {
+            let phase = (step * k as f64) % TAU;
+            Complex::new(s * phase.cos() as f32, -s * phase.sin() as f32)
+        })
+        .collect();
+
+    let taps = ((rate / 3800.0).round() as usize).max(2);
+    for _ in 0..2 {
+        box_filter(&mut baseband, taps);
+    }
+
+    let mut freq = Vec::with_capacity(n);
+    freq.push(LEADER_HZ);
+    for w in baseband.windows(2) {
+        let dphi = (w[1] * w[0].conj()).arg() as f64;
+        freq.push(LEADER_HZ + (dphi * rate / TAU) as f32);
+    }
+    // Each causal stage delays by (taps - 1) / 2
+    let delay = (taps - 1).min(n - 1);
+    freq.drain(..delay);
+    let last = *freq.last().unwrap_or(&LEADER_HZ);
+    freq.resize(n, last);
+    freq
+}
+
+/// Causal moving average over `taps` samples, in place.
+fn box_filter(values: &mut [Complex<f32>], taps: usize) {
+    let input = values.to_vec();
+    let mut sum = Complex::new(0.0f32, 0.0);
+    for (k, value) in values.iter_mut().enumerate() {
+        sum += input[k];
+        if k >= taps {
+            sum -= input[k - taps];
+        }
+        *value = sum / taps.min(k + 1) as f32;
+    }
+}
+
+/// Every VIS header in a demodulated frequency track, in time order.
+///
+/// A header is a leader tone at 1900 Hz, then a 1200 Hz start bit, seven
+/// data bits (LSB first, 1100 Hz = 1, 1300 Hz = 0), an even parity bit and a
+/// 1200 Hz stop bit, 30 ms each. Codes that fail parity or name no
+/// supported mode are skipped.
+pub fn find_vis_headers(freq: &[f32], sample_rate: u32) -> Vec<VisHeader> {
+    let per_ms = sample_rate as f64 / 1000.0;
+    // Millisecond means: steady enough to read tones, fine enough for bits
+    let ms_count = (freq.len() as f64 / per_ms) as usize;
+    let track: Vec<f32> = (0..ms_count)
+        .map(|k| {
+            let block = &freq[(k as f64 * per_ms) as usize..(((k + 1) as f64 * per_ms) as usize).min(freq.len())];
+            block.iter().sum::<f32>() / block.len().max(1) as f32
+        })
+        .collect();
+
+    let mut headers = Vec::new();
+    let mut run_start: Option<usize> = None;
+    let mut k = 0;
+    while k < track.len() {
+        if (track[k] - LEADER_HZ).abs() < LEADER_TOL_HZ {
+            run_start.get_or_insert(k);
+            k += 1;
+            continue;
+        }
+        if let Some(start) = run_start.take() {
+            if k - start >= MIN_LEADER_MS {
+                if let Some(mode_code) = read_vis_code(&track, k) {
+                    let end_ms = k as f64 + 10.0 * VIS_BIT_MS;
+                    headers.push(VisHeader {
+                        mode: mode_code.0,
+                        code: mode_code.1,
+                        start: (start as f64 * per_ms) as usize,
+                        end: ((end_ms * per_ms).round() as usize).min(freq.len()),
+                    });
+                    k = end_ms as usize;
+                    continue;
+                }
+            }
+        }
+        k += 1;
+    }
+    headers
+}
+
+/// Read the ten VIS bits starting at millisecond `start` of `track`.
+fn read_vis_code(track: &[f32], start: usize) -> Option<(SstvMode, u8)> {
+    let bit = VIS_BIT_MS as usize;
+    // Middle two thirds of each bit, clear of the tone transitions
+    let bit_mean = |index: usize| -> Option<f32> {
+        let from = start + index * bit + bit / 6;
+        let to = start + (index + 1) * bit - bit / 6;
+        let window = track.get(from..to)?;
+        Some(window.iter().sum::<f32>() / window.len() as f32)
+    };
+    let is_sync = |mean: f32| (mean - SYNC_HZ).abs() < VIS_SYNC_TOL_HZ;
+    if !is_sync(bit_mean(0)?) || !is_sync(bit_mean(9)?) {
+        return None;
+    }
+    let mut bits = [false; 8];
+    for (i, value) in bits.iter_mut().enumerate() {
+        let mean = bit_mean(i + 1)?;
+        if !(VIS_ONE_HZ - 50.0..=VIS_ZERO_HZ + 50.0).contains(&mean) {
+            return None;
+        }
+        *value = mean < SYNC_HZ;
+    }
+    if bits.iter().filter(|&&b| b).count() % 2 != 0 {
+        tracing::debug!(?bits, "VIS parity error");
+        return None;
+    }
+    let code = bits[..7].iter().rev().fold(0u8, |acc, &b| (acc << 1) | b as u8);
+    match SstvMode::from_vis(code) {
+        Some(mode) => Some((mode, code)),
+        None => {
+            tracing::debug!(code, "Unsupported SSTV VIS code");
+            None
+        }
+    }
+}
+
+/// A decoded SSTV picture: RGB pixels, 3 bytes per pixel.
+#[derive(Debug, Clone)]
+pub struct FmImage {
+    pub pixels: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+    pub mode: SstvMode,
+    /// The header the mode came from; `None` when the caller named the mode
+    /// and no header was found.
+    pub vis: Option<VisHeader>,
+}
+
+#[derive(Default)]
+pub struct FmSstvDecoder;
+
+impl FmSstvDecoder {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// VIS headers in `samples`: where each transmission starts and its mode.
+    pub fn find_transmissions(&self, samples: &[f32], sample_rate: u32) -> Vec<VisHeader> {
+        find_vis_headers(&demodulate(samples, sample_rate), sample_rate)
+    }
+
+    /// Decode the first transmission in `samples`.
+    ///
+    /// The mode is `params.sstv_mode` when set, otherwise the first VIS
+    /// header's. The picture starts after that header (at the start of
+    /// `samples` with neither). With `params.sync_lock`, lines follow the
+    /// tracked sync pulses; otherwise, or when the syncs do not hold the
+    /// mode's cadence, the nominal line timing. Levels are absolute
+    /// frequencies, so no contrast stretch, inversion or gamma applies.
+    /// `params.speed_factor` is not applied either: a mis-speed rip also
+    /// shifts every tone.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DecoderError::InvalidParams`] below an 8 kHz sample rate,
+    /// [`DecoderError::NoVisHeader`] when no mode is named and no header is
+    /// found, and [`DecoderError::InsufficientSamples`] when not one full
+    /// line follows.
+    pub fn decode(&self, samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Result<FmImage> {
+        if sample_rate < MIN_SAMPLE_RATE {
+            return Err(VoyagerError::Decoder(DecoderError::InvalidParams {
+                reason: format!("sample rate {sample_rate} Hz too low for SSTV (needs {MIN_SAMPLE_RATE} Hz)"),
+            }));
+        }
+        if samples.is_empty() {
+            return Err(VoyagerError::Decoder(DecoderError::InsufficientSamples {
+                needed: 1,
+                actual: 0,
+            }));
+        }
+
+        let freq = demodulate(samples, sample_rate);
+        let vis = find_vis_headers(&freq, sample_rate).into_iter().next();
+        let mode = match (params.sstv_mode, &vis) {
+            (Some(mode), Some(header)) if mode != header.mode => {
+                tracing::warn!(selected = %mode, vis = %header.mode, "VIS header names a different mode; decoding as selected");
+                mode
+            }
+            (Some(mode), _) => mode,
+            (None, Some(header)) => header.mode,
+            (None, None) => return Err(VoyagerError::Decoder(DecoderError::NoVisHeader)),
+        };
+        let start = vis.map_or(0, |header| header.end);
+        let spec = mode.spec();
+        tracing::debug!(%mode, start, "Decoding SSTV transmission");
+
+        let anchors = line_anchors(&freq, sample_rate, &spec, start, params.sync_lock);
+        let levels: Vec<f32> = freq.iter().map(|&f| (f - BLACK_HZ) / (WHITE_HZ - BLACK_HZ)).collect();
+        let rows = render_rows(&levels, sample_rate, &spec, &anchors);
+        if rows.is_empty() {
+            return Err(VoyagerError::Decoder(DecoderError::InsufficientSamples {
+                needed: start + (spec.first_sync_end_ms - spec.sync_ms + spec.line_ms) as usize * sample_rate as usize / 1000,
+                actual: samples.len(),
+            }));
+        }
+
+        let height = rows.len();
+        tracing::debug!(%mode, height, "SSTV decode completed");
+        Ok(FmImage {
+            pixels: rows.into_iter().flatten().flatten().collect(),
+            width: spec.width,
+            height,
+            mode,
+            vis,
+        })
+    }
+}
+
+/// End-of-sync positions of every line of the picture starting at `start`,
+/// in fractional samples.
+///
+/// With `sync_lock` the sync pulses are tracked on a trace that spikes
+/// where each pulse ends, so the shared tracker's falling-edge rule lands
+/// on the line start. A track that holds the mode's cadence is extended
+/// back to the first line and on to the last; anything else falls back to
+/// the nominal timing from `start`.
+fn line_anchors(freq: &[f32], sample_rate: u32, spec: &ModeSpec, start: usize, sync_lock: bool) -> Vec<f64> {
+    let per_ms = sample_rate as f64 / 1000.0;
+    let nominal = spec.line_ms * per_ms;
+    let first = start as f64 + spec.first_sync_end_ms * per_ms;
+    let count = spec.sync_lines();
+
+    if sync_lock && start < freq.len() {
+        let trace = sync_trace(&freq[start..], ((spec.sync_ms / 2.0 * per_ms) as usize).max(1));
+        let params = SyncParams {
+            expected_line_ms: spec.line_ms as f32,
+            ..SyncParams::default()
+        };
+        let tracked = track_line_syncs_with_lock(&trace, sample_rate, &params);
+        if let Some(summary) = interval_summary(&tracked.positions, sample_rate) {
+            let period = summary.median_samples;
+            if tracked.positions.len() >= 4 && (period - nominal).abs() / nominal < CADENCE_TOL {
+                let mut anchors: Vec<f64> = tracked.positions.iter().map(|&p| (start + p) as f64).collect();
+                // Lines before the track seeded, back to the first line
+                while anchors[0] - period > first - period / 2.0 {
+                    anchors.insert(0, anchors[0] - period);
+                }
+                // The tracker stops a period short of the end
+                while anchors.len() < count {
+                    let next = anchors[anchors.len() - 1] + period;
+                    if next >= freq.len() as f64 {
+                        break;
+                    }
+                    anchors.push(next);
+                }
+                anchors.truncate(count);
+                tracing::debug!(lines = anchors.len(), period, "Sync-locked SSTV lines");
+                return anchors;
+            }
+        }
+        tracing::debug!("No SSTV sync cadence found; using nominal line timing");
+    }
+    (0..count).map(|k| first + k as f64 * nominal).collect()
+}
+
+/// Sync trace: `sync(i) - sync(i + lag)`, where `sync` is how far the
+/// frequency sits toward the 1200 Hz sync tone. It peaks over the last
+/// `lag` samples of each sync pulse and drops to zero where the pulse ends.
+fn sync_trace(freq: &[f32], lag: usize) -> Vec<f32> {
+    let sync: Vec<f32> = freq
+        .iter()
+        .map(|&f| ((SYNC_EDGE_HZ + 100.0 - f) / 100.0).clamp(0.0, 1.0))
+        .collect();
+    (0..sync.len())
+        .map(|i| sync[i] - sync.get(i + lag).copied().unwrap_or(0.0))
+        .collect()
+}
+
+/// Image rows (RGB pixels) for each line anchor. Lines whose scans run off
+/// either end of the buffer are skipped.
+fn render_rows(levels: &[f32], sample_rate: u32, spec: &ModeSpec, anchors: &[f64]) -> Vec<Vec<[u8; 3]>> {
+    let per_ms = sample_rate as f64 / 1000.0;
+    let width = spec.width;
+    let scan = |anchor: f64, scan: Scan| -> Option<Vec<u8>> {
+        let from = anchor + scan.offset_ms * per_ms;
+        let to = from + scan.len_ms * per_ms;
+        // A final scan ending right at the buffer end may overrun it by the
+        // sync edge's few samples of lag
+        if from < 0.0 || to > levels.len() as f64 + SCAN_OVERRUN_MS * per_ms {
+            return None;
+        }
+        let (from, to) = (from.round() as usize, (to.round() as usize).min(levels.len()));
+        let mut line = Vec::with_capacity(width);
+        resample_line(&levels[from..to], width, &mut line);
+        Some(line.into_iter().map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8).collect())
+    };
+
+    let mut rows = Vec::new();
+    match spec.layout {
+        Layout::Rgb { red, green, blue } => {
+            for &anchor in anchors {
+                if let (Some(r), Some(g), Some(b)) = (scan(anchor, red), scan(anchor, green), scan(anchor, blue)) {
+                    rows.push((0..width).map(|x| [r[x], g[x], b[x]]).collect());
+                }
+            }
+        }
+        Layout::Ycc {
+            luma,
+            red_diff,
+            blue_diff,
+        } => {
+            for &anchor in anchors {
+                if let (Some(y), Some(cr), Some(cb)) = (scan(anchor, luma), scan(anchor, red_diff), scan(anchor, blue_diff)) {
+                    rows.push(ycc_row(&y, &cr, &cb));
+                }
+            }
+        }
+        Layout::DualLuma {
+            luma,
+            red_diff,
+            blue_diff,
+        } => {
+            for &anchor in anchors {
+                let scans = (
+                    scan(anchor, luma[0]),
+                    scan(anchor, luma[1]),
+                    scan(anchor, red_diff),
+                    scan(anchor, blue_diff),
+                );
+                if let (Some(y0), Some(y1), Some(cr), Some(cb)) = scans {
+                    rows.push(ycc_row(&y0, &cr, &cb));
+                    rows.push(ycc_row(&y1, &cr, &cb));
+                }
+            }
+        }
+        Layout::AlternatingChroma { luma, separator, chroma } => {
+            // (luma, chroma, chroma is B-Y): a 2300 Hz separator marks B-Y
+            let lines: Vec<(Vec<u8>, Vec<u8>, bool)> = anchors
+                .iter()
+                .filter_map(|&anchor| {
+                    let sep = scan(anchor, separator)?;
+                    let blue = sep.iter().map(|&v| v as u32).sum::<u32>() > 128 * sep.len() as u32;
+                    Some((scan(anchor, luma)?, scan(anchor, chroma)?, blue))
+                })
+                .collect();
+            let neutral = vec![128u8; width];
+            for (i, (y, own, blue)) in lines.iter().enumerate() {
+                // The missing difference comes from the neighbouring line
+                let other = [i.checked_sub(1), Some(i + 1)]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|j| lines.get(j))
+                    .find(|line| line.2 != *blue)
+                    .map_or(&neutral, |line| &line.1);
+                let (cr, cb) = if *blue { (other, own) } else { (own, other) };
+                rows.push(ycc_row(y, cr, cb));
+            }
+        }
+    }
+    rows.truncate(spec.height);
+    rows
+}
+
+/// Full-range BT.601 YCbCr to RGB for one row.
+fn ycc_row(y: &[u8], cr: &[u8], cb: &[u8]) -> Vec<[u8; 3]> {
+    y.iter()
+        .zip(cr)
+        .zip(cb)
+        .map(|((&y, &cr), &cb)| {
+            let (y, cr, cb) = (y as f32, cr as f32 - 128.0, cb as f32 - 128.0);
+            let channel = |v: f32| v.round().clamp(0.0, 255.0) as u8;
+            [
+                channel(y + 1.402 * cr),
+                channel(y - 0.344_136 * cb - 0.714_136 * cr),
+                channel(y + 1.772 * cb),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{encode_sstv, generate_sine_wave};
+
+    /// Eight vertical color bars, `width` x `height` RGB.
+    fn color_bars(width: usize, height: usize) -> Vec<u8> {
+        const BARS: [[u8; 3]; 8] = [
+            [255, 255, 255],
+            [255, 255, 0],
+            [0, 255, 255],
+            [0, 255, 0],
+            [255, 0, 255],
+            [255, 0, 0],
+            [0, 0, 255],
+            [0, 0, 0],
+        ];
+        (0..width * height).flat_map(|i| BARS[(i % width) * 8 / width]).collect()
+    }
+
+    /// Mean absolute channel error between the decoded picture and the
+    /// source, over the middle of each bar (away from edge smear).
+    fn bar_error(image: &FmImage, source: &[u8]) -> f64 {
+        let (w, mut total, mut count) = (image.width, 0.0, 0usize);
+        for y in 0..image.height {
+            for x in 0..w {
+                let within_bar = (x * 8 % w) as f64 / w as f64;
+                if !(0.25..0.75).contains(&within_bar) {
+                    continue;
+                }
+                for c in 0..3 {
+                    let i = (y * w + x) * 3 + c;
+                    total += (image.pixels[i] as f64 - source[i] as f64).abs();
+                    count += 1;
+                }
+            }
+        }
+        total / count as f64
+    }
+
+    #[test]
+    fn demodulates_tone_frequencies() {
+        for hz in [1200.0, 1500.0, 1900.0, 2300.0] {
+            let freq = demodulate(&generate_sine_wave(hz, 0.1, 11_025, 0.5), 11_025);
+            let settled = &freq[200..freq.len() - 200];
+            let mean = settled.iter().sum::<f32>() / settled.len() as f32;
+            assert!((mean - hz).abs() < 5.0, "{hz} Hz read as {mean}");
+        }
+    }
+
+    #[test]
+    fn vis_selects_mode_and_decodes_each_family() {
+        const RATE: u32 = 11_025;
+        for mode in [
+            SstvMode::MartinM2,
+            SstvMode::ScottieS2,
+            SstvMode::Robot36,
+            SstvMode::Robot72,
+            SstvMode::Pd50,
+        ] {
+            let spec = mode.spec();
+            let source = color_bars(spec.width, spec.height);
+            let audio = encode_sstv(mode, &source, RATE);
+
+            let image = FmSstvDecoder::new()
+                .decode(&audio, &DecoderParams::default(), RATE)
+                .unwrap_or_else(|e| panic!("{mode}: {e}"));
+            assert_eq!(image.mode, mode);
+            assert_eq!(image.vis.map(|v| v.code), Some(mode.vis_code()));
+            assert_eq!(image.width, spec.width);
+            assert_eq!(image.height, spec.height, "{mode}");
+            let error = bar_error(&image, &source[..image.pixels.len()]);
+            assert!(error < 20.0, "{mode}: mean bar error {error:.1}");
+        }
+    }
+
+    #[test]
+    fn finds_every_transmission_and_honors_explicit_mode() {
+        const RATE: u32 = 8_000;
+        let spec = SstvMode::Robot36.spec();
+        let first = encode_sstv(SstvMode::Robot36, &color_bars(spec.width, spec.height), RATE);
+        let mut audio = first.clone();
+        audio.extend(vec![0.0; RATE as usize]);
+        let second_at = audio.len();
+        audio.extend(encode_sstv(SstvMode::MartinM2, &color_bars(320, 256), RATE));
+
+        let headers = FmSstvDecoder::new().find_transmissions(&audio, RATE);
+        let modes: Vec<SstvMode> = headers.iter().map(|h| h.mode).collect();
+        assert_eq!(modes, [SstvMode::Robot36, SstvMode::MartinM2]);
+        assert!(headers[1].start >= second_at && headers[1].start < second_at + RATE as usize);
+
+        // Without its header the picture decodes only with the mode named
+        let headerless = &first[headers[0].end..];
+        let decoder = FmSstvDecoder::new();
+        assert!(matches!(
+            decoder.decode(headerless, &DecoderParams::default(), RATE),
+            Err(VoyagerError::Decoder(DecoderError::NoVisHeader))
+        ));
+        let params = DecoderParams {
+            sstv_mode: Some(SstvMode::Robot36),
+            ..DecoderParams::default()
+        };
+        let image = decoder.decode(headerless, &params, RATE).unwrap();
+        assert!(image.vis.is_none());
+        assert!(image.height + 2 >= spec.height, "{} rows", image.height);
+    }
+
+    #[test]
+    fn mode_names_parse_and_vis_codes_are_unique() {
+        assert_eq!("martin-m1".parse(), Ok(SstvMode::MartinM1));
+        assert_eq!("Scottie S2".parse(), Ok(SstvMode::ScottieS2));
+        assert_eq!("pd120".parse(), Ok(SstvMode::Pd120));
+        assert!("wraase".parse::<SstvMode>().is_err());
+        for mode in SstvMode::ALL {
+            assert_eq!(SstvMode::from_vis(mode.vis_code()), Some(mode));
+        }
+        // Published durations: Martin M1 about 114 s, Robot 36 about 36 s
+        assert!((SstvMode::MartinM1.spec().duration_secs() - 114.3).abs() < 0.2);
+        assert!((SstvMode::Robot36.spec().duration_secs() - 36.0).abs() < 0.1);
+    }
+}
@@ -0,0 +1,451 @@
+//! Golden-image regression harness: decodes a manifest of cases (file,
+//! channel, time window, decoder settings) and compares each output with a
+//! stored baseline image, so a decoder change that degrades some frame
+//! fails loudly instead of slipping through.
+//!
+//! The manifest is TOML:
+//!
+//! ```toml
+//! min_ssim = 0.98          # default tolerance for every case
+//! baselines = "baselines"  # baseline directory, relative to the manifest
+//!
+//! [[cases]]
+//! name = "calibration_circle"
+//! file = "../../assets/sync_image1.wav"  # relative to the manifest
+//! channel = "left"
+//! start_secs = 0.0
+//! duration_secs = 5.0      # omit to decode to the end of the file
+//! min_ssim = 0.99          # optional, per case
+//! [cases.params]           # DecoderParams; unset fields take the defaults
+//! invert = true
+//! ```
+//!
+//! Outputs are scored by SSIM against `<baselines>/<name>.png` (per color
+//! plane for color outputs, the worst plane counting). A failing case writes
+//! the actual output, the baseline and an amplified difference image to the
+//! diff directory. Blessing rewrites the baselines from the current decoder.
+//! Cases whose audio is absent (the record rips are not in the repository)
+//! are skipped.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use image::{DynamicImage, GrayImage, RgbImage};
+use serde::Deserialize;
+
+use crate::audio::WaveformChannel;
+use crate::backend;
+use crate::compare::similarity;
+use crate::pipeline::{DecodingPipeline, RecordFrames};
+use crate::sample_source::open_source;
+use crate::sstv::DecoderParams;
+
+/// Tolerance when neither the manifest nor the case sets one.
+pub const DEFAULT_MIN_SSIM: f64 = 0.98;
+
+/// Differences are scaled by this in diff images, so small drifts show.
+const DIFF_GAIN: u16 = 4;
+
+/// A manifest of golden cases.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoldenManifest {
+    /// SSIM a case must reach unless it sets its own
+    #[serde(default = "default_min_ssim")]
+    pub min_ssim: f64,
+    /// Baseline directory, relative to the manifest
+    #[serde(default = "default_baselines")]
+    pub baselines: PathBuf,
+    #[serde(default)]
+    pub cases: Vec<GoldenCase>,
+}
+
+fn default_min_ssim() -> f64 {
+    DEFAULT_MIN_SSIM
+}
+
+fn default_baselines() -> PathBuf {
+    PathBuf::from("baselines")
+}
+
+/// One decode to check.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoldenCase {
+    /// Baseline and diff file stem; letters, digits, `-` and `_`
+    pub name: String,
+    /// Audio file, relative to the manifest
+    pub file: PathBuf,
+    #[serde(default = "default_channel")]
+    pub channel: WaveformChannel,
+    /// Window start; for frame backends, a position inside the frame
+    #[serde(default)]
+    pub start_secs: f64,
+    /// Window length; `None` decodes to the end of the file
+    #[serde(default)]
+    pub duration_secs: Option<f64>,
+    #[serde(default)]
+    pub params: DecoderParams,
+    /// Overrides the manifest's tolerance
+    #[serde(default)]
+    pub min_ssim: Option<f64>,
+}
+
+fn default_channel() -> WaveformChannel {
+    WaveformChannel::Left
+}
+
+impl GoldenManifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).with_context(|| format!("reading manifest {}", path.display()))?;
+        let manifest: Self = toml::from_str(&contents).with_context(|| format!("parsing manifest {}", path.display()))?;
+        let mut names = std::collections::HashSet::new();
+        for case in &manifest.cases {
+            anyhow::ensure!(
+                !case.name.is_empty() && case.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+                "case name {:?} must be letters, digits, '-' and '_' (it names the baseline file)",
+                case.name
+            );
+            anyhow::ensure!(names.insert(&case.name), "duplicate case name {:?}", case.name);
+        }
+        Ok(manifest)
+    }
+}
+
+/// Decode `case`, with its file resolved against `base`.
+pub fn decode_case(case: &GoldenCase, base: &Path) -> Result<DynamicImage> {
+    let path = base.join(&case.file);
+    let source = open_source(&path, 0).with_context(|| format!("loading {}", path.display()))?;
+    source.require_channel(case.channel)?;
+    let rate = source.sample_rate() as f64;
+    let start = ((case.start_secs.max(0.0) * rate) as usize).min(source.len());
+    let end = case.duration_secs.map_or(source.len(), |d| {
+        start.saturating_add((d.max(0.0) * rate) as usize).min(source.len())
+    });
+    let pipeline = DecodingPipeline::new();
+    let result = if backend::for_mode(case.params.mode).info().capabilities.frames {
+        let frames = RecordFrames::segment(&*source, case.channel, &case.params)?;
+        let index = frames
+            .frame_at(start)
+            .with_context(|| format!("no image frame at {:.3}s", case.start_secs))?;
+        pipeline.process_frame(&*source, &frames, index, &case.params)?
+    } else {
+        anyhow::ensure!(start < end, "empty window at {:.3}s", case.start_secs);
+        let samples = source.read(case.channel, start..end)?;
+        pipeline.process(&samples, &case.params, source.sample_rate())?
+    };
+    Ok(result.to_dynamic_image()?)
+}
+
+/// How a harness run treats its cases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenOptions {
+    /// Write each output as the new baseline instead of checking it
+    pub bless: bool,
+    /// Where failing cases write their actual/expected/diff images
+    pub diff_dir: PathBuf,
+    /// Run only the case with this name
+    pub only: Option<String>,
+}
+
+/// What happened to one case.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaseOutcome {
+    Passed {
+        ssim: f64,
+    },
+    /// Below tolerance, a size change, a decode error or a missing
+    /// baseline; `diff` is set when images were written
+    Failed {
+        ssim: Option<f64>,
+        reason: String,
+        diff: Option<PathBuf>,
+    },
+    Blessed {
+        path: PathBuf,
+    },
+    /// The case's audio is not present
+    Skipped {
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseReport {
+    pub name: String,
+    pub outcome: CaseOutcome,
+}
+
+impl CaseReport {
+    pub fn is_failure(&self) -> bool {
+        matches!(self.outcome, CaseOutcome::Failed { .. })
+    }
+}
+
+/// Run (or bless) every case of the manifest at `path`. Errors only when
+/// the manifest cannot be read or `only` names no case; case failures are
+/// reported per case.
+pub fn run_manifest(path: &Path, options: &GoldenOptions) -> Result<Vec<CaseReport>> {
+    let manifest = GoldenManifest::load(path)?;
+    let base = path.parent().unwrap_or(Path::new("."));
+    let baselines = base.join(&manifest.baselines);
+    let cases: Vec<&GoldenCase> = manifest
+        .cases
+        .iter()
+        .filter(|case| options.only.as_ref().is_none_or(|only| &case.name == only))
+        .collect();
+    if let Some(only) = &options.only {
+        anyhow::ensure!(!cases.is_empty(), "no case named {only:?} in {}", path.display());
+    }
+    let reports = cases
+        .into_iter()
+        .map(|case| {
+            let min_ssim = case.min_ssim.unwrap_or(manifest.min_ssim);
+            CaseReport {
+                name: case.name.clone(),
+                outcome: run_case(case, base, &baselines, min_ssim, options),
+            }
+        })
+        .collect();
+    Ok(reports)
+}
+
+fn run_case(case: &GoldenCase, base: &Path, baselines: &Path, min_ssim: f64, options: &GoldenOptions) -> CaseOutcome {
+    let audio = base.join(&case.file);
+    if !audio.exists() {
+        return CaseOutcome::Skipped {
+            reason: format!("{} not present", audio.display()),
+        };
+    }
+    let failed = |reason: String| CaseOutcome::Failed {
+        ssim: None,
+        reason,
+        diff: None,
+    };
+    let actual = match decode_case(case, base) {
+        Ok(image) => image,
+        Err(e) => return failed(format!("decode failed: {e:#}")),
+    };
+    let baseline_path = baselines.join(format!("{}.png", case.name));
+
+    if options.bless {
+        let written = std::fs::create_dir_all(baselines)
+            .with_context(|| format!("creating {}", baselines.display()))
+            .and_then(|()| {
+                actual
+                    .save(&baseline_path)
+                    .with_context(|| format!("writing {}", baseline_path.display()))
+            });
+        return match written {
+            Ok(()) => CaseOutcome::Blessed { path: baseline_path },
+            Err(e) => failed(format!("{e:#}")),
+        };
+    }
+
+    let expected = match image::open(&baseline_path) {
+        Ok(image) => image,
+        Err(e) => {
+            return failed(format!(
+                "no baseline at {} ({e}); bless the case to create it",
+                baseline_path.display()
+            ))
+        }
+    };
+    let (ssim, reason) = if actual.width() != expected.width() || actual.height() != expected.height() {
+        (
+            None,
+            format!(
+                "size changed: {}x{} (baseline {}x{})",
+                actual.width(),
+                actual.height(),
+                expected.width(),
+                expected.height()
+            ),
+        )
+    } else {
+        let ssim = image_ssim(&actual, &expected);
+        if ssim >= min_ssim {
+            return CaseOutcome::Passed { ssim };
+        }
+        (Some(ssim), format!("SSIM {ssim:.4} below {min_ssim}"))
+    };
+    let diff = match write_diff(&options.diff_dir, &case.name, &actual, &expected) {
+        Ok(()) => Some(options.diff_dir.clone()),
+        Err(e) => {
+            tracing::warn!(case = %case.name, error = %e, "Could not write golden diff images");
+            None
+        }
+    };
+    CaseOutcome::Failed { ssim, reason, diff }
+}
+
+/// SSIM of equally sized images: on luma for grayscale, otherwise the
+/// worst of the R, G and B planes (a swapped plane should not average out).
+pub fn image_ssim(actual: &DynamicImage, expected: &DynamicImage) -> f64 {
+    let gray = |image: &DynamicImage| image.color().channel_count() < 3;
+    if gray(actual) && gray(expected) {
+        return similarity(&actual.to_luma8(), &expected.to_luma8()).ssim;
+    }
+    let (a, b) = (actual.to_rgb8(), expected.to_rgb8());
+    (0..3)
+        .map(|plane| similarity(&rgb_plane(&a, plane), &rgb_plane(&b, plane)).ssim)
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn rgb_plane(image: &RgbImage, plane: usize) -> GrayImage {
+    GrayImage::from_fn(image.width(), image.height(), |x, y| {
+        image::Luma([image.get_pixel(x, y)[plane]])
+    })
+}
+
+/// Difference image: the baseline dimmed, with the largest per-channel
+/// difference (amplified) in red.
+pub fn diff_image(actual: &DynamicImage, expected: &DynamicImage) -> RgbImage {
+    let (a, b) = (actual.to_rgb8(), expected.to_rgb8());
+    let luma = expected.to_luma8();
+    RgbImage::from_fn(b.width(), b.height(), |x, y| {
+        let (p, q) = (a.get_pixel(x, y), b.get_pixel(x, y));
+        let difference = (0..3).map(|c| p[c].abs_diff(q[c])).max().unwrap_or(0) as u16;
+        let shade = (luma.get_pixel(x, y)[0] as u16 * 2 / 5) as u8;
+        let red = (difference * DIFF_GAIN).min(255) as u8;
+        image::Rgb([shade.max(red), shade, shade])
+    })
+}
+
+/// Write `<name>.actual.png`, `<name>.expected.png` and `<name>.diff.png`.
+fn write_diff(dir: &Path, name: &str, actual: &DynamicImage, expected: &DynamicImage) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let save = |suffix: &str, image: &DynamicImage| {
+        let path = dir.join(format!("{name}.{suffix}.png"));
+        image.save(&path).with_context(|| format!("writing {}", path.display()))
+    };
+    save("actual", actual)?;
+    save("expected", expected)?;
+    if actual.width() == expected.width() && actual.height() == expected.height() {
+        save("diff", &DynamicImage::ImageRgb8(diff_image(actual, expected)))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{create_test_wav_file, encode_image_to_audio};
+
+    const RATE: u32 = 16_000;
+    const LINE_MS: f32 = 8.32;
+
+    /// A manifest with one window case over a synthetic recording, in a
+    /// fresh directory (the WAV is copied beside it).
+    fn fixture(extra: &str) -> (tempfile::TempDir, PathBuf) {
+        let (width, lines) = (64usize, 96usize);
+        let pixels: Vec<u8> = (0..width * lines)
+            .map(|i| (((i % width) * 4 + (i / width) * 2) % 256) as u8)
+            .collect();
+        let audio = encode_image_to_audio(&pixels, width, RATE, LINE_MS);
+        let wav = create_test_wav_file(&audio, RATE, 1);
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::copy(wav.path(), dir.path().join("ramp.wav")).unwrap();
+        let manifest = dir.path().join("golden.toml");
+        std::fs::write(
+            &manifest,
+            format!(
+                "min_ssim = 0.99\n{extra}\n[[cases]]\nname = \"ramp\"\nfile = \"ramp.wav\"\n\
+                 [cases.params]\nline_duration_ms = {LINE_MS}\nwidth = 128\n\n\
+                 [[cases]]\nname = \"absent\"\nfile = \"missing.wav\"\n"
+            ),
+        )
+        .unwrap();
+        (dir, manifest)
+    }
+
+    fn options(dir: &Path, bless: bool) -> GoldenOptions {
+        GoldenOptions {
+            bless,
+            diff_dir: dir.join("diffs"),
+            only: None,
+        }
+    }
+
+    #[test]
+    fn bless_then_check_passes_and_skips_absent_audio() {
+        let (dir, manifest) = fixture("");
+        let reports = run_manifest(&manifest, &options(dir.path(), false)).unwrap();
+        assert!(
+            matches!(&reports[0].outcome, CaseOutcome::Failed { reason, .. } if reason.contains("no baseline")),
+            "{reports:?}"
+        );
+        assert!(matches!(reports[1].outcome, CaseOutcome::Skipped { .. }));
+
+        let blessed = run_manifest(&manifest, &options(dir.path(), true)).unwrap();
+        assert!(matches!(&blessed[0].outcome, CaseOutcome::Blessed { path } if path.ends_with("baselines/ramp.png")));
+
+        let reports = run_manifest(&manifest, &options(dir.path(), false)).unwrap();
+        assert!(
+            matches!(reports[0].outcome, CaseOutcome::Passed { ssim } if ssim > 0.999),
+            "{reports:?}"
+        );
+        assert!(!reports.iter().any(CaseReport::is_failure));
+    }
+
+    #[test]
+    fn a_degraded_baseline_fails_and_writes_diffs() {
+        let (dir, manifest) = fixture("baselines = \"golden\"");
+        run_manifest(&manifest, &options(dir.path(), true)).unwrap();
+        // Damage a band of the baseline, as a decoder regression would
+        let path = dir.path().join("golden/ramp.png");
+        let mut baseline = image::open(&path).unwrap().to_luma8();
+        let (width, height) = baseline.dimensions();
+        for y in height / 3..height / 2 {
+            for x in 0..width {
+                baseline.put_pixel(x, y, image::Luma([255 - baseline.get_pixel(x, y)[0]]));
+            }
+        }
+        baseline.save(&path).unwrap();
+
+        let only = GoldenOptions {
+            only: Some("ramp".to_string()),
+            ..options(dir.path(), false)
+        };
+        let reports = run_manifest(&manifest, &only).unwrap();
+        assert_eq!(reports.len(), 1);
+        let CaseOutcome::Failed {
+            ssim: Some(ssim),
+            diff: Some(diffs),
+            ..
+        } = &reports[0].outcome
+        else {
+            panic!("expected a failure with diffs: {reports:?}");
+        };
+        assert!(*ssim < 0.99);
+        let diff = image::open(diffs.join("ramp.diff.png")).unwrap().to_rgb8();
+        let damaged = diff.get_pixel(width / 2, height * 5 / 12);
+        let intact = diff.get_pixel(width / 2, height / 10);
+        assert!(damaged[0] > damaged[1] && intact[0] == intact[1], "{damaged:?} {intact:?}");
+        assert!(diffs.join("ramp.actual.png").exists() && diffs.join("ramp.expected.png").exists());
+
+        let unknown = GoldenOptions {
+            only: Some("nope".to_string()),
+            ..options(dir.path(), false)
+        };
+        assert!(run_manifest(&manifest, &unknown).is_err());
+    }
+
+    #[test]
+    fn manifests_reject_unsafe_and_duplicate_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("golden.toml");
+        for cases in [
+            "[[cases]]\nname = \"../x\"\nfile = \"a.wav\"\n",
+            "[[cases]]\nname = \"a\"\nfile = \"a.wav\"\n[[cases]]\nname = \"a\"\nfile = \"b.wav\"\n",
+        ] {
+            std::fs::write(&path, cases).unwrap();
+            assert!(GoldenManifest::load(&path).is_err(), "{cases}");
+        }
+        std::fs::write(&path, "[[cases]]\nname = \"a\"\nfile = \"a.wav\"\nstart_secs = 2.5\n").unwrap();
+        let manifest = GoldenManifest::load(&path).unwrap();
+        assert_eq!(manifest.min_ssim, DEFAULT_MIN_SSIM);
+        assert_eq!(manifest.cases[0].channel, WaveformChannel::Left);
+        assert_eq!(manifest.cases[0].params, DecoderParams::default());
+    }
+}
@@ -1,5 +1,6 @@
 use egui::ColorImage;
 
+use crate::backend;
 use crate::sstv::DecoderMode;
 
 /// Convert pixel array to an egui ColorImage
@@ -18,8 +19,9 @@ pub fn image_from_pixels(pixels: &[u8], mode: DecoderMode, width: usize) -> Colo
         return ColorImage::new([width, 1], vec![egui::Color32::BLACK; width]);
     }
 
-    match mode {
-        DecoderMode::Grayscale => {
+    // The mode's backend says whether its pixels are RGB
+    match backend::for_mode(mode).info().capabilities.color {
+        false => {
             let height = pixels.len() / width;
             let height = if height == 0 { 1 } else { height };
 
@@ -43,7 +45,7 @@ pub fn image_from_pixels(pixels: &[u8], mode: DecoderMode, width: usize) -> Colo
             }
             img
         }
-        DecoderMode::PseudoColor => {
+        true => {
             let bytes_per_pixel = 3;
             let height = pixels.len() / (width * bytes_per_pixel);
             let height = if height == 0 { 1 } else { height };
@@ -57,7 +59,7 @@ pub fn image_from_pixels(pixels: &[u8], mode: DecoderMode, width: usize) -> Colo
                     pixels_len = pixels.len(),
                     row_size,
                     discarded = pixels.len() % row_size,
-                    "PseudoColor: partial row detected and discarded"
+                    "RGB: partial row detected and discarded"
                 );
             }
 
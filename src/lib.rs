@@ -3,15 +3,28 @@
 pub mod analysis;
 pub mod audio;
 pub mod audio_state;
+pub mod backend;
 pub mod batch;
 pub mod catalog;
 pub mod cli;
+pub mod codec;
+pub mod compare;
 pub mod config;
+pub mod envelope;
 pub mod error;
+pub mod fm_sstv;
+pub mod golden;
 pub mod image_output;
 pub mod metrics;
+pub mod pcm;
 pub mod pipeline;
+pub mod preset;
+pub mod project;
+pub mod registration;
+pub mod riff;
+pub mod sample_source;
 pub mod sstv;
+pub mod streaming;
 pub mod utils;
 
 // Test fixtures for synthetic audio generation
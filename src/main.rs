@@ -10,16 +10,29 @@ use app::VoyagerApp;
 pub mod analysis;
 pub mod audio;
 pub mod audio_state;
+pub mod backend;
 pub mod batch;
 pub mod catalog;
 pub mod cli;
+pub mod codec;
+pub mod compare;
 pub mod config;
+pub mod envelope;
 pub mod error;
+pub mod fm_sstv;
+pub mod golden;
 pub mod image_output;
 pub mod metrics;
+pub mod pcm;
 pub mod pipeline;
+pub mod preset;
+pub mod project;
+pub mod registration;
+pub mod riff;
+pub mod sample_source;
 pub mod services;
 pub mod sstv;
+pub mod streaming;
 pub mod test_fixtures;
 pub mod ui;
 pub mod utils;
@@ -28,6 +41,8 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 
+use crate::audio::WaveformChannel;
+use crate::preset::Preset;
 use crate::sstv::DecoderMode;
 
 #[derive(Parser)]
@@ -39,6 +54,16 @@ struct Cli {
     /// Load this WAV file on startup (GUI mode)
     #[arg(long, global = false)]
     load: Option<PathBuf>,
+
+    /// Open this project (.voyager.toml) on startup (GUI mode)
+    #[arg(long, global = false, conflicts_with = "load")]
+    project: Option<PathBuf>,
+
+    /// Decoder preset supplying the defaults for batch and diagnostics
+    /// commands, or applied on startup in GUI mode (built-in or from the
+    /// config file)
+    #[arg(long, global = true)]
+    preset: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -53,9 +78,17 @@ enum Commands {
         #[arg(short, long)]
         output: PathBuf,
 
-        /// Decoder mode (Grayscale or PseudoColor)
-        #[arg(short, long, value_enum, default_value_t = ModeArg::Grayscale)]
-        mode: ModeArg,
+        /// Decoding backend (see `backends`). frame-triplet segments each
+        /// file into frames and writes one PNG per grayscale frame and per
+        /// composited color triplet; sstv writes one PNG per amateur SSTV
+        /// transmission found
+        #[arg(short, long, default_value = "grayscale", value_parser = cli::mode_parser())]
+        mode: DecoderMode,
+
+        /// Channel: left, right, mid, side or a number from 1 [default:
+        /// preset's]
+        #[arg(short, long)]
+        channel: Option<WaveformChannel>,
     },
 
     /// Diagnostics: decode windows, spectrograms, sync detection, stats
@@ -63,21 +96,6 @@ enum Commands {
     Diagnostics(cli::DiagnosticsCommand),
 }
 
-#[derive(clap::ValueEnum, Clone, Debug)]
-enum ModeArg {
-    Grayscale,
-    PseudoColor,
-}
-
-impl From<ModeArg> for DecoderMode {
-    fn from(val: ModeArg) -> Self {
-        match val {
-            ModeArg::Grayscale => DecoderMode::Grayscale,
-            ModeArg::PseudoColor => DecoderMode::PseudoColor,
-        }
-    }
-}
-
 fn main() -> eframe::Result {
     // Initialize tracing subscriber
     fmt()
@@ -90,31 +108,52 @@ fn main() -> eframe::Result {
         .init();
 
     let cli = Cli::parse();
+    let resolve_preset = |name: Option<&str>| match cli::resolve_preset(name) {
+        Ok(preset) => preset,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            std::process::exit(1);
+        }
+    };
 
-    match cli.command {
-        Some(Commands::Batch { input, output, mode }) => {
-            let args = batch::BatchArgs {
-                input_pattern: input,
-                output_dir: output,
-                mode: mode.into(),
-            };
-
-            if let Err(e) = batch::run_batch_processing(args) {
-                tracing::error!("Batch processing failed: {}", e);
-                std::process::exit(1);
+    if let Some(command) = cli.command {
+        let preset = resolve_preset(cli.preset.as_deref());
+        match command {
+            Commands::Batch {
+                input,
+                output,
+                mode,
+                channel,
+            } => {
+                let preset = Preset {
+                    channel: channel.unwrap_or(preset.channel),
+                    ..preset
+                };
+                let args = batch::BatchArgs {
+                    input_pattern: input,
+                    output_dir: output,
+                    mode,
+                    preset,
+                };
+
+                if let Err(e) = batch::run_batch_processing(args) {
+                    tracing::error!("Batch processing failed: {}", e);
+                    std::process::exit(1);
+                }
             }
-            return Ok(());
-        }
-        Some(Commands::Diagnostics(command)) => {
-            if let Err(e) = cli::run(command) {
-                eprintln!("error: {e:#}");
-                std::process::exit(1);
+            Commands::Diagnostics(command) => {
+                if let Err(e) = cli::run(command, &preset) {
+                    eprintln!("error: {e:#}");
+                    std::process::exit(1);
+                }
             }
-            return Ok(());
         }
-        None => {}
+        return Ok(());
     }
 
+    // Resolved before the window opens so an unknown name fails like it
+    // does for the subcommands
+    let preset = cli.preset.as_deref().map(|name| resolve_preset(Some(name)));
     tracing::info!("Starting Voyager Golden Record Explorer");
 
     let options = eframe::NativeOptions {
@@ -136,6 +175,13 @@ fn main() -> eframe::Result {
             if let Some(path) = cli.load {
                 app.load_wav_from_path(&path);
             }
+            if let Some(path) = cli.project {
+                app.open_project(&cc.egui_ctx, &path);
+            }
+            // Applied last, so it overrides the settings a project saved
+            if let Some(preset) = preset {
+                app.apply_preset(&preset);
+            }
             Ok(Box::new(app))
         }),
     )
@@ -0,0 +1,448 @@
+//! Headerless interleaved PCM input, from a file or stdin (`-`), so the CLI
+//! can take audio piped from `sox`, `ffmpeg` or a capture tool without a
+//! temp file.
+//!
+//! Raw files are random-access like any WAV (see
+//! [`crate::sample_source::StreamingWavReader::open_raw`]). Stdin can only
+//! be read once, front to back: [`PcmStream`] reads it in blocks, so
+//! detectors that work block by block run in constant memory, and
+//! [`PcmStream::into_resident`] buffers it for commands that need random
+//! access.
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use hound::{SampleFormat, WavSpec};
+
+use crate::audio::{WavReader, WaveformChannel};
+use crate::codec::{AudioFormat, Container};
+use crate::error::{AudioError, Result};
+use crate::sample_source::{decode_frames, Block};
+
+/// The input path that means "read stdin".
+pub const STDIN_PATH: &str = "-";
+
+/// Frames read per refill when skipping.
+const SKIP_CHUNK_FRAMES: usize = 1 << 16;
+
+/// Whether `path` names stdin rather than a file.
+pub fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == STDIN_PATH
+}
+
+/// Sample encoding of headerless PCM, all little-endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PcmEncoding {
+    /// Unsigned 8-bit, as in 8-bit WAV.
+    U8,
+    S16,
+    /// Signed 24-bit packed in 3 bytes.
+    S24,
+    S32,
+    F32,
+}
+
+impl PcmEncoding {
+    /// Width of one sample in bytes.
+    pub fn bytes(self) -> u16 {
+        match self {
+            Self::U8 => 1,
+            Self::S16 => 2,
+            Self::S24 => 3,
+            Self::S32 | Self::F32 => 4,
+        }
+    }
+}
+
+/// Layout of a headerless PCM input, which has nothing to sniff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawPcmFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub encoding: PcmEncoding,
+}
+
+impl RawPcmFormat {
+    /// The equivalent WAV spec, for the shared sample decoding.
+    pub fn spec(&self) -> WavSpec {
+        let (sample_format, bits_per_sample) = match self.encoding {
+            PcmEncoding::F32 => (SampleFormat::Float, 32),
+            encoding => (SampleFormat::Int, encoding.bytes() * 8),
+        };
+        WavSpec {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            bits_per_sample,
+            sample_format,
+        }
+    }
+
+    /// Bytes per interleaved frame.
+    pub fn block_align(&self) -> usize {
+        self.encoding.bytes() as usize * self.channels as usize
+    }
+
+    /// Check the layout the same way file headers are checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AudioError::InvalidSampleRate`] below 8 kHz and
+    /// [`AudioError::UnsupportedChannels`] for zero channels.
+    pub fn validate(&self) -> Result<()> {
+        if self.sample_rate < 8000 {
+            return Err(AudioError::InvalidSampleRate { rate: self.sample_rate }.into());
+        }
+        if self.channels == 0 {
+            return Err(AudioError::UnsupportedChannels { channels: 0 }.into());
+        }
+        Ok(())
+    }
+}
+
+/// One-pass reader of interleaved PCM (typically stdin).
+pub struct PcmStream {
+    reader: Box<dyn Read + Send>,
+    /// Named in errors; `-` for stdin.
+    path: PathBuf,
+    format: RawPcmFormat,
+    /// Frames consumed so far.
+    position: usize,
+    /// Absolute frame at which reads stop, from [`PcmStream::limit`].
+    end: Option<usize>,
+}
+
+impl PcmStream {
+    /// Open `path` (or stdin for `-`) as raw PCM of `format`.
+    ///
+    /// # Errors
+    ///
+    /// [`RawPcmFormat::validate`] errors, and [`AudioError::LoadFailed`] when
+    /// the file cannot be opened.
+    pub fn open(path: &Path, format: RawPcmFormat) -> Result<Self> {
+        let reader: Box<dyn Read + Send> = if is_stdin(path) {
+            Box::new(io::stdin())
+        } else {
+            Box::new(std::fs::File::open(path).map_err(|e| AudioError::LoadFailed {
+                path: path.to_path_buf(),
+                source: hound::Error::IoError(e),
+            })?)
+        };
+        Self::from_reader(reader, path, format)
+    }
+
+    /// Wrap any reader; `path` only names it in errors.
+    ///
+    /// # Errors
+    ///
+    /// [`RawPcmFormat::validate`] errors.
+    pub fn from_reader(reader: Box<dyn Read + Send>, path: &Path, format: RawPcmFormat) -> Result<Self> {
+        format.validate()?;
+        Ok(Self {
+            reader: Box::new(io::BufReader::new(reader)),
+            path: path.to_path_buf(),
+            format,
+            position: 0,
+            end: None,
+        })
+    }
+
+    pub fn format(&self) -> RawPcmFormat {
+        self.format
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.format.sample_rate
+    }
+
+    /// Frames consumed so far (skipped or read).
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Stop reading `frames` frames after the current position.
+    pub fn limit(&mut self, frames: usize) {
+        self.end = Some(self.position.saturating_add(frames));
+    }
+
+    /// Discard up to `frames` frames; returns how many were skipped (fewer
+    /// at the end of the stream).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AudioError::ReadFailed`] when the stream fails.
+    pub fn skip(&mut self, frames: usize) -> Result<usize> {
+        let mut skipped = 0;
+        while skipped < frames {
+            let chunk = (frames - skipped).min(SKIP_CHUNK_FRAMES);
+            let n = self.read_bytes(chunk)?.len() / self.format.block_align();
+            if n == 0 {
+                break;
+            }
+            skipped += n;
+        }
+        Ok(skipped)
+    }
+
+    /// Read up to `frames` frames of `channel`. Shorter at the end of the
+    /// stream (or the limit); empty once it is exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AudioError::ReadFailed`] when the stream fails.
+    pub fn read(&mut self, channel: WaveformChannel, frames: usize) -> Result<Vec<f32>> {
+        let bytes = self.read_bytes(frames)?;
+        Ok(decode_frames(
+            &self.format.spec(),
+            self.format.encoding.bytes(),
+            &bytes,
+            channel,
+        ))
+    }
+
+    /// Read the rest of the stream (up to the limit) into memory, frame 0
+    /// being the current position.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AudioError::ReadFailed`] when the stream fails and
+    /// [`AudioError::EmptyFile`] when nothing is left to read.
+    pub fn into_resident(mut self) -> Result<WavReader> {
+        let remaining = self.end.map_or(usize::MAX, |end| end.saturating_sub(self.position));
+        let mut bytes = Vec::new();
+        (&mut self.reader)
+            .take(remaining.saturating_mul(self.format.block_align()) as u64)
+            .read_to_end(&mut bytes)
+            .map_err(|source| AudioError::ReadFailed {
+                path: self.path.clone(),
+                source,
+            })?;
+        bytes.truncate(bytes.len() - bytes.len() % self.format.block_align());
+        if bytes.is_empty() {
+            return Err(AudioError::EmptyFile { path: self.path }.into());
+        }
+        self.position += bytes.len() / self.format.block_align();
+
+        let spec = self.format.spec();
+        let width = self.format.encoding.bytes();
+        let channel_data: Vec<Arc<[f32]>> = (0..self.format.channels)
+            .map(|index| decode_frames(&spec, width, &bytes, WaveformChannel::index(index)).into())
+            .collect();
+        Ok(WavReader {
+            channel_data,
+            sample_rate: self.format.sample_rate,
+            channels: self.format.channels,
+            format: AudioFormat::wav(Container::Raw, &spec),
+        })
+    }
+
+    /// Walk the rest of the stream in blocks laid out like
+    /// [`crate::sample_source::read_blocks`]: `block_len` owned frames,
+    /// padded with `margin` frames of context on both sides (clamped to the
+    /// frames this walk reads). Offsets count from the start of the stream.
+    pub fn blocks(self, channel: WaveformChannel, block_len: usize, margin: usize) -> PcmBlocks {
+        let origin = self.position;
+        PcmBlocks {
+            stream: self,
+            channel,
+            block_len: block_len.max(1),
+            margin,
+            origin,
+            buffer: Vec::new(),
+            buffer_offset: origin,
+            next: origin,
+            exhausted: false,
+        }
+    }
+
+    /// Read up to `frames` whole frames of raw bytes.
+    fn read_bytes(&mut self, frames: usize) -> Result<Vec<u8>> {
+        let frames = match self.end {
+            Some(end) => frames.min(end.saturating_sub(self.position)),
+            None => frames,
+        };
+        let block_align = self.format.block_align();
+        let mut bytes = vec![0u8; frames * block_align];
+        let mut filled = 0;
+        while filled < bytes.len() {
+            match self.reader.read(&mut bytes[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(source) => {
+                    return Err(AudioError::ReadFailed {
+                        path: self.path.clone(),
+                        source,
+                    }
+                    .into())
+                }
+            }
+        }
+        // A partial frame can only be the truncated tail of the stream
+        bytes.truncate(filled - filled % block_align);
+        self.position += bytes.len() / block_align;
+        Ok(bytes)
+    }
+}
+
+/// Iterator returned by [`PcmStream::blocks`].
+pub struct PcmBlocks {
+    stream: PcmStream,
+    channel: WaveformChannel,
+    block_len: usize,
+    margin: usize,
+    /// First frame of the walk; margins never reach before it.
+    origin: usize,
+    /// Decoded frames from `buffer_offset` up to the stream position.
+    buffer: Vec<f32>,
+    buffer_offset: usize,
+    /// First owned frame of the next block.
+    next: usize,
+    exhausted: bool,
+}
+
+impl PcmBlocks {
+    /// Frame index of `buffer[0]` onwards that has been read.
+    fn buffer_end(&self) -> usize {
+        self.buffer_offset + self.buffer.len()
+    }
+
+    /// Read until the buffer reaches `frame` or the stream ends.
+    fn fill_to(&mut self, frame: usize) -> Result<()> {
+        while !self.exhausted && self.buffer_end() < frame {
+            let samples = self.stream.read(self.channel, frame - self.buffer_end())?;
+            if samples.is_empty() {
+                self.exhausted = true;
+            }
+            self.buffer.extend(samples);
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for PcmBlocks {
+    type Item = Result<Block>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let owned_start = self.next;
+        if let Err(e) = self.fill_to(owned_start + self.block_len + self.margin) {
+            self.exhausted = true;
+            self.next = usize::MAX;
+            return Some(Err(e));
+        }
+        let end = self.buffer_end();
+        if owned_start >= end {
+            return None;
+        }
+        let owned_end = (owned_start + self.block_len).min(end);
+        let offset = owned_start.saturating_sub(self.margin).max(self.origin);
+        let samples = self.buffer[offset - self.buffer_offset..(owned_end + self.margin).min(end) - self.buffer_offset].to_vec();
+
+        // The next block's leading margin is all that must stay buffered
+        let keep_from = owned_end.saturating_sub(self.margin).max(self.origin);
+        self.buffer.drain(..keep_from - self.buffer_offset);
+        self.buffer_offset = keep_from;
+        self.next = owned_end;
+        Some(Ok(Block {
+            offset,
+            owned: owned_start..owned_end,
+            samples,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample_source::{read_blocks, SampleSource, StreamingWavReader};
+
+    fn format(channels: u16, encoding: PcmEncoding) -> RawPcmFormat {
+        RawPcmFormat {
+            sample_rate: 8_000,
+            channels,
+            encoding,
+        }
+    }
+
+    fn stream(bytes: Vec<u8>, format: RawPcmFormat) -> PcmStream {
+        PcmStream::from_reader(Box::new(io::Cursor::new(bytes)), Path::new(STDIN_PATH), format).unwrap()
+    }
+
+    fn s16_stereo(frames: usize) -> Vec<u8> {
+        (0..frames as i16)
+            .flat_map(|i| [i * 10, -i * 10])
+            .flat_map(i16::to_le_bytes)
+            .collect()
+    }
+
+    #[test]
+    fn decodes_each_encoding() {
+        let cases: [(PcmEncoding, Vec<u8>, f32); 5] = [
+            (PcmEncoding::U8, vec![255], 127.0 / 127.0),
+            (PcmEncoding::S16, (-16384i16).to_le_bytes().to_vec(), -16384.0 / 32767.0),
+            (PcmEncoding::S24, vec![0x00, 0x00, 0x40], 4_194_304.0 / 8_388_607.0),
+            (
+                PcmEncoding::S32,
+                (1i32 << 30).to_le_bytes().to_vec(),
+                (1u32 << 30) as f32 / i32::MAX as f32,
+            ),
+            (PcmEncoding::F32, 0.25f32.to_le_bytes().to_vec(), 0.25),
+        ];
+        for (encoding, bytes, expected) in cases {
+            let mut piped = stream(bytes, format(1, encoding));
+            assert_eq!(piped.read(WaveformChannel::Left, 10).unwrap(), [expected], "{encoding:?}");
+        }
+    }
+
+    #[test]
+    fn skip_limit_and_truncated_tail() {
+        let mut bytes = s16_stereo(100);
+        bytes.push(7); // half a sample at the end of the pipe
+        let mut piped = stream(bytes, format(2, PcmEncoding::S16));
+        assert_eq!(piped.skip(10).unwrap(), 10);
+        piped.limit(5);
+        let right = piped.read(WaveformChannel::Right, 100).unwrap();
+        assert_eq!(right.len(), 5);
+        assert_eq!(right[0], -100.0 / 32767.0);
+        assert!(piped.read(WaveformChannel::Right, 100).unwrap().is_empty());
+
+        let mut short = stream(s16_stereo(100), format(2, PcmEncoding::S16));
+        assert_eq!(short.skip(1000).unwrap(), 100);
+    }
+
+    #[test]
+    fn blocks_match_ranged_reads_of_the_same_data() {
+        let bytes = s16_stereo(1000);
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), &bytes).unwrap();
+        let raw = StreamingWavReader::open_raw(file.path(), format(2, PcmEncoding::S16)).unwrap();
+        assert_eq!(raw.len(), 1000);
+        assert_eq!(raw.format().container, Container::Raw);
+
+        let mut piped = stream(bytes, format(2, PcmEncoding::S16));
+        piped.skip(37).unwrap();
+        let streamed: Vec<Block> = piped.blocks(WaveformChannel::Right, 128, 20).map(|b| b.unwrap()).collect();
+        let ranged: Vec<Block> = read_blocks(&raw, WaveformChannel::Right, 37..1000, 128, 20)
+            .map(|b| b.unwrap())
+            .collect();
+        assert_eq!(streamed.len(), ranged.len());
+        for (s, r) in streamed.iter().zip(&ranged) {
+            assert_eq!((s.offset, &s.owned), (r.offset, &r.owned));
+            assert_eq!(s.samples, r.samples);
+        }
+    }
+
+    #[test]
+    fn into_resident_splits_every_channel() {
+        let mut piped = stream(s16_stereo(50), format(2, PcmEncoding::S16));
+        piped.skip(10).unwrap();
+        let resident = piped.into_resident().unwrap();
+        assert_eq!(SampleSource::len(&resident), 40);
+        assert_eq!(resident.get_samples(WaveformChannel::Left)[0], 100.0 / 32767.0);
+        assert_eq!(resident.get_samples(WaveformChannel::Right)[0], -100.0 / 32767.0);
+        assert_eq!(resident.format.to_string(), "Raw · PCM 16-bit");
+
+        assert!(stream(Vec::new(), format(1, PcmEncoding::S16)).into_resident().is_err());
+        assert!(PcmStream::from_reader(Box::new(io::empty()), Path::new("-"), format(0, PcmEncoding::S16)).is_err());
+    }
+}
@@ -1,9 +1,18 @@
+use std::ops::Range;
+
 use anyhow::{Context, Result};
 use egui::ColorImage;
 use image::{DynamicImage, GrayImage, Luma, Rgba, RgbaImage};
 use thiserror::Error;
 
-use crate::sstv::{DecoderMode, DecoderParams, SstvDecoder};
+use crate::analysis::{detect_content, find_image_bounds_in, ContentMask, ContentParams, ImageBounds, LineSync};
+use crate::audio::WaveformChannel;
+use crate::backend;
+use crate::catalog::{color_triplets, FRAMES_PER_CHANNEL};
+use crate::fm_sstv::SstvMode;
+use crate::registration::{plane_center, register_plane, Plane, Registration, RegistrationParams};
+use crate::sample_source::SampleSource;
+use crate::sstv::{normalize_levels, percentile_bounds, DecoderMode, DecoderParams};
 
 #[derive(Debug, Error)]
 pub enum PipelineError {
@@ -17,15 +26,65 @@ pub struct PipelineResult {
     pub width: u32,
     pub height: u32,
     pub mode: DecoderMode,
+    /// Plane registration behind a color-triplet composite; `None` for
+    /// everything else.
+    pub registration: Option<Registration>,
+    /// Amateur SSTV mode the picture decoded as; `None` outside
+    /// [`DecoderMode::Sstv`].
+    pub sstv_mode: Option<SstvMode>,
+    /// Sync diagnostics, one per row, when rows are scan lines (grayscale
+    /// windows and frames, the live stream); `None` otherwise.
+    pub line_sync: Option<Vec<LineSync>>,
+    /// Source sample range behind each row, when rows map to the audio
+    /// (grayscale and pseudo-color windows, grayscale frames, the live
+    /// stream). Backends give them relative to the samples they decoded;
+    /// [`Self::offset_lines`] makes them absolute.
+    pub line_spans: Option<Vec<Range<usize>>>,
+    /// Luminance level behind each pixel before normalization, row-major,
+    /// for grayscale results straight from decoded levels (windows, frames,
+    /// the live stream); `None` otherwise.
+    pub levels: Option<Vec<f32>>,
 }
 
 impl PipelineResult {
+    /// Whether `pixels` is RGB (3 bytes per pixel) rather than grayscale.
+    pub fn is_color(&self) -> bool {
+        backend::for_mode(self.mode).info().capabilities.color
+    }
+
+    /// Shift `line_spans` by `offset` samples: where the decoded buffer
+    /// started in the source.
+    pub fn offset_lines(mut self, offset: usize) -> Self {
+        for span in self.line_spans.iter_mut().flatten() {
+            *span = span.start + offset..span.end + offset;
+        }
+        self
+    }
+
+    /// Pre-normalization level of the pixel at `column`, `row`.
+    pub fn level_at(&self, column: usize, row: usize) -> Option<f32> {
+        let width = self.width as usize;
+        let levels = self.levels.as_ref().filter(|l| l.len() == width * self.height as usize)?;
+        (column < width).then(|| levels.get(row * width + column).copied())?
+    }
+
+    /// Source sample range of `row`, when rows map to the audio.
+    pub fn row_span(&self, row: usize) -> Option<Range<usize>> {
+        let spans = self.line_spans.as_ref().filter(|s| s.len() == self.height as usize)?;
+        spans.get(row).cloned()
+    }
+
+    /// Row decoded from source sample `position`, if any.
+    pub fn row_at(&self, position: usize) -> Option<usize> {
+        let spans = self.line_spans.as_ref().filter(|s| s.len() == self.height as usize)?;
+        let row = spans.partition_point(|span| span.start <= position).checked_sub(1)?;
+        spans[row].contains(&position).then_some(row)
+    }
+
     pub fn to_dynamic_image(&self) -> Result<DynamicImage, PipelineError> {
         // Compute expected length based on mode
-        let expected_len = match self.mode {
-            DecoderMode::Grayscale => (self.width * self.height) as usize,
-            DecoderMode::PseudoColor => (self.width * self.height * 3) as usize,
-        };
+        let bytes_per_pixel = if self.is_color() { 3 } else { 1 };
+        let expected_len = (self.width * self.height) as usize * bytes_per_pixel;
 
         // Validate pixel buffer length upfront
         if self.pixels.len() < expected_len {
@@ -35,89 +94,100 @@ impl PipelineResult {
             });
         }
 
-        // Proceed with current loops unchanged
-        match self.mode {
-            DecoderMode::Grayscale => {
-                let mut buffer = GrayImage::new(self.width, self.height);
-                for y in 0..self.height {
-                    for x in 0..self.width {
-                        let idx = (y * self.width + x) as usize;
-                        if idx < self.pixels.len() {
-                            let pixel = self.pixels[idx];
-                            buffer.put_pixel(x, y, Luma([pixel]));
-                        }
+        if !self.is_color() {
+            let mut buffer = GrayImage::new(self.width, self.height);
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let idx = (y * self.width + x) as usize;
+                    if idx < self.pixels.len() {
+                        let pixel = self.pixels[idx];
+                        buffer.put_pixel(x, y, Luma([pixel]));
                     }
                 }
-                Ok(DynamicImage::ImageLuma8(buffer))
             }
-            DecoderMode::PseudoColor => {
-                let mut buffer = RgbaImage::new(self.width, self.height);
-                for y in 0..self.height {
-                    for x in 0..self.width {
-                        let idx = (y * self.width + x) as usize * 3;
-                        if idx + 3 <= self.pixels.len() {
-                            let r = self.pixels[idx];
-                            let g = self.pixels[idx + 1];
-                            let b = self.pixels[idx + 2];
-                            buffer.put_pixel(x, y, Rgba([r, g, b, 255]));
-                        }
-                    }
+            return Ok(DynamicImage::ImageLuma8(buffer));
+        }
+
+        let mut buffer = RgbaImage::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let idx = (y * self.width + x) as usize * 3;
+                if idx + 3 <= self.pixels.len() {
+                    let r = self.pixels[idx];
+                    let g = self.pixels[idx + 1];
+                    let b = self.pixels[idx + 2];
+                    buffer.put_pixel(x, y, Rgba([r, g, b, 255]));
                 }
-                Ok(DynamicImage::ImageRgba8(buffer))
             }
         }
+        Ok(DynamicImage::ImageRgba8(buffer))
     }
 
     pub fn to_egui_image(&self) -> ColorImage {
-        match self.mode {
-            DecoderMode::Grayscale => {
-                let mut img = ColorImage::new(
-                    [self.width as usize, self.height as usize],
-                    vec![egui::Color32::BLACK; (self.width * self.height) as usize],
-                );
-                for (i, p) in self.pixels.iter().enumerate() {
-                    if i < img.pixels.len() {
-                        img.pixels[i] = egui::Color32::from_gray(*p);
-                    }
+        let mut img = ColorImage::new(
+            [self.width as usize, self.height as usize],
+            vec![egui::Color32::BLACK; (self.width * self.height) as usize],
+        );
+        if !self.is_color() {
+            for (i, p) in self.pixels.iter().enumerate() {
+                if i < img.pixels.len() {
+                    img.pixels[i] = egui::Color32::from_gray(*p);
                 }
-                img
             }
-            DecoderMode::PseudoColor => {
-                let mut img = ColorImage::new(
-                    [self.width as usize, self.height as usize],
-                    vec![egui::Color32::BLACK; (self.width * self.height) as usize],
-                );
-                for i in 0..img.pixels.len() {
-                    let src_idx = i * 3;
-                    if src_idx + 3 <= self.pixels.len() {
-                        let r = self.pixels[src_idx];
-                        let g = self.pixels[src_idx + 1];
-                        let b = self.pixels[src_idx + 2];
-                        img.pixels[i] = egui::Color32::from_rgb(r, g, b);
-                    }
-                }
-                img
+            return img;
+        }
+        for i in 0..img.pixels.len() {
+            let src_idx = i * 3;
+            if src_idx + 3 <= self.pixels.len() {
+                let r = self.pixels[src_idx];
+                let g = self.pixels[src_idx + 1];
+                let b = self.pixels[src_idx + 2];
+                img.pixels[i] = egui::Color32::from_rgb(r, g, b);
             }
         }
+        img
     }
 }
 
 /// Composite three grayscale frames (red, green, blue members of a Voyager
 /// color triplet) into one RGB image.
 ///
-/// Per-line sync locking aligns content *within* each scan line, but the
-/// frames start at their own segmentation boundaries, so the planes can be
-/// offset from each other by tens of scan lines (rows). Registration:
-/// cross-correlate each plane's row-mean luminance profile against the red
-/// plane and shift by the best lag before stacking, cropping to the common
-/// overlap.
+/// The frames start at their own segmentation boundaries and were scanned
+/// separately, so the planes are offset by tens of rows and can differ by
+/// a few pixels horizontally and slightly in scale and rotation. Green and
+/// blue are registered against red ([`crate::registration`]), resampled
+/// into red's coordinates and stacked, cropped to the common overlap.
+///
+/// With no content masks to hand this registers over the fixed central
+/// region ([`ContentMask::central`]); see [`composite_rgb_masked`].
 pub fn composite_rgb(red: &PipelineResult, grn: &PipelineResult, blu: &PipelineResult) -> Result<DynamicImage> {
-    for (name, frame) in [("red", red), ("green", grn), ("blue", blu)] {
+    let central = |f: &PipelineResult| ContentMask::central(f.height as usize, f.width as usize);
+    Ok(composite_rgb_masked([red, grn, blu], [&central(red), &central(grn), &central(blu)])?.image)
+}
+
+/// A registered color composite and the plane transforms behind it.
+#[derive(Debug, Clone)]
+pub struct Composite {
+    pub image: DynamicImage,
+    pub registration: Registration,
+}
+
+/// [`composite_rgb`] registering only over each plane's content, and
+/// reporting the estimated transforms alongside the image.
+pub fn composite_rgb_masked(frames: [&PipelineResult; 3], masks: [&ContentMask; 3]) -> Result<Composite> {
+    let [red, grn, blu] = frames;
+    for (name, frame, mask) in [("red", red, masks[0]), ("green", grn, masks[1]), ("blue", blu, masks[2])] {
         anyhow::ensure!(
             frame.mode == DecoderMode::Grayscale,
             "{name} frame is {:?}, composite needs Grayscale",
             frame.mode
         );
+        anyhow::ensure!(
+            mask.height() == frame.height as usize,
+            "{name} content mask has {} rows, frame has {}",
+            mask.height(),
+            frame.height
+        );
     }
     anyhow::ensure!(
         red.width == grn.width && grn.width == blu.width,
@@ -129,165 +199,257 @@ pub fn composite_rgb(red: &PipelineResult, grn: &PipelineResult, blu: &PipelineR
     let min_height = red.height.min(grn.height).min(blu.height);
     anyhow::ensure!(min_height > 0, "empty frame in triplet");
 
-    // Row offsets of green/blue relative to red, from profile correlation.
-    let max_lag = (min_height / 4).clamp(1, 128) as i64;
-    let profile_r = row_profile(red);
-    let lag_g = best_row_lag(&profile_r, &row_profile(grn), max_lag);
-    let lag_b = best_row_lag(&profile_r, &row_profile(blu), max_lag);
-    tracing::debug!(lag_g, lag_b, "composite registration offsets (rows vs red)");
-
-    // Overlap in red-plane row coordinates: row y reads green at y+lag_g and
-    // blue at y+lag_b, all of which must be in range.
-    let y_min = 0.max(-lag_g).max(-lag_b);
-    let y_max = (red.height as i64)
-        .min(grn.height as i64 - lag_g)
-        .min(blu.height as i64 - lag_b);
-    anyhow::ensure!(y_max > y_min, "no overlapping rows after registration");
-
-    let width = red.width;
-    let height = (y_max - y_min) as u32;
+    let planes = [(red, masks[0]), (grn, masks[1]), (blu, masks[2])].map(|(frame, mask)| Plane {
+        pixels: &frame.pixels,
+        width: frame.width as usize,
+        height: frame.height as usize,
+        mask,
+    });
+    let params = RegistrationParams::default();
+    let registration = Registration {
+        green: register_plane(&planes[0], &planes[1], &params),
+        blue: register_plane(&planes[0], &planes[2], &params),
+    };
+    tracing::debug!(%registration, "composite registration (vs red)");
+
+    // Overlap in red-plane coordinates from the translation part; residual
+    // rotation/scale overrun at the edges samples the clamped border.
+    let (g, b) = (registration.green.transform, registration.blue.transform);
+    let lower = |shift: f64| (-shift).round() as i64;
+    let upper = |extent: u32, shift: f64| (extent as f64 - shift).round() as i64;
+    let y_min = 0.max(lower(g.dy)).max(lower(b.dy));
+    let y_max = (red.height as i64).min(upper(grn.height, g.dy)).min(upper(blu.height, b.dy));
+    let x_min = 0.max(lower(g.dx)).max(lower(b.dx));
+    let x_max = (red.width as i64).min(upper(grn.width, g.dx)).min(upper(blu.width, b.dx));
+    anyhow::ensure!(y_max > y_min && x_max > x_min, "no overlapping pixels after registration");
+
+    let center = plane_center(&planes[0]);
+    let (width, height) = ((x_max - x_min) as u32, (y_max - y_min) as u32);
     let mut buffer = image::RgbImage::new(width, height);
     for y in 0..height {
-        let ry = (y as i64 + y_min) as usize;
+        let ry = y as i64 + y_min;
         for x in 0..width {
-            let x = x as usize;
-            let r = red.pixels[ry * width as usize + x];
-            let g = grn.pixels[(ry as i64 + lag_g) as usize * width as usize + x];
-            let b = blu.pixels[(ry as i64 + lag_b) as usize * width as usize + x];
-            buffer.put_pixel(x as u32, y, image::Rgb([r, g, b]));
+            let rx = x as i64 + x_min;
+            let r = red.pixels[ry as usize * red.width as usize + rx as usize];
+            let (gx, gy) = g.apply(rx as f64, ry as f64, center);
+            let (bx, by) = b.apply(rx as f64, ry as f64, center);
+            buffer.put_pixel(
+                x,
+                y,
+                image::Rgb([r, sample_bilinear(&planes[1], gx, gy), sample_bilinear(&planes[2], bx, by)]),
+            );
         }
     }
-    Ok(DynamicImage::ImageRgb8(buffer))
+    Ok(Composite {
+        image: DynamicImage::ImageRgb8(buffer),
+        registration,
+    })
+}
+
+/// Bilinear sample of a plane at (`x`, `y`), clamped to the plane edge.
+/// Integer coordinates return the pixel unchanged.
+fn sample_bilinear(plane: &Plane, x: f64, y: f64) -> u8 {
+    let x = x.clamp(0.0, (plane.width - 1) as f64);
+    let y = y.clamp(0.0, (plane.height - 1) as f64);
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(plane.width - 1), (y0 + 1).min(plane.height - 1));
+    let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+    let px = |x: usize, y: usize| plane.pixels[y * plane.width + x] as f64;
+    let top = px(x0, y0) + (px(x1, y0) - px(x0, y0)) * fx;
+    let bottom = px(x0, y1) + (px(x1, y1) - px(x0, y1)) * fx;
+    (top + (bottom - top) * fy).round().clamp(0.0, 255.0) as u8
 }
 
 /// Composite a color triplet from raw decoded levels (red, green, blue
 /// planes, row-major at `width` per line). Percentile bounds are computed
 /// jointly over the three planes — stretching each frame by its own bounds
 /// would skew the color balance — then the planes are normalized with the
-/// shared transform, registered, and stacked via [`composite_rgb`].
+/// shared transform, registered, and stacked via [`composite_rgb_masked`].
+///
+/// Content masks are detected from the levels alone; callers holding
+/// per-line sync-lock status should use [`composite_triplet_masked`].
 pub fn composite_triplet_levels(planes: [&[f32]; 3], width: usize, invert: bool, gamma: f32) -> Result<DynamicImage> {
+    let params = ContentParams::default();
+    let masks = planes.map(|levels| detect_content(levels, width, None, &params));
+    Ok(composite_triplet_masked(planes, [&masks[0], &masks[1], &masks[2]], width, invert, gamma)?.image)
+}
+
+/// [`composite_triplet_levels`] with caller-supplied content masks: the
+/// joint bounds cover content pixels only and registration honors the
+/// masks. Returns the composite with its registration report.
+pub fn composite_triplet_masked(
+    planes: [&[f32]; 3],
+    masks: [&ContentMask; 3],
+    width: usize,
+    invert: bool,
+    gamma: f32,
+) -> Result<Composite> {
     anyhow::ensure!(width > 0, "width must be non-zero");
-    let joint: Vec<f32> = planes.iter().flat_map(|p| p.iter().copied()).collect();
-    let (lo, hi) = crate::sstv::percentile_bounds(&joint, 0.01, 0.99);
+    let mut joint: Vec<f32> = planes.iter().zip(masks).flat_map(|(p, m)| m.values(p, width)).collect();
+    if joint.is_empty() {
+        joint = planes.iter().flat_map(|p| p.iter().copied()).collect();
+    }
+    let (lo, hi) = percentile_bounds(&joint, 0.01, 0.99);
     let frames: Vec<PipelineResult> = planes
         .iter()
         .map(|levels| PipelineResult {
-            pixels: crate::sstv::normalize_levels(levels, lo, hi, invert, gamma),
+            pixels: normalize_levels(levels, lo, hi, invert, gamma),
             width: width as u32,
             height: (levels.len() / width) as u32,
             mode: DecoderMode::Grayscale,
+            registration: None,
+            sstv_mode: None,
+            line_sync: None,
+            line_spans: None,
+            levels: None,
         })
         .collect();
-    composite_rgb(&frames[0], &frames[1], &frames[2])
+    composite_rgb_masked([&frames[0], &frames[1], &frames[2]], masks)
 }
 
-/// Mean luminance per row (scan line) of a grayscale frame, computed over
-/// the central columns only: the line-start region holds sync residue and
-/// the row ends hold edge junk, both of which would dominate the profile.
-fn row_profile(frame: &PipelineResult) -> Vec<f64> {
-    let width = frame.width as usize;
-    let lo = width / 5;
-    let hi = (width * 9) / 10;
-    let span = (hi - lo).max(1) as f64;
-    frame
-        .pixels
-        .chunks_exact(width)
-        .map(|row| {
-            row[lo.min(row.len() - 1)..hi.min(row.len())]
-                .iter()
-                .map(|&p| p as f64)
-                .sum::<f64>()
-                / span
-        })
-        .collect()
+/// What a frame decodes to under [`DecoderMode::FrameTriplet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameGroup {
+    /// Grayscale frame, decoded on its own.
+    Single(usize),
+    /// Frame indices `[red, green, blue]` of a color triplet.
+    Triplet([usize; 3]),
 }
 
-/// Lag of `b` relative to `a` (in rows) maximizing normalized correlation:
-/// a[i] aligns with b[i + lag]. Correlates only over the central 60% of
-/// `a`'s rows — the first/last rows of a segmented frame are inter-image
-/// leader junk whose strong, repetitive structure can outweigh the picture
-/// content. Returns 0 for degenerate (flat) profiles.
-fn best_row_lag(a: &[f64], b: &[f64], max_lag: i64) -> i64 {
-    let a_lo = (a.len() / 5) as i64;
-    let a_hi = (a.len() * 4 / 5) as i64;
-    let mut best = (0i64, f64::NEG_INFINITY);
-    for lag in -max_lag..=max_lag {
-        let start = a_lo.max(-lag);
-        let end = a_hi.min(b.len() as i64 - lag);
-        if end - start < 16 {
-            continue;
-        }
-        let n = (end - start) as f64;
-        let (mut sa, mut sb) = (0.0, 0.0);
-        for i in start..end {
-            sa += a[i as usize];
-            sb += b[(i + lag) as usize];
-        }
-        let (ma, mb) = (sa / n, sb / n);
-        let (mut num, mut va, mut vb) = (0.0, 0.0, 0.0);
-        for i in start..end {
-            let da = a[i as usize] - ma;
-            let db = b[(i + lag) as usize] - mb;
-            num += da * db;
-            va += da * da;
-            vb += db * db;
-        }
-        if va <= f64::EPSILON || vb <= f64::EPSILON {
-            continue;
-        }
-        let corr = num / (va * vb).sqrt();
-        if corr > best.1 {
-            best = (lag, corr);
+impl FrameGroup {
+    /// Frame index that identifies the group: the frame itself, or the
+    /// triplet's red member.
+    pub fn lead(&self) -> usize {
+        match *self {
+            FrameGroup::Single(idx) => idx,
+            FrameGroup::Triplet([red, _, _]) => red,
         }
     }
-    best.0
 }
 
-pub struct DecodingPipeline {
-    decoder: SstvDecoder,
+/// One channel of the record segmented into image frames, so any position
+/// resolves to its frame and a color frame to its blue/green/red siblings.
+///
+/// Bounds are absolute sample positions in the source. Triplet grouping comes
+/// from the reference catalog and only applies when the segmentation found
+/// exactly [`FRAMES_PER_CHANNEL`] frames; otherwise the frame indices cannot
+/// be trusted to line up with the catalog and every frame decodes alone.
+#[derive(Debug, Clone)]
+pub struct RecordFrames {
+    channel: WaveformChannel,
+    bounds: Vec<ImageBounds>,
+    triplets: Vec<[usize; 3]>,
 }
 
-impl DecodingPipeline {
-    pub fn new() -> Self {
+impl RecordFrames {
+    /// Segment the whole of `channel` into frames at the line cadence in
+    /// `params`. This is a full pass over the channel (block-wise for
+    /// streamed sources); callers keep the result for the loaded file.
+    pub fn segment(source: &dyn SampleSource, channel: WaveformChannel, params: &DecoderParams) -> Result<Self> {
+        let bounds = find_image_bounds_in(source, channel, 0..source.len(), &params.segment_params())
+            .context("Failed to segment record")?;
+        Ok(Self::from_bounds(channel, bounds))
+    }
+
+    /// Wrap already-segmented bounds (absolute, in time order).
+    pub fn from_bounds(channel: WaveformChannel, bounds: Vec<ImageBounds>) -> Self {
+        let triplets = if bounds.len() == FRAMES_PER_CHANNEL {
+            color_triplets(channel)
+        } else {
+            tracing::warn!(
+                frames = bounds.len(),
+                expected = FRAMES_PER_CHANNEL,
+                "Segmented frame count does not match the catalog; color triplets disabled"
+            );
+            Vec::new()
+        };
         Self {
-            decoder: SstvDecoder::new(),
+            channel,
+            bounds,
+            triplets,
         }
     }
 
-    pub fn process(&self, samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Result<PipelineResult> {
-        let pixels = self
-            .decoder
-            .decode(samples, params, sample_rate)
-            .context("Failed to decode audio")?;
-
-        // Detect empty pixel data immediately, fail before computing dimensions
-        if pixels.is_empty() {
-            anyhow::bail!("Decoded pixels empty");
-        }
+    pub fn channel(&self) -> WaveformChannel {
+        self.channel
+    }
 
-        let width = params.effective_width();
-        let row_size = match params.mode {
-            DecoderMode::Grayscale => width,
-            DecoderMode::PseudoColor => width * 3,
-        };
+    pub fn bounds(&self) -> &[ImageBounds] {
+        &self.bounds
+    }
 
-        if pixels.len() % row_size != 0 {
-            anyhow::bail!(
-                "Pixel buffer length ({}) not evenly divisible by row size ({}) for mode {:?}",
-                pixels.len(),
-                row_size,
-                params.mode
-            );
+    /// Whether frame indices line up with the reference catalog.
+    pub fn is_catalog_aligned(&self) -> bool {
+        !self.triplets.is_empty()
+    }
+
+    /// Frame at sample `position`: the last frame starting at or before it,
+    /// so the gap after a frame still selects that frame. `None` before the
+    /// first frame.
+    pub fn frame_at(&self, position: usize) -> Option<usize> {
+        self.bounds.partition_point(|b| b.start_sample <= position).checked_sub(1)
+    }
+
+    /// How frame `index` decodes.
+    pub fn group(&self, index: usize) -> FrameGroup {
+        self.triplets
+            .iter()
+            .find(|t| t.contains(&index))
+            .map_or(FrameGroup::Single(index), |&t| FrameGroup::Triplet(t))
+    }
+
+    /// Every frame group once, in record order.
+    pub fn groups(&self) -> Vec<FrameGroup> {
+        let mut groups: Vec<FrameGroup> = Vec::new();
+        for index in 0..self.bounds.len() {
+            let group = self.group(index);
+            if groups.last() != Some(&group) {
+                groups.push(group);
+            }
         }
+        groups
+    }
+}
 
-        let height = (pixels.len() / row_size) as u32;
+/// Decoding entry point: dispatches to the [`crate::backend`] registered
+/// for `params.mode`.
+pub struct DecodingPipeline;
 
-        Ok(PipelineResult {
-            pixels,
-            width: width as u32,
-            height,
-            mode: params.mode,
-        })
+impl DecodingPipeline {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Decode a window of samples with the backend for `params.mode`.
+    pub fn process(&self, samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Result<PipelineResult> {
+        backend::for_mode(params.mode).decode(samples, params, sample_rate)
+    }
+
+    /// [`Self::process_frame`] for the frame at sample `position`.
+    pub fn process_frame_at(
+        &self,
+        source: &dyn SampleSource,
+        frames: &RecordFrames,
+        position: usize,
+        params: &DecoderParams,
+    ) -> Result<PipelineResult> {
+        let index = frames.frame_at(position).context("No image frame at this position")?;
+        self.process_frame(source, frames, index, params)
+    }
+
+    /// Decode frame `index` of `frames` with the backend for `params.mode`.
+    /// Under [`DecoderMode::FrameTriplet`] a color frame reads and
+    /// composites all three members of its triplet (RGB result, joint
+    /// contrast bounds, registered planes), a grayscale frame decodes alone
+    /// (Grayscale result, its own bounds).
+    pub fn process_frame(
+        &self,
+        source: &dyn SampleSource,
+        frames: &RecordFrames,
+        index: usize,
+        params: &DecoderParams,
+    ) -> Result<PipelineResult> {
+        backend::for_mode(params.mode).decode_frame(source, frames, index, params)
     }
 }
 
@@ -307,9 +469,35 @@ mod tests {
             width,
             height,
             mode: DecoderMode::Grayscale,
+            registration: None,
+            sstv_mode: None,
+            line_sync: None,
+            line_spans: None,
+            levels: None,
         }
     }
 
+    #[test]
+    fn line_spans_map_rows_to_source_positions() {
+        let mut result = gray_frame(4, 3, 0);
+        // A skipped sync interval leaves a gap between rows 1 and 2
+        result.line_spans = Some(vec![0..100, 100..200, 260..360]);
+        let result = result.offset_lines(1_000);
+        assert_eq!(result.row_span(2), Some(1_260..1_360));
+        assert_eq!(result.row_at(999), None);
+        assert_eq!(result.row_at(1_000), Some(0));
+        assert_eq!(result.row_at(1_199), Some(1));
+        assert_eq!(result.row_at(1_230), None, "inside the gap");
+        assert_eq!(result.row_at(1_300), Some(2));
+        assert_eq!(result.row_at(1_360), None);
+
+        // Spans that do not match the rows map nothing
+        let mut cropped = result.clone();
+        cropped.height = 2;
+        assert_eq!((cropped.row_span(0), cropped.row_at(1_000)), (None, None));
+        assert_eq!(gray_frame(4, 3, 0).row_at(0), None);
+    }
+
     #[test]
     fn composite_crops_to_smallest_height_and_maps_planes() {
         // Flat planes: degenerate profiles, no registration shift.
@@ -333,6 +521,11 @@ mod tests {
             width,
             height,
             mode: DecoderMode::Grayscale,
+            registration: None,
+            sstv_mode: None,
+            line_sync: None,
+            line_spans: None,
+            levels: None,
         }
     }
 
@@ -373,6 +566,70 @@ mod tests {
         }
     }
 
+    /// Textured 32x64 levels plane with a bright band at `band_row` under a
+    /// saturated 6-row leader, as segmentation hands over the first lines.
+    fn leadered_levels(band_row: usize) -> Vec<f32> {
+        let width = 32;
+        (0..width * 64)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                match y {
+                    0..6 => 5.0,
+                    _ if y == band_row => 1.0,
+                    _ => 0.2 + 0.1 * ((x * 7 + y * 13) % 17) as f32 / 17.0,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn composite_triplet_levels_ignores_leader_junk() {
+        let (r, g, b) = (leadered_levels(20), leadered_levels(26), leadered_levels(17));
+        let img = composite_triplet_levels([&r, &g, &b], 32, false, 1.0).unwrap().to_rgb8();
+        // Unmasked, the leader sets the upper bound (5.0) and the band lands
+        // near 1/5 gray; the identical leaders also pull registration to 0.
+        let bright: Vec<u32> = (0..img.height())
+            .filter(|&y| img.get_pixel(16, y).0.iter().all(|&c| c > 240))
+            .collect();
+        assert_eq!(bright.len(), 1, "expected one aligned full-scale band: {bright:?}");
+    }
+
+    #[test]
+    fn composite_rgb_masked_corrects_2d_offsets_and_reports_them() {
+        use crate::registration::RegistrationMethod;
+        use crate::test_fixtures::textured_plane;
+
+        let (w, h) = (192usize, 192usize);
+        let frame = |pixels| PipelineResult {
+            pixels,
+            width: w as u32,
+            height: h as u32,
+            mode: DecoderMode::Grayscale,
+            registration: None,
+            sstv_mode: None,
+            line_sync: None,
+            line_spans: None,
+            levels: None,
+        };
+        let red = frame(textured_plane(w, h, |x, y| (x, y)));
+        // Green sees the pattern 4 px right and 9 rows down of red.
+        let grn = frame(textured_plane(w, h, |x, y| (x - 4.0, y - 9.0)));
+        let blu = frame(textured_plane(w, h, |x, y| (x, y)));
+        let mask = ContentMask::full(h, w);
+
+        let composite = composite_rgb_masked([&red, &grn, &blu], [&mask, &mask, &mask]).unwrap();
+        let green = &composite.registration.green;
+        assert_eq!(green.method, RegistrationMethod::Similarity, "{green}");
+        assert!(
+            (green.transform.dx - 4.0).abs() < 0.2 && (green.transform.dy - 9.0).abs() < 0.2,
+            "{green}"
+        );
+        let rgb = composite.image.to_rgb8();
+        assert!(rgb.width() < w as u32 && rgb.height() < h as u32, "overlap not cropped");
+        let worst = rgb.pixels().map(|p| (p[0] as i32 - p[1] as i32).abs()).max().unwrap();
+        assert!(worst <= 3, "red/green disagree by {worst} after registration");
+    }
+
     #[test]
     fn composite_rejects_mismatched_widths() {
         let r = gray_frame(4, 8, 0);
@@ -381,6 +638,151 @@ mod tests {
         assert!(composite_rgb(&r, &g, &b).is_err());
     }
 
+    fn bounds(start_sample: usize, end_sample: usize) -> ImageBounds {
+        ImageBounds {
+            start_sample,
+            end_sample,
+            start_secs: 0.0,
+            end_secs: 0.0,
+            line_count: 0,
+            median_interval_samples: 0.0,
+            confidence: 1.0,
+        }
+    }
+
+    #[test]
+    fn record_frames_group_catalog_triplets_and_locate_positions() {
+        let spaced: Vec<ImageBounds> = (0..FRAMES_PER_CHANNEL).map(|i| bounds(i * 100 + 10, i * 100 + 90)).collect();
+        let frames = RecordFrames::from_bounds(WaveformChannel::Left, spaced.clone());
+        assert!(frames.is_catalog_aligned());
+        // Left frames 7-9 are the Solar Spectrum triplet, stored blue-first
+        for member in 7..=9 {
+            assert_eq!(frames.group(member), FrameGroup::Triplet([9, 8, 7]));
+        }
+        assert_eq!(frames.group(0), FrameGroup::Single(0));
+        let triplet_count = color_triplets(WaveformChannel::Left).len();
+        assert_eq!(frames.groups().len(), FRAMES_PER_CHANNEL - 2 * triplet_count);
+
+        assert_eq!(frames.frame_at(5), None);
+        assert_eq!(frames.frame_at(10), Some(0));
+        assert_eq!(frames.frame_at(95), Some(0), "gap after a frame selects that frame");
+        assert_eq!(frames.frame_at(850), Some(8));
+        assert_eq!(frames.frame_at(usize::MAX), Some(FRAMES_PER_CHANNEL - 1));
+
+        // Without a catalog-sized segmentation every frame decodes alone
+        let partial = RecordFrames::from_bounds(WaveformChannel::Left, spaced[..20].to_vec());
+        assert!(!partial.is_catalog_aligned());
+        assert_eq!(partial.group(8), FrameGroup::Single(8));
+    }
+
+    #[test]
+    fn process_frame_composites_color_siblings() {
+        use crate::audio::WavReader;
+        use crate::test_fixtures::{create_test_wav_file, encode_image_to_audio};
+
+        const WIDTH: usize = 64;
+        const LINES: usize = 96;
+        const RATE: u32 = 16_000;
+        const LINE_MS: f32 = 8.32;
+        let plane = |scale: f32| -> Vec<u8> {
+            (0..WIDTH * LINES)
+                .map(|i| (((i % WIDTH) * 3 + (i / WIDTH) * 2) % 256) as f32 * scale)
+                .map(|v| v as u8)
+                .collect()
+        };
+
+        // Record order is blue, green, red
+        let mut audio = Vec::new();
+        let mut spans = Vec::new();
+        for scale in [0.25, 0.5, 1.0] {
+            let start = audio.len();
+            audio.extend(encode_image_to_audio(&plane(scale), WIDTH, RATE, LINE_MS));
+            spans.push(bounds(start, audio.len()));
+        }
+        let file = create_test_wav_file(&audio, RATE, 1);
+        let source = WavReader::from_file(file.path()).unwrap();
+
+        // Catalog-sized record with the first Left triplet (7-9) on real audio
+        let mut all: Vec<ImageBounds> = (0..FRAMES_PER_CHANNEL).map(|_| spans[2].clone()).collect();
+        all[7] = spans[0].clone();
+        all[8] = spans[1].clone();
+        all[9] = spans[2].clone();
+        let frames = RecordFrames::from_bounds(WaveformChannel::Left, all);
+
+        let params = DecoderParams {
+            line_duration_ms: LINE_MS,
+            width: WIDTH as u32,
+            mode: DecoderMode::FrameTriplet,
+            ..DecoderParams::default()
+        };
+        let pipeline = DecodingPipeline::new();
+
+        let color = pipeline.process_frame(&source, &frames, 8, &params).unwrap();
+        assert_eq!(color.mode, DecoderMode::FrameTriplet);
+        assert_eq!(color.width, WIDTH as u32);
+        assert!(color.height as usize >= LINES - 4, "height {}", color.height);
+        let image = color.to_dynamic_image().unwrap().to_rgb8();
+        let mean = |c: usize| image.pixels().map(|p| p[c] as f64).sum::<f64>() / (image.width() * image.height()) as f64;
+        let (r, g, b) = (mean(0), mean(1), mean(2));
+        // Sync residue lifts every plane's floor, so compare ordering rather
+        // than the 4:2:1 source ratios
+        assert!(r > g + 10.0 && g > b + 5.0, "planes out of order: R {r:.1} G {g:.1} B {b:.1}");
+
+        let gray = pipeline.process_frame(&source, &frames, 0, &params).unwrap();
+        assert_eq!(gray.mode, DecoderMode::Grayscale);
+        assert_eq!(gray.pixels.len(), (gray.width * gray.height) as usize);
+    }
+
+    #[test]
+    fn synthetic_record_segments_into_catalog_triplets() {
+        // Whole side end to end: segment the generated record, which must
+        // come out catalog-aligned, then composite a triplet from its own
+        // bounds with the red plane dominant
+        use crate::audio::WavReader;
+        use crate::test_fixtures::{catalog_frames, create_test_wav_file, synthesize_record, RecordOptions};
+
+        const WIDTH: usize = 64;
+        const RATE: u32 = 16_000;
+        let opts = RecordOptions {
+            junk_lines: 12,
+            ..RecordOptions::default()
+        };
+        let record = synthesize_record(&catalog_frames(WaveformChannel::Left, WIDTH, 96), RATE, &opts);
+        let file = create_test_wav_file(&record.samples, RATE, 1);
+        let source = WavReader::from_file(file.path()).unwrap();
+
+        let mut params = DecoderParams {
+            width: WIDTH as u32,
+            mode: DecoderMode::FrameTriplet,
+            ..DecoderParams::default()
+        };
+        params.segment.min_lines = 40;
+        params.segment.expected_lines = 96;
+        let frames = RecordFrames::segment(&source, WaveformChannel::Left, &params).unwrap();
+        assert!(frames.is_catalog_aligned(), "{} frames", frames.bounds().len());
+        for (got, want) in frames.bounds().iter().zip(&record.bounds) {
+            assert!(got.start_sample.abs_diff(want.start_sample) < 40, "{got:?} vs {want:?}");
+        }
+
+        let [red, _, _] = color_triplets(WaveformChannel::Left)[0];
+        let color = DecodingPipeline::new().process_frame(&source, &frames, red, &params).unwrap();
+        assert_eq!(color.mode, DecoderMode::FrameTriplet);
+        let image = color.to_dynamic_image().unwrap().to_rgb8();
+        let mean = |c: usize| image.pixels().map(|p| p[c] as f64).sum::<f64>() / (image.width() * image.height()) as f64;
+        let (r, g, b) = (mean(0), mean(1), mean(2));
+        assert!(r > g + 10.0 && g > b + 5.0, "planes out of order: R {r:.1} G {g:.1} B {b:.1}");
+    }
+
+    #[test]
+    fn process_rejects_frame_triplet_windows() {
+        let params = DecoderParams {
+            mode: DecoderMode::FrameTriplet,
+            ..DecoderParams::default()
+        };
+        let samples = vec![0.0f32; 48_000];
+        assert!(DecodingPipeline::new().process(&samples, &params, 48_000).is_err());
+    }
+
     #[test]
     fn composite_rejects_pseudocolor_frames() {
         let r = gray_frame(4, 8, 0);
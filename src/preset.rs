@@ -0,0 +1,269 @@
+//! Named decoder presets: per-rip bundles of decoder, sync and segmentation
+//! settings.
+//!
+//! Rips of the record differ in polarity, transfer speed and level, and each
+//! one ends up hand-tuned the same way every session. A [`Preset`] captures
+//! that tuning under a name. The built-in presets cover the common sources;
+//! `[[presets]]` tables in the config file add more, or replace a built-in
+//! of the same name. The GUI controls panel, every CLI subcommand and
+//! `batch` select one by name (`--preset`).
+
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::{SegmentImagesParams, SyncParams, SPEED_FACTOR_RANGE};
+use crate::audio::WaveformChannel;
+use crate::error::ConfigError;
+use crate::sstv::DecoderParams;
+
+/// Preset used when none is selected.
+pub const DEFAULT_PRESET: &str = "48k remaster";
+
+/// One named set of decode settings, as stored in the config file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    pub name: String,
+    /// Nominal scan-line duration in ms (1.0-100.0)
+    pub line_duration_ms: f32,
+    /// Fixed playback-speed factor of the rip (0.25-4.0); omit to measure
+    /// it per file
+    pub speed_factor: Option<f32>,
+    /// Brightness polarity inversion
+    pub invert: bool,
+    /// Gamma applied after normalization (0.1-10.0)
+    pub gamma: f32,
+    /// Image width in pixels
+    pub width: u32,
+    /// Align lines to detected syncs instead of fixed-period slicing
+    pub sync_lock: bool,
+    /// Correct wow and flutter from the sync timing (with `sync_lock`)
+    pub time_base: bool,
+    /// Channel carrying the images
+    pub channel: WaveformChannel,
+    /// Sync detector tuning
+    pub sync: SyncParams,
+    /// Frame segmentation tuning
+    pub segment: SegmentImagesParams,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        let params = DecoderParams::default();
+        Self {
+            // Required in config tables: an empty name fails validation.
+            name: String::new(),
+            line_duration_ms: params.line_duration_ms,
+            speed_factor: None,
+            invert: params.invert,
+            gamma: params.gamma,
+            width: params.width,
+            sync_lock: params.sync_lock,
+            time_base: params.time_base,
+            channel: WaveformChannel::Left,
+            sync: params.sync,
+            segment: params.segment,
+        }
+    }
+}
+
+impl Preset {
+    /// Apply the preset's settings to `params`, leaving the mode and decode
+    /// window alone. A preset that measures speed per file resets the
+    /// factor to 1.0 until it is measured.
+    pub fn apply(&self, params: &mut DecoderParams) {
+        params.line_duration_ms = self.line_duration_ms;
+        params.speed_factor = self.speed_factor.unwrap_or(1.0);
+        params.invert = self.invert;
+        params.gamma = self.gamma;
+        params.width = self.width;
+        params.sync_lock = self.sync_lock;
+        params.time_base = self.time_base;
+        params.sync = self.sync;
+        params.segment = self.segment;
+    }
+
+    /// Default decoder parameters with this preset applied.
+    pub fn decoder_params(&self) -> DecoderParams {
+        let mut params = DecoderParams::default();
+        self.apply(&mut params);
+        params
+    }
+
+    /// Whether `params` still carries this preset's settings (the GUI shows
+    /// the preset as modified otherwise).
+    pub fn matches(&self, params: &DecoderParams) -> bool {
+        let mut applied = *params;
+        self.apply(&mut applied);
+        if self.speed_factor.is_none() {
+            // Measured per file, so any factor is the preset's.
+            applied.speed_factor = params.speed_factor;
+        }
+        applied == *params
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| {
+            Err(ConfigError::ValidationFailed {
+                reason: format!("Preset \"{}\": {reason}", self.name),
+            })
+        };
+        if self.name.trim().is_empty() {
+            return Err(ConfigError::ValidationFailed {
+                reason: "Preset name must not be empty".to_string(),
+            });
+        }
+        if !(1.0..=100.0).contains(&self.line_duration_ms) {
+            return invalid(format!("line duration {}ms out of range 1-100ms", self.line_duration_ms));
+        }
+        if let Some(factor) = self.speed_factor {
+            if !SPEED_FACTOR_RANGE.contains(&factor) {
+                return invalid(format!("speed factor {factor} out of range 0.25-4.0"));
+            }
+        }
+        if !(0.1..=10.0).contains(&self.gamma) {
+            return invalid(format!("gamma {} out of range 0.1-10.0", self.gamma));
+        }
+        if self.width == 0 {
+            return invalid("width must be > 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Presets that ship with the application.
+pub fn builtin_presets() -> Vec<Preset> {
+    vec![
+        // The common 48 kHz remaster: nominal speed (measured per file to
+        // catch mislabeled transfers), default tuning.
+        Preset {
+            name: DEFAULT_PRESET.to_string(),
+            ..Preset::default()
+        },
+        // High-rate masters are transferred at speed, and probing a
+        // multi-gigabyte file for it buys nothing.
+        Preset {
+            name: "384k master".to_string(),
+            speed_factor: Some(1.0),
+            ..Preset::default()
+        },
+        // Double-speed rips: ~4.16 ms lines in the file.
+        Preset {
+            name: "2x rip".to_string(),
+            speed_factor: Some(2.0),
+            ..Preset::default()
+        },
+    ]
+}
+
+/// Built-in presets followed by `user` presets; a user preset replaces the
+/// built-in of the same name in place.
+pub fn merge_presets(user: &[Preset]) -> Vec<Preset> {
+    let mut presets = builtin_presets();
+    for preset in user {
+        match presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset.clone(),
+            None => presets.push(preset.clone()),
+        }
+    }
+    presets
+}
+
+/// Validate user presets: each must be in range, and names must be unique.
+pub fn validate_presets(user: &[Preset]) -> Result<(), ConfigError> {
+    for (i, preset) in user.iter().enumerate() {
+        preset.validate()?;
+        if user[..i].iter().any(|p| p.name == preset.name) {
+            return Err(ConfigError::ValidationFailed {
+                reason: format!("Preset \"{}\" defined twice", preset.name),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_presets_extend_and_override_builtins() {
+        let user = vec![
+            Preset {
+                name: "2x rip".to_string(),
+                invert: true,
+                ..Preset::default()
+            },
+            Preset {
+                name: "cassette".to_string(),
+                gamma: 1.4,
+                channel: WaveformChannel::Right,
+                ..Preset::default()
+            },
+        ];
+        let presets = merge_presets(&user);
+        assert_eq!(presets.len(), builtin_presets().len() + 1);
+        let fast = presets.iter().find(|p| p.name == "2x rip").unwrap();
+        assert!(fast.invert);
+        assert_eq!(presets.last().unwrap().name, "cassette");
+        validate_presets(&user).unwrap();
+
+        let twice = vec![user[1].clone(), user[1].clone()];
+        assert!(validate_presets(&twice).is_err());
+        let bad = vec![Preset {
+            name: "bad".to_string(),
+            speed_factor: Some(10.0),
+            ..Preset::default()
+        }];
+        assert!(validate_presets(&bad).is_err());
+        assert!(validate_presets(&[Preset::default()]).is_err(), "unnamed preset");
+    }
+
+    #[test]
+    fn apply_and_match_track_params() {
+        let fast = &builtin_presets()[2];
+        let mut params = DecoderParams {
+            mode: crate::sstv::DecoderMode::FrameTriplet,
+            ..DecoderParams::default()
+        };
+        fast.apply(&mut params);
+        assert_eq!(params.speed_factor, 2.0);
+        assert!((params.sync_params().expected_line_ms - 4.16).abs() < 1e-4);
+        assert_eq!(params.segment_params().sync, params.sync_params());
+        assert_eq!(params.mode, crate::sstv::DecoderMode::FrameTriplet);
+        assert!(fast.matches(&params));
+
+        params.gamma = 1.5;
+        assert!(!fast.matches(&params));
+
+        // A measuring preset matches whatever factor was measured.
+        let remaster = &builtin_presets()[0];
+        let mut measured = remaster.decoder_params();
+        measured.speed_factor = 1.25;
+        assert!(remaster.matches(&measured));
+    }
+
+    #[test]
+    fn preset_tables_round_trip_through_toml() {
+        let toml_str = r#"
+name = "noisy rip"
+gamma = 1.2
+speed_factor = 2.0
+channel = "right"
+
+[sync]
+peak_height = 0.6
+
+[segment]
+min_lines = 300
+"#;
+        let preset: Preset = toml::from_str(toml_str).unwrap();
+        assert_eq!(preset.channel, WaveformChannel::Right);
+        assert_eq!(preset.sync.peak_height, 0.6);
+        assert_eq!(preset.sync.min_spacing_frac, SyncParams::default().min_spacing_frac);
+        assert_eq!(preset.segment.min_lines, 300);
+        assert_eq!(preset.line_duration_ms, 8.32);
+
+        let back: Preset = toml::from_str(&toml::to_string(&preset).unwrap()).unwrap();
+        assert_eq!(back, preset);
+    }
+}
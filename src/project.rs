@@ -0,0 +1,265 @@
+//! Session project files (`*.voyager.toml`).
+//!
+//! A project records where a working session stands: the audio file and
+//! channel, the playhead, the decoder settings, the frame bounds from the
+//! last segmentation, bookmarks, and per-frame setting overrides. The GUI
+//! saves and reopens them (`--project` at startup). The audio path is stored
+//! relative to the project file when the audio sits beside or below it, so a
+//! project directory can be moved as a whole.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::ImageBounds;
+use crate::audio::WaveformChannel;
+use crate::error::ProjectError;
+use crate::sstv::DecoderParams;
+
+/// File-name suffix of project files.
+pub const PROJECT_EXTENSION: &str = "voyager.toml";
+
+/// Format version written by this build. Files from newer builds are
+/// rejected rather than half-read.
+pub const PROJECT_VERSION: u32 = 1;
+
+/// A named position in the audio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Sample offset in the channel
+    pub position_samples: usize,
+    pub label: String,
+}
+
+/// Settings one frame group decodes with instead of the session's, for
+/// frames whose polarity or level differs from their neighbours. Keyed by
+/// the group's lead frame ([`crate::pipeline::FrameGroup::lead`]); unset
+/// fields keep the session value.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct FrameOverride {
+    pub frame: usize,
+    pub invert: Option<bool>,
+    pub gamma: Option<f32>,
+    pub sync_lock: Option<bool>,
+}
+
+impl FrameOverride {
+    pub fn apply(&self, params: &mut DecoderParams) {
+        if let Some(invert) = self.invert {
+            params.invert = invert;
+        }
+        if let Some(gamma) = self.gamma {
+            params.gamma = gamma;
+        }
+        if let Some(sync_lock) = self.sync_lock {
+            params.sync_lock = sync_lock;
+        }
+    }
+}
+
+/// `params` with the override for `frame` (if any) applied.
+pub fn params_for_frame(params: &DecoderParams, overrides: &[FrameOverride], frame: usize) -> DecoderParams {
+    let mut params = *params;
+    if let Some(frame_override) = overrides.iter().find(|o| o.frame == frame) {
+        frame_override.apply(&mut params);
+    }
+    params
+}
+
+/// One saved session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    /// Format version ([`PROJECT_VERSION`] when written by this build)
+    pub version: u32,
+    /// Audio file. Absolute in memory; see [`Project::save`] for the
+    /// on-disk form.
+    pub wav_path: PathBuf,
+    pub channel: WaveformChannel,
+    /// Playhead, in samples
+    #[serde(default)]
+    pub position_samples: usize,
+    /// Preset the settings started from
+    #[serde(default)]
+    pub preset: String,
+    #[serde(default)]
+    pub params: DecoderParams,
+    /// Frame bounds segmented on `channel` with `params`, reused on open
+    /// instead of rescanning the whole channel. Empty when the session had
+    /// no segmentation.
+    #[serde(default)]
+    pub bounds: Vec<ImageBounds>,
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default)]
+    pub overrides: Vec<FrameOverride>,
+}
+
+impl Project {
+    /// A project for `wav_path` with default settings.
+    pub fn new(wav_path: PathBuf) -> Self {
+        Self {
+            version: PROJECT_VERSION,
+            wav_path,
+            channel: WaveformChannel::Left,
+            position_samples: 0,
+            preset: String::new(),
+            params: DecoderParams::default(),
+            bounds: Vec::new(),
+            bookmarks: Vec::new(),
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Default project path for an audio file: `<stem>.voyager.toml` beside
+    /// it.
+    pub fn default_path_for(wav_path: &Path) -> PathBuf {
+        wav_path.with_extension(PROJECT_EXTENSION)
+    }
+
+    /// Read a project file, resolving a relative audio path against the
+    /// project's directory.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ProjectError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|source| ProjectError::LoadFailed {
+            path: Box::new(path.to_path_buf()),
+            source,
+        })?;
+        let invalid = |source| ProjectError::InvalidFormat {
+            path: Box::new(path.to_path_buf()),
+            source,
+        };
+
+        // Check the version before the schema, so a newer file reports that
+        // instead of whichever field changed.
+        let value: toml::Value = toml::from_str(&contents).map_err(invalid)?;
+        let version = value.get("version").and_then(toml::Value::as_integer).unwrap_or(0);
+        if version > PROJECT_VERSION as i64 {
+            return Err(ProjectError::UnsupportedVersion {
+                version: version.try_into().unwrap_or(u32::MAX),
+                supported: PROJECT_VERSION,
+            });
+        }
+        let mut project: Project = value.try_into().map_err(invalid)?;
+
+        if project.wav_path.is_relative() {
+            let base = path.parent().unwrap_or(Path::new("."));
+            project.wav_path = base.join(&project.wav_path);
+        }
+        Ok(project)
+    }
+
+    /// Write the project to `path` at [`PROJECT_VERSION`]. The audio path is
+    /// stored relative to the project's directory when it lies inside it.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ProjectError> {
+        let path = path.as_ref();
+        let save_failed = |source| ProjectError::SaveFailed {
+            path: Box::new(path.to_path_buf()),
+            source,
+        };
+
+        let mut on_disk = self.clone();
+        on_disk.version = PROJECT_VERSION;
+        let base = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => std::path::absolute(parent).map_err(save_failed)?,
+            _ => std::env::current_dir().map_err(save_failed)?,
+        };
+        let wav_path = std::path::absolute(&self.wav_path).map_err(save_failed)?;
+        if let Ok(relative) = wav_path.strip_prefix(&base) {
+            on_disk.wav_path = relative.to_path_buf();
+        }
+
+        let contents = toml::to_string_pretty(&on_disk).map_err(|source| ProjectError::SerializationFailed { source })?;
+        std::fs::write(path, contents).map_err(save_failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sstv::DecoderMode;
+
+    fn sample_project(wav_path: PathBuf) -> Project {
+        Project {
+            channel: WaveformChannel::Right,
+            position_samples: 123_456,
+            preset: "2x rip".to_string(),
+            params: DecoderParams {
+                speed_factor: 2.0,
+                invert: true,
+                mode: DecoderMode::FrameTriplet,
+                ..DecoderParams::default()
+            },
+            bounds: vec![ImageBounds {
+                start_sample: 1_000,
+                end_sample: 9_000,
+                start_secs: 1_000.0 / 48_000.0,
+                end_secs: 9_000.0 / 48_000.0,
+                line_count: 40,
+                median_interval_samples: 199.5,
+                confidence: 0.5,
+            }],
+            bookmarks: vec![Bookmark {
+                position_samples: 48_000,
+                label: "calibration circle".to_string(),
+            }],
+            overrides: vec![FrameOverride {
+                frame: 3,
+                gamma: Some(1.4),
+                ..FrameOverride::default()
+            }],
+            ..Project::new(wav_path)
+        }
+    }
+
+    #[test]
+    fn round_trips_with_audio_path_relative_to_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let wav = dir.path().join("rips").join("side_a.wav");
+        let project = sample_project(wav.clone());
+        let path = dir.path().join("session.voyager.toml");
+        project.save(&path).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("wav_path = \"rips"), "{contents}");
+        assert!(contents.contains("mode = \"frame-triplet\""), "{contents}");
+
+        let loaded = Project::load(&path).unwrap();
+        assert_eq!(loaded.wav_path, wav);
+        assert_eq!(Project { wav_path: wav, ..loaded }, project);
+    }
+
+    #[test]
+    fn rejects_newer_versions_and_fills_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("future.voyager.toml");
+        std::fs::write(
+            &path,
+            "version = 99\nwav_path = \"a.wav\"\nchannel = \"left\"\nnew_field = 1\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            Project::load(&path),
+            Err(ProjectError::UnsupportedVersion { version: 99, .. })
+        ));
+
+        std::fs::write(&path, "version = 1\nwav_path = \"/data/a.wav\"\nchannel = \"left\"\n").unwrap();
+        let minimal = Project::load(&path).unwrap();
+        assert_eq!(minimal, Project::new(PathBuf::from("/data/a.wav")));
+    }
+
+    #[test]
+    fn frame_overrides_apply_only_to_their_frame() {
+        let params = DecoderParams::default();
+        let overrides = [FrameOverride {
+            frame: 3,
+            invert: Some(true),
+            gamma: Some(1.4),
+            sync_lock: None,
+        }];
+        let overridden = params_for_frame(&params, &overrides, 3);
+        assert!(overridden.invert);
+        assert_eq!(overridden.gamma, 1.4);
+        assert_eq!(overridden.sync_lock, params.sync_lock);
+        assert_eq!(params_for_frame(&params, &overrides, 4), params);
+    }
+}
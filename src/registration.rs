@@ -0,0 +1,579 @@
+//! Plane registration for color composites.
+//!
+//! The members of a Voyager color triplet were scanned separately and each
+//! decoded frame starts at its own segmentation boundary, so the planes
+//! disagree by tens of rows vertically, a few pixels horizontally
+//! (line-start jitter at the sync edge), and slightly in scale and rotation
+//! (playback speed drift and slant differ between the three scans).
+//!
+//! Registration runs coarse to fine: row-profile correlation gives an
+//! integer vertical shift, block matching on textured tiles measures
+//! local displacements around it, and a similarity transform is fitted to
+//! those displacements with outlier rejection. When the tiles can't support
+//! a fit the result degrades to their median shift, then to the profile
+//! shift, and [`PlaneRegistration::method`] says which one was used.
+
+use std::fmt;
+
+use crate::analysis::ContentMask;
+
+#[derive(Debug, Clone)]
+pub struct RegistrationParams {
+    /// Tile edge length in pixels for block matching.
+    pub tile: usize,
+    /// Block-matching search radius around the profile shift, in pixels.
+    pub search: usize,
+    /// Tiles whose reference pixels vary less than this (std, gray levels)
+    /// are too flat to match.
+    pub min_tile_std: f32,
+    /// Tile matches with normalized correlation below this are discarded.
+    pub min_tile_corr: f64,
+    /// Fitted rotations beyond this are implausible for a scan and are
+    /// rejected in favour of a translation.
+    pub max_rotation_deg: f64,
+    /// Fitted scales further than this from 1.0 are rejected likewise.
+    pub max_scale_dev: f64,
+}
+
+impl Default for RegistrationParams {
+    fn default() -> Self {
+        Self {
+            tile: 48,
+            search: 6,
+            min_tile_std: 6.0,
+            min_tile_corr: 0.6,
+            max_rotation_deg: 3.0,
+            max_scale_dev: 0.05,
+        }
+    }
+}
+
+/// Fewest inlier tiles a similarity fit may rest on.
+const MIN_FIT_TILES: usize = 6;
+/// Outlier rejection passes over the tile displacements.
+const FIT_PASSES: usize = 3;
+/// Fewest overlapping profile entries a coarse lag may rest on.
+const MIN_PROFILE_OVERLAP: usize = 16;
+
+/// A grayscale plane and its content mask.
+#[derive(Debug, Clone, Copy)]
+pub struct Plane<'a> {
+    pub pixels: &'a [u8],
+    pub width: usize,
+    pub height: usize,
+    pub mask: &'a ContentMask,
+}
+
+/// Similarity transform from reference-plane pixel coordinates into a
+/// moving plane: rotate by `rotation_deg` and scale about the reference
+/// center, then shift by (`dx`, `dy`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaneTransform {
+    pub dx: f64,
+    pub dy: f64,
+    pub rotation_deg: f64,
+    pub scale: f64,
+}
+
+impl PlaneTransform {
+    pub const IDENTITY: Self = Self {
+        dx: 0.0,
+        dy: 0.0,
+        rotation_deg: 0.0,
+        scale: 1.0,
+    };
+
+    pub fn translation(dx: f64, dy: f64) -> Self {
+        Self {
+            dx,
+            dy,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Map reference pixel (`x`, `y`) into the moving plane; `center` is
+    /// the reference plane's center.
+    pub fn apply(&self, x: f64, y: f64, center: (f64, f64)) -> (f64, f64) {
+        let (sin, cos) = self.rotation_deg.to_radians().sin_cos();
+        let (px, py) = (x - center.0, y - center.1);
+        (
+            self.scale * (cos * px - sin * py) + center.0 + self.dx,
+            self.scale * (sin * px + cos * py) + center.1 + self.dy,
+        )
+    }
+}
+
+impl fmt::Display for PlaneTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "dx {:+.2} dy {:+.2} rot {:+.3}° scale {:.4}",
+            self.dx, self.dy, self.rotation_deg, self.scale
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationMethod {
+    /// Integer row shift from luminance profiles; no tile matched.
+    Profile,
+    /// Median tile displacement: too few tiles for a fit, or the fit was
+    /// implausible.
+    Translation,
+    /// Similarity transform fitted to tile displacements.
+    Similarity,
+}
+
+impl fmt::Display for RegistrationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Profile => "profile",
+            Self::Translation => "translation",
+            Self::Similarity => "similarity",
+        })
+    }
+}
+
+/// How one plane was registered against the reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaneRegistration {
+    pub transform: PlaneTransform,
+    pub method: RegistrationMethod,
+    /// Tiles textured enough to attempt a match.
+    pub tiles_total: usize,
+    /// Tiles the final estimate rests on (fit inliers).
+    pub tiles_used: usize,
+    /// RMS distance between the tile displacements and the estimate, in
+    /// pixels. Zero for [`RegistrationMethod::Profile`].
+    pub residual_px: f64,
+}
+
+impl PlaneRegistration {
+    fn profile(dx: f64, dy: f64, tiles_total: usize) -> Self {
+        Self {
+            transform: PlaneTransform::translation(dx, dy),
+            method: RegistrationMethod::Profile,
+            tiles_total,
+            tiles_used: 0,
+            residual_px: 0.0,
+        }
+    }
+}
+
+impl fmt::Display for PlaneRegistration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}, {}/{} tiles, residual {:.2} px)",
+            self.transform, self.method, self.tiles_used, self.tiles_total, self.residual_px
+        )
+    }
+}
+
+/// Green and blue plane registrations against the red plane of a composite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registration {
+    pub green: PlaneRegistration,
+    pub blue: PlaneRegistration,
+}
+
+impl fmt::Display for Registration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "green {}; blue {}", self.green, self.blue)
+    }
+}
+
+/// Register `moving` against `reference`, restricted to both planes'
+/// content. Planes must share a width; heights may differ.
+pub fn register_plane(reference: &Plane, moving: &Plane, params: &RegistrationParams) -> PlaneRegistration {
+    // Coarse: integer row shift from luminance profiles.
+    let max_row_lag = (reference.height.min(moving.height) / 4).clamp(1, 128) as i64;
+    let dy = profile_lag(
+        &row_profile(reference),
+        &row_profile(moving),
+        max_row_lag,
+        &reference.mask.rows,
+        &moving.mask.rows,
+    );
+    // Horizontal offsets (sync-edge jitter) sit well inside the tile
+    // search radius, and column profiles are too broad to resolve them.
+    let dx = 0;
+
+    // Fine: block-match textured tiles around the coarse shift.
+    let (matches, tiles_total) = match_tiles(reference, moving, (dx, dy), params);
+    if matches.is_empty() {
+        return PlaneRegistration::profile(dx as f64, dy as f64, tiles_total);
+    }
+
+    let center = plane_center(reference);
+    if matches.len() >= MIN_FIT_TILES {
+        if let Some((transform, inliers, residual_px)) = fit_similarity(&matches, center) {
+            let plausible =
+                transform.rotation_deg.abs() <= params.max_rotation_deg && (transform.scale - 1.0).abs() <= params.max_scale_dev;
+            if plausible {
+                return PlaneRegistration {
+                    transform,
+                    method: RegistrationMethod::Similarity,
+                    tiles_total,
+                    tiles_used: inliers,
+                    residual_px,
+                };
+            }
+            tracing::debug!(%transform, "Implausible similarity fit; falling back to translation");
+        }
+    }
+
+    let (tdx, tdy) = median_displacement(&matches);
+    let residual_px = rms(matches.iter().map(|m| ((m.dx - tdx).powi(2) + (m.dy - tdy).powi(2)).sqrt()));
+    PlaneRegistration {
+        transform: PlaneTransform::translation(tdx, tdy),
+        method: RegistrationMethod::Translation,
+        tiles_total,
+        tiles_used: matches.len(),
+        residual_px,
+    }
+}
+
+/// Center of a plane in pixel coordinates, the pivot for rotation/scale.
+pub fn plane_center(plane: &Plane) -> (f64, f64) {
+    ((plane.width as f64 - 1.0) / 2.0, (plane.height as f64 - 1.0) / 2.0)
+}
+
+/// One tile's measured displacement: reference tile center (`x`, `y`)
+/// appears at (`x + dx`, `y + dy`) in the moving plane.
+#[derive(Debug, Clone, Copy)]
+struct TileMatch {
+    x: f64,
+    y: f64,
+    dx: f64,
+    dy: f64,
+}
+
+fn match_tiles(reference: &Plane, moving: &Plane, shift: (i64, i64), params: &RegistrationParams) -> (Vec<TileMatch>, usize) {
+    let (t, s) = (params.tile, params.search as i64);
+    let (gx, gy) = shift;
+    let columns = reference.mask.columns.clone();
+    let mut matches = Vec::new();
+    let mut total = 0;
+    if t == 0 || columns.len() < t {
+        return (matches, total);
+    }
+
+    let mut y0 = 0;
+    while y0 + t <= reference.height {
+        let rows_ok = (y0..y0 + t).all(|y| reference.mask.is_content(y))
+            && (y0 as i64 + gy - s..y0 as i64 + gy + t as i64 + s).all(|y| y >= 0 && moving.mask.is_content(y as usize));
+        if !rows_ok {
+            y0 += 1;
+            continue;
+        }
+        let mut x0 = columns.start;
+        while x0 + t <= columns.end {
+            let (mx0, mx1) = (x0 as i64 + gx - s, x0 as i64 + gx + t as i64 + s);
+            if mx0 < 0 || mx1 > moving.width as i64 {
+                x0 += t;
+                continue;
+            }
+            let tile: Vec<f64> = (y0..y0 + t)
+                .flat_map(|y| {
+                    reference.pixels[y * reference.width + x0..y * reference.width + x0 + t]
+                        .iter()
+                        .map(|&p| p as f64)
+                })
+                .collect();
+            let (mean, std) = mean_std(&tile);
+            if std < params.min_tile_std as f64 {
+                x0 += t;
+                continue;
+            }
+            total += 1;
+
+            // Normalized correlation over the search window; ties break
+            // toward the coarse shift so flat directions don't wander.
+            let side = (2 * s + 1) as usize;
+            let mut scores = vec![f64::NEG_INFINITY; side * side];
+            let mut best = (0i64, 0i64, f64::NEG_INFINITY);
+            for oy in -s..=s {
+                for ox in -s..=s {
+                    let (mx, my) = ((x0 as i64 + gx + ox) as usize, (y0 as i64 + gy + oy) as usize);
+                    let score = ncc(&tile, mean, std, moving, mx, my, t);
+                    scores[((oy + s) as usize) * side + (ox + s) as usize] = score;
+                    let closer = ox.abs() + oy.abs() < best.0.abs() + best.1.abs();
+                    if score > best.2 || (score == best.2 && closer) {
+                        best = (ox, oy, score);
+                    }
+                }
+            }
+            let (ox, oy, score) = best;
+            if score >= params.min_tile_corr {
+                let at = |dx: i64, dy: i64| scores[((oy + dy + s) as usize) * side + (ox + dx + s) as usize];
+                let fx = if ox.abs() < s {
+                    parabola_peak(at(-1, 0), score, at(1, 0))
+                } else {
+                    0.0
+                };
+                let fy = if oy.abs() < s {
+                    parabola_peak(at(0, -1), score, at(0, 1))
+                } else {
+                    0.0
+                };
+                let half = (t as f64 - 1.0) / 2.0;
+                matches.push(TileMatch {
+                    x: x0 as f64 + half,
+                    y: y0 as f64 + half,
+                    dx: (gx + ox) as f64 + fx,
+                    dy: (gy + oy) as f64 + fy,
+                });
+            }
+            x0 += t;
+        }
+        y0 += t;
+    }
+    (matches, total)
+}
+
+/// Normalized cross-correlation of a reference tile (with precomputed mean
+/// and std) against the `t`-square block of `moving` at (`mx`, `my`).
+fn ncc(tile: &[f64], mean: f64, std: f64, moving: &Plane, mx: usize, my: usize, t: usize) -> f64 {
+    let (mut sum, mut sum_sq, mut cross) = (0.0, 0.0, 0.0);
+    for row in 0..t {
+        let start = (my + row) * moving.width + mx;
+        for (col, &p) in moving.pixels[start..start + t].iter().enumerate() {
+            let m = p as f64;
+            sum += m;
+            sum_sq += m * m;
+            cross += (tile[row * t + col] - mean) * m;
+        }
+    }
+    let n = (t * t) as f64;
+    let m_std = (sum_sq / n - (sum / n).powi(2)).max(0.0).sqrt();
+    if m_std <= f64::EPSILON {
+        return f64::NEG_INFINITY;
+    }
+    cross / (n * std * m_std)
+}
+
+/// Sub-pixel offset of a peak from three samples around it, in -0.5..0.5.
+fn parabola_peak(left: f64, center: f64, right: f64) -> f64 {
+    let denom = left - 2.0 * center + right;
+    if !denom.is_finite() || denom.abs() <= f64::EPSILON {
+        return 0.0;
+    }
+    (0.5 * (left - right) / denom).clamp(-0.5, 0.5)
+}
+
+/// Least-squares similarity transform over the tile matches, re-fitted
+/// after dropping outliers. Returns the transform, inlier count and RMS
+/// residual, or `None` for a degenerate (collinear-point) layout.
+fn fit_similarity(matches: &[TileMatch], center: (f64, f64)) -> Option<(PlaneTransform, usize, f64)> {
+    let mut inliers: Vec<TileMatch> = matches.to_vec();
+    let mut fit = solve_similarity(&inliers, center)?;
+    for _ in 0..FIT_PASSES {
+        let residuals: Vec<f64> = matches.iter().map(|m| residual(&fit, m, center)).collect();
+        let mut sorted = residuals.clone();
+        sorted.sort_unstable_by(f64::total_cmp);
+        let threshold = (3.0 * sorted[sorted.len() / 2]).max(1.0);
+        let kept: Vec<TileMatch> = matches
+            .iter()
+            .zip(&residuals)
+            .filter(|(_, &r)| r <= threshold)
+            .map(|(m, _)| *m)
+            .collect();
+        if kept.len() < MIN_FIT_TILES || kept.len() == inliers.len() {
+            break;
+        }
+        inliers = kept;
+        fit = solve_similarity(&inliers, center)?;
+    }
+    let residual_px = rms(inliers.iter().map(|m| residual(&fit, m, center)));
+    Some((fit, inliers.len(), residual_px))
+}
+
+fn residual(transform: &PlaneTransform, m: &TileMatch, center: (f64, f64)) -> f64 {
+    let (px, py) = transform.apply(m.x, m.y, center);
+    ((px - m.x - m.dx).powi(2) + (py - m.y - m.dy).powi(2)).sqrt()
+}
+
+/// Closed-form similarity fit q = [a -b; b a] p + t, with p the tile
+/// centers and q their matched positions, both relative to `center`.
+fn solve_similarity(matches: &[TileMatch], center: (f64, f64)) -> Option<PlaneTransform> {
+    let n = matches.len() as f64;
+    let p: Vec<(f64, f64)> = matches.iter().map(|m| (m.x - center.0, m.y - center.1)).collect();
+    let q: Vec<(f64, f64)> = matches.iter().zip(&p).map(|(m, p)| (p.0 + m.dx, p.1 + m.dy)).collect();
+    let p_mean = (p.iter().map(|v| v.0).sum::<f64>() / n, p.iter().map(|v| v.1).sum::<f64>() / n);
+    let q_mean = (q.iter().map(|v| v.0).sum::<f64>() / n, q.iter().map(|v| v.1).sum::<f64>() / n);
+    let (mut norm, mut dot, mut cross) = (0.0, 0.0, 0.0);
+    for (pi, qi) in p.iter().zip(&q) {
+        let (px, py) = (pi.0 - p_mean.0, pi.1 - p_mean.1);
+        let (qx, qy) = (qi.0 - q_mean.0, qi.1 - q_mean.1);
+        norm += px * px + py * py;
+        dot += px * qx + py * qy;
+        cross += px * qy - py * qx;
+    }
+    if norm <= f64::EPSILON {
+        return None;
+    }
+    let (a, b) = (dot / norm, cross / norm);
+    Some(PlaneTransform {
+        dx: q_mean.0 - (a * p_mean.0 - b * p_mean.1),
+        dy: q_mean.1 - (b * p_mean.0 + a * p_mean.1),
+        rotation_deg: b.atan2(a).to_degrees(),
+        scale: (a * a + b * b).sqrt(),
+    })
+}
+
+fn median_displacement(matches: &[TileMatch]) -> (f64, f64) {
+    let median = |mut values: Vec<f64>| {
+        values.sort_unstable_by(f64::total_cmp);
+        values[values.len() / 2]
+    };
+    (
+        median(matches.iter().map(|m| m.dx).collect()),
+        median(matches.iter().map(|m| m.dy).collect()),
+    )
+}
+
+fn rms(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, n) = values.fold((0.0, 0usize), |(sum, n), v| (sum + v * v, n + 1));
+    if n == 0 {
+        0.0
+    } else {
+        (sum / n as f64).sqrt()
+    }
+}
+
+fn mean_std(values: &[f64]) -> (f64, f64) {
+    let n = values.len().max(1) as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    (mean, var.sqrt())
+}
+
+/// Mean luminance per row over the mask's content columns: the line-start
+/// region holds sync residue and the row ends hold edge junk, both of which
+/// would dominate the profile.
+fn row_profile(plane: &Plane) -> Vec<f64> {
+    let (lo, hi) = (plane.mask.columns.start, plane.mask.columns.end);
+    let span = hi.saturating_sub(lo).max(1) as f64;
+    plane
+        .pixels
+        .chunks_exact(plane.width.max(1))
+        .map(|row| {
+            row[lo.min(row.len() - 1)..hi.min(row.len())]
+                .iter()
+                .map(|&p| p as f64)
+                .sum::<f64>()
+                / span
+        })
+        .collect()
+}
+
+/// Lag of `b` relative to `a` maximizing normalized correlation: a[i]
+/// aligns with b[i + lag]. Correlates only entries that are content in
+/// both (`a_keep[i]` and `b_keep[i + lag]`) — the first/last rows of a
+/// segmented frame are inter-image leader junk whose strong, repetitive
+/// structure can outweigh the picture content. Returns 0 for degenerate
+/// (flat) profiles.
+fn profile_lag(a: &[f64], b: &[f64], max_lag: i64, a_keep: &[bool], b_keep: &[bool]) -> i64 {
+    let keep = |flags: &[bool], i: i64| flags.get(i as usize).copied().unwrap_or(false);
+    let mut best = (0i64, f64::NEG_INFINITY);
+    for lag in -max_lag..=max_lag {
+        let start = 0.max(-lag);
+        let end = (a.len() as i64).min(b.len() as i64 - lag);
+        let idx: Vec<usize> = (start..end)
+            .filter(|&i| keep(a_keep, i) && keep(b_keep, i + lag))
+            .map(|i| i as usize)
+            .collect();
+        if idx.len() < MIN_PROFILE_OVERLAP {
+            continue;
+        }
+        let n = idx.len() as f64;
+        let lagged = |i: usize| (i as i64 + lag) as usize;
+        let ma = idx.iter().map(|&i| a[i]).sum::<f64>() / n;
+        let mb = idx.iter().map(|&i| b[lagged(i)]).sum::<f64>() / n;
+        let (mut num, mut va, mut vb) = (0.0, 0.0, 0.0);
+        for &i in &idx {
+            let da = a[i] - ma;
+            let db = b[lagged(i)] - mb;
+            num += da * db;
+            va += da * da;
+            vb += db * db;
+        }
+        if va <= f64::EPSILON || vb <= f64::EPSILON {
+            continue;
+        }
+        let corr = num / (va * vb).sqrt();
+        if corr > best.1 {
+            best = (lag, corr);
+        }
+    }
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::textured_plane;
+
+    #[test]
+    fn recovers_similarity_transform() {
+        let (w, h) = (256usize, 256usize);
+        let truth = PlaneTransform {
+            dx: 3.4,
+            dy: -7.0,
+            rotation_deg: 0.8,
+            scale: 1.01,
+        };
+        let center = ((w as f64 - 1.0) / 2.0, (h as f64 - 1.0) / 2.0);
+        let reference = textured_plane(w, h, |x, y| (x, y));
+        // Moving pixel q shows the pattern at the reference point p with
+        // truth.apply(p) == q.
+        let (sin, cos) = truth.rotation_deg.to_radians().sin_cos();
+        let moving = textured_plane(w, h, |x, y| {
+            let (qx, qy) = (
+                (x - center.0 - truth.dx) / truth.scale,
+                (y - center.1 - truth.dy) / truth.scale,
+            );
+            (cos * qx + sin * qy + center.0, -sin * qx + cos * qy + center.1)
+        });
+        let mask = ContentMask::full(h, w);
+        let plane = |pixels| Plane {
+            pixels,
+            width: w,
+            height: h,
+            mask: &mask,
+        };
+
+        let reg = register_plane(&plane(&reference), &plane(&moving), &RegistrationParams::default());
+        assert_eq!(reg.method, RegistrationMethod::Similarity, "{reg}");
+        let t = reg.transform;
+        assert!((t.dx - truth.dx).abs() < 0.3 && (t.dy - truth.dy).abs() < 0.3, "{reg}");
+        assert!((t.rotation_deg - truth.rotation_deg).abs() < 0.1, "{reg}");
+        assert!((t.scale - truth.scale).abs() < 0.003, "{reg}");
+        assert!(reg.tiles_used >= MIN_FIT_TILES && reg.residual_px < 0.5, "{reg}");
+    }
+
+    #[test]
+    fn featureless_planes_fall_back_to_profile_shift() {
+        // One bright row per plane, otherwise black: rows register, but no
+        // tile has texture to match.
+        let (w, h) = (64usize, 64usize);
+        let banded = |row: usize| {
+            let mut pixels = vec![0u8; w * h];
+            pixels[row * w..(row + 1) * w].fill(255);
+            pixels
+        };
+        let (reference, moving) = (banded(30), banded(35));
+        let mask = ContentMask::central(h, w);
+        let plane = |pixels| Plane {
+            pixels,
+            width: w,
+            height: h,
+            mask: &mask,
+        };
+        let reg = register_plane(&plane(&reference), &plane(&moving), &RegistrationParams::default());
+        assert_eq!(reg.method, RegistrationMethod::Profile);
+        assert_eq!(reg.transform, PlaneTransform::translation(0.0, 5.0));
+        assert_eq!(reg.tiles_used, 0);
+    }
+}
@@ -0,0 +1,572 @@
+//! Windowed sample access: the [`SampleSource`] trait and its on-disk
+//! implementation.
+//!
+//! [`WavReader`] decodes the whole file into two resident channel buffers,
+//! which is fine for carved clips but needs ~2x the file size in RAM for the
+//! multi-gigabyte record rips (and is unusable for the 384 kHz masters).
+//! [`StreamingWavReader`] instead keeps only the parsed header and reads the
+//! requested frame range from disk on each call, so decode windows, spectrum
+//! frames and envelope scans cost memory proportional to what they touch.
+//!
+//! Only WAV can be streamed: compressed formats (see [`crate::codec`]) have
+//! no fixed frame layout to seek into, so [`open_source`] always decodes them
+//! into memory.
+//!
+//! Consumers that need the whole channel (the sync scan, segmentation) walk
+//! it with [`read_blocks`] instead of asking for one giant slice.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::{Deref, Range};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use hound::{SampleFormat, WavSpec};
+
+use crate::audio::{WavReader, WaveformChannel};
+use crate::codec::{AudioFormat, Container};
+use crate::error::{AudioError, Result};
+use crate::pcm::RawPcmFormat;
+use crate::riff;
+
+/// Random-access, per-channel view of an audio file.
+///
+/// Indices are frames (one sample per channel). Channels past the last one
+/// read the last, so mono sources return the same data for both channels,
+/// matching [`WavReader`]; check user input with
+/// [`SampleSource::require_channel`].
+pub trait SampleSource: Send + Sync {
+    /// Sample rate in Hz.
+    fn sample_rate(&self) -> u32;
+
+    /// Channel count of the underlying file (any nonzero count).
+    fn channels(&self) -> u16;
+
+    /// Length in frames.
+    fn len(&self) -> usize;
+
+    /// Container and codec of the underlying file.
+    fn format(&self) -> AudioFormat;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check that `channel` exists in this source.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AudioError::ChannelNotPresent`] for a channel number past
+    /// the file's last channel, or mid/side of a mono file.
+    fn require_channel(&self, channel: WaveformChannel) -> Result<()> {
+        if channel.is_available(self.channels()) {
+            Ok(())
+        } else {
+            Err(AudioError::ChannelNotPresent {
+                channel: channel.to_string(),
+                channels: self.channels(),
+            }
+            .into())
+        }
+    }
+
+    /// Read `range` of `channel` into a new buffer. The range is clamped to
+    /// the source length, so reads past the end return a shorter (possibly
+    /// empty) buffer rather than an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AudioError::ReadFailed`] when the backing file cannot be read.
+    fn read(&self, channel: WaveformChannel, range: Range<usize>) -> Result<Vec<f32>>;
+
+    /// The whole channel, when it is already resident in memory. Streaming
+    /// sources return `None`; callers fall back to ranged reads.
+    fn resident(&self, _channel: WaveformChannel) -> Option<Arc<[f32]>> {
+        None
+    }
+}
+
+impl SampleSource for WavReader {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn len(&self) -> usize {
+        self.channel_data[0].len()
+    }
+
+    fn format(&self) -> AudioFormat {
+        self.format
+    }
+
+    fn read(&self, channel: WaveformChannel, range: Range<usize>) -> Result<Vec<f32>> {
+        let end = range.end.min(self.len());
+        let start = range.start.min(end);
+        Ok(match channel.physical(self.channels) {
+            Some(index) => self.channel_data[index][start..end].to_vec(),
+            None => {
+                let right = WaveformChannel::Right.physical(self.channels).unwrap_or(0);
+                channel.mix(&self.channel_data[0][start..end], &self.channel_data[right][start..end])
+            }
+        })
+    }
+
+    /// Physical channels only; mid/side are mixed per read.
+    fn resident(&self, channel: WaveformChannel) -> Option<Arc<[f32]>> {
+        channel
+            .physical(self.channels)
+            .map(|index| Arc::clone(&self.channel_data[index]))
+    }
+}
+
+/// WAV reader that decodes frame ranges from disk on demand.
+///
+/// Reads RIFF, RF64 and Wave64 files (see [`crate::riff`]), so frame
+/// positions past 4 GB work. Only the header is parsed up front; each [`SampleSource::read`] seeks to
+/// the requested frames and decodes them. Reads are serialized on an internal
+/// file handle, so one instance can be shared (via `Arc`) between the UI,
+/// the decode worker and the playback thread.
+pub struct StreamingWavReader {
+    path: PathBuf,
+    file: Mutex<File>,
+    container: Container,
+    spec: WavSpec,
+    /// Container width of one sample; can exceed `bits_per_sample / 8`
+    /// (e.g. 24-bit audio in 4-byte slots).
+    bytes_per_sample: u16,
+    /// Byte offset of the first frame in the file.
+    data_offset: u64,
+    frames: usize,
+}
+
+impl StreamingWavReader {
+    /// Open a WAV file for ranged reads.
+    ///
+    /// # Errors
+    ///
+    /// Same validation as [`WavReader::from_file`]: [`AudioError::LoadFailed`]
+    /// for unreadable or unsupported files, [`AudioError::InvalidSampleRate`],
+    /// [`AudioError::UnsupportedChannels`], and [`AudioError::EmptyFile`] when
+    /// the data chunk holds no frames.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let load_failed = |source: hound::Error| AudioError::LoadFailed {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        let mut file = File::open(path).map_err(|e| load_failed(hound::Error::IoError(e)))?;
+        let layout = riff::locate_data(&mut file).map_err(|e| load_failed(hound::Error::IoError(e)))?;
+        let spec = layout.spec;
+        if spec.sample_rate < 8000 {
+            return Err(AudioError::InvalidSampleRate { rate: spec.sample_rate }.into());
+        }
+        if spec.channels == 0 {
+            return Err(AudioError::UnsupportedChannels { channels: spec.channels }.into());
+        }
+        let bytes_per_sample = layout.block_align / spec.channels;
+        if !is_supported_layout(spec.sample_format, bytes_per_sample, spec.bits_per_sample) {
+            return Err(load_failed(hound::Error::Unsupported).into());
+        }
+
+        // A truncated rip can declare more data than the file holds; trust
+        // whichever is smaller.
+        let file_len = file.metadata().map(|m| m.len()).unwrap_or(u64::MAX);
+        let data_len = layout.data_len.min(file_len.saturating_sub(layout.data_offset));
+        let frames = (data_len / layout.block_align as u64) as usize;
+        if frames == 0 {
+            return Err(AudioError::EmptyFile {
+                path: path.to_path_buf(),
+            }
+            .into());
+        }
+
+        tracing::info!(
+            path = %path.display(),
+            container = layout.container.name(),
+            sample_rate = spec.sample_rate,
+            channels = spec.channels,
+            format = ?spec.sample_format,
+            bits = spec.bits_per_sample,
+            frames,
+            "Opened WAV file for streaming"
+        );
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+            container: layout.container,
+            spec,
+            bytes_per_sample,
+            data_offset: layout.data_offset,
+            frames,
+        })
+    }
+
+    /// Open a headerless PCM file (see [`crate::pcm`]) for ranged reads. A trailing partial frame
+    /// is ignored.
+    ///
+    /// # Errors
+    ///
+    /// [`RawPcmFormat::validate`] errors, [`AudioError::LoadFailed`] when the
+    /// file cannot be opened, and [`AudioError::EmptyFile`] when it holds no
+    /// whole frame.
+    pub fn open_raw<P: AsRef<Path>>(path: P, format: RawPcmFormat) -> Result<Self> {
+        let path = path.as_ref();
+        format.validate()?;
+        let file = File::open(path).map_err(|e| AudioError::LoadFailed {
+            path: path.to_path_buf(),
+            source: hound::Error::IoError(e),
+        })?;
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        let frames = (len / format.block_align() as u64) as usize;
+        if frames == 0 {
+            return Err(AudioError::EmptyFile {
+                path: path.to_path_buf(),
+            }
+            .into());
+        }
+        tracing::info!(
+            path = %path.display(),
+            sample_rate = format.sample_rate,
+            channels = format.channels,
+            encoding = ?format.encoding,
+            frames,
+            "Opened raw PCM file for streaming"
+        );
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+            container: Container::Raw,
+            spec: format.spec(),
+            bytes_per_sample: format.encoding.bytes(),
+            data_offset: 0,
+            frames,
+        })
+    }
+}
+
+impl SampleSource for StreamingWavReader {
+    fn sample_rate(&self) -> u32 {
+        self.spec.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.spec.channels
+    }
+
+    fn len(&self) -> usize {
+        self.frames
+    }
+
+    fn format(&self) -> AudioFormat {
+        AudioFormat::wav(self.container, &self.spec)
+    }
+
+    fn read(&self, channel: WaveformChannel, range: Range<usize>) -> Result<Vec<f32>> {
+        let end = range.end.min(self.frames);
+        let start = range.start.min(end);
+        if start == end {
+            return Ok(Vec::new());
+        }
+
+        let block_align = self.bytes_per_sample as usize * self.spec.channels as usize;
+        let mut bytes = vec![0u8; (end - start) * block_align];
+        {
+            let mut file = self.file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            file.seek(SeekFrom::Start(self.data_offset + (start * block_align) as u64))
+                .and_then(|_| file.read_exact(&mut bytes))
+                .map_err(|source| AudioError::ReadFailed {
+                    path: self.path.clone(),
+                    source,
+                })?;
+        }
+
+        Ok(decode_frames(&self.spec, self.bytes_per_sample, &bytes, channel))
+    }
+}
+
+/// Decode `channel` from whole interleaved frames of `spec` samples, each
+/// `bytes_per_sample` wide. Mid/side mix the first two channels.
+pub(crate) fn decode_frames(spec: &WavSpec, bytes_per_sample: u16, bytes: &[u8], channel: WaveformChannel) -> Vec<f32> {
+    let width = bytes_per_sample as usize;
+    let block_align = width * spec.channels as usize;
+    let decode_channel = |index: usize| -> Vec<f32> {
+        let offset = index * width;
+        bytes
+            .chunks_exact(block_align)
+            .map(|frame| decode_sample(spec, &frame[offset..offset + width]))
+            .collect()
+    };
+    match channel.physical(spec.channels) {
+        Some(index) => decode_channel(index),
+        None => {
+            let right = WaveformChannel::Right.physical(spec.channels).unwrap_or(0);
+            channel.mix(&decode_channel(0), &decode_channel(right))
+        }
+    }
+}
+
+fn decode_sample(spec: &WavSpec, bytes: &[u8]) -> f32 {
+    match (spec.sample_format, bytes.len()) {
+        (SampleFormat::Float, _) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        // 8-bit WAV is unsigned; same scale as the in-memory i16 path.
+        (SampleFormat::Int, 1) => (bytes[0] as i16 - 128) as f32 / 127.0,
+        (SampleFormat::Int, 2) => {
+            let scale = ((1u32 << (spec.bits_per_sample - 1)) - 1) as f32;
+            i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / scale
+        }
+        (SampleFormat::Int, n) => {
+            // Sign-extend the top valid byte; 24-in-4 containers ignore
+            // the padding byte like hound does.
+            let raw = if n == 3 || spec.bits_per_sample == 24 {
+                i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8
+            } else {
+                i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+            };
+            let scale = ((1u64 << (spec.bits_per_sample.min(32) - 1)) - 1) as f32;
+            raw as f32 / scale
+        }
+    }
+}
+
+fn is_supported_layout(format: SampleFormat, bytes_per_sample: u16, bits: u16) -> bool {
+    match format {
+        SampleFormat::Float => bytes_per_sample == 4 && bits == 32,
+        SampleFormat::Int => matches!((bytes_per_sample, bits), (1, 8) | (2, 16) | (3, 24) | (4, 24) | (4, 32)),
+    }
+}
+
+/// Open `path` as a sample source: WAV files up to `resident_limit_bytes`
+/// are decoded into memory (fast random access, zero-copy playback), larger
+/// ones are streamed from disk. Other formats are always decoded into memory.
+///
+/// # Errors
+///
+/// Propagates the loader's validation errors.
+pub fn open_source<P: AsRef<Path>>(path: P, resident_limit_bytes: u64) -> Result<Arc<dyn SampleSource>> {
+    let path = path.as_ref();
+    let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let streamable = matches!(Container::sniff(path), Ok(Some(c)) if c.is_wave());
+    if size > resident_limit_bytes && streamable {
+        Ok(Arc::new(StreamingWavReader::open(path)?))
+    } else {
+        Ok(Arc::new(WavReader::from_file(path)?))
+    }
+}
+
+/// One block of a [`read_blocks`] walk.
+pub struct Block {
+    /// Absolute frame index of `samples[0]`.
+    pub offset: usize,
+    /// Absolute frames this block is responsible for. Results found in the
+    /// context margins belong to the neighbouring block and should be
+    /// dropped, so each position is reported exactly once.
+    pub owned: Range<usize>,
+    pub samples: Vec<f32>,
+}
+
+impl Block {
+    /// The samples of the owned frames, without the context margins.
+    pub fn owned_samples(&self) -> &[f32] {
+        &self.samples[self.owned.start - self.offset..self.owned.end - self.offset]
+    }
+}
+
+/// Walk `range` of `channel` in blocks of `block_len` frames, each padded
+/// with `margin` frames of context on both sides (clamped to `range`).
+///
+/// Detectors that look a little past the current sample (peak picking,
+/// falling-edge search, FFT chunks) run on each padded block and keep only
+/// the hits inside [`Block::owned`].
+///
+/// `source` is any pointer to a source: a reference, or an `Arc` when the
+/// walk must own it.
+pub fn read_blocks<S, D>(
+    source: S,
+    channel: WaveformChannel,
+    range: Range<usize>,
+    block_len: usize,
+    margin: usize,
+) -> impl Iterator<Item = Result<Block>>
+where
+    S: Deref<Target = D>,
+    D: SampleSource + ?Sized,
+{
+    let end = range.end.min(source.len());
+    let block_len = block_len.max(1);
+    (range.start..end).step_by(block_len).map(move |owned_start| {
+        let owned_end = (owned_start + block_len).min(end);
+        let offset = owned_start.saturating_sub(margin).max(range.start);
+        let samples = source.read(channel, offset..(owned_end + margin).min(end))?;
+        Ok(Block {
+            offset,
+            owned: owned_start..owned_end,
+            samples,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    fn write_wav(spec: WavSpec, frames: usize, sample: impl Fn(usize, u16) -> i32) -> NamedTempFile {
+        let file = NamedTempFile::new().unwrap();
+        let mut writer = hound::WavWriter::create(file.path(), spec).unwrap();
+        for i in 0..frames {
+            for ch in 0..spec.channels {
+                match spec.sample_format {
+                    SampleFormat::Float => writer.write_sample(sample(i, ch) as f32 / 1000.0).unwrap(),
+                    SampleFormat::Int => writer.write_sample(sample(i, ch)).unwrap(),
+                }
+            }
+        }
+        writer.finalize().unwrap();
+        file
+    }
+
+    fn spec(channels: u16, bits: u16, format: SampleFormat) -> WavSpec {
+        WavSpec {
+            channels,
+            sample_rate: 48_000,
+            bits_per_sample: bits,
+            sample_format: format,
+        }
+    }
+
+    fn assert_matches_resident(path: &Path) {
+        let resident = WavReader::from_file(path).unwrap();
+        let streamed = StreamingWavReader::open(path).unwrap();
+        assert_eq!(streamed.len(), SampleSource::len(&resident));
+        assert_eq!(streamed.channels(), resident.channels);
+        for channel in WaveformChannel::available(resident.channels) {
+            let full = streamed.read(channel, 0..usize::MAX).unwrap();
+            assert_eq!(full.as_slice(), &*resident.get_samples(channel));
+            let window = streamed.read(channel, 100..350).unwrap();
+            assert_eq!(window.as_slice(), &resident.get_samples(channel)[100..350]);
+        }
+    }
+
+    #[test]
+    fn streamed_reads_match_resident_decode_for_each_format() {
+        let ramp = |i: usize, ch: u16| (i as i32 % 200 - 100) * if ch == 0 { 1 } else { -1 };
+        let cases = [
+            (spec(1, 16, SampleFormat::Int), 1000),
+            (spec(2, 16, SampleFormat::Int), 100),
+            (spec(2, 8, SampleFormat::Int), 100),
+            (spec(2, 24, SampleFormat::Int), 40_000),
+            (spec(2, 32, SampleFormat::Int), 10_000_000),
+            (spec(2, 32, SampleFormat::Float), 5),
+        ];
+        for (spec, scale) in cases {
+            let file = write_wav(spec, 1000, |i, ch| ramp(i, ch) * scale / 100);
+            assert_matches_resident(file.path());
+        }
+    }
+
+    #[test]
+    fn streamed_reads_select_any_channel() {
+        let file = write_wav(spec(4, 16, SampleFormat::Int), 1000, |i, ch| {
+            (i as i32 % 200 - 100) * (ch as i32 + 1) * 50
+        });
+        let source = StreamingWavReader::open(file.path()).unwrap();
+        assert_eq!(
+            source.read(WaveformChannel::index(3), 0..1).unwrap(),
+            [-20_000.0 / i16::MAX as f32]
+        );
+        assert!(source.require_channel(WaveformChannel::index(3)).is_ok());
+        assert!(source.require_channel(WaveformChannel::index(4)).is_err());
+        assert_matches_resident(file.path());
+    }
+
+    #[test]
+    fn reads_clamp_to_the_end_of_the_data() {
+        let file = write_wav(spec(1, 16, SampleFormat::Int), 500, |i, _| i as i32);
+        let source = StreamingWavReader::open(file.path()).unwrap();
+        assert_eq!(source.read(WaveformChannel::Left, 480..600).unwrap().len(), 20);
+        assert!(source.read(WaveformChannel::Left, 700..800).unwrap().is_empty());
+    }
+
+    #[test]
+    fn skips_unknown_chunks_before_data() {
+        // Hand-built file with a LIST chunk between fmt and data.
+        let mut bytes = Vec::new();
+        let samples: [i16; 4] = [100, -100, 200, -200];
+        let list = b"INFOxy";
+        let riff_len = 4 + (8 + 16) + (8 + list.len()) + (8 + samples.len() * 2);
+        bytes.extend(b"RIFF");
+        bytes.extend((riff_len as u32).to_le_bytes());
+        bytes.extend(b"WAVEfmt ");
+        bytes.extend(16u32.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(8000u32.to_le_bytes());
+        bytes.extend(16000u32.to_le_bytes());
+        bytes.extend(2u16.to_le_bytes());
+        bytes.extend(16u16.to_le_bytes());
+        bytes.extend(b"LIST");
+        bytes.extend((list.len() as u32).to_le_bytes());
+        bytes.extend(list);
+        bytes.extend(b"data");
+        bytes.extend(((samples.len() * 2) as u32).to_le_bytes());
+        for s in samples {
+            bytes.extend(s.to_le_bytes());
+        }
+        let file = NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), &bytes).unwrap();
+
+        assert_matches_resident_short(file.path(), samples.len());
+    }
+
+    fn assert_matches_resident_short(path: &Path, frames: usize) {
+        let resident = WavReader::from_file(path).unwrap();
+        let streamed = StreamingWavReader::open(path).unwrap();
+        assert_eq!(streamed.len(), frames);
+        assert_eq!(
+            streamed.read(WaveformChannel::Left, 0..frames).unwrap().as_slice(),
+            &*resident.get_samples(WaveformChannel::Left)
+        );
+    }
+
+    #[test]
+    fn open_source_streams_only_above_the_resident_limit() {
+        let file = write_wav(spec(1, 16, SampleFormat::Int), 1000, |i, _| i as i32);
+        let resident = open_source(file.path(), u64::MAX).unwrap();
+        assert!(resident.resident(WaveformChannel::Left).is_some());
+        let streamed = open_source(file.path(), 0).unwrap();
+        assert!(streamed.resident(WaveformChannel::Left).is_none());
+        assert_eq!(streamed.len(), 1000);
+    }
+
+    #[test]
+    fn compressed_files_are_always_resident() {
+        let samples: Vec<f32> = (0..1000).map(|i| (i as f32 / 500.0) - 1.0).collect();
+        let file = crate::test_fixtures::create_test_flac_file(&samples, 8000, 1);
+        let source = open_source(file.path(), 0).unwrap();
+        assert!(source.resident(WaveformChannel::Left).is_some());
+        assert_eq!(source.format().container, Container::Flac);
+        assert_eq!(source.len(), 1000);
+    }
+
+    #[test]
+    fn blocks_cover_the_range_exactly_once() {
+        let file = write_wav(spec(1, 16, SampleFormat::Int), 1000, |i, _| i as i32);
+        let source = StreamingWavReader::open(file.path()).unwrap();
+        let mut next = 50;
+        for block in read_blocks(&source, WaveformChannel::Left, 50..990, 300, 20) {
+            let block = block.unwrap();
+            assert_eq!(block.owned.start, next);
+            assert!(block.offset >= 50 && block.offset <= block.owned.start);
+            assert!(block.offset + block.samples.len() <= 990);
+            next = block.owned.end;
+        }
+        assert_eq!(next, 990);
+    }
+}
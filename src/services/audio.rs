@@ -6,8 +6,12 @@ use std::time::Duration;
 #[cfg(feature = "audio_playback")]
 use rodio::Source;
 
+#[cfg(feature = "audio_playback")]
+use crate::audio::WaveformChannel;
 #[cfg(feature = "audio_playback")]
 use crate::error::{AudioError, Result};
+#[cfg(feature = "audio_playback")]
+use crate::sample_source::SampleSource;
 
 #[cfg(feature = "audio_playback")]
 /// Audio source that plays from a shared buffer of f32 samples with zero-copy seeking.
@@ -107,3 +111,104 @@ impl Source for AudioBufferSource {
         Some(Duration::from_secs_f64(duration_secs))
     }
 }
+
+#[cfg(feature = "audio_playback")]
+/// Read-ahead block length for [`ChunkedSource`], in seconds.
+const CHUNK_SECS: f64 = 0.5;
+
+#[cfg(feature = "audio_playback")]
+/// Audio source that plays one channel of a [`SampleSource`] by reading it in
+/// short blocks. Streamed files have no resident buffer for
+/// [`AudioBufferSource`] to share, so playback pulls from disk instead.
+pub struct ChunkedSource {
+    source: Arc<dyn SampleSource>,
+    channel: WaveformChannel,
+    /// Absolute frame index of `block[0]`.
+    block_start: usize,
+    block: Vec<f32>,
+    /// Read position within `block`.
+    cursor: usize,
+}
+
+#[cfg(feature = "audio_playback")]
+impl ChunkedSource {
+    /// Create a source playing `channel` from frame `offset`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AudioError::BufferTooShort`] if `offset` is past the end of
+    /// the source, or [`AudioError::InvalidSampleRate`] for a zero rate.
+    pub fn new(source: Arc<dyn SampleSource>, channel: WaveformChannel, offset: usize) -> Result<Self, AudioError> {
+        if offset >= source.len() {
+            return Err(AudioError::BufferTooShort {
+                needed: offset + 1,
+                actual: source.len(),
+            });
+        }
+        if source.sample_rate() == 0 {
+            return Err(AudioError::InvalidSampleRate { rate: 0 });
+        }
+        Ok(Self {
+            source,
+            channel,
+            block_start: offset,
+            block: Vec::new(),
+            cursor: 0,
+        })
+    }
+
+    fn remaining(&self) -> usize {
+        self.source.len().saturating_sub(self.block_start + self.cursor)
+    }
+}
+
+#[cfg(feature = "audio_playback")]
+impl Iterator for ChunkedSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cursor >= self.block.len() {
+            self.block_start += self.block.len();
+            self.cursor = 0;
+            let block_len = ((CHUNK_SECS * self.source.sample_rate() as f64) as usize).max(1);
+            self.block = match self.source.read(self.channel, self.block_start..self.block_start + block_len) {
+                Ok(block) => block,
+                Err(e) => {
+                    // End playback rather than emit silence that looks like signal.
+                    tracing::error!(error = %e, position = self.block_start, "Playback read failed");
+                    self.block_start = self.source.len();
+                    Vec::new()
+                }
+            };
+        }
+        let sample = self.block.get(self.cursor).copied()?;
+        self.cursor += 1;
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+#[cfg(feature = "audio_playback")]
+impl Source for ChunkedSource {
+    fn current_span_len(&self) -> Option<usize> {
+        Some(self.remaining())
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f64(
+            self.remaining() as f64 / self.source.sample_rate() as f64,
+        ))
+    }
+}
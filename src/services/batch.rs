@@ -7,7 +7,8 @@ use std::sync::mpsc::Receiver;
 use std::sync::Arc;
 use std::thread::JoinHandle;
 
-use crate::sstv::{DecoderMode, DecoderParams};
+use crate::preset::Preset;
+use crate::sstv::DecoderMode;
 
 /// Processing state of a single batch queue entry.
 #[derive(Debug, Clone, PartialEq)]
@@ -44,9 +45,10 @@ impl BatchRunner {
         self.worker.is_some()
     }
 
-    /// Spawn the batch worker over `queue`. Returns the cancellation flag so
-    /// the panel's Stop button can share it.
-    pub fn start(&mut self, queue: Vec<BatchItem>, output_dir: PathBuf, mode: DecoderMode) -> Arc<AtomicBool> {
+    /// Spawn the batch worker over `queue`, decoding each file with
+    /// `preset`. Returns the cancellation flag so the panel's Stop button can
+    /// share it.
+    pub fn start(&mut self, queue: Vec<BatchItem>, output_dir: PathBuf, mode: DecoderMode, preset: Preset) -> Arc<AtomicBool> {
         let cancel_flag = Arc::new(AtomicBool::new(false));
         self.cancel = Some(cancel_flag.clone());
 
@@ -56,11 +58,6 @@ impl BatchRunner {
         let worker_cancel = cancel_flag.clone();
         self.worker = Some(std::thread::spawn(move || {
             let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                let params = DecoderParams {
-                    mode,
-                    ..Default::default()
-                };
-
                 let total = queue.len();
 
                 for (index, item) in queue.iter().enumerate() {
@@ -71,7 +68,7 @@ impl BatchRunner {
 
                     let _ = tx.send(BatchProgressMsg::ItemStatus(index, BatchStatus::Processing));
 
-                    let result = crate::batch::process_single_file(&item.path, &output_dir, &params);
+                    let result = crate::batch::process_single_file(&item.path, &output_dir, &preset, mode);
 
                     let status = match result {
                         Ok(_) => BatchStatus::Done,
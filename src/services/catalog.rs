@@ -0,0 +1,199 @@
+//! Background catalog scan: segments each stereo channel of the loaded file
+//! into its image frames and renders a small thumbnail of every frame, for
+//! the catalog browser. Results stream back as they are ready so the grid
+//! fills in frame by frame.
+
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use image::imageops::FilterType;
+use image::GrayImage;
+
+use crate::audio::WaveformChannel;
+use crate::pipeline::RecordFrames;
+use crate::sample_source::SampleSource;
+use crate::sstv::{DecoderMode, DecoderParams, SstvDecoder};
+
+/// Thumbnail width in pixels; height follows the frame's line count.
+pub const THUMBNAIL_WIDTH: u32 = 96;
+
+/// Progress from a [`CatalogScan`].
+pub enum CatalogMsg {
+    /// `channel` is segmented; thumbnails for its frames follow.
+    Segmented(WaveformChannel, Arc<RecordFrames>),
+    /// Thumbnail of frame `index` of `channel`.
+    Thumbnail(WaveformChannel, usize, GrayImage),
+    /// Frame `index` of `channel` could not be thumbnailed.
+    ThumbnailFailed(WaveformChannel, usize),
+    /// Segmenting `channel` failed; its frames are skipped.
+    Failed(WaveformChannel, String),
+}
+
+/// A running scan. Dropping it stops the worker after the frame in hand.
+pub struct CatalogScan {
+    rx: Receiver<CatalogMsg>,
+}
+
+impl CatalogScan {
+    /// Scan `channels` of `source` with `params`' line cadence and
+    /// segmentation settings. A channel given already-segmented frames
+    /// skips the segmentation pass.
+    pub fn start(
+        source: Arc<dyn SampleSource>,
+        channels: Vec<(WaveformChannel, Option<Arc<RecordFrames>>)>,
+        params: DecoderParams,
+    ) -> Self {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let start = Instant::now();
+            for (channel, frames) in channels {
+                if scan_channel(source.as_ref(), channel, frames, &params, &tx).is_err() {
+                    // Receiver dropped: a new file or a closed panel
+                    return;
+                }
+            }
+            tracing::info!(
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "Background catalog scan completed"
+            );
+        });
+        Self { rx }
+    }
+
+    /// Drain available messages without blocking.
+    pub fn poll(&self) -> Vec<CatalogMsg> {
+        self.rx.try_iter().collect()
+    }
+}
+
+/// Segment (unless `frames` are given) and thumbnail one channel; every
+/// frame gets a thumbnail or a failure message. Errs only when the receiver
+/// has gone away.
+fn scan_channel(
+    source: &dyn SampleSource,
+    channel: WaveformChannel,
+    frames: Option<Arc<RecordFrames>>,
+    params: &DecoderParams,
+    tx: &Sender<CatalogMsg>,
+) -> std::result::Result<(), std::sync::mpsc::SendError<CatalogMsg>> {
+    let frames = match frames {
+        Some(frames) => frames,
+        None => match RecordFrames::segment(source, channel, params) {
+            Ok(frames) => Arc::new(frames),
+            Err(e) => {
+                tracing::warn!(channel = %channel.label(), error = %e, "Catalog segmentation failed");
+                return tx.send(CatalogMsg::Failed(channel, e.to_string()));
+            }
+        },
+    };
+    tx.send(CatalogMsg::Segmented(channel, Arc::clone(&frames)))?;
+    for index in 0..frames.bounds().len() {
+        match frame_thumbnail(source, &frames, index, params) {
+            Ok(thumbnail) => tx.send(CatalogMsg::Thumbnail(channel, index, thumbnail))?,
+            Err(e) => {
+                tracing::debug!(channel = %channel.label(), frame = index, error = %e, "No thumbnail for frame");
+                tx.send(CatalogMsg::ThumbnailFailed(channel, index))?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Grayscale thumbnail of frame `index`, [`THUMBNAIL_WIDTH`] wide with the
+/// aspect of a full-width decode. Each frame is rendered alone, so color
+/// triplet members show as their separate planes.
+pub fn frame_thumbnail(
+    source: &dyn SampleSource,
+    frames: &RecordFrames,
+    index: usize,
+    params: &DecoderParams,
+) -> Result<GrayImage> {
+    let bounds = frames
+        .bounds()
+        .get(index)
+        .with_context(|| format!("Frame {index} out of range ({} frames)", frames.bounds().len()))?;
+    let samples = source.read(frames.channel(), bounds.start_sample..bounds.end_sample)?;
+    // Bin-averaging straight to the thumbnail width is the horizontal
+    // anti-alias; rows are filtered down below
+    let thumb_params = DecoderParams {
+        mode: DecoderMode::Grayscale,
+        width: THUMBNAIL_WIDTH,
+        ..*params
+    };
+    let pixels = SstvDecoder::new()
+        .decode(&samples, &thumb_params, source.sample_rate())
+        .with_context(|| format!("Failed to decode frame {index}"))?;
+    let rows = (pixels.len() / THUMBNAIL_WIDTH as usize) as u32;
+    anyhow::ensure!(rows > 0, "Frame {index} decoded no complete lines");
+    let mut image = GrayImage::from_raw(THUMBNAIL_WIDTH, rows, pixels).context("Thumbnail buffer size mismatch")?;
+    let height = (rows as u64 * THUMBNAIL_WIDTH as u64 / params.effective_width() as u64).max(1) as u32;
+    if height < rows {
+        image = image::imageops::resize(&image, THUMBNAIL_WIDTH, height, FilterType::Triangle);
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::ImageBounds;
+    use crate::audio::WavReader;
+    use crate::test_fixtures::{create_test_wav_file, encode_image_to_audio};
+
+    #[test]
+    fn thumbnails_keep_the_decoded_aspect() {
+        const RATE: u32 = 16_000;
+        const LINE_MS: f32 = 8.32;
+        let (width, lines) = (128usize, 256usize);
+        let pixels: Vec<u8> = (0..width * lines).map(|i| ((i / width) % 256) as u8).collect();
+        let audio = encode_image_to_audio(&pixels, width, RATE, LINE_MS);
+        let file = create_test_wav_file(&audio, RATE, 1);
+        let source = WavReader::from_file(file.path()).unwrap();
+        let bounds = ImageBounds {
+            start_sample: 0,
+            end_sample: audio.len(),
+            start_secs: 0.0,
+            end_secs: audio.len() as f64 / RATE as f64,
+            line_count: lines,
+            median_interval_samples: 0.0,
+            confidence: 1.0,
+        };
+        let frames = RecordFrames::from_bounds(WaveformChannel::Left, vec![bounds]);
+        let params = DecoderParams {
+            line_duration_ms: LINE_MS,
+            width: 512,
+            ..DecoderParams::default()
+        };
+
+        let thumbnail = frame_thumbnail(&source, &frames, 0, &params).unwrap();
+        assert_eq!(thumbnail.width(), THUMBNAIL_WIDTH);
+        // ~256 lines at 512 px per line: about half as tall as wide
+        let height = thumbnail.height() as i64;
+        assert!((height - 48).abs() <= 2, "thumbnail height {height}");
+        // The row gradient survives: darker at the top than the bottom
+        assert!(thumbnail.get_pixel(48, 2)[0] < thumbnail.get_pixel(48, height as u32 - 3)[0]);
+        assert!(frame_thumbnail(&source, &frames, 1, &params).is_err());
+
+        // A frame too short for a line still gets a message, so the scan
+        // can finish
+        let stub = ImageBounds {
+            start_sample: 0,
+            end_sample: 10,
+            ..frames.bounds()[0].clone()
+        };
+        let frames = RecordFrames::from_bounds(WaveformChannel::Left, vec![frames.bounds()[0].clone(), stub]);
+        let (tx, rx) = std::sync::mpsc::channel();
+        scan_channel(&source, WaveformChannel::Left, Some(Arc::new(frames)), &params, &tx).unwrap();
+        let messages: Vec<CatalogMsg> = rx.try_iter().collect();
+        assert!(matches!(
+            messages[..],
+            [
+                CatalogMsg::Segmented(..),
+                CatalogMsg::Thumbnail(_, 0, _),
+                CatalogMsg::ThumbnailFailed(_, 1)
+            ]
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+//! Background reference comparison for the compare panel: finds the
+//! references, segments the channel if needed, and scores each reference
+//! in turn (see [`crate::compare`]), streaming results back.
+
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::Instant;
+
+use image::imageops::FilterType;
+use image::GrayImage;
+
+use crate::audio::WaveformChannel;
+use crate::compare::{compare_reference, find_references, Comparison, Orientation, Reference};
+use crate::pipeline::RecordFrames;
+use crate::sample_source::SampleSource;
+use crate::sstv::DecoderParams;
+
+/// Widest side-by-side image kept for display.
+pub const VIEW_WIDTH: u32 = 320;
+
+/// Progress from a [`CompareRun`].
+pub enum CompareMsg {
+    /// References found, in the order results follow.
+    References(Vec<Reference>),
+    /// Frames the references are matched against; tells whether catalog
+    /// indices (and composites) apply.
+    Segmented(Arc<RecordFrames>),
+    /// Result for reference `index`, images reduced to [`VIEW_WIDTH`].
+    Compared(usize, Result<Comparison, String>),
+    /// The run could not start (no references, segmentation failed).
+    Failed(String),
+}
+
+/// A running comparison. Dropping it stops the worker after the reference
+/// in hand.
+pub struct CompareRun {
+    rx: Receiver<CompareMsg>,
+}
+
+impl CompareRun {
+    /// Compare the references in `dir` against `channel` of `source`,
+    /// reusing `frames` when the channel is already segmented.
+    pub fn start(
+        source: Arc<dyn SampleSource>,
+        channel: WaveformChannel,
+        frames: Option<Arc<RecordFrames>>,
+        dir: PathBuf,
+        params: DecoderParams,
+        orientation: Orientation,
+    ) -> Self {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let start = Instant::now();
+            let references = match find_references(&dir) {
+                Ok(references) if references.is_empty() => {
+                    let _ = tx.send(CompareMsg::Failed(format!(
+                        "No reference images named by catalog index in {}",
+                        dir.display()
+                    )));
+                    return;
+                }
+                Ok(references) => references,
+                Err(e) => {
+                    let _ = tx.send(CompareMsg::Failed(format!("{e:#}")));
+                    return;
+                }
+            };
+            if tx.send(CompareMsg::References(references.clone())).is_err() {
+                return;
+            }
+            let frames = match frames {
+                Some(frames) => frames,
+                None => match RecordFrames::segment(source.as_ref(), channel, &params) {
+                    Ok(frames) => Arc::new(frames),
+                    Err(e) => {
+                        let _ = tx.send(CompareMsg::Failed(format!("{e:#}")));
+                        return;
+                    }
+                },
+            };
+            if tx.send(CompareMsg::Segmented(Arc::clone(&frames))).is_err() {
+                return;
+            }
+            for (index, reference) in references.iter().enumerate() {
+                let result = compare_reference(source.as_ref(), &frames, reference, &params, orientation)
+                    .map(|comparison| Comparison {
+                        decoded: for_view(&comparison.decoded),
+                        reference: for_view(&comparison.reference),
+                        ..comparison
+                    })
+                    .map_err(|e| format!("{e:#}"));
+                if tx.send(CompareMsg::Compared(index, result)).is_err() {
+                    // Receiver dropped: cancelled or a new file
+                    return;
+                }
+            }
+            tracing::info!(
+                references = references.len(),
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "Reference comparison completed"
+            );
+        });
+        Self { rx }
+    }
+
+    /// Drain available messages without blocking.
+    pub fn poll(&self) -> Vec<CompareMsg> {
+        self.rx.try_iter().collect()
+    }
+}
+
+/// `image` reduced to at most [`VIEW_WIDTH`] wide.
+fn for_view(image: &GrayImage) -> GrayImage {
+    if image.width() <= VIEW_WIDTH {
+        return image.clone();
+    }
+    let height = ((image.height() as u64 * VIEW_WIDTH as u64) / image.width() as u64).max(1) as u32;
+    image::imageops::resize(image, VIEW_WIDTH, height, FilterType::Triangle)
+}
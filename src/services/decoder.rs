@@ -3,8 +3,12 @@ use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
-use crate::pipeline::{DecodingPipeline, PipelineResult};
+use crate::audio::WaveformChannel;
+use crate::backend;
+use crate::pipeline::{DecodingPipeline, PipelineResult, RecordFrames};
+use crate::sample_source::SampleSource;
 use crate::sstv::DecoderParams;
+use crate::streaming::{DecodedLine, StreamingDecoder};
 
 /// Owns the background decode worker: channels, request ids, queue depth
 /// accounting, health monitoring, and restart. `VoyagerApp` talks to this
@@ -36,14 +40,18 @@ impl DecodeOrchestrator {
     }
 
     /// Enqueue a decode request (non-blocking). Returns false when the queue
-    /// is full or the worker is gone.
+    /// is full or the worker is gone. The worker reads only the decode window
+    /// from `source`, so streamed files are never loaded in full. Backends
+    /// with [`crate::backend::Capabilities::frames`] (frame triplet) decode the frame of
+    /// `frames` at `start_offset` instead of a window.
     #[allow(clippy::too_many_arguments)]
     pub fn request(
         &mut self,
-        samples: Arc<[f32]>,
+        source: Arc<dyn SampleSource>,
+        channel: WaveformChannel,
         start_offset: usize,
         params: DecoderParams,
-        sample_rate: u32,
+        frames: Option<Arc<RecordFrames>>,
         max_queue: usize,
         generation: u64,
     ) -> bool {
@@ -57,10 +65,11 @@ impl DecodeOrchestrator {
         let request = DecodeRequest {
             id: self.next_id,
             generation,
-            samples,
+            source,
+            channel,
             start_offset,
             params,
-            sample_rate,
+            frames,
         };
         self.next_id += 1;
         // Stamp activity when the queue transitions idle -> busy, otherwise a
@@ -162,22 +171,137 @@ impl Drop for DecodeOrchestrator {
     }
 }
 
-/// Request to decode audio samples in background thread.
+/// Live decode for backends with
+/// [`crate::backend::Capabilities::streaming`]: a worker feeding the audio
+/// between successive playhead positions through one [`StreamingDecoder`],
+/// so each interval decodes only the new audio and the image grows line by
+/// line. A session covers one continuous stretch of playback with fixed
+/// settings; the caller starts a new one on a seek, input or settings
+/// change. Dropping it stops the worker.
+pub struct LiveDecodeSession {
+    tx: Sender<usize>,
+    rx: Receiver<LiveDecodeResult>,
+    generation: u64,
+    params: DecoderParams,
+    /// Last position requested, which the worker decodes up to.
+    requested: usize,
+}
+
+/// Lines completed by one [`LiveDecodeSession::advance`].
 #[derive(Debug)]
+pub struct LiveDecodeResult {
+    /// Completed lines, `start` as an absolute sample position in the source
+    pub lines: Vec<DecodedLine>,
+    /// Time taken to read and decode the new audio
+    pub decode_duration: Duration,
+    /// Error message if the read failed
+    pub error: Option<String>,
+}
+
+impl LiveDecodeSession {
+    /// Start decoding `channel` of `source` from sample `start`.
+    ///
+    /// # Errors
+    ///
+    /// Invalid `params`; see [`StreamingDecoder::new`].
+    pub fn start(
+        source: Arc<dyn SampleSource>,
+        channel: WaveformChannel,
+        start: usize,
+        params: DecoderParams,
+        generation: u64,
+    ) -> crate::error::Result<Self> {
+        let mut decoder = StreamingDecoder::new(&params, source.sample_rate())?;
+        let (tx, request_rx) = std::sync::mpsc::channel::<usize>();
+        let (result_tx, rx) = std::sync::mpsc::channel::<LiveDecodeResult>();
+        thread::spawn(move || {
+            let mut fed = start;
+            while let Ok(target) = request_rx.recv() {
+                // Catch up to the latest playhead in one read.
+                let target = request_rx.try_iter().fold(target, usize::max).min(source.len());
+                if target <= fed {
+                    continue;
+                }
+                let start_time = Instant::now();
+                let result = match source.read(channel, fed..target) {
+                    Ok(samples) => {
+                        fed = target;
+                        let mut lines = decoder.push(&samples);
+                        for line in &mut lines {
+                            line.start += start;
+                        }
+                        LiveDecodeResult {
+                            lines,
+                            decode_duration: start_time.elapsed(),
+                            error: None,
+                        }
+                    }
+                    Err(e) => LiveDecodeResult {
+                        lines: Vec::new(),
+                        decode_duration: start_time.elapsed(),
+                        error: Some(e.to_string()),
+                    },
+                };
+                if result_tx.send(result).is_err() {
+                    break;
+                }
+            }
+            tracing::debug!("Live decode session ended");
+        });
+        Ok(Self {
+            tx,
+            rx,
+            generation,
+            params,
+            requested: start,
+        })
+    }
+
+    /// Input-state generation the session was started under.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn params(&self) -> &DecoderParams {
+        &self.params
+    }
+
+    /// Whether playback at `position` carries on from this session: not
+    /// behind the last request, and at most `max_gap` samples past it.
+    pub fn continues(&self, position: usize, max_gap: usize) -> bool {
+        position >= self.requested && position - self.requested <= max_gap
+    }
+
+    /// Decode up to sample `position` (non-blocking). Returns false when
+    /// the worker is gone.
+    pub fn advance(&mut self, position: usize) -> bool {
+        self.requested = self.requested.max(position);
+        self.tx.send(position).is_ok()
+    }
+
+    /// Drain all available results without blocking.
+    pub fn poll(&self) -> Vec<LiveDecodeResult> {
+        self.rx.try_iter().collect()
+    }
+}
+
+/// Request to decode audio samples in background thread.
 pub struct DecodeRequest {
     /// Unique request ID for matching results to requests
     pub id: u64,
     /// Input-state generation (bumped on file load / channel switch). Results
     /// from an older generation are stale and must not be displayed.
     pub generation: u64,
-    /// Shared audio buffer (Arc enables zero-copy sharing with worker thread)
-    pub samples: Arc<[f32]>,
+    /// Shared sample source; the worker reads the decode window from it
+    pub source: Arc<dyn SampleSource>,
+    /// Channel to decode
+    pub channel: WaveformChannel,
     /// Starting sample position for decode window
     pub start_offset: usize,
     /// Decoder parameters (line duration, threshold)
     pub params: DecoderParams,
-    /// Sample rate in Hz (needed for samples-per-line calculation)
-    pub sample_rate: u32,
+    /// Segmented record for frame-decoding backends
+    pub frames: Option<Arc<RecordFrames>>,
 }
 
 /// Result from background decoding operation.
@@ -210,19 +334,13 @@ pub fn spawn_decode_worker() -> (Sender<DecodeRequest>, Receiver<DecodeResult>,
             let start_time = Instant::now();
             tracing::debug!("Starting decode for request {}", request.id);
 
-            // Handle slicing here since DecodingPipeline expects a slice
-            // We need to calculate the slice based on start_offset and decode_window_secs
-            let window_duration_secs = request.params.decode_window_secs;
-            let window_samples = (window_duration_secs * request.sample_rate as f64) as usize;
-            let end_offset = request.start_offset.saturating_add(window_samples).min(request.samples.len());
-
-            let samples_slice = if request.start_offset < request.samples.len() {
-                &request.samples[request.start_offset..end_offset]
+            let decoded = if backend::for_mode(request.params.mode).info().capabilities.frames {
+                decode_frame(&pipeline, &request)
             } else {
-                &[]
+                decode_window(&pipeline, &request)
             };
 
-            let result = match pipeline.process(samples_slice, &request.params, request.sample_rate) {
+            let result = match decoded {
                 Ok(pipeline_result) => {
                     tracing::debug!("Decode successful for request {}", request.id);
                     DecodeResult {
@@ -256,3 +374,27 @@ pub fn spawn_decode_worker() -> (Sender<DecodeRequest>, Receiver<DecodeResult>,
 
     (request_tx, result_rx, handle)
 }
+
+/// Read just the decode window; for resident sources this is a copy of a few
+/// seconds, for streamed ones a single ranged read.
+fn decode_window(pipeline: &DecodingPipeline, request: &DecodeRequest) -> anyhow::Result<PipelineResult> {
+    let sample_rate = request.source.sample_rate();
+    let window_samples = (request.params.window_secs() * sample_rate as f64) as usize;
+    let samples = request.source.read(
+        request.channel,
+        request.start_offset..request.start_offset.saturating_add(window_samples),
+    )?;
+    Ok(pipeline
+        .process(&samples, &request.params, sample_rate)?
+        .offset_lines(request.start_offset))
+}
+
+/// Decode the whole frame under the request position, with its color
+/// siblings when it belongs to a triplet.
+fn decode_frame(pipeline: &DecodingPipeline, request: &DecodeRequest) -> anyhow::Result<PipelineResult> {
+    let frames = request
+        .frames
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("Record is still being segmented into frames"))?;
+    pipeline.process_frame_at(request.source.as_ref(), frames, request.start_offset, &request.params)
+}
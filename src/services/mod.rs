@@ -1,4 +1,6 @@
 pub mod audio;
 pub mod batch;
+pub mod catalog;
+pub mod compare;
 pub mod decoder;
 pub mod playback;
@@ -1,28 +1,53 @@
 use std::f32::consts::PI;
+use std::ops::Range;
 
 use realfft::{RealFftPlanner, RealToComplex};
 
-use crate::analysis::sync::{interval_summary, track_line_syncs, SyncParams};
+use crate::analysis::content::ContentMask;
+use crate::analysis::segment::SegmentImagesParams;
+use crate::analysis::speed::SPEED_FACTOR_RANGE;
+use crate::analysis::sync::{interval_summary, track_line_syncs_with_lock, LineSync, SyncParams, SyncTrack};
+use crate::analysis::timebase::{fit_time_base, WowFlutter};
+use crate::audio::WaveformChannel;
 use crate::error::{DecoderError, Result, VoyagerError};
+use crate::fm_sstv::{SstvMode, VIS_HEADER_SECS};
+use crate::sample_source::{read_blocks, SampleSource};
 
 /// Calibration tone frequency in Hz. Long ~1200 Hz tone regions precede image
 /// sections on the record; this drives navigation, not per-line decoding.
 const TARGET_FREQ_HZ: f32 = 1200.0;
 /// FFT chunk size for frequency analysis
 const CHUNK_SIZE: usize = 2048;
+/// Block length for [`SstvDecoder::find_tone_regions_in`], in seconds.
+const TONE_SCAN_BLOCK_SECS: f64 = 60.0;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum DecoderMode {
     Grayscale,
+    /// Legacy: successive scan lines taken as R, G, B.
     PseudoColor,
+    /// Voyager color: the whole frame at the decode position plus its
+    /// catalog siblings, composited as R/G/B planes. Needs the channel
+    /// segmented into frames; see [`crate::pipeline::RecordFrames`].
+    FrameTriplet,
+    /// Amateur FM SSTV (Martin, Scottie, Robot, PD), decoded by
+    /// [`crate::fm_sstv::FmSstvDecoder`] rather than this module.
+    Sstv,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct DecoderParams {
     /// Nominal scan-line duration in ms. With `sync_lock` this seeds the sync
     /// search and serves as the fallback slicing period; the actual per-line
     /// timing comes from the detected sync positions.
     pub line_duration_ms: f32,
+    /// Playback speed of the source relative to the record: 2.0 for a rip
+    /// running at double speed. Lines in the file last
+    /// `line_duration_ms / speed_factor`; see
+    /// [`crate::analysis::estimate_speed`].
+    pub speed_factor: f32,
     /// Invert brightness polarity. The record rips differ in sign relative to
     /// the cover instructions, so this is empirical per source.
     pub invert: bool,
@@ -30,6 +55,11 @@ pub struct DecoderParams {
     pub gamma: f32,
     /// Align lines to detected sync edges instead of fixed-period slicing.
     pub sync_lock: bool,
+    /// With `sync_lock`, take out wow and flutter before slicing: the audio
+    /// is resampled onto a time base fitted to its sync positions (see
+    /// [`crate::analysis::timebase`]). Whole-window decodes only; the
+    /// streaming decoder slices the audio as it arrives.
+    pub time_base: bool,
     /// Live-decode window length in seconds (used by the decode worker to
     /// slice around the playback position, not by `decode` itself).
     pub decode_window_secs: f64,
@@ -37,6 +67,14 @@ pub struct DecoderParams {
     /// Image width in pixels. Default is 512, which matches the Voyager spacecraft
     /// imaging system's standard frame width used for transmitting planetary imagery.
     pub width: u32,
+    /// Sync detector tuning. Its `expected_line_ms` is ignored; see
+    /// [`Self::sync_params`].
+    pub sync: SyncParams,
+    /// Frame segmentation tuning for [`DecoderMode::FrameTriplet`]. Its
+    /// `sync` is ignored; see [`Self::segment_params`].
+    pub segment: SegmentImagesParams,
+    /// Mode for [`DecoderMode::Sstv`]; `None` reads it from the VIS header.
+    pub sstv_mode: Option<SstvMode>,
 }
 
 impl DecoderParams {
@@ -46,18 +84,63 @@ impl DecoderParams {
     pub fn effective_width(&self) -> usize {
         (self.width as usize).max(1)
     }
+
+    /// Line duration as it appears in the source, after the speed factor.
+    /// Everything that measures time in the file (sync search, segmentation,
+    /// fixed-period slicing) uses this rather than `line_duration_ms`.
+    pub fn effective_line_ms(&self) -> f32 {
+        self.line_duration_ms / self.speed_factor
+    }
+
+    /// Sync detector parameters at the effective line duration.
+    pub fn sync_params(&self) -> SyncParams {
+        SyncParams {
+            expected_line_ms: self.effective_line_ms(),
+            ..self.sync
+        }
+    }
+
+    /// Decode window length in seconds. In [`DecoderMode::Sstv`] it stretches
+    /// to a whole transmission (VIS header included) of the selected mode,
+    /// or of the longest one when the mode comes from the header.
+    pub fn window_secs(&self) -> f64 {
+        if self.mode != DecoderMode::Sstv {
+            return self.decode_window_secs;
+        }
+        let picture = match self.sstv_mode {
+            Some(mode) => mode.spec().duration_secs(),
+            None => SstvMode::ALL
+                .iter()
+                .map(|mode| mode.spec().duration_secs())
+                .fold(0.0, f64::max),
+        };
+        self.decode_window_secs.max(picture + VIS_HEADER_SECS)
+    }
+
+    /// Segmentation parameters, sharing [`Self::sync_params`].
+    pub fn segment_params(&self) -> SegmentImagesParams {
+        SegmentImagesParams {
+            sync: self.sync_params(),
+            ..self.segment
+        }
+    }
 }
 
 impl Default for DecoderParams {
     fn default() -> Self {
         Self {
             line_duration_ms: 8.32,
+            speed_factor: 1.0,
             invert: false,
             gamma: 1.0,
             sync_lock: true,
+            time_base: false,
             decode_window_secs: 2.0,
             mode: DecoderMode::Grayscale,
             width: 512,
+            sync: SyncParams::default(),
+            segment: SegmentImagesParams::default(),
+            sstv_mode: None,
         }
     }
 }
@@ -213,6 +296,40 @@ impl SstvDecoder {
         sync_positions.first().map(|&pos| start_position + pos)
     }
 
+    /// [`Self::find_tone_regions`] over one channel of a [`SampleSource`],
+    /// scanned in one-minute blocks so streamed files never load in full.
+    ///
+    /// # Errors
+    ///
+    /// Propagates read failures from the source.
+    pub fn find_tone_regions_in(&self, source: &dyn SampleSource, channel: WaveformChannel) -> Result<Vec<usize>> {
+        let block_len = (TONE_SCAN_BLOCK_SECS * source.sample_rate() as f64) as usize;
+        self.find_tone_regions_blocked(source, channel, block_len)
+    }
+
+    fn find_tone_regions_blocked(
+        &self,
+        source: &dyn SampleSource,
+        channel: WaveformChannel,
+        block_len: usize,
+    ) -> Result<Vec<usize>> {
+        let mut positions: Vec<usize> = Vec::new();
+        for block in read_blocks(source, channel, 0..source.len(), block_len, CHUNK_SIZE * 2) {
+            let block = block?;
+            for pos in self.find_tone_regions(&block.samples, source.sample_rate()) {
+                let pos = block.offset + pos;
+                // The chunk stepping grid restarts in every block, so a tone
+                // straddling a boundary can be found from both sides; keep
+                // the same 2-chunk skip the single-buffer scan applies.
+                let fresh = positions.last().is_none_or(|&last| pos >= last + CHUNK_SIZE * 2);
+                if block.owned.contains(&pos) && fresh {
+                    positions.push(pos);
+                }
+            }
+        }
+        Ok(positions)
+    }
+
     /// Decode audio samples into image pixels.
     ///
     /// The record encodes images as baseband slow-scan video: the
@@ -232,7 +349,8 @@ impl SstvDecoder {
     /// # Returns
     ///
     /// Grayscale pixels (0-255) in row-major order, `params.width` pixels
-    /// wide (default 512). In PseudoColor mode, returns RGB pixels (3 bytes per pixel).
+    /// wide (default 512), whatever `params.mode`: color modes are built on
+    /// top of this by their backends ([`crate::backend`]).
     ///
     /// # Errors
     ///
@@ -247,50 +365,7 @@ impl SstvDecoder {
         // --- Normalization ---
         // Percentile contrast stretch is robust to sync-spike outliers.
         let (lo, hi) = percentile_bounds(&levels, 0.01, 0.99);
-        let mut image = normalize_levels(&levels, lo, hi, params.invert, params.gamma);
-
-        // Post-process for PseudoColor mode
-        if params.mode == DecoderMode::PseudoColor {
-            // Group 3 lines (R, G, B) into one color line
-            // Current image buffer contains grayscale pixels (0 or 255)
-            // We need to transform this into RGB pixels
-            // Format: [R, G, B, R, G, B, ...]
-
-            let num_pixels = image.len();
-            let num_lines = num_pixels / width;
-            let num_color_lines = num_lines / 3;
-
-            // Warn if we're discarding incomplete color lines (not divisible by 3)
-            if !num_lines.is_multiple_of(3) {
-                tracing::warn!(
-                    num_lines,
-                    discarded_lines = num_lines % 3,
-                    "PseudoColor: incomplete color lines detected, truncating to {} complete color lines",
-                    num_color_lines
-                );
-            }
-
-            let mut color_image = Vec::with_capacity(num_color_lines * width * 3);
-
-            for line_idx in 0..num_color_lines {
-                let r_start = (line_idx * 3) * width;
-                let g_start = (line_idx * 3 + 1) * width;
-                let b_start = (line_idx * 3 + 2) * width;
-
-                for x in 0..width {
-                    let r = image[r_start + x];
-                    let g = image[g_start + x];
-                    let b = image[b_start + x];
-
-                    color_image.push(r);
-                    color_image.push(g);
-                    color_image.push(b);
-                }
-            }
-
-            // Replace image with color image
-            image = color_image;
-        }
+        let image = normalize_levels(&levels, lo, hi, params.invert, params.gamma);
 
         tracing::debug!(lines_decoded, pixels = image.len(), "Decode operation completed");
 
@@ -303,18 +378,13 @@ impl SstvDecoder {
     /// where stretching each frame by its own bounds would skew the color
     /// balance.
     pub fn decode_levels(&self, samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Result<Vec<f32>> {
-        // Validate parameters
-        if !(1.0..=100.0).contains(&params.line_duration_ms) {
-            return Err(VoyagerError::Decoder(DecoderError::InvalidLineDuration {
-                duration_ms: params.line_duration_ms,
-            }));
-        }
+        Ok(self.decode_lines(samples, params, sample_rate)?.levels)
+    }
 
-        if !(0.1..=10.0).contains(&params.gamma) {
-            return Err(VoyagerError::Decoder(DecoderError::InvalidParams {
-                reason: format!("gamma {} out of range 0.1-10.0", params.gamma),
-            }));
-        }
+    /// [`Self::decode_levels`] plus per-line sync-lock status, for content
+    /// masking ([`crate::analysis::content`]).
+    pub fn decode_lines(&self, samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Result<DecodedLines> {
+        let samples_per_line = validate_params(params, sample_rate)?;
 
         // Validate samples
         if samples.is_empty() {
@@ -324,16 +394,6 @@ impl SstvDecoder {
             }));
         }
 
-        let samples_per_line = (params.line_duration_ms / 1000.0 * sample_rate as f32).round() as usize;
-        if samples_per_line == 0 {
-            return Err(VoyagerError::Decoder(DecoderError::InvalidParams {
-                reason: format!(
-                    "Calculated samples_per_line is 0 (line_duration={}, sample_rate={})",
-                    params.line_duration_ms, sample_rate
-                ),
-            }));
-        }
-
         if samples.len() < samples_per_line {
             return Err(VoyagerError::Decoder(DecoderError::InsufficientSamples {
                 needed: samples_per_line,
@@ -348,51 +408,95 @@ impl SstvDecoder {
         // Sync-locked when the detector finds a consistent line cadence;
         // otherwise fixed-period slicing at the nominal duration. Re-anchoring
         // at every detected sync keeps timing error from accumulating (slant).
-        let line_ranges = self.segment_lines(samples, params, sample_rate, samples_per_line, max_lines);
+        let tracked = params
+            .sync_lock
+            .then(|| track_line_syncs_with_lock(samples, sample_rate, &params.sync_params()));
+        // With time-base correction the lines are sliced from the corrected
+        // audio, where each one lasts one period. The track carries over
+        // rather than being redone on the resampled audio, whose sync dips
+        // are no longer exactly flat for the edge search to settle on.
+        let time_base = if params.time_base {
+            let time_base = tracked.as_ref().and_then(fit_time_base);
+            if time_base.is_none() {
+                tracing::debug!("No usable sync track for time-base correction; decoding uncorrected");
+            }
+            time_base
+        } else {
+            None
+        };
+        let corrected = time_base.as_ref().map(|time_base| time_base.resample(samples));
+        let tracked = match (&time_base, tracked) {
+            (Some(time_base), Some(track)) => Some(time_base.carry_track(&track)),
+            (_, tracked) => tracked,
+        };
+        let source = corrected.as_deref().unwrap_or(samples);
+        let (line_ranges, sync, skipped) = self.segment_lines(source, tracked, sample_rate, samples_per_line, max_lines);
+        let locked = sync.iter().map(LineSync::is_locked).collect();
 
         // --- Per-line level extraction ---
-        // Resample each line to `width` luminance levels. Bin-averaging on
-        // downsample doubles as the anti-alias filter; linear interpolation
-        // covers the upsample case (e.g. 400 samples/line at 48 kHz -> 512 px).
+        // Resample each line to `width` luminance levels from its sub-sample
+        // bounds. Bin-averaging on downsample doubles as the anti-alias
+        // filter; linear interpolation covers the upsample case (e.g. 400
+        // samples/line at 48 kHz -> 512 px).
         let mut levels: Vec<f32> = Vec::with_capacity(width * line_ranges.len());
-        for range in &line_ranges {
-            let slice = &samples[range.clone()];
-            resample_line(slice, width, &mut levels);
+        for bounds in &line_ranges {
+            resample_span(source, bounds.clone(), width, &mut levels);
         }
-        Ok(levels)
+        // Spans always index `samples`, corrected or not
+        let to_source = |pos: f64| {
+            let pos = time_base.as_ref().map_or(pos, |time_base| time_base.to_source(pos));
+            (pos.round().max(0.0) as usize).min(samples.len())
+        };
+        let spans = line_ranges.iter().map(|r| to_source(r.start)..to_source(r.end)).collect();
+        let flutter = time_base.map(|time_base| time_base.flutter(sample_rate));
+        if let Some(flutter) = &flutter {
+            tracing::debug!(%flutter, "Time-base corrected");
+        }
+        Ok(DecodedLines {
+            levels,
+            locked,
+            sync,
+            spans,
+            skipped,
+            flutter,
+        })
     }
 
-    /// Segment samples into per-line ranges. Prefers sync-locked boundaries;
+    /// Segment samples into per-line ranges. Prefers sync-locked boundaries
+    /// from `tracked` (the buffer's sync track, `None` without sync lock);
     /// falls back to fixed-period slicing when sync structure is absent or
-    /// inconsistent with the nominal line duration.
+    /// inconsistent with the nominal line duration. Line bounds are sample
+    /// positions, fractional where the track timed them finer than a
+    /// sample ([`SyncTrack::starts`]). The second vector holds
+    /// each line's sync diagnostics; fixed-period slices are
+    /// [`SyncState::Fixed`](crate::analysis::SyncState::Fixed). Last comes
+    /// the count of sync intervals skipped as not one line long.
     fn segment_lines(
         &self,
         samples: &[f32],
-        params: &DecoderParams,
+        tracked: Option<SyncTrack>,
         sample_rate: u32,
         samples_per_line: usize,
         max_lines: usize,
-    ) -> Vec<std::ops::Range<usize>> {
-        if params.sync_lock {
-            let sync_params = SyncParams {
-                expected_line_ms: params.line_duration_ms,
-                ..SyncParams::default()
-            };
-            let positions = track_line_syncs(samples, sample_rate, &sync_params);
-            if let Some(summary) = interval_summary(&positions, sample_rate) {
+    ) -> (Vec<Range<f64>>, Vec<LineSync>, usize) {
+        if let Some(tracked) = tracked {
+            let positions = &tracked.positions;
+            if let Some(summary) = interval_summary(positions, sample_rate) {
                 let median = summary.median_samples;
                 let nominal = samples_per_line as f64;
                 // Trust sync lock only when the detected cadence is plausibly
                 // the line cadence the caller asked about.
                 if positions.len() >= 4 && (median - nominal).abs() / nominal < 0.3 {
-                    let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+                    let mut ranges: Vec<Range<f64>> = Vec::new();
+                    let mut sync = Vec::new();
                     let mut skipped = 0usize;
-                    for w in positions.windows(2) {
+                    for (i, w) in positions.windows(2).enumerate() {
                         let interval = (w[1] - w[0]) as f64;
                         // Skip gaps that are not a single line (dropouts, the
                         // inter-image boundary, double-triggers).
                         if interval >= median * 0.7 && interval <= median * 1.3 {
-                            ranges.push(w[0]..w[1]);
+                            ranges.push(tracked.starts[i]..tracked.starts[i + 1]);
+                            sync.push(tracked.line(i));
                             if ranges.len() >= max_lines {
                                 break;
                             }
@@ -400,42 +504,152 @@ impl SstvDecoder {
                             skipped += 1;
                         }
                     }
-                    if skipped > 0 && params.mode == DecoderMode::PseudoColor {
-                        // Dropped lines shift the positional R/G/B grouping for
-                        // everything after the gap; the mode has no way to
-                        // recover phase, so at least say so.
-                        tracing::warn!(
-                            skipped,
-                            "PseudoColor: sync gaps broke line continuity; RGB channel grouping may be shifted"
-                        );
-                    }
                     if !ranges.is_empty() {
                         tracing::debug!(
                             lines = ranges.len(),
                             median_interval = median,
                             "Sync-locked line segmentation"
                         );
-                        return ranges;
+                        return (ranges, sync, skipped);
                     }
                 }
             }
-            tracing::debug!("Sync lock requested but no consistent line cadence found; using fixed-period slicing");
+            match tracked.fallback {
+                Some(reason) => tracing::debug!(%reason, "Sync tracking fell back to raw detections"),
+                None => tracing::debug!("Sync lock requested but no consistent line cadence found; using fixed-period slicing"),
+            }
         }
 
         let mut ranges = Vec::new();
         let mut i = 0;
         while i + samples_per_line <= samples.len() && ranges.len() < max_lines {
-            ranges.push(i..i + samples_per_line);
+            ranges.push(i as f64..(i + samples_per_line) as f64);
             i += samples_per_line;
         }
-        ranges
+        let sync = vec![LineSync::fixed(samples_per_line as f64); ranges.len()];
+        (ranges, sync, 0)
+    }
+}
+
+/// Check the parameters every line-decoding path shares and return the
+/// nominal line length in samples at `sample_rate`.
+///
+/// # Errors
+///
+/// Returns [`DecoderError::InvalidLineDuration`] if line duration is out of range 1-100ms,
+/// [`DecoderError::InvalidParams`] if the speed factor or gamma is out of range or
+/// the line rounds to no samples.
+pub(crate) fn validate_params(params: &DecoderParams, sample_rate: u32) -> Result<usize> {
+    if !(1.0..=100.0).contains(&params.line_duration_ms) {
+        return Err(VoyagerError::Decoder(DecoderError::InvalidLineDuration {
+            duration_ms: params.line_duration_ms,
+        }));
+    }
+
+    if !SPEED_FACTOR_RANGE.contains(&params.speed_factor) {
+        return Err(VoyagerError::Decoder(DecoderError::InvalidParams {
+            reason: format!("speed factor {} out of range 0.25-4.0", params.speed_factor),
+        }));
+    }
+
+    if !(0.1..=10.0).contains(&params.gamma) {
+        return Err(VoyagerError::Decoder(DecoderError::InvalidParams {
+            reason: format!("gamma {} out of range 0.1-10.0", params.gamma),
+        }));
+    }
+
+    let samples_per_line = (params.effective_line_ms() / 1000.0 * sample_rate as f32).round() as usize;
+    if samples_per_line == 0 {
+        return Err(VoyagerError::Decoder(DecoderError::InvalidParams {
+            reason: format!(
+                "Calculated samples_per_line is 0 (line_duration={}, sample_rate={})",
+                params.effective_line_ms(),
+                sample_rate
+            ),
+        }));
+    }
+    Ok(samples_per_line)
+}
+
+/// Raw decoded levels with per-line sync-lock status; see
+/// [`SstvDecoder::decode_lines`].
+#[derive(Debug, Clone, Default)]
+pub struct DecodedLines {
+    /// Row-major luminance levels, `width` per line, before normalization.
+    pub levels: Vec<f32>,
+    /// One flag per decoded line: both ends anchored on a detected sync.
+    /// All false under fixed-period slicing.
+    pub locked: Vec<bool>,
+    /// Sync diagnostics per decoded line; `locked` summarizes them.
+    pub sync: Vec<LineSync>,
+    /// Sample range of each decoded line in the decoded buffer (mapped back
+    /// from the corrected audio under time-base correction).
+    pub spans: Vec<std::ops::Range<usize>>,
+    /// Sync intervals dropped as not one line long (dropouts, boundaries):
+    /// line continuity breaks at each.
+    pub skipped: usize,
+    /// Speed variation taken out by time-base correction
+    /// ([`DecoderParams::time_base`]); `None` when it did not run.
+    pub flutter: Option<WowFlutter>,
+}
+
+/// Resample the line spanning `bounds` of `samples` to `width` luminance
+/// levels, appending to `out`. [`resample_line`] for sub-sample bounds:
+/// when downsampling, each sample (held over `[k, k + 1)`) counts toward a
+/// pixel by the fraction of it the pixel's bin covers, so a line starting
+/// partway into a sample is not rounded to either neighbour; when
+/// upsampling, levels are interpolated at the exact pixel positions.
+pub(crate) fn resample_span(samples: &[f32], bounds: Range<f64>, width: usize, out: &mut Vec<f32>) {
+    let start = bounds.start.max(0.0);
+    let end = bounds.end.min(samples.len() as f64);
+    let len = end - start;
+    if len <= 0.0 || len.is_nan() {
+        out.extend(std::iter::repeat_n(0.0, width));
+        return;
+    }
+    if len >= width as f64 {
+        // Running integral over the samples the line touches
+        let first = start.floor() as usize;
+        let last = (end.ceil() as usize).min(samples.len());
+        let mut prefix = Vec::with_capacity(last - first + 1);
+        prefix.push(0.0f64);
+        for &v in &samples[first..last] {
+            prefix.push(prefix[prefix.len() - 1] + v as f64);
+        }
+        let integral = |t: f64| {
+            let k = ((t.floor() as usize).max(first) - first).min(last - first);
+            let partial = if first + k < last {
+                (t - (first + k) as f64) * samples[first + k] as f64
+            } else {
+                0.0
+            };
+            prefix[k] + partial
+        };
+        let step = len / width as f64;
+        for x in 0..width {
+            let (a, b) = (start + x as f64 * step, start + (x + 1) as f64 * step);
+            out.push(((integral(b) - integral(a)) / (b - a)) as f32);
+        }
+    } else {
+        let n = samples.len();
+        let step = if width > 1 {
+            (len - 1.0).max(0.0) / (width as f64 - 1.0)
+        } else {
+            0.0
+        };
+        for x in 0..width {
+            let pos = (start + x as f64 * step).min((n - 1) as f64);
+            let k = pos.floor() as usize;
+            let fract = (pos - k as f64) as f32;
+            out.push(samples[k] * (1.0 - fract) + samples[(k + 1).min(n - 1)] * fract);
+        }
     }
 }
 
 /// Resample one line of samples to `width` luminance levels, appending to
 /// `out`. Bin-averaging when downsampling (anti-aliased), linear
 /// interpolation when upsampling.
-fn resample_line(slice: &[f32], width: usize, out: &mut Vec<f32>) {
+pub(crate) fn resample_line(slice: &[f32], width: usize, out: &mut Vec<f32>) {
     let n = slice.len();
     if n == 0 {
         out.extend(std::iter::repeat_n(0.0, width));
@@ -511,11 +725,23 @@ pub fn percentile_bounds(values: &[f32], lo_pct: f64, hi_pct: f64) -> (f32, f32)
     (lo, hi)
 }
 
+/// [`percentile_bounds`] over the content pixels of a `width`-wide plane,
+/// so leader bands and sync residue can't drag the stretch. Falls back to
+/// every value when the mask selects nothing.
+pub fn percentile_bounds_masked(levels: &[f32], width: usize, mask: &ContentMask, lo_pct: f64, hi_pct: f64) -> (f32, f32) {
+    let content: Vec<f32> = mask.values(levels, width).collect();
+    if content.is_empty() {
+        return percentile_bounds(levels, lo_pct, hi_pct);
+    }
+    percentile_bounds(&content, lo_pct, hi_pct)
+}
+
 #[cfg(test)]
 mod tests {
     use std::f32::consts::PI;
 
     use super::*;
+    use crate::analysis::SyncState;
 
     fn generate_test_signal(frequency: f32, duration_secs: f32, sample_rate: u32) -> Vec<f32> {
         let num_samples = (duration_secs * sample_rate as f32) as usize;
@@ -720,6 +946,42 @@ mod tests {
         assert!(inverted[w / 4] > inverted[3 * w / 4]);
     }
 
+    #[test]
+    fn test_decode_speed_factor_compensates_fast_rip() {
+        let decoder = SstvDecoder::new();
+        let sample_rate = 48_000;
+        let width = 64usize;
+        let n_lines = 120usize;
+        let pixels = crate::test_fixtures::textured_plane(width, n_lines, |x, y| (x, y));
+        let nominal = DecoderParams {
+            width: width as u32,
+            ..DecoderParams::default()
+        };
+        // A 2x rip: the same image with every line half as long.
+        let audio = crate::test_fixtures::encode_image_to_audio(&pixels, width, sample_rate, nominal.line_duration_ms / 2.0);
+
+        let compensated = DecoderParams {
+            speed_factor: 2.0,
+            ..nominal
+        };
+        assert!((compensated.effective_line_ms() - 4.16).abs() < 1e-4);
+        let lines = decoder.decode_lines(&audio, &compensated, sample_rate).unwrap();
+        let height = lines.levels.len() / width;
+        assert!((n_lines - 2..=n_lines).contains(&height), "height {height}");
+        assert!(lines.locked.iter().filter(|&&l| l).count() > n_lines * 9 / 10);
+
+        // Uncompensated, the nominal sync spacing skips every other sync and
+        // each row folds two lines.
+        let folded = decoder.decode_lines(&audio, &nominal, sample_rate).unwrap();
+        assert!(folded.levels.len() / width <= n_lines / 2 + 1);
+
+        let invalid = DecoderParams {
+            speed_factor: 8.0,
+            ..nominal
+        };
+        assert!(decoder.decode_lines(&audio, &invalid, sample_rate).is_err());
+    }
+
     #[test]
     fn test_decode_honors_non_default_width() {
         let decoder = SstvDecoder::new();
@@ -742,39 +1004,6 @@ mod tests {
         assert_eq!(result.len() % params.width as usize, 0);
     }
 
-    #[test]
-    fn test_decode_pseudo_color_rgb_packing() {
-        let decoder = SstvDecoder::new();
-        let params = DecoderParams {
-            line_duration_ms: 1.0,
-            mode: DecoderMode::PseudoColor,
-            ..Default::default()
-        };
-
-        let sample_rate = 1000;
-        let samples_per_line = (params.line_duration_ms / 1000.0 * sample_rate as f32).round() as usize;
-        assert_eq!(samples_per_line, 1);
-
-        // Three lines: R=high, G=low, B=high -> expect (255,0,255) for every pixel.
-        let samples = vec![
-            1.0, // R line
-            0.0, // G line
-            1.0, // B line
-        ];
-
-        let result = decoder
-            .decode(&samples, &params, sample_rate)
-            .expect("PseudoColor decode should succeed");
-
-        // One color line of width 512 => 512 * 3 bytes
-        assert_eq!(result.len(), 512 * 3);
-
-        // All pixels should match the expected RGB triplet
-        for chunk in result.chunks_exact(3) {
-            assert_eq!(chunk, [255, 0, 255]);
-        }
-    }
-
     #[test]
     fn test_find_tone_regions() {
         let decoder = SstvDecoder::new();
@@ -798,6 +1027,48 @@ mod tests {
         assert!(positions[0] < sync_signal.len());
     }
 
+    #[test]
+    fn test_find_tone_regions_in_blocks() {
+        use std::sync::Arc;
+
+        let decoder = SstvDecoder::new();
+        let sample_rate = 44100;
+        let sync_signal = generate_test_signal(TARGET_FREQ_HZ, 0.1, sample_rate);
+        let noise_signal = generate_noise(0.3, sample_rate);
+        let mut combined = Vec::new();
+        for _ in 0..4 {
+            combined.extend(&noise_signal);
+            combined.extend(&sync_signal);
+        }
+        let samples: Arc<[f32]> = combined.into();
+        let reader = crate::audio::WavReader {
+            channel_data: vec![samples.clone()],
+            sample_rate,
+            channels: 1,
+            format: Default::default(),
+        };
+
+        // Blocks far shorter than one noise+tone period, so every tone sits
+        // near a block boundary at some point of the scan.
+        let blocked = decoder
+            .find_tone_regions_blocked(&reader, WaveformChannel::Left, 5000)
+            .expect("in-memory reads cannot fail");
+        let period = noise_signal.len() + sync_signal.len();
+        let onsets: Vec<usize> = (0..4).map(|k| k * period + noise_signal.len()).collect();
+        for onset in &onsets {
+            assert!(
+                blocked.iter().any(|p| p.abs_diff(*onset) <= CHUNK_SIZE),
+                "tone at {onset} missed: {blocked:?}"
+            );
+        }
+        for p in &blocked {
+            assert!(
+                onsets.iter().any(|o| p.abs_diff(*o) <= sync_signal.len()),
+                "spurious detection at {p}: {blocked:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_find_next_tone_region() {
         let decoder = SstvDecoder::new();
@@ -887,6 +1158,41 @@ mod tests {
         assert!((out[0] - 0.25).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_resample_span_whole_sample_bounds_match_resample_line() {
+        let samples: Vec<f32> = (0..1000).map(|i| ((i * 37) % 101) as f32 / 100.0).collect();
+        for (start, end, width) in [(100, 500, 100), (3, 403, 400), (10, 50, 64)] {
+            let (mut span, mut line) = (Vec::new(), Vec::new());
+            resample_span(&samples, start as f64..end as f64, width, &mut span);
+            resample_line(&samples[start..end], width, &mut line);
+            for (a, b) in span.iter().zip(&line) {
+                assert!((a - b).abs() < 1e-5, "{start}..{end} @ {width}: {a} vs {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_resample_span_honors_fractional_bounds() {
+        // On a ramp of one per sample, a quarter-sample later start raises
+        // every pixel by a quarter
+        let ramp: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+        for width in [100, 512] {
+            let (mut whole, mut shifted) = (Vec::new(), Vec::new());
+            resample_span(&ramp, 100.0..500.0, width, &mut whole);
+            resample_span(&ramp, 100.25..500.25, width, &mut shifted);
+            for (a, b) in whole.iter().zip(&shifted) {
+                assert!((b - a - 0.25).abs() < 1e-3, "width {width}: {a} -> {b}");
+            }
+        }
+        // Bounds past the buffer clamp to it; an empty span is zeros
+        let mut out = Vec::new();
+        resample_span(&ramp, 990.5..1010.0, 4, &mut out);
+        resample_span(&ramp, 20.0..20.0, 2, &mut out);
+        assert_eq!(out.len(), 6);
+        assert!(out[..4].iter().all(|v| (990.0..1000.0).contains(v)));
+        assert_eq!(out[4..], [0.0, 0.0]);
+    }
+
     // ---- percentile_bounds: robustness to non-finite values ----
 
     #[test]
@@ -921,8 +1227,34 @@ mod tests {
         assert_eq!(percentile_bounds(&values, 0.01, 0.99), (0.0, 1.0));
     }
 
+    #[test]
+    fn test_percentile_bounds_masked_ignores_junk_rows() {
+        // Two picture rows in 0.4..0.6 between saturated leader rows.
+        let mut levels = vec![1.0; 4 * 4];
+        levels[4..12].copy_from_slice(&[0.4, 0.45, 0.5, 0.6, 0.4, 0.45, 0.5, 0.6]);
+        let mask = ContentMask {
+            rows: vec![false, true, true, false],
+            columns: 0..4,
+        };
+        assert_eq!(percentile_bounds_masked(&levels, 4, &mask, 0.0, 1.0), (0.4, 0.6));
+        assert_eq!(percentile_bounds(&levels, 0.0, 1.0), (0.4, 1.0));
+
+        let empty = ContentMask {
+            rows: vec![false; 4],
+            columns: 0..4,
+        };
+        assert_eq!(percentile_bounds_masked(&levels, 4, &empty, 0.0, 1.0), (0.4, 1.0));
+    }
+
     // ---- segment_lines: sync-locked vs fixed-period fallback ----
 
+    /// The track `decode_lines` hands to `segment_lines`.
+    fn tracked(samples: &[f32], params: &DecoderParams, sample_rate: u32) -> Option<SyncTrack> {
+        params
+            .sync_lock
+            .then(|| track_line_syncs_with_lock(samples, sample_rate, &params.sync_params()))
+    }
+
     #[test]
     fn test_segment_lines_sync_locked() {
         let decoder = SstvDecoder::new();
@@ -940,7 +1272,14 @@ mod tests {
         let audio = crate::test_fixtures::encode_image_to_audio(&pixels, width, sample_rate, params.line_duration_ms);
         let samples_per_line = (params.line_duration_ms / 1000.0 * sample_rate as f32).round() as usize;
 
-        let ranges = decoder.segment_lines(&audio, &params, sample_rate, samples_per_line, 1000);
+        let (ranges, sync, _) = decoder.segment_lines(
+            &audio,
+            tracked(&audio, &params, sample_rate),
+            sample_rate,
+            samples_per_line,
+            1000,
+        );
+        let locked: Vec<bool> = sync.iter().map(LineSync::is_locked).collect();
 
         // The sync-locked path must engage (>= 4 detected line syncs) and yield a
         // line per detected interval, near the nominal cadence.
@@ -958,11 +1297,17 @@ mod tests {
         // falling through to fixed-period slicing: the fixed-period path emits
         // ranges all exactly `samples_per_line` wide, whereas sync-locked
         // follows the encoded (drifting) cadence, so some ranges differ.
-        let all_nominal = ranges.iter().all(|r| r.end - r.start == samples_per_line);
+        let all_nominal = ranges.iter().all(|r| r.end - r.start == samples_per_line as f64);
         assert!(
             !all_nominal,
             "segmentation matches fixed-period fallback exactly; sync lock did not engage"
         );
+        assert_eq!(locked.len(), ranges.len());
+        assert!(
+            locked.iter().filter(|&&l| l).count() * 2 > locked.len(),
+            "clean syncs mostly coasted: {locked:?}"
+        );
+        assert!(sync.iter().all(|line| line.state != SyncState::Fixed && line.period > 0.0));
     }
 
     #[test]
@@ -975,12 +1320,21 @@ mod tests {
         // the decoder falls back to evenly-spaced fixed-period slicing.
         let samples = vec![0.5f32; samples_per_line * 5];
 
-        let ranges = decoder.segment_lines(&samples, &params, sample_rate, samples_per_line, 1000);
+        let (ranges, sync, _) = decoder.segment_lines(
+            &samples,
+            tracked(&samples, &params, sample_rate),
+            sample_rate,
+            samples_per_line,
+            1000,
+        );
+        let locked: Vec<bool> = sync.iter().map(LineSync::is_locked).collect();
 
         assert_eq!(ranges.len(), 5);
+        assert_eq!(locked, vec![false; 5], "fixed-period slices are never locked");
+        assert!(sync.iter().all(|line| line.state == SyncState::Fixed));
         for (i, r) in ranges.iter().enumerate() {
-            assert_eq!(r.start, i * samples_per_line);
-            assert_eq!(r.end - r.start, samples_per_line);
+            assert_eq!(r.start, (i * samples_per_line) as f64);
+            assert_eq!(r.end - r.start, samples_per_line as f64);
         }
     }
 
@@ -997,8 +1351,15 @@ mod tests {
         let samples_per_line = 400usize;
         let samples = vec![0.5f32; samples_per_line * 10];
 
-        let ranges = decoder.segment_lines(&samples, &params, sample_rate, samples_per_line, 3);
+        let (ranges, sync, _) = decoder.segment_lines(
+            &samples,
+            tracked(&samples, &params, sample_rate),
+            sample_rate,
+            samples_per_line,
+            3,
+        );
         assert_eq!(ranges.len(), 3);
+        assert_eq!(sync.len(), 3);
     }
 
     // Property-based tests using proptest
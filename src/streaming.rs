@@ -0,0 +1,455 @@
+//! Incremental scan-line decoding.
+//!
+//! [`crate::sstv::SstvDecoder::decode_lines`] takes a whole window and
+//! tracks its syncs from scratch, which is right for a still decode but
+//! wasteful for playback, where consecutive windows overlap almost
+//! entirely. [`StreamingDecoder`] is fed audio in chunks of any size and
+//! hands back each scan line as soon as the sync after it is found, keeping
+//! only the audio of the line in progress: live playback grows the image
+//! line by line, and stdin or multi-hour files decode in constant memory.
+//!
+//! Sync tracking is the same predictive lock as
+//! [`crate::analysis::track_line_syncs_with_lock`], seeded from the first
+//! [`ACQUIRE_LINES`] lines instead of the whole buffer. Where no line
+//! cadence is found the decoder slices at the nominal period, as the
+//! window decoder does, and keeps looking.
+
+use std::ops::Range;
+
+use crate::analysis::sync::{detect_line_syncs, interval_summary, LineSync, SyncLock, SyncParams, SyncState};
+use crate::error::Result;
+use crate::pipeline::PipelineResult;
+use crate::sstv::{normalize_levels, percentile_bounds, resample_span, validate_params, DecoderMode, DecoderParams};
+
+/// Lines of audio buffered to find the line cadence before tracking.
+pub const ACQUIRE_LINES: usize = 64;
+/// Consecutive coasted lines after which the lock counts as lost and the
+/// cadence is acquired afresh. Long enough to coast through a dark band.
+const LOST_LOCK_LINES: usize = 128;
+
+/// One completed scan line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedLine {
+    /// Stream position of the line's first sample, counting from the first
+    /// sample pushed.
+    pub start: usize,
+    /// Line length in samples.
+    pub len: usize,
+    /// `width` luminance levels, before normalization.
+    pub levels: Vec<f32>,
+    /// How the line's ends were placed; [`SyncState::Fixed`] under
+    /// fixed-period slicing.
+    pub sync: LineSync,
+}
+
+impl DecodedLine {
+    /// Both ends anchored on a detected sync.
+    pub fn is_locked(&self) -> bool {
+        self.sync.is_locked()
+    }
+}
+
+/// Stateful scan-line decoder; see the module docs.
+pub struct StreamingDecoder {
+    sample_rate: u32,
+    sync: SyncParams,
+    sync_lock: bool,
+    width: usize,
+    samples_per_line: usize,
+    /// Audio not yet consumed by an emitted line.
+    buffer: Vec<f32>,
+    /// Stream position of `buffer[0]`.
+    origin: usize,
+    state: State,
+}
+
+enum State {
+    /// Looking for the line cadence in the buffered audio, or slicing at the
+    /// nominal period when sync lock is off.
+    Acquiring,
+    Tracking(Track),
+}
+
+struct Track {
+    lock: SyncLock,
+    period: f64,
+    /// Stream position of the current line start.
+    pos: f64,
+    /// How the current line start was placed.
+    state: SyncState,
+    /// Consecutive coasted line starts.
+    coasted: usize,
+}
+
+impl StreamingDecoder {
+    /// # Errors
+    ///
+    /// The parameter errors of [`crate::sstv::SstvDecoder::decode_lines`].
+    pub fn new(params: &DecoderParams, sample_rate: u32) -> Result<Self> {
+        let samples_per_line = validate_params(params, sample_rate)?;
+        Ok(Self {
+            sample_rate,
+            sync: params.sync_params(),
+            sync_lock: params.sync_lock,
+            width: params.effective_width(),
+            samples_per_line,
+            buffer: Vec::new(),
+            origin: 0,
+            state: State::Acquiring,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Whether line starts currently follow a tracked sync cadence.
+    pub fn is_tracking(&self) -> bool {
+        matches!(self.state, State::Tracking(_))
+    }
+
+    /// Feed the next samples of the stream and take the lines they complete.
+    pub fn push(&mut self, samples: &[f32]) -> Vec<DecodedLine> {
+        self.buffer.extend_from_slice(samples);
+        self.decode(false)
+    }
+
+    /// End of stream: acquire on whatever is still buffered and take the
+    /// remaining whole lines. A tracked line with no sync after it is
+    /// dropped, as in a window decode.
+    pub fn finish(mut self) -> Vec<DecodedLine> {
+        self.decode(true)
+    }
+
+    fn decode(&mut self, flush: bool) -> Vec<DecodedLine> {
+        let mut lines = Vec::new();
+        loop {
+            let progressed = match self.state {
+                State::Acquiring => self.acquire(flush, &mut lines),
+                State::Tracking(_) => self.track(&mut lines),
+            };
+            if !progressed {
+                break;
+            }
+        }
+        if let State::Tracking(track) = &self.state {
+            // One trim per call: the buffer keeps the line in progress.
+            let keep = (track.pos.floor() as usize).saturating_sub(self.origin);
+            self.consume(keep);
+        }
+        lines
+    }
+
+    /// Seed a sync lock from the first [`ACQUIRE_LINES`] of buffered audio,
+    /// or slice half of it at the nominal period when it has no plausible
+    /// line cadence. Returns whether anything changed.
+    fn acquire(&mut self, flush: bool, lines: &mut Vec<DecodedLine>) -> bool {
+        let spl = self.samples_per_line;
+        if !self.sync_lock {
+            return self.slice_fixed(self.buffer.len() / spl, lines);
+        }
+        let window_len = spl * ACQUIRE_LINES;
+        if self.buffer.len() < window_len && !(flush && self.buffer.len() >= spl) {
+            return false;
+        }
+        let window = &self.buffer[..window_len.min(self.buffer.len())];
+        let detected = detect_line_syncs(window, self.sample_rate, &self.sync);
+        // The same cadence check the window decoder applies before trusting
+        // sync lock.
+        let nominal = spl as f64;
+        let cadence = detected.len() >= 4
+            && interval_summary(&detected, self.sample_rate)
+                .is_some_and(|summary| (summary.median_samples - nominal).abs() / nominal < 0.3);
+        let lock = if cadence {
+            SyncLock::acquire(window, &detected, self.sample_rate, &self.sync)
+        } else {
+            None
+        };
+        match lock {
+            Some(lock) => {
+                // Audio before the first coherent sync is leader; the window
+                // decoder skips it too.
+                let pos = self.origin as f64 + lock.start;
+                tracing::debug!(position = pos, period = lock.period, "Streaming decoder acquired sync lock");
+                self.state = State::Tracking(Track {
+                    lock,
+                    period: lock.period,
+                    pos,
+                    state: SyncState::Locked,
+                    coasted: 0,
+                });
+                true
+            }
+            None => {
+                let count = if flush { window.len() / spl } else { ACQUIRE_LINES / 2 };
+                self.slice_fixed(count, lines)
+            }
+        }
+    }
+
+    /// Advance the lock by one line, emitting it. Returns false until the
+    /// buffer reaches past the next sync.
+    fn track(&mut self, lines: &mut Vec<DecodedLine>) -> bool {
+        let State::Tracking(track) = &mut self.state else {
+            return false;
+        };
+        let origin = self.origin as f64;
+        let Some(step) = track.lock.step(&self.buffer, track.pos - origin, &mut track.period) else {
+            return false;
+        };
+        let next = step.next + origin;
+        let sync = LineSync {
+            state: track.state.min(step.state),
+            swing: step.swing,
+            period: track.period,
+        };
+        lines.push(line_at(&self.buffer, self.origin, track.pos..next, self.width, sync));
+        track.coasted = if step.state.is_anchored() { 0 } else { track.coasted + 1 };
+        track.pos = next;
+        track.state = step.state;
+        if track.coasted >= LOST_LOCK_LINES {
+            tracing::debug!(position = next, "Streaming decoder lost sync lock; reacquiring");
+            let keep = (next.floor() as usize).saturating_sub(self.origin);
+            self.state = State::Acquiring;
+            self.consume(keep);
+        }
+        true
+    }
+
+    /// Emit `count` unlocked lines of the nominal length from the front of
+    /// the buffer.
+    fn slice_fixed(&mut self, count: usize, lines: &mut Vec<DecodedLine>) -> bool {
+        let spl = self.samples_per_line;
+        for i in 0..count {
+            let start = (self.origin + i * spl) as f64;
+            lines.push(line_at(
+                &self.buffer,
+                self.origin,
+                start..start + spl as f64,
+                self.width,
+                LineSync::fixed(spl as f64),
+            ));
+        }
+        self.consume(count * spl);
+        count > 0
+    }
+
+    fn consume(&mut self, samples: usize) {
+        let samples = samples.min(self.buffer.len());
+        self.buffer.drain(..samples);
+        self.origin += samples;
+    }
+}
+
+/// The line over stream positions `bounds` (sub-sample, as the tracker
+/// times them) of a buffer starting at stream position `origin`.
+fn line_at(buffer: &[f32], origin: usize, bounds: Range<f64>, width: usize, sync: LineSync) -> DecodedLine {
+    let mut levels = Vec::with_capacity(width);
+    resample_span(
+        buffer,
+        bounds.start - origin as f64..bounds.end - origin as f64,
+        width,
+        &mut levels,
+    );
+    let (start, end) = (bounds.start.round() as usize, bounds.end.round() as usize);
+    DecodedLine {
+        start,
+        len: end - start,
+        levels,
+        sync,
+    }
+}
+
+/// The most recent decoded lines, held for display and normalized on
+/// demand with the percentile stretch of a window decode.
+#[derive(Debug, Clone)]
+pub struct LiveImage {
+    width: usize,
+    max_lines: usize,
+    levels: Vec<f32>,
+    sync: Vec<LineSync>,
+    spans: Vec<Range<usize>>,
+}
+
+impl LiveImage {
+    /// An empty image of `width`-level lines that scrolls past `max_lines`.
+    pub fn new(width: usize, max_lines: usize) -> Self {
+        Self {
+            width: width.max(1),
+            max_lines: max_lines.max(1),
+            levels: Vec::new(),
+            sync: Vec::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, line: &DecodedLine) {
+        debug_assert_eq!(line.levels.len(), self.width, "line width differs from the image");
+        self.levels.extend_from_slice(&line.levels);
+        self.sync.push(line.sync);
+        self.spans.push(line.start..line.start + line.len);
+        let excess = self.sync.len().saturating_sub(self.max_lines);
+        if excess > 0 {
+            self.levels.drain(..excess * self.width);
+            self.sync.drain(..excess);
+            self.spans.drain(..excess);
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.sync.len()
+    }
+
+    /// Per-line sync diagnostics, oldest first.
+    pub fn sync(&self) -> &[LineSync] {
+        &self.sync
+    }
+
+    /// Grayscale image of the held lines with `params`' inversion and
+    /// gamma; `None` before the first line.
+    pub fn to_result(&self, params: &DecoderParams) -> Option<PipelineResult> {
+        if self.sync.is_empty() {
+            return None;
+        }
+        let (lo, hi) = percentile_bounds(&self.levels, 0.01, 0.99);
+        Some(PipelineResult {
+            pixels: normalize_levels(&self.levels, lo, hi, params.invert, params.gamma),
+            width: self.width as u32,
+            height: self.height() as u32,
+            mode: DecoderMode::Grayscale,
+            registration: None,
+            sstv_mode: None,
+            line_sync: Some(self.sync.clone()),
+            line_spans: Some(self.spans.clone()),
+            levels: Some(self.levels.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sstv::SstvDecoder;
+    use crate::test_fixtures::encode_image_to_audio;
+
+    const RATE: u32 = 48_000;
+
+    fn gradient_audio(width: usize, lines: usize, params: &DecoderParams) -> Vec<f32> {
+        let pixels: Vec<u8> = (0..lines)
+            .flat_map(|y| (0..width).map(move |x| ((x + y) * 255 / (width + lines)) as u8))
+            .collect();
+        encode_image_to_audio(&pixels, width, RATE, params.line_duration_ms)
+    }
+
+    fn stream(params: &DecoderParams, samples: &[f32], chunk: usize) -> Vec<DecodedLine> {
+        let mut decoder = StreamingDecoder::new(params, RATE).unwrap();
+        let mut lines: Vec<DecodedLine> = samples.chunks(chunk).flat_map(|c| decoder.push(c)).collect();
+        lines.extend(decoder.finish());
+        lines
+    }
+
+    #[test]
+    fn matches_the_window_decoder_in_any_chunking() {
+        let params = DecoderParams {
+            width: 64,
+            ..DecoderParams::default()
+        };
+        let audio = gradient_audio(64, 200, &params);
+        let whole = SstvDecoder::new().decode_lines(&audio, &params, RATE).unwrap();
+
+        let lines = stream(&params, &audio, audio.len());
+        assert_eq!(lines.len(), whole.locked.len());
+        let levels: Vec<f32> = lines.iter().flat_map(|l| l.levels.iter().copied()).collect();
+        assert_eq!(levels, whole.levels);
+        assert_eq!(lines.iter().map(|l| l.sync).collect::<Vec<_>>(), whole.sync);
+        for pair in lines.windows(2) {
+            assert_eq!(pair[0].start + pair[0].len, pair[1].start, "lines are contiguous");
+        }
+
+        for chunk in [1, 333, 4_800] {
+            assert_eq!(stream(&params, &audio, chunk), lines, "chunk {chunk}");
+        }
+    }
+
+    #[test]
+    fn lines_arrive_as_their_syncs_do() {
+        let params = DecoderParams {
+            width: 64,
+            ..DecoderParams::default()
+        };
+        let audio = gradient_audio(64, 200, &params);
+        let spl = validate_params(&params, RATE).unwrap();
+        let mut decoder = StreamingDecoder::new(&params, RATE).unwrap();
+
+        assert!(decoder.push(&audio[..spl * ACQUIRE_LINES / 2]).is_empty(), "still acquiring");
+        let first = decoder.push(&audio[spl * ACQUIRE_LINES / 2..spl * (ACQUIRE_LINES + 1)]);
+        assert!(decoder.is_tracking());
+        assert!(first.len() >= ACQUIRE_LINES - 2, "{} lines after acquisition", first.len());
+        assert!(first.iter().all(DecodedLine::is_locked));
+        // Constant memory: only the line in progress stays buffered.
+        assert!(decoder.buffer.len() < spl * 3, "{} samples buffered", decoder.buffer.len());
+
+        let more = decoder.push(&audio[spl * (ACQUIRE_LINES + 1)..spl * (ACQUIRE_LINES + 11)]);
+        assert_eq!(more.len(), 10);
+        assert_eq!(more[0].start, first.last().map(|l| l.start + l.len).unwrap());
+    }
+
+    #[test]
+    fn slices_at_the_nominal_period_without_a_cadence() {
+        let params = DecoderParams {
+            width: 8,
+            ..DecoderParams::default()
+        };
+        let spl = validate_params(&params, RATE).unwrap();
+        let flat = vec![0.25f32; spl * 100 + 7];
+        let lines = stream(&params, &flat, 1_000);
+        assert_eq!(lines.len(), 100);
+        assert!(lines.iter().all(|l| l.sync.state == SyncState::Fixed && l.len == spl));
+        assert_eq!(lines[99].start, 99 * spl);
+
+        let unlocked = DecoderParams {
+            sync_lock: false,
+            ..params
+        };
+        let mut decoder = StreamingDecoder::new(&unlocked, RATE).unwrap();
+        assert_eq!(decoder.push(&flat[..spl * 3 + 1]).len(), 3, "no acquisition delay");
+        assert!(!decoder.is_tracking());
+    }
+
+    #[test]
+    fn rejects_invalid_params() {
+        let params = DecoderParams {
+            gamma: 0.0,
+            ..DecoderParams::default()
+        };
+        assert!(StreamingDecoder::new(&params, RATE).is_err());
+    }
+
+    #[test]
+    fn live_image_scrolls_and_normalizes() {
+        let mut image = LiveImage::new(2, 3);
+        assert!(image.to_result(&DecoderParams::default()).is_none());
+        for i in 0..5 {
+            image.push(&DecodedLine {
+                start: i * 10,
+                len: 10,
+                levels: vec![i as f32, i as f32 + 0.5],
+                sync: LineSync {
+                    state: if i % 2 == 0 { SyncState::Locked } else { SyncState::Coasted },
+                    swing: 1.0,
+                    period: 10.0,
+                },
+            });
+        }
+        assert_eq!(image.height(), 3);
+        let locked: Vec<bool> = image.sync().iter().map(LineSync::is_locked).collect();
+        assert_eq!(locked, [true, false, true]);
+        let result = image.to_result(&DecoderParams::default()).unwrap();
+        assert_eq!((result.width, result.height), (2, 3));
+        assert_eq!(result.pixels.first(), Some(&0));
+        assert_eq!(result.pixels.last(), Some(&255));
+        // Rows keep the source positions of the lines still held
+        assert_eq!(result.row_span(0), Some(20..30));
+        assert_eq!(result.row_at(45), Some(2));
+    }
+}
@@ -153,6 +153,9 @@ pub struct EncodeOptions {
     pub slant_samples_per_line: f32,
     /// Additive deterministic noise amplitude.
     pub noise_amplitude: f32,
+    /// Tape and transfer impairments applied to the encoded audio (see
+    /// [`degrade`]). Crosstalk needs a second channel and is ignored here.
+    pub tape: TapeDegradation,
 }
 
 impl Default for EncodeOptions {
@@ -160,6 +163,7 @@ impl Default for EncodeOptions {
         Self {
             slant_samples_per_line: 0.0,
             noise_amplitude: 0.0,
+            tape: TapeDegradation::default(),
         }
     }
 }
@@ -186,7 +190,7 @@ pub fn encode_image_to_audio(pixels: &[u8], width: usize, sample_rate: u32, line
     encode_image_to_audio_with(pixels, width, sample_rate, line_duration_ms, &EncodeOptions::default())
 }
 
-/// [`encode_image_to_audio`] with slant and noise injection.
+/// [`encode_image_to_audio`] with slant, noise and tape degradation.
 pub fn encode_image_to_audio_with(
     pixels: &[u8],
     width: usize,
@@ -241,9 +245,474 @@ pub fn encode_image_to_audio_with(
         t += period;
     }
 
+    degrade(&audio, sample_rate, &opts.tape)
+}
+
+/// Playback-chain impairments of an analog tape transfer and its digital
+/// distribution, for [`degrade`]. Every field at zero leaves the signal
+/// untouched ([`Default`]); all randomness derives from `seed`, each
+/// impairment from its own stream, so switching one on or off does not
+/// reshuffle the others.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TapeDegradation {
+    pub seed: u64,
+    /// Slow speed wander: peak fractional deviation (0.002 = 0.2%) and rate.
+    pub wow_depth: f32,
+    pub wow_hz: f32,
+    /// Fast speed jitter: peak fractional deviation and main rate.
+    pub flutter_depth: f32,
+    pub flutter_hz: f32,
+    /// Mean dropouts per second, their mean length, and the fraction of
+    /// signal lost at the bottom of one (0-1).
+    pub dropouts_per_sec: f32,
+    pub dropout_ms: f32,
+    pub dropout_depth: f32,
+    /// Peak offset of a slowly wandering baseline.
+    pub dc_drift: f32,
+    /// Mains hum: fundamental amplitude (odd harmonics follow) and frequency.
+    pub hum_amplitude: f32,
+    pub hum_hz: f32,
+    /// Fraction of the other track bleeding in ([`degrade_stereo`] only).
+    pub crosstalk: f32,
+    /// Second-order high- and low-pass band edges in Hz; zero for none.
+    pub highpass_hz: f32,
+    pub lowpass_hz: f32,
+    /// Hard clipping level; zero for none.
+    pub clip_level: f32,
+    /// Strength (0-1) of MP3-style transform coding: per-block spectral
+    /// quantization that leaves holes, pre-echo and a lowered bandwidth.
+    pub lossy: f32,
+}
+
+impl TapeDegradation {
+    /// A plausible consumer rip of a worn analog transfer: audible wow and
+    /// flutter, occasional shallow dropouts, 60 Hz hum, a little bleed,
+    /// 9 kHz bandwidth, near-full-scale clipping and moderate lossy coding.
+    pub fn typical(seed: u64) -> Self {
+        Self {
+            seed,
+            wow_depth: 0.002,
+            wow_hz: 0.5,
+            flutter_depth: 0.000_8,
+            flutter_hz: 8.0,
+            dropouts_per_sec: 0.2,
+            dropout_ms: 15.0,
+            dropout_depth: 0.7,
+            dc_drift: 0.03,
+            hum_amplitude: 0.01,
+            hum_hz: 60.0,
+            crosstalk: 0.02,
+            highpass_hz: 20.0,
+            lowpass_hz: 9_000.0,
+            clip_level: 0.95,
+            lossy: 0.3,
+        }
+    }
+}
+
+/// SplitMix64: small, seedable and plenty for test impairments.
+struct SeededRng(u64);
+
+impl SeededRng {
+    /// Independent stream `stream` of `seed`.
+    fn new(seed: u64, stream: u64) -> Self {
+        Self(seed ^ stream.wrapping_mul(0xD1B5_4A32_D192_ED03))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn phase(&mut self) -> f64 {
+        self.unit() * std::f64::consts::TAU
+    }
+}
+
+/// Apply `tape` to a mono signal, in playback-chain order: speed variation,
+/// band limits, dropouts, DC drift and hum, clipping, lossy coding. Wow and
+/// flutter resample the signal, so its length changes slightly.
+pub fn degrade(samples: &[f32], sample_rate: u32, tape: &TapeDegradation) -> Vec<f32> {
+    let rate = sample_rate as f64;
+    let mut audio = vary_speed(samples, rate, tape);
+    band_limit(&mut audio, rate, tape);
+    apply_dropouts(&mut audio, rate, tape);
+    add_drift_and_hum(&mut audio, rate, tape);
+    if tape.clip_level > 0.0 {
+        for s in &mut audio {
+            *s = s.clamp(-tape.clip_level, tape.clip_level);
+        }
+    }
+    if tape.lossy > 0.0 {
+        audio = lossy_code(&audio, tape);
+    }
     audio
 }
 
+/// [`degrade`] a stereo pair after mixing `crosstalk` of each track into the
+/// other. Both tracks share one seed: one capstan drives them and a dropout
+/// spans the tape width.
+pub fn degrade_stereo(left: &[f32], right: &[f32], sample_rate: u32, tape: &TapeDegradation) -> (Vec<f32>, Vec<f32>) {
+    let len = left.len().min(right.len());
+    let bleed = |a: &[f32], b: &[f32]| -> Vec<f32> { (0..len).map(|i| a[i] + tape.crosstalk * b[i]).collect() };
+    let (l, r) = (bleed(left, right), bleed(right, left));
+    (degrade(&l, sample_rate, tape), degrade(&r, sample_rate, tape))
+}
+
+fn vary_speed(samples: &[f32], rate: f64, tape: &TapeDegradation) -> Vec<f32> {
+    if (tape.wow_depth == 0.0 && tape.flutter_depth == 0.0) || samples.len() < 2 {
+        return samples.to_vec();
+    }
+    use std::f64::consts::TAU;
+    let mut rng = SeededRng::new(tape.seed, 1);
+    let (wow_phase, flutter_phase, jitter_phase) = (rng.phase(), rng.phase(), rng.phase());
+    let (wow, flutter) = (tape.wow_depth as f64, tape.flutter_depth as f64);
+    let (wow_hz, flutter_hz) = (tape.wow_hz as f64, tape.flutter_hz as f64);
+
+    // Read through the input at the instantaneous speed of each output sample
+    let mut audio = Vec::with_capacity(samples.len() + samples.len() / 100);
+    let mut pos = 0.0f64;
+    while pos < (samples.len() - 1) as f64 {
+        let i = pos as usize;
+        let frac = (pos - i as f64) as f32;
+        audio.push(samples[i] + (samples[i + 1] - samples[i]) * frac);
+        let t = audio.len() as f64 / rate;
+        // Flutter is narrowband jitter around its main rate, not a pure tone
+        let speed = 1.0
+            + wow * (TAU * wow_hz * t + wow_phase).sin()
+            + flutter
+                * (0.7 * (TAU * flutter_hz * t + flutter_phase).sin() + 0.3 * (TAU * 2.7 * flutter_hz * t + jitter_phase).sin());
+        pos += speed;
+    }
+    audio
+}
+
+/// Transposed direct-form II biquad (RBJ cookbook coefficients).
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn new(cutoff_hz: f64, rate: f64, highpass: bool) -> Self {
+        let w = std::f64::consts::TAU * cutoff_hz / rate;
+        let alpha = w.sin() / std::f64::consts::SQRT_2; // Q = 1/sqrt(2)
+        let cos = w.cos();
+        let a0 = 1.0 + alpha;
+        let b = if highpass {
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0]
+        } else {
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0]
+        };
+        Self {
+            b: b.map(|v| v / a0),
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+            z: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let x = x as f64;
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y as f32
+    }
+}
+
+fn band_limit(audio: &mut [f32], rate: f64, tape: &TapeDegradation) {
+    let nyquist = rate / 2.0;
+    let mut filters: Vec<Biquad> = [(tape.highpass_hz, true), (tape.lowpass_hz, false)]
+        .into_iter()
+        .filter(|&(hz, _)| hz > 0.0 && (hz as f64) < nyquist)
+        .map(|(hz, highpass)| Biquad::new(hz as f64, rate, highpass))
+        .collect();
+    for filter in &mut filters {
+        for s in audio.iter_mut() {
+            *s = filter.process(*s);
+        }
+    }
+}
+
+fn apply_dropouts(audio: &mut [f32], rate: f64, tape: &TapeDegradation) {
+    if tape.dropouts_per_sec <= 0.0 || tape.dropout_ms <= 0.0 || tape.dropout_depth <= 0.0 {
+        return;
+    }
+    let mut rng = SeededRng::new(tape.seed, 2);
+    let chance = tape.dropouts_per_sec as f64 / rate;
+    let mut i = 0;
+    while i < audio.len() {
+        if rng.unit() >= chance {
+            i += 1;
+            continue;
+        }
+        // Length spread 0.5-1.5x the mean, with ramps of a fifth at each end
+        let len = ((tape.dropout_ms as f64 / 1000.0 * rate * (0.5 + rng.unit())) as usize).max(2);
+        let ramp = (len / 5).max(1) as f32;
+        let end = (i + len).min(audio.len());
+        for (k, s) in audio[i..end].iter_mut().enumerate() {
+            let shape = (k.min(len - k) as f32 / ramp).min(1.0);
+            *s *= 1.0 - tape.dropout_depth.min(1.0) * shape;
+        }
+        i += len;
+    }
+}
+
+fn add_drift_and_hum(audio: &mut [f32], rate: f64, tape: &TapeDegradation) {
+    use std::f64::consts::{PI, TAU};
+    if tape.dc_drift > 0.0 {
+        // Smooth value noise: a random level every quarter second, cosine
+        // interpolated between
+        let mut rng = SeededRng::new(tape.seed, 3);
+        let knot = (rate / 4.0).max(1.0);
+        let knots: Vec<f64> = (0..=(audio.len() as f64 / knot) as usize + 1)
+            .map(|_| (rng.unit() * 2.0 - 1.0) * tape.dc_drift as f64)
+            .collect();
+        for (i, s) in audio.iter_mut().enumerate() {
+            let x = i as f64 / knot;
+            let k = x as usize;
+            let blend = 0.5 - 0.5 * ((x - k as f64) * PI).cos();
+            *s += (knots[k] + (knots[k + 1] - knots[k]) * blend) as f32;
+        }
+    }
+    if tape.hum_amplitude > 0.0 && tape.hum_hz > 0.0 {
+        let phase = SeededRng::new(tape.seed, 4).phase();
+        for (i, s) in audio.iter_mut().enumerate() {
+            let theta = TAU * tape.hum_hz as f64 * i as f64 / rate + phase;
+            let hum = theta.sin() + 0.3 * (3.0 * theta).sin() + 0.15 * (5.0 * theta).sin();
+            *s += tape.hum_amplitude * hum as f32;
+        }
+    }
+}
+
+/// Block transform coding in the manner of a low-bitrate MP3: 50%-overlapped
+/// sine-windowed blocks, each spectrum quantized in magnitude to a step
+/// relative to its own peak (small bins drop out entirely) and cut above a
+/// bandwidth that shrinks with `lossy`, then overlap-added back.
+fn lossy_code(audio: &[f32], tape: &TapeDegradation) -> Vec<f32> {
+    use realfft::num_complex::Complex;
+    use realfft::RealFftPlanner;
+
+    const BLOCK: usize = 1024;
+    const HOP: usize = BLOCK / 2;
+    let lossy = tape.lossy.clamp(0.0, 1.0);
+    let mut planner = RealFftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(BLOCK);
+    let inverse = planner.plan_fft_inverse(BLOCK);
+    let mut block = forward.make_input_vec();
+    let mut spectrum = forward.make_output_vec();
+    // Sine window on analysis and synthesis: squares sum to one at 50% overlap
+    let window: Vec<f32> = (0..BLOCK)
+        .map(|n| (std::f32::consts::PI * (n as f32 + 0.5) / BLOCK as f32).sin())
+        .collect();
+    let bins = spectrum.len();
+    let cutoff = ((bins as f32) * (1.0 - 0.6 * lossy)) as usize;
+
+    // Pad a hop each side so every input sample is covered by two blocks
+    let mut padded = vec![0.0f32; HOP];
+    padded.extend(audio);
+    padded.resize(padded.len().div_ceil(HOP) * HOP + HOP, 0.0);
+    let mut out = vec![0.0f32; padded.len()];
+    for start in (0..=padded.len() - BLOCK).step_by(HOP) {
+        for (dst, (s, w)) in block.iter_mut().zip(padded[start..start + BLOCK].iter().zip(&window)) {
+            *dst = s * w;
+        }
+        forward
+            .process(&mut block, &mut spectrum)
+            .expect("forward FFT of a full block");
+        let peak = spectrum.iter().map(|c| c.norm()).fold(0.0f32, f32::max);
+        let step = lossy * 0.25 * peak;
+        for (k, c) in spectrum.iter_mut().enumerate() {
+            let magnitude = c.norm();
+            *c = if k >= cutoff || magnitude < step {
+                Complex::new(0.0, 0.0)
+            } else if step > 0.0 {
+                *c * ((magnitude / step).round() * step / magnitude)
+            } else {
+                *c
+            };
+        }
+        // The inverse needs purely real DC and Nyquist bins
+        spectrum[0].im = 0.0;
+        spectrum[bins - 1].im = 0.0;
+        inverse
+            .process(&mut spectrum, &mut block)
+            .expect("inverse FFT of a full block");
+        for (n, v) in block.iter().enumerate() {
+            out[start + n] += v * window[n] / BLOCK as f32;
+        }
+    }
+    out[HOP..HOP + audio.len()].to_vec()
+}
+
+/// Phase-continuous FM tone writer for [`encode_sstv`]. Tone boundaries are
+/// kept in fractional milliseconds so sub-sample pixel times don't drift.
+struct FmWriter {
+    sample_rate: f64,
+    phase: f64,
+    elapsed_ms: f64,
+    audio: Vec<f32>,
+}
+
+impl FmWriter {
+    fn tone(&mut self, hz: f64, ms: f64) {
+        self.elapsed_ms += ms;
+        let end = (self.elapsed_ms * self.sample_rate / 1000.0).round() as usize;
+        while self.audio.len() < end {
+            self.phase = (self.phase + std::f64::consts::TAU * hz / self.sample_rate) % std::f64::consts::TAU;
+            self.audio.push(0.5 * self.phase.sin() as f32);
+        }
+    }
+
+    /// One scan: each value 0-255 held for an equal share of `ms`.
+    fn scan(&mut self, values: &[f64], ms: f64) {
+        for &v in values {
+            self.tone(1500.0 + v.clamp(0.0, 255.0) / 255.0 * 800.0, ms / values.len() as f64);
+        }
+    }
+}
+
+/// Encode an RGB image (3 bytes per pixel, the mode's full size) as an
+/// amateur FM SSTV transmission, VIS header first.
+///
+/// Written from the published mode timings, tone by tone in transmission
+/// order (Scottie's starting sync, Robot 36's alternating chroma, PD's
+/// shared chroma over row pairs), rather than from the decoder's scan
+/// table, so round trips check one against the other.
+pub fn encode_sstv(mode: crate::fm_sstv::SstvMode, rgb: &[u8], sample_rate: u32) -> Vec<f32> {
+    use crate::fm_sstv::SstvMode;
+
+    let (width, height) = match mode {
+        SstvMode::Robot36 | SstvMode::Robot72 => (320, 240),
+        SstvMode::Pd120 | SstvMode::Pd180 | SstvMode::Pd240 => (640, 496),
+        SstvMode::Pd160 => (512, 400),
+        SstvMode::Pd290 => (800, 616),
+        _ => (320, 256),
+    };
+    assert_eq!(rgb.len(), width * height * 3, "image must be {width}x{height} RGB");
+    let channel = |row: usize, c: usize| -> Vec<f64> { (0..width).map(|x| rgb[(row * width + x) * 3 + c] as f64).collect() };
+    // Full-range BT.601
+    let luma = |row: usize| -> Vec<f64> {
+        let (r, g, b) = (channel(row, 0), channel(row, 1), channel(row, 2));
+        (0..width).map(|x| 0.299 * r[x] + 0.587 * g[x] + 0.114 * b[x]).collect()
+    };
+    let red_diff = |row: usize| -> Vec<f64> {
+        let (r, g, b) = (channel(row, 0), channel(row, 1), channel(row, 2));
+        (0..width)
+            .map(|x| 128.0 + 0.5 * r[x] - 0.418_688 * g[x] - 0.081_312 * b[x])
+            .collect()
+    };
+    let blue_diff = |row: usize| -> Vec<f64> {
+        let (r, g, b) = (channel(row, 0), channel(row, 1), channel(row, 2));
+        (0..width)
+            .map(|x| 128.0 - 0.168_736 * r[x] - 0.331_264 * g[x] + 0.5 * b[x])
+            .collect()
+    };
+
+    let mut fm = FmWriter {
+        sample_rate: sample_rate as f64,
+        phase: 0.0,
+        elapsed_ms: 0.0,
+        audio: Vec::new(),
+    };
+
+    // VIS: leader, break, leader, start bit, 7 data bits LSB first, even
+    // parity, stop bit
+    fm.tone(1900.0, 300.0);
+    fm.tone(1200.0, 10.0);
+    fm.tone(1900.0, 300.0);
+    fm.tone(1200.0, 30.0);
+    let code = mode.vis_code();
+    for bit in 0..7 {
+        fm.tone(if code >> bit & 1 == 1 { 1100.0 } else { 1300.0 }, 30.0);
+    }
+    fm.tone(if code.count_ones() % 2 == 1 { 1100.0 } else { 1300.0 }, 30.0);
+    fm.tone(1200.0, 30.0);
+
+    match mode {
+        SstvMode::MartinM1 | SstvMode::MartinM2 => {
+            let scan = if mode == SstvMode::MartinM1 { 146.432 } else { 73.216 };
+            for row in 0..height {
+                fm.tone(1200.0, 4.862);
+                fm.tone(1500.0, 0.572);
+                for c in [1, 2, 0] {
+                    fm.scan(&channel(row, c), scan);
+                    fm.tone(1500.0, 0.572);
+                }
+            }
+        }
+        SstvMode::ScottieS1 | SstvMode::ScottieS2 => {
+            let scan = if mode == SstvMode::ScottieS1 { 138.24 } else { 88.064 };
+            fm.tone(1200.0, 9.0);
+            for row in 0..height {
+                fm.tone(1500.0, 1.5);
+                fm.scan(&channel(row, 1), scan);
+                fm.tone(1500.0, 1.5);
+                fm.scan(&channel(row, 2), scan);
+                fm.tone(1200.0, 9.0);
+                fm.tone(1500.0, 1.5);
+                fm.scan(&channel(row, 0), scan);
+            }
+        }
+        SstvMode::Robot36 => {
+            for row in 0..height {
+                fm.tone(1200.0, 9.0);
+                fm.tone(1500.0, 3.0);
+                fm.scan(&luma(row), 88.0);
+                // Even rows carry R-Y behind a black separator, odd rows B-Y
+                // behind a white one
+                let odd = row % 2 == 1;
+                fm.tone(if odd { 2300.0 } else { 1500.0 }, 4.5);
+                fm.tone(1900.0, 1.5);
+                fm.scan(&if odd { blue_diff(row) } else { red_diff(row) }, 44.0);
+            }
+        }
+        SstvMode::Robot72 => {
+            for row in 0..height {
+                fm.tone(1200.0, 9.0);
+                fm.tone(1500.0, 3.0);
+                fm.scan(&luma(row), 138.0);
+                fm.tone(1500.0, 4.5);
+                fm.tone(1900.0, 1.5);
+                fm.scan(&red_diff(row), 69.0);
+                fm.tone(2300.0, 4.5);
+                fm.tone(1900.0, 1.5);
+                fm.scan(&blue_diff(row), 69.0);
+            }
+        }
+        _ => {
+            let scan = match mode {
+                SstvMode::Pd50 => 91.52,
+                SstvMode::Pd90 => 170.24,
+                SstvMode::Pd120 => 121.6,
+                SstvMode::Pd160 => 195.584,
+                SstvMode::Pd180 => 183.04,
+                SstvMode::Pd240 => 244.48,
+                _ => 228.8,
+            };
+            let average = |a: Vec<f64>, b: Vec<f64>| -> Vec<f64> { a.iter().zip(&b).map(|(a, b)| (a + b) / 2.0).collect() };
+            for row in (0..height).step_by(2) {
+                fm.tone(1200.0, 20.0);
+                fm.tone(1500.0, 2.08);
+                fm.scan(&luma(row), scan);
+                fm.scan(&average(red_diff(row), red_diff(row + 1)), scan);
+                fm.scan(&average(blue_diff(row), blue_diff(row + 1)), scan);
+                fm.scan(&luma(row + 1), scan);
+            }
+        }
+    }
+    fm.audio
+}
+
 /// Create a complete WAV file in memory for testing
 ///
 /// Returns a temporary file handle that can be used with WavReader.
@@ -291,6 +760,326 @@ pub fn create_test_wav_file(samples: &[f32], sample_rate: u32, channels: u16) ->
     file
 }
 
+fn to_i16(samples: &[f32]) -> impl Iterator<Item = i16> + '_ {
+    samples.iter().map(|&s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+}
+
+/// Create a 16-bit AIFF file for testing the non-WAV loaders; same sample
+/// conversion as [`create_test_wav_file`].
+pub fn create_test_aiff_file(samples: &[f32], sample_rate: u32, channels: u16) -> tempfile::NamedTempFile {
+    let frames = (samples.len() / channels as usize) as u32;
+    let mut bytes = Vec::new();
+    bytes.extend(b"FORM");
+    bytes.extend((4 + (8 + 18) + (8 + 8 + samples.len() as u32 * 2)).to_be_bytes());
+    bytes.extend(b"AIFF");
+
+    bytes.extend(b"COMM");
+    bytes.extend(18u32.to_be_bytes());
+    bytes.extend(channels.to_be_bytes());
+    bytes.extend(frames.to_be_bytes());
+    bytes.extend(16u16.to_be_bytes());
+    // Sample rate as an 80-bit IEEE extended float (integer rates only)
+    let exponent = 31 - sample_rate.leading_zeros();
+    bytes.extend((16383 + exponent as u16).to_be_bytes());
+    bytes.extend(((sample_rate as u64) << (63 - exponent)).to_be_bytes());
+
+    bytes.extend(b"SSND");
+    bytes.extend((8 + samples.len() as u32 * 2).to_be_bytes());
+    bytes.extend([0u8; 8]); // offset, block size
+    for sample in to_i16(samples) {
+        bytes.extend(sample.to_be_bytes());
+    }
+
+    let file = tempfile::NamedTempFile::new().expect("create temp file");
+    std::fs::write(file.path(), bytes).unwrap();
+    file
+}
+
+/// Create a 16-bit FLAC file for testing the non-WAV loaders. Frames hold
+/// verbatim (uncompressed) subframes of 4096 samples, which every decoder
+/// must accept; same sample conversion as [`create_test_wav_file`].
+pub fn create_test_flac_file(samples: &[f32], sample_rate: u32, channels: u16) -> tempfile::NamedTempFile {
+    create_corrupt_flac_file(samples, sample_rate, channels, &[])
+}
+
+/// [`create_test_flac_file`] with the frames numbered in `corrupt` made
+/// undecodable (a set subframe padding bit) but still well delimited, so a
+/// decoder rejects each of them as one bad packet.
+pub fn create_corrupt_flac_file(samples: &[f32], sample_rate: u32, channels: u16, corrupt: &[usize]) -> tempfile::NamedTempFile {
+    const BLOCK: usize = 4096;
+
+    fn crc8(bytes: &[u8]) -> u8 {
+        bytes.iter().fold(0u8, |crc, &b| {
+            (0..8).fold(crc ^ b, |c, _| if c & 0x80 != 0 { (c << 1) ^ 0x07 } else { c << 1 })
+        })
+    }
+    fn crc16(bytes: &[u8]) -> u16 {
+        bytes.iter().fold(0u16, |crc, &b| {
+            (0..8).fold(crc ^ ((b as u16) << 8), |c, _| {
+                if c & 0x8000 != 0 {
+                    (c << 1) ^ 0x8005
+                } else {
+                    c << 1
+                }
+            })
+        })
+    }
+    // Frame numbers use FLAC's UTF-8-style variable-length coding.
+    fn coded_number(n: u32, out: &mut Vec<u8>) {
+        if n < 0x80 {
+            out.push(n as u8);
+            return;
+        }
+        let mut tail = Vec::new();
+        let mut rest = n;
+        while rest >= (0x40 >> tail.len()) {
+            tail.push(0x80 | (rest & 0x3F) as u8);
+            rest >>= 6;
+        }
+        let lead = !(0xFFu8 >> (tail.len() + 1));
+        out.push(lead | rest as u8);
+        out.extend(tail.iter().rev());
+    }
+
+    let pcm: Vec<i16> = to_i16(samples).collect();
+    let channels_usize = channels as usize;
+    let frames = pcm.len() / channels_usize;
+
+    let mut bytes = Vec::from(*b"fLaC");
+    // STREAMINFO, flagged as the last metadata block
+    bytes.extend([0x80, 0, 0, 34]);
+    bytes.extend((BLOCK as u16).to_be_bytes());
+    bytes.extend((BLOCK as u16).to_be_bytes());
+    bytes.extend([0u8; 6]); // frame sizes unknown
+    let packed = (sample_rate as u64) << 44 | ((channels as u64 - 1) << 41) | (15u64 << 36) | frames as u64;
+    bytes.extend(packed.to_be_bytes());
+    bytes.extend([0u8; 16]); // MD5 unknown
+
+    for (number, start) in (0..frames).step_by(BLOCK).enumerate() {
+        let len = BLOCK.min(frames - start);
+        let mut frame = vec![0xFF, 0xF8, 0x70, ((channels as u8 - 1) << 4) | 0x08];
+        coded_number(number as u32, &mut frame);
+        frame.extend((len as u16 - 1).to_be_bytes());
+        frame.push(crc8(&frame));
+        for ch in 0..channels_usize {
+            // Verbatim subframe
+            frame.push(if corrupt.contains(&number) { 0x82 } else { 0x02 });
+            for i in start..start + len {
+                frame.extend(pcm[i * channels_usize + ch].to_be_bytes());
+            }
+        }
+        frame.extend(crc16(&frame).to_be_bytes());
+        bytes.extend(frame);
+    }
+
+    let file = tempfile::NamedTempFile::new().expect("create temp file");
+    std::fs::write(file.path(), bytes).unwrap();
+    file
+}
+
+/// Render a deterministic grayscale texture, `width` x `height`, sampling
+/// the continuous pattern at `warp(x, y)` for each pixel. An identity warp
+/// gives the reference plane; any other warp gives a moving plane with a
+/// known geometric relation to it, for plane registration tests.
+///
+/// The pattern mixes incommensurate ripples (local texture for block
+/// matching) with a broad bump on each axis (so row and column luminance
+/// profiles have a unique alignment).
+pub fn textured_plane(width: usize, height: usize, warp: impl Fn(f64, f64) -> (f64, f64)) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let (u, v) = warp(x as f64, y as f64);
+            let ripple = 30.0 * (u / 7.3).sin() * (v / 5.1).cos()
+                + 22.0 * ((u + 2.0 * v) / 11.7).sin()
+                + 18.0 * ((3.0 * u - v) / 9.1).cos();
+            let bumps = 45.0 * (-(v - height as f64 * 0.4).powi(2) / 900.0).exp()
+                + 40.0 * (-(u - width as f64 * 0.6).powi(2) / 700.0).exp();
+            pixels.push((90.0 + ripple + bumps).clamp(0.0, 255.0) as u8);
+        }
+    }
+    pixels
+}
+
+/// One image frame of a synthetic record side.
+#[derive(Debug, Clone)]
+pub struct SyntheticFrame {
+    /// Grayscale pixels, `width` per line.
+    pub pixels: Vec<u8>,
+    pub width: usize,
+    /// Catalog role, carried through to [`SyntheticRecord::roles`].
+    pub role: crate::catalog::ColorRole,
+    /// Insert a [`RecordOptions::dropout_secs`] silence after this many
+    /// lines, splitting the frame's sync cadence in two (a false split for
+    /// segmentation cleanup to rejoin).
+    pub dropout_after_line: Option<usize>,
+    /// Start the next frame immediately, with no gap or junk in between
+    /// (a fused run for segmentation cleanup to split).
+    pub fused_with_next: bool,
+}
+
+impl SyntheticFrame {
+    pub fn new(pixels: Vec<u8>, width: usize, role: crate::catalog::ColorRole) -> Self {
+        Self {
+            pixels,
+            width,
+            role,
+            dropout_after_line: None,
+            fused_with_next: false,
+        }
+    }
+
+    pub fn lines(&self) -> usize {
+        self.pixels.len() / self.width
+    }
+}
+
+/// Layout of a synthetic record side for [`synthesize_record`].
+#[derive(Debug, Clone, Copy)]
+pub struct RecordOptions {
+    pub line_duration_ms: f32,
+    /// Length of the lead-in calibration tone. The tone sits at the line
+    /// rate, so it sync-locks like the real one and only the tone filter
+    /// rejects it. Zero for none.
+    pub lead_in_secs: f32,
+    /// Silence between frames (halved around a junk band when there is one).
+    pub gap_secs: f32,
+    /// Lines of sync-locked noise placed in each inter-frame gap, standing in
+    /// for the short junk bands between real frames. Zero for none.
+    pub junk_lines: usize,
+    /// Length of the silence inserted at [`SyntheticFrame::dropout_after_line`].
+    pub dropout_secs: f32,
+    /// Slant and noise applied to every frame and junk band.
+    pub encode: EncodeOptions,
+}
+
+impl Default for RecordOptions {
+    fn default() -> Self {
+        Self {
+            line_duration_ms: 8.32,
+            lead_in_secs: 3.0,
+            gap_secs: 0.05,
+            junk_lines: 0,
+            dropout_secs: 0.03,
+            encode: EncodeOptions::default(),
+        }
+    }
+}
+
+/// A synthetic record side and where its frames really are.
+#[derive(Debug, Clone)]
+pub struct SyntheticRecord {
+    pub samples: Vec<f32>,
+    /// Ground truth per frame, in the convention of
+    /// [`crate::analysis::segment::find_image_bounds`]: from the first
+    /// line's sync falling edge to one line period past the last one.
+    pub bounds: Vec<crate::analysis::segment::ImageBounds>,
+    pub roles: Vec<crate::catalog::ColorRole>,
+}
+
+/// Build a whole record side from `frames` with the forward model of
+/// [`encode_image_to_audio_with`]: lead-in tone, then each frame followed by
+/// a gap (silence, optionally around a junk band), with the requested false
+/// splits and fused pairs.
+pub fn synthesize_record(frames: &[SyntheticFrame], sample_rate: u32, opts: &RecordOptions) -> SyntheticRecord {
+    use crate::analysis::segment::ImageBounds;
+
+    let nominal = opts.line_duration_ms / 1000.0 * sample_rate as f32;
+    let silence = |secs: f32| vec![0.0f32; (secs * sample_rate as f32) as usize];
+    let encode =
+        |pixels: &[u8], width: usize| encode_image_to_audio_with(pixels, width, sample_rate, opts.line_duration_ms, &opts.encode);
+    // The sync detector reports line starts at the bottom of the spike's
+    // falling edge, half the sync structure into the line.
+    let marker = ((nominal.round() * ENCODE_SYNC_FRAC) as usize).max(4) / 2;
+
+    let mut samples = generate_sine_wave(1000.0 / opts.line_duration_ms, opts.lead_in_secs, sample_rate, 0.8);
+    samples.extend(silence(opts.gap_secs));
+
+    let mut spans = Vec::with_capacity(frames.len());
+    for (index, frame) in frames.iter().enumerate() {
+        let start = samples.len();
+        match frame.dropout_after_line {
+            Some(split) if split > 0 && split < frame.lines() => {
+                let (head, tail) = frame.pixels.split_at(split * frame.width);
+                samples.extend(encode(head, frame.width));
+                samples.extend(silence(opts.dropout_secs));
+                samples.extend(encode(tail, frame.width));
+            }
+            _ => samples.extend(encode(&frame.pixels, frame.width)),
+        }
+        spans.push((start, samples.len()));
+
+        if frame.fused_with_next && index + 1 < frames.len() {
+            continue;
+        }
+        if opts.junk_lines > 0 {
+            let junk: Vec<u8> = (0..opts.junk_lines * frame.width)
+                .map(|i| ((i * 97 + index * 31) % 251) as u8)
+                .collect();
+            samples.extend(silence(opts.gap_secs / 2.0));
+            samples.extend(encode(&junk, frame.width));
+            samples.extend(silence(opts.gap_secs / 2.0));
+        } else {
+            samples.extend(silence(opts.gap_secs));
+        }
+    }
+
+    let bounds = frames
+        .iter()
+        .zip(spans)
+        .map(|(frame, (start, end))| {
+            // Periods grow linearly under slant, so the median is the middle one
+            let lines = frame.lines();
+            let median = nominal as f64 + opts.encode.slant_samples_per_line as f64 * (lines / 2) as f64;
+            let (start_sample, end_sample) = (start + marker, (end + marker).min(samples.len()));
+            ImageBounds {
+                start_sample,
+                end_sample,
+                start_secs: start_sample as f64 / sample_rate as f64,
+                end_secs: end_sample as f64 / sample_rate as f64,
+                line_count: lines,
+                median_interval_samples: median,
+                confidence: 1.0,
+            }
+        })
+        .collect();
+
+    SyntheticRecord {
+        samples,
+        bounds,
+        roles: frames.iter().map(|f| f.role).collect(),
+    }
+}
+
+/// The catalog sequence of `channel` as synthetic frames of `width` x
+/// `lines`: grayscale frames are distinct [`textured_plane`] views, and each
+/// color triplet is one picture whose red, green and blue planes carry full,
+/// 60% and 30% brightness, so a correct composite comes out red-dominant.
+pub fn catalog_frames(channel: crate::audio::WaveformChannel, width: usize, lines: usize) -> Vec<SyntheticFrame> {
+    use crate::catalog::{channel_catalog, ColorRole};
+
+    channel_catalog(channel)
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            // Triplet members (blue, green, red in record order) share the
+            // blue member's view
+            let (view, scale) = match entry.color {
+                ColorRole::Bnw => (index, 1.0),
+                ColorRole::Blu => (index, 0.3),
+                ColorRole::Grn => (index - 1, 0.6),
+                ColorRole::Red => (index - 2, 1.0),
+            };
+            let shift = 13.0 * view as f64;
+            let pixels = textured_plane(width, lines, |x, y| (x + shift, y))
+                .into_iter()
+                .map(|p| (p as f32 * scale) as u8)
+                .collect();
+            SyntheticFrame::new(pixels, width, entry.color)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -339,6 +1128,199 @@ mod tests {
         assert!(slanted.len() > no_slant.len() + 4000);
     }
 
+    #[test]
+    fn test_synthesize_record_layout() {
+        use crate::catalog::ColorRole;
+
+        let frame = |lines: usize| SyntheticFrame::new(vec![128u8; 8 * lines], 8, ColorRole::Bnw);
+        let mut split = frame(40);
+        split.dropout_after_line = Some(20);
+        let mut fused = frame(40);
+        fused.fused_with_next = true;
+        let opts = RecordOptions {
+            lead_in_secs: 0.5,
+            junk_lines: 5,
+            ..Default::default()
+        };
+        let record = synthesize_record(&[frame(40), split, fused, frame(40)], 8_000, &opts);
+
+        assert_eq!(record.bounds.len(), 4);
+        assert_eq!(record.roles, vec![ColorRole::Bnw; 4]);
+        // Lead-in plus the first gap precede the first frame
+        assert!(record.bounds[0].start_sample > 4_000);
+        // Frames span their lines, the false split its dropout too
+        let span = |i: usize| record.bounds[i].end_sample - record.bounds[i].start_sample;
+        assert!(span(0).abs_diff(40 * 67) < 40, "{}", span(0));
+        assert!(span(1).abs_diff(40 * 67 + 240) < 40, "{}", span(1));
+        // Fused frames abut; others are separated by gap and junk
+        assert_eq!(record.bounds[2].end_sample, record.bounds[3].start_sample);
+        assert!(record.bounds[1].end_sample + 5 * 66 < record.bounds[2].start_sample);
+        assert!(record.bounds.iter().all(|b| b.line_count == 40));
+    }
+
+    #[test]
+    fn test_catalog_frames_follow_the_catalog_roles() {
+        use crate::audio::WaveformChannel;
+        use crate::catalog::{channel_catalog, color_triplets, FRAMES_PER_CHANNEL};
+
+        let frames = catalog_frames(WaveformChannel::Right, 16, 8);
+        assert_eq!(frames.len(), FRAMES_PER_CHANNEL);
+        for (frame, entry) in frames.iter().zip(channel_catalog(WaveformChannel::Right)) {
+            assert_eq!(frame.role, entry.color);
+            assert_eq!(frame.lines(), 8);
+        }
+        // Triplet planes are one picture at decreasing brightness red to blue
+        let mean = |i: usize| frames[i].pixels.iter().map(|&p| p as f64).sum::<f64>() / frames[i].pixels.len() as f64;
+        for [red, grn, blu] in color_triplets(WaveformChannel::Right) {
+            assert!(mean(red) > mean(grn) && mean(grn) > mean(blu));
+        }
+    }
+
+    #[test]
+    fn test_tape_degradation_is_seeded_and_off_by_default() {
+        let signal = generate_chirp(100.0, 3_000.0, 1.0, 16_000, 0.5);
+        assert_eq!(degrade(&signal, 16_000, &TapeDegradation::default()), signal);
+
+        let once = degrade(&signal, 16_000, &TapeDegradation::typical(7));
+        assert_eq!(once, degrade(&signal, 16_000, &TapeDegradation::typical(7)));
+        assert_ne!(once, degrade(&signal, 16_000, &TapeDegradation::typical(8)));
+        assert_ne!(once, signal[..once.len().min(signal.len())]);
+    }
+
+    #[test]
+    fn test_wow_and_flutter_vary_the_time_base() {
+        // Impulses every 100 samples come out unevenly spaced, on average
+        // still 100 apart
+        let mut clicks = vec![0.0f32; 80_000];
+        for i in (0..clicks.len()).step_by(100) {
+            clicks[i] = 1.0;
+        }
+        let tape = TapeDegradation {
+            wow_depth: 0.01,
+            wow_hz: 1.0,
+            flutter_depth: 0.002,
+            flutter_hz: 10.0,
+            ..Default::default()
+        };
+        let wobbly = degrade(&clicks, 8_000, &tape);
+        let peaks: Vec<usize> = (1..wobbly.len() - 1)
+            .filter(|&i| wobbly[i] > 0.2 && wobbly[i] >= wobbly[i - 1] && wobbly[i] > wobbly[i + 1])
+            .collect();
+        let intervals: Vec<usize> = peaks.windows(2).map(|w| w[1] - w[0]).collect();
+        let (min, max) = (*intervals.iter().min().unwrap(), *intervals.iter().max().unwrap());
+        assert!(min <= 99 && max >= 101, "intervals {min}..{max}");
+        let mean = (peaks[peaks.len() - 1] - peaks[0]) as f64 / intervals.len() as f64;
+        assert!((mean - 100.0).abs() < 0.5, "mean interval {mean}");
+    }
+
+    #[test]
+    fn test_tape_impairments_shape_the_signal() {
+        let rate = 16_000;
+        let only = |tape: TapeDegradation| TapeDegradation { seed: 3, ..tape };
+        let rms = |s: &[f32]| (s.iter().map(|v| v * v).sum::<f32>() / s.len() as f32).sqrt();
+
+        // Dropouts dip a steady level toward 1 - depth and recover
+        let steady = vec![0.5f32; rate as usize * 10];
+        let dropped = degrade(
+            &steady,
+            rate,
+            &only(TapeDegradation {
+                dropouts_per_sec: 2.0,
+                dropout_ms: 20.0,
+                dropout_depth: 0.8,
+                ..Default::default()
+            }),
+        );
+        let floor = dropped.iter().copied().fold(f32::MAX, f32::min);
+        assert!(floor < 0.2, "deepest dropout {floor}");
+        assert!(dropped.iter().filter(|&&v| v == 0.5).count() > dropped.len() / 2);
+
+        // Hum and drift on silence
+        let silence = vec![0.0f32; rate as usize];
+        let hum = degrade(
+            &silence,
+            rate,
+            &only(TapeDegradation {
+                hum_amplitude: 0.1,
+                hum_hz: 50.0,
+                ..Default::default()
+            }),
+        );
+        assert!(
+            (rms(&hum) - 0.1 * (1.1125f32 / 2.0).sqrt()).abs() < 0.005,
+            "hum rms {}",
+            rms(&hum)
+        );
+        let drift = degrade(
+            &silence,
+            rate,
+            &only(TapeDegradation {
+                dc_drift: 0.1,
+                ..Default::default()
+            }),
+        );
+        assert!(drift.iter().all(|v| v.abs() <= 0.1) && rms(&drift) > 0.01);
+
+        // Low-pass removes a tone above its edge, clipping caps the level
+        let high = generate_sine_wave(5_000.0, 1.0, rate, 0.8);
+        let filtered = degrade(
+            &high,
+            rate,
+            &only(TapeDegradation {
+                lowpass_hz: 1_000.0,
+                clip_level: 0.5,
+                ..Default::default()
+            }),
+        );
+        assert!(rms(&filtered[1_000..]) < 0.03, "filtered rms {}", rms(&filtered[1_000..]));
+        let clipped = degrade(
+            &high,
+            rate,
+            &only(TapeDegradation {
+                clip_level: 0.5,
+                ..Default::default()
+            }),
+        );
+        assert!(clipped.iter().all(|v| v.abs() <= 0.5));
+
+        // Lossy coding keeps a strong tone but drops a quiet one beside it
+        let loud = generate_sine_wave(440.0, 1.0, rate, 0.5);
+        let quiet = generate_sine_wave(3_000.0, 1.0, rate, 0.01);
+        let mix: Vec<f32> = loud.iter().zip(&quiet).map(|(a, b)| a + b).collect();
+        let coded = degrade(
+            &mix,
+            rate,
+            &only(TapeDegradation {
+                lossy: 0.5,
+                ..Default::default()
+            }),
+        );
+        assert_eq!(coded.len(), mix.len());
+        let kept =
+            |tone: &[f32]| coded.iter().zip(tone).map(|(c, t)| c * t).sum::<f32>() / tone.iter().map(|t| t * t).sum::<f32>();
+        assert!(
+            kept(&loud) > 0.9 && kept(&quiet) < 0.2,
+            "kept {} / {}",
+            kept(&loud),
+            kept(&quiet)
+        );
+    }
+
+    #[test]
+    fn test_crosstalk_bleeds_between_tracks() {
+        let left = generate_sine_wave(440.0, 0.1, 8_000, 0.5);
+        let right = vec![0.0f32; left.len()];
+        let tape = TapeDegradation {
+            crosstalk: 0.1,
+            ..Default::default()
+        };
+        let (l, r) = degrade_stereo(&left, &right, 8_000, &tape);
+        assert_eq!(l, left);
+        for (bleed, source) in r.iter().zip(&left) {
+            assert!((bleed - 0.1 * source).abs() < 1e-6);
+        }
+    }
+
     #[test]
     fn test_sine_wave_generation() {
         let signal = generate_sine_wave(440.0, 0.1, 44100, 0.5);
@@ -3,9 +3,17 @@ use std::sync::{atomic::AtomicBool, Arc};
 
 use eframe::egui;
 
+use crate::audio::WaveformChannel;
+use crate::backend;
+use crate::codec;
 use crate::sstv::DecoderMode;
 use crate::ui::theme;
 
+/// Physical channels offered by the channel selector. Queued files are not
+/// opened until processing, so the list cannot follow their layout; a file
+/// without the chosen channel fails on its own.
+const SELECTABLE_CHANNELS: u16 = 8;
+
 // The batch domain types live in the service layer so the backend doesn't
 // depend on the UI; re-exported here for the panel and existing call sites.
 pub use crate::services::batch::{BatchItem, BatchStatus};
@@ -15,6 +23,8 @@ pub struct BatchPanel {
     pub queue: Vec<BatchItem>,
     pub output_dir: Option<PathBuf>,
     pub selected_mode: DecoderMode,
+    /// Channel override for every file; `None` keeps the preset's.
+    pub selected_channel: Option<WaveformChannel>,
     pub is_processing: bool,
     pub current_index: usize,
     pub progress: f32,
@@ -28,6 +38,7 @@ impl Default for BatchPanel {
             queue: Vec::new(),
             output_dir: None,
             selected_mode: DecoderMode::Grayscale,
+            selected_channel: None,
             is_processing: false,
             current_index: 0,
             progress: 0.0,
@@ -58,7 +69,10 @@ impl BatchPanel {
         theme::panel_frame().show(ui, |ui| {
             ui.horizontal(|ui| {
                 if ui.button("Add Files…").clicked() {
-                    if let Some(paths) = rfd::FileDialog::new().add_filter("WAV", &["wav"]).pick_files() {
+                    if let Some(paths) = rfd::FileDialog::new()
+                        .add_filter("Audio", codec::AUDIO_EXTENSIONS)
+                        .pick_files()
+                    {
                         for path in paths {
                             if !self.queue.iter().any(|item| item.path == path) {
                                 self.queue.push(BatchItem {
@@ -89,13 +103,25 @@ impl BatchPanel {
             ui.horizontal(|ui| {
                 ui.label(egui::RichText::new("Mode:").color(theme::TEXT_MUTED));
                 egui::ComboBox::from_id_salt("batch_mode_combo")
-                    .selected_text(match self.selected_mode {
-                        DecoderMode::Grayscale => "Binary (B/W)",
-                        DecoderMode::PseudoColor => "PseudoColor",
-                    })
+                    .selected_text(backend::for_mode(self.selected_mode).info().name)
+                    .show_ui(ui, |ui| {
+                        for backend in backend::backends() {
+                            let info = backend.info();
+                            ui.selectable_value(&mut self.selected_mode, info.mode, info.name)
+                                .on_hover_text(info.description);
+                        }
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Channel:").color(theme::TEXT_MUTED));
+                egui::ComboBox::from_id_salt("batch_channel_combo")
+                    .selected_text(self.selected_channel.map_or("Preset".to_string(), WaveformChannel::label))
                     .show_ui(ui, |ui| {
-                        ui.selectable_value(&mut self.selected_mode, DecoderMode::Grayscale, "Binary (B/W)");
-                        ui.selectable_value(&mut self.selected_mode, DecoderMode::PseudoColor, "PseudoColor");
+                        ui.selectable_value(&mut self.selected_channel, None, "Preset");
+                        for channel in WaveformChannel::available(SELECTABLE_CHANNELS) {
+                            ui.selectable_value(&mut self.selected_channel, Some(channel), channel.label());
+                        }
                     });
             });
         });
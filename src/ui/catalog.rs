@@ -0,0 +1,303 @@
+//! Catalog browser: a thumbnail grid of every frame on each stereo channel,
+//! labeled from the reference catalog with color-triplet members marked by
+//! their plane. Segmentation and thumbnails come from a background
+//! [`CatalogScan`]; clicking a frame jumps there.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use eframe::egui;
+use egui::{Color32, TextureHandle};
+
+use crate::audio::WaveformChannel;
+use crate::catalog::{channel_catalog, ColorRole};
+use crate::pipeline::RecordFrames;
+use crate::sample_source::SampleSource;
+use crate::services::catalog::{CatalogMsg, CatalogScan, THUMBNAIL_WIDTH};
+use crate::sstv::DecoderParams;
+use crate::ui::theme;
+
+/// Frames of one channel and the thumbnails received so far.
+struct ChannelFrames {
+    channel: WaveformChannel,
+    frames: Option<Arc<RecordFrames>>,
+    thumbnails: Vec<Thumbnail>,
+    error: Option<String>,
+}
+
+/// Where one frame's thumbnail stands.
+#[derive(Clone)]
+enum Thumbnail {
+    Pending,
+    Ready(TextureHandle),
+    /// The frame did not decode; the cell stays a placeholder
+    Failed,
+}
+
+/// Floating catalog window; see the module docs.
+#[derive(Default)]
+pub struct CatalogPanel {
+    pub visible: bool,
+    /// Channel whose grid is shown
+    channel: Option<WaveformChannel>,
+    channels: Vec<ChannelFrames>,
+    scan: Option<CatalogScan>,
+}
+
+impl CatalogPanel {
+    /// Drop the frames and thumbnails. Must be called when the file or the
+    /// settings segmentation depends on (line cadence, speed factor) change.
+    pub fn invalidate(&mut self) {
+        self.channels.clear();
+        self.scan = None;
+    }
+
+    /// Start the background scan when the panel is open and holds nothing
+    /// for the loaded file. `known` is a channel already segmented by the
+    /// app, reused rather than segmented again.
+    pub fn ensure_scan(&mut self, source: &Arc<dyn SampleSource>, params: &DecoderParams, known: Option<&Arc<RecordFrames>>) {
+        if !self.visible || self.scan.is_some() || !self.channels.is_empty() || source.is_empty() {
+            return;
+        }
+        let mut channels = vec![WaveformChannel::Left];
+        if source.channels() >= 2 {
+            channels.push(WaveformChannel::Right);
+        }
+        let request = channels
+            .iter()
+            .map(|&channel| (channel, known.filter(|f| f.channel() == channel).cloned()))
+            .collect();
+        self.channels = channels
+            .into_iter()
+            .map(|channel| ChannelFrames {
+                channel,
+                frames: None,
+                thumbnails: Vec::new(),
+                error: None,
+            })
+            .collect();
+        self.scan = Some(CatalogScan::start(Arc::clone(source), request, *params));
+    }
+
+    fn poll_scan(&mut self, ctx: &egui::Context) {
+        let Some(scan) = &self.scan else {
+            return;
+        };
+        for msg in scan.poll() {
+            match msg {
+                CatalogMsg::Segmented(channel, frames) => {
+                    if let Some(entry) = self.channels.iter_mut().find(|c| c.channel == channel) {
+                        entry.thumbnails = vec![Thumbnail::Pending; frames.bounds().len()];
+                        entry.frames = Some(frames);
+                    }
+                }
+                CatalogMsg::Thumbnail(channel, index, image) => {
+                    let Some(slot) = self
+                        .channels
+                        .iter_mut()
+                        .find(|c| c.channel == channel)
+                        .and_then(|c| c.thumbnails.get_mut(index))
+                    else {
+                        continue;
+                    };
+                    let size = [image.width() as usize, image.height() as usize];
+                    let pixels = image.pixels().map(|p| Color32::from_gray(p[0])).collect();
+                    let texture = egui::ColorImage::new(size, pixels);
+                    *slot = Thumbnail::Ready(ctx.load_texture(
+                        format!("catalog_{}_{index}", channel.label()),
+                        texture,
+                        Default::default(),
+                    ));
+                }
+                CatalogMsg::ThumbnailFailed(channel, index) => {
+                    if let Some(slot) = self
+                        .channels
+                        .iter_mut()
+                        .find(|c| c.channel == channel)
+                        .and_then(|c| c.thumbnails.get_mut(index))
+                    {
+                        *slot = Thumbnail::Failed;
+                    }
+                }
+                CatalogMsg::Failed(channel, error) => {
+                    if let Some(entry) = self.channels.iter_mut().find(|c| c.channel == channel) {
+                        entry.error = Some(error);
+                    }
+                }
+            }
+        }
+        let done = self
+            .channels
+            .iter()
+            .all(|c| c.error.is_some() || (c.frames.is_some() && c.thumbnails.iter().all(|t| !matches!(t, Thumbnail::Pending))));
+        if !done {
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+    }
+
+    /// Draw the window. `current` is the channel and playhead, to highlight
+    /// the frame being played. Returns the channel and start sample of a
+    /// clicked frame.
+    pub fn draw(&mut self, ctx: &egui::Context, current: (WaveformChannel, usize)) -> Option<(WaveformChannel, usize)> {
+        // A closed panel lets the scan run on; its results queue until reopened
+        if !self.visible {
+            return None;
+        }
+        self.poll_scan(ctx);
+
+        let mut visible = self.visible;
+        let mut jump = None;
+        egui::Window::new("Catalog")
+            .open(&mut visible)
+            .resize(|r| r.default_size([640.0, 520.0]))
+            .show(ctx, |ui| {
+                jump = self.draw_content(ui, current);
+            });
+        self.visible = visible;
+        jump
+    }
+
+    fn draw_content(&mut self, ui: &mut egui::Ui, current: (WaveformChannel, usize)) -> Option<(WaveformChannel, usize)> {
+        if self.channels.is_empty() {
+            ui.label(egui::RichText::new("Load a recording to browse its frames").color(theme::TEXT_MUTED));
+            return None;
+        }
+        let shown = self
+            .channel
+            .filter(|channel| self.channels.iter().any(|c| c.channel == *channel))
+            .unwrap_or(self.channels[0].channel);
+        ui.horizontal(|ui| {
+            for entry in &self.channels {
+                let count = entry.frames.as_ref().map_or(0, |f| f.bounds().len());
+                let label = format!("{} ({count})", entry.channel.label());
+                if ui.selectable_label(shown == entry.channel, label).clicked() {
+                    self.channel = Some(entry.channel);
+                }
+            }
+        });
+        ui.add_space(4.0);
+
+        let entry = self.channels.iter().find(|c| c.channel == shown)?;
+        if let Some(error) = &entry.error {
+            ui.colored_label(theme::ERROR, format!("Segmentation failed: {error}"));
+            return None;
+        }
+        let Some(frames) = &entry.frames else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(egui::RichText::new("Segmenting into frames…").color(theme::TEXT_MUTED));
+            });
+            return None;
+        };
+        let aligned = frames.is_catalog_aligned();
+        if !aligned {
+            ui.colored_label(
+                theme::AMBER,
+                format!(
+                    "{} frames found; the catalog has {} per channel, so frames are left unlabeled",
+                    frames.bounds().len(),
+                    crate::catalog::FRAMES_PER_CHANNEL
+                ),
+            );
+        }
+        let catalog = channel_catalog(entry.channel);
+        let playing = (current.0 == entry.channel).then(|| frames.frame_at(current.1)).flatten();
+
+        let mut jump = None;
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                ui.spacing_mut().item_spacing = egui::vec2(6.0, 6.0);
+                for (index, bounds) in frames.bounds().iter().enumerate() {
+                    let catalog_entry = aligned.then(|| &catalog[index]);
+                    let cell = frame_cell(
+                        ui,
+                        index,
+                        entry.thumbnails.get(index).unwrap_or(&Thumbnail::Pending),
+                        catalog_entry,
+                        playing == Some(index),
+                    );
+                    let hover = match catalog_entry {
+                        Some(c) => format!("{index}: {}\n{:.1}–{:.1} s", c.label, bounds.start_secs, bounds.end_secs),
+                        None => format!("Frame {index}\n{:.1}–{:.1} s", bounds.start_secs, bounds.end_secs),
+                    };
+                    if cell.on_hover_text(hover).clicked() {
+                        jump = Some((entry.channel, bounds.start_sample));
+                    }
+                }
+            });
+        });
+        jump
+    }
+}
+
+/// Plane color marking a color-triplet member.
+fn role_color(role: ColorRole) -> Option<Color32> {
+    match role {
+        ColorRole::Bnw => None,
+        ColorRole::Red => Some(Color32::from_rgb(0xE0, 0x4F, 0x4F)),
+        ColorRole::Grn => Some(Color32::from_rgb(0x4F, 0xC8, 0x6A)),
+        ColorRole::Blu => Some(Color32::from_rgb(0x4F, 0x7F, 0xE0)),
+    }
+}
+
+/// One grid cell: the thumbnail (or a placeholder while it is pending, or
+/// a cross for a frame that did not decode), the frame number and short
+/// title, a plane-colored bar for triplet members, and an accent outline on
+/// the frame being played.
+fn frame_cell(
+    ui: &mut egui::Ui,
+    index: usize,
+    thumbnail: &Thumbnail,
+    entry: Option<&crate::catalog::CatalogEntry>,
+    playing: bool,
+) -> egui::Response {
+    let width = THUMBNAIL_WIDTH as f32;
+    let image_height = width * 0.75;
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(width, image_height + 18.0), egui::Sense::click());
+    let painter = ui.painter_at(rect.expand(2.0));
+    let image_rect = egui::Rect::from_min_size(rect.min, egui::vec2(width, image_height));
+    painter.rect_filled(image_rect, 2.0, theme::WELL);
+    match thumbnail {
+        Thumbnail::Ready(texture) => {
+            // Letterboxed into the cell at the thumbnail's own aspect
+            let size = texture.size_vec2();
+            let scale = (width / size.x).min(image_height / size.y);
+            let fitted = egui::Rect::from_center_size(image_rect.center(), size * scale);
+            painter.image(
+                texture.id(),
+                fitted,
+                egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+                Color32::WHITE,
+            );
+        }
+        Thumbnail::Pending | Thumbnail::Failed => {
+            let mark = if matches!(thumbnail, Thumbnail::Failed) { "×" } else { "…" };
+            painter.text(
+                image_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                mark,
+                egui::FontId::proportional(14.0),
+                theme::TEXT_MUTED,
+            );
+        }
+    }
+    if let Some(color) = entry.and_then(|e| role_color(e.color)) {
+        let bar = egui::Rect::from_min_size(image_rect.left_bottom() - egui::vec2(0.0, 3.0), egui::vec2(width, 3.0));
+        painter.rect_filled(bar, 0.0, color);
+    }
+    if playing || response.hovered() {
+        let color = if playing { theme::ACCENT } else { theme::TEXT_MUTED };
+        painter.rect_stroke(image_rect, 2.0, egui::Stroke::new(1.5, color), egui::StrokeKind::Outside);
+    }
+    // Titles are "Title, Credit": the title alone fits under a thumbnail
+    let title = entry.map_or("", |e| e.label.split(',').next().unwrap_or(e.label));
+    let caption = painter.layout_no_wrap(
+        format!("{index} {title}"),
+        egui::FontId::proportional(10.0),
+        theme::TEXT_MUTED,
+    );
+    let caption_rect = egui::Rect::from_min_size(image_rect.left_bottom() + egui::vec2(0.0, 2.0), egui::vec2(width, 14.0));
+    ui.painter_at(caption_rect)
+        .galley(caption_rect.min, caption, theme::TEXT_MUTED);
+    response
+}
@@ -0,0 +1,422 @@
+//! Reference comparison window: pick a directory of reference decodes,
+//! score every decoded frame against its reference in the background, and
+//! review the table with each frame side by side with its reference.
+//! Scores can be saved as a baseline and later runs flagged against it.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use eframe::egui;
+use egui::{Color32, TextureHandle};
+use image::GrayImage;
+
+use crate::audio::WaveformChannel;
+use crate::catalog::{channel_catalog, CatalogEntry, FRAMES_PER_CHANNEL};
+use crate::compare::{Baseline, Comparison, Orientation, Reference, Similarity, Tolerance};
+use crate::pipeline::RecordFrames;
+use crate::sample_source::SampleSource;
+use crate::services::compare::{CompareMsg, CompareRun};
+use crate::sstv::DecoderParams;
+use crate::ui::theme;
+
+/// What a run compares against: the loaded file and the app's settings.
+pub struct CompareInput<'a> {
+    pub source: Option<&'a Arc<dyn SampleSource>>,
+    pub channel: WaveformChannel,
+    pub params: &'a DecoderParams,
+    /// The channel's frames when the app has segmented it already
+    pub frames: Option<&'a Arc<RecordFrames>>,
+}
+
+/// One reference and, once scored, its result.
+struct Row {
+    reference: Reference,
+    outcome: Option<Result<Scored, String>>,
+}
+
+/// A scored reference with its images uploaded for display.
+struct Scored {
+    similarity: Similarity,
+    decoded: TextureHandle,
+    reference: TextureHandle,
+}
+
+/// Floating compare window; see the module docs.
+#[derive(Default)]
+pub struct ComparePanel {
+    pub visible: bool,
+    reference_dir: Option<PathBuf>,
+    orientation: Orientation,
+    baseline: Option<(PathBuf, Baseline)>,
+    tolerance: Tolerance,
+    rows: Vec<Row>,
+    /// Channel the rows were compared on, with its catalog when the
+    /// segmentation lines up with it
+    channel: Option<(WaveformChannel, bool)>,
+    selected: Option<usize>,
+    run: Option<CompareRun>,
+    error: Option<String>,
+}
+
+impl ComparePanel {
+    /// Drop results and stop a run; called when another file is loaded.
+    pub fn invalidate(&mut self) {
+        self.rows.clear();
+        self.channel = None;
+        self.selected = None;
+        self.run = None;
+        self.error = None;
+    }
+
+    fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    fn start(&mut self, input: &CompareInput, dir: PathBuf) {
+        let Some(source) = input.source else {
+            return;
+        };
+        self.invalidate();
+        let frames = input.frames.filter(|f| f.channel() == input.channel).cloned();
+        self.run = Some(CompareRun::start(
+            Arc::clone(source),
+            input.channel,
+            frames,
+            dir,
+            *input.params,
+            self.orientation,
+        ));
+    }
+
+    fn poll_run(&mut self, ctx: &egui::Context) {
+        let Some(run) = &self.run else {
+            return;
+        };
+        let mut finished = false;
+        for msg in run.poll() {
+            match msg {
+                CompareMsg::References(references) => {
+                    self.rows = references
+                        .into_iter()
+                        .map(|reference| Row {
+                            reference,
+                            outcome: None,
+                        })
+                        .collect();
+                }
+                CompareMsg::Segmented(frames) => {
+                    self.channel = Some((frames.channel(), frames.is_catalog_aligned()));
+                }
+                CompareMsg::Compared(index, result) => {
+                    let Some(row) = self.rows.get_mut(index) else {
+                        continue;
+                    };
+                    row.outcome = Some(result.map(|comparison| upload(ctx, index, comparison)));
+                    if self.selected.is_none() {
+                        self.selected = Some(index);
+                    }
+                    finished = index + 1 == self.rows.len();
+                }
+                CompareMsg::Failed(error) => {
+                    self.error = Some(error);
+                    finished = true;
+                }
+            }
+        }
+        if finished {
+            self.run = None;
+        } else {
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+    }
+
+    /// Draw the window. A run started here compares `input`.
+    pub fn draw(&mut self, ctx: &egui::Context, input: CompareInput) {
+        // Results keep arriving while the window is closed; they are read
+        // when it reopens
+        if !self.visible {
+            return;
+        }
+        self.poll_run(ctx);
+
+        let mut visible = self.visible;
+        egui::Window::new("Compare with references")
+            .open(&mut visible)
+            .resize(|r| r.default_size([720.0, 620.0]))
+            .show(ctx, |ui| {
+                self.draw_content(ui, &input);
+            });
+        self.visible = visible;
+    }
+
+    fn draw_content(&mut self, ui: &mut egui::Ui, input: &CompareInput) {
+        theme::section_label(ui, "References");
+        theme::panel_frame().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Reference Dir…").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                        self.reference_dir = Some(path);
+                    }
+                }
+                match &self.reference_dir {
+                    Some(dir) => ui.monospace(dir.to_string_lossy()),
+                    None => ui.colored_label(theme::AMBER, "Not selected"),
+                };
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.orientation.rotate, "Rotate 90°")
+                    .on_hover_text("Turn decodes to match the references (Voyager lines are vertical scans)");
+                ui.checkbox(&mut self.orientation.flip, "Flip");
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Load Baseline…").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().add_filter("Baseline", &["toml"]).pick_file() {
+                        match Baseline::load(&path) {
+                            Ok(baseline) => self.baseline = Some((path, baseline)),
+                            Err(e) => self.error = Some(format!("{e:#}")),
+                        }
+                    }
+                }
+                let scores = self.scores();
+                if ui
+                    .add_enabled(!scores.is_empty() && !self.is_running(), egui::Button::new("Save Baseline…"))
+                    .clicked()
+                {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Baseline", &["toml"])
+                        .set_file_name("compare_baseline.toml")
+                        .save_file()
+                    {
+                        let baseline = Baseline::from_scores(scores);
+                        match baseline.save(&path) {
+                            Ok(()) => self.baseline = Some((path, baseline)),
+                            Err(e) => self.error = Some(format!("{e:#}")),
+                        }
+                    }
+                }
+                match &self.baseline {
+                    Some((path, baseline)) => {
+                        let name = path.file_name().unwrap_or_default().to_string_lossy();
+                        ui.label(
+                            egui::RichText::new(format!("{name} ({} scores)", baseline.frames.len())).color(theme::TEXT_MUTED),
+                        );
+                        if ui
+                            .small_button("✕")
+                            .on_hover_text("Stop comparing with this baseline")
+                            .clicked()
+                        {
+                            self.baseline = None;
+                        }
+                    }
+                    None => {
+                        ui.label(egui::RichText::new("No baseline").color(theme::TEXT_MUTED));
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                if self.is_running() {
+                    if ui.button("Cancel").clicked() {
+                        self.run = None;
+                    }
+                    let done = self.rows.iter().filter(|r| r.outcome.is_some()).count();
+                    ui.spinner();
+                    ui.label(format!("{done} / {}", self.rows.len()));
+                } else {
+                    let ready = input.source.is_some() && self.reference_dir.is_some();
+                    let run = ui
+                        .add_enabled(ready, egui::Button::new("▶ Compare"))
+                        .on_disabled_hover_text("Load a recording and pick a reference directory");
+                    if run.clicked() {
+                        if let Some(dir) = self.reference_dir.clone() {
+                            self.start(input, dir);
+                        }
+                    }
+                    ui.label(
+                        egui::RichText::new(format!("{} channel, current decoder settings", input.channel.label()))
+                            .color(theme::TEXT_MUTED),
+                    );
+                }
+            });
+            if let Some(error) = &self.error {
+                ui.colored_label(theme::ERROR, error);
+            }
+        });
+
+        if self.rows.is_empty() {
+            return;
+        }
+        ui.add_space(8.0);
+        self.draw_summary(ui);
+        self.draw_table(ui);
+        self.draw_side_by_side(ui);
+    }
+
+    /// Scores of every compared reference.
+    fn scores(&self) -> Vec<(crate::compare::ReferenceKey, Similarity)> {
+        self.rows
+            .iter()
+            .filter_map(|row| match &row.outcome {
+                Some(Ok(scored)) => Some((row.reference.key, scored.similarity)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn catalog_entry(&self, frame: usize) -> Option<&'static CatalogEntry> {
+        match self.channel {
+            Some((channel, true)) => channel_catalog(channel).get(frame),
+            _ => None,
+        }
+    }
+
+    /// Names of the metrics of `row` that regressed against the baseline;
+    /// a reference that no longer compares counts as failed.
+    fn regressions(&self, row: &Row) -> Vec<&'static str> {
+        let Some(previous) = self.baseline.as_ref().and_then(|(_, b)| b.get(row.reference.key)) else {
+            return Vec::new();
+        };
+        match &row.outcome {
+            Some(Ok(scored)) => self.tolerance.regressions(&scored.similarity, previous),
+            Some(Err(_)) => vec!["failed"],
+            None => Vec::new(),
+        }
+    }
+
+    fn draw_summary(&self, ui: &mut egui::Ui) {
+        let scores = self.scores();
+        let regressed = self.rows.iter().filter(|row| !self.regressions(row).is_empty()).count();
+        ui.horizontal(|ui| {
+            theme::section_label(ui, &format!("Results ({} of {})", scores.len(), self.rows.len()));
+            if !scores.is_empty() {
+                let mean =
+                    |metric: fn(&Similarity) -> f64| scores.iter().map(|(_, s)| metric(s)).sum::<f64>() / scores.len() as f64;
+                ui.label(
+                    egui::RichText::new(format!(
+                        "mean SSIM {:.3} · PSNR {:.2} dB · corr {:.3}",
+                        mean(|s| s.ssim),
+                        mean(|s| s.psnr_db),
+                        mean(|s| s.correlation)
+                    ))
+                    .color(theme::TEXT_MUTED),
+                );
+            }
+            if regressed > 0 {
+                ui.colored_label(theme::ERROR, format!("{regressed} regressed"));
+            }
+        });
+        if let Some((channel, false)) = self.channel {
+            ui.colored_label(
+                theme::AMBER,
+                format!(
+                    "{} is not segmented into the catalog's {FRAMES_PER_CHANNEL} frames; indices may not match the references",
+                    channel.label()
+                ),
+            );
+        }
+    }
+
+    fn draw_table(&mut self, ui: &mut egui::Ui) {
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .id_salt("compare_table")
+            .max_height(220.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                egui::Grid::new("compare_grid")
+                    .striped(true)
+                    .num_columns(7)
+                    .spacing([12.0, 2.0])
+                    .show(ui, |ui| {
+                        for heading in ["Reference", "SSIM", "PSNR", "Corr", "ΔSSIM", "", "Label"] {
+                            ui.label(egui::RichText::new(heading).color(theme::TEXT_MUTED));
+                        }
+                        ui.end_row();
+
+                        for (index, row) in self.rows.iter().enumerate() {
+                            let key = row.reference.key.to_string();
+                            if ui.selectable_label(self.selected == Some(index), key).clicked() {
+                                clicked = Some(index);
+                            }
+                            let previous = self.baseline.as_ref().and_then(|(_, b)| b.get(row.reference.key));
+                            match &row.outcome {
+                                Some(Ok(scored)) => {
+                                    let s = scored.similarity;
+                                    ui.monospace(format!("{:.3}", s.ssim));
+                                    ui.monospace(format!("{:.2}", s.psnr_db));
+                                    ui.monospace(format!("{:.3}", s.correlation));
+                                    ui.monospace(previous.map_or_else(String::new, |p| format!("{:+.3}", s.ssim - p.ssim)));
+                                }
+                                Some(Err(error)) => {
+                                    ui.colored_label(theme::ERROR, "failed").on_hover_text(error);
+                                    for _ in 0..3 {
+                                        ui.label("");
+                                    }
+                                }
+                                None => {
+                                    ui.label(egui::RichText::new("…").color(theme::TEXT_MUTED));
+                                    for _ in 0..3 {
+                                        ui.label("");
+                                    }
+                                }
+                            }
+                            let worse = self.regressions(row);
+                            if worse.is_empty() {
+                                ui.label("");
+                            } else {
+                                ui.colored_label(theme::ERROR, format!("▼ {}", worse.join(", ")));
+                            }
+                            let label = self.catalog_entry(row.reference.key.frame()).map_or("", |e| e.label);
+                            ui.label(egui::RichText::new(label).color(theme::TEXT_MUTED));
+                            ui.end_row();
+                        }
+                    });
+            });
+        if clicked.is_some() {
+            self.selected = clicked;
+        }
+    }
+
+    fn draw_side_by_side(&self, ui: &mut egui::Ui) {
+        let Some(row) = self.selected.and_then(|index| self.rows.get(index)) else {
+            return;
+        };
+        ui.add_space(8.0);
+        theme::section_label(ui, &format!("{} side by side", row.reference.key));
+        let Some(Ok(scored)) = &row.outcome else {
+            if let Some(Err(error)) = &row.outcome {
+                ui.colored_label(theme::ERROR, error);
+            }
+            return;
+        };
+        let half = ((ui.available_width() - 12.0) / 2.0).max(32.0);
+        ui.horizontal_top(|ui| {
+            for (title, texture) in [("Decoded (aligned)", &scored.decoded), ("Reference", &scored.reference)] {
+                ui.vertical(|ui| {
+                    ui.label(egui::RichText::new(title).color(theme::TEXT_MUTED));
+                    let size = texture.size_vec2();
+                    let scale = (half / size.x).min(ui.available_height().max(64.0) / size.y);
+                    ui.image((texture.id(), size * scale));
+                });
+            }
+        });
+    }
+}
+
+/// Upload the images of a comparison as textures.
+fn upload(ctx: &egui::Context, index: usize, comparison: Comparison) -> Scored {
+    let texture = |name: &str, image: &GrayImage| {
+        let size = [image.width() as usize, image.height() as usize];
+        let pixels = image.pixels().map(|p| Color32::from_gray(p[0])).collect();
+        ctx.load_texture(
+            format!("compare_{name}_{index}"),
+            egui::ColorImage::new(size, pixels),
+            Default::default(),
+        )
+    };
+    Scored {
+        similarity: comparison.similarity,
+        decoded: texture("decoded", &comparison.decoded),
+        reference: texture("reference", &comparison.reference),
+    }
+}
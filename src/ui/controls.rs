@@ -1,4 +1,5 @@
-//! Transport bar: file open, playback controls, timecode readout, sync skip.
+//! Transport bar: file and project open/save, playback controls, timecode
+//! readout, sync skip, bookmarking.
 
 use eframe::egui;
 
@@ -7,9 +8,12 @@ use crate::utils::format_timecode;
 
 pub enum ControlAction {
     OpenWav,
+    OpenProject,
+    SaveProject,
     TogglePlayback,
     StopPlayback,
     SeekToNextSync,
+    AddBookmark,
 }
 
 pub struct ControlsPanel;
@@ -28,9 +32,15 @@ impl ControlsPanel {
         let mut action = None;
 
         ui.horizontal(|ui| {
-            if ui.button("Open WAV…").clicked() {
+            if ui.button("Open Audio…").clicked() {
                 action = Some(ControlAction::OpenWav);
             }
+            if ui.button("Open Project…").clicked() {
+                action = Some(ControlAction::OpenProject);
+            }
+            if ui.add_enabled(has_audio, egui::Button::new("Save Project…")).clicked() {
+                action = Some(ControlAction::SaveProject);
+            }
 
             ui.separator();
 
@@ -62,6 +72,10 @@ impl ControlsPanel {
             if ui.add_enabled(has_audio, egui::Button::new(skip_text)).clicked() {
                 action = Some(ControlAction::SeekToNextSync);
             }
+
+            if ui.add_enabled(has_audio, egui::Button::new("🔖 Bookmark")).clicked() {
+                action = Some(ControlAction::AddBookmark);
+            }
         });
 
         action
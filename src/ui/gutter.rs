@@ -0,0 +1,74 @@
+//! Sync-lock gutter beside the decoded image: one colored band per scan
+//! line showing how the decoder placed it, with the line's diagnostics on
+//! hover.
+
+use eframe::egui;
+use egui::Color32;
+
+use crate::analysis::{LineSync, SyncState};
+use crate::ui::theme;
+
+/// Gutter width in points.
+pub const GUTTER_WIDTH: f32 = 8.0;
+
+/// Gutter color for a line state: teal for locked, fading through cyan
+/// and gray to amber where the decoder was guessing.
+pub fn state_color(state: SyncState) -> Color32 {
+    match state {
+        SyncState::Locked => theme::ACCENT,
+        SyncState::Weak => theme::CYAN,
+        SyncState::Detected => theme::TEXT_MUTED,
+        SyncState::Coasted => theme::AMBER,
+        SyncState::Fixed => theme::PANEL_BORDER,
+    }
+}
+
+/// Share of `lines` anchored on a sync at both ends.
+pub fn locked_fraction(lines: &[LineSync]) -> f32 {
+    if lines.is_empty() {
+        return 0.0;
+    }
+    lines.iter().filter(|line| line.is_locked()).count() as f32 / lines.len() as f32
+}
+
+/// Draw the gutter for `lines`, one per image row, into `rect`: row 0
+/// starts `top` points below the rect's top (negative when scrolled past)
+/// and each row is `row_height` points tall. Periods in the hover text are
+/// in ms when `sample_rate` is known.
+pub fn draw(ui: &egui::Ui, rect: egui::Rect, lines: &[LineSync], top: f32, row_height: f32, sample_rate: Option<u32>) {
+    let response = ui.interact(rect, ui.id().with("sync_gutter"), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, theme::WELL);
+    if lines.is_empty() || row_height <= 0.0 {
+        return;
+    }
+    let y_of = |row: usize| rect.top() + top + row as f32 * row_height;
+
+    // One rect per run of equal state keeps tall images cheap to paint
+    let mut run_start = 0;
+    for i in 1..=lines.len() {
+        if i < lines.len() && lines[i].state == lines[run_start].state {
+            continue;
+        }
+        let band = egui::Rect::from_min_max(egui::pos2(rect.left(), y_of(run_start)), egui::pos2(rect.right(), y_of(i)));
+        if band.intersects(rect) {
+            painter.rect_filled(band, 0.0, state_color(lines[run_start].state));
+        }
+        run_start = i;
+    }
+
+    if let Some(pointer) = response.hover_pos() {
+        let row = (pointer.y - rect.top() - top) / row_height;
+        let Some(&line) = (row >= 0.0).then(|| lines.get(row as usize)).flatten() else {
+            return;
+        };
+        let period = match sample_rate {
+            Some(rate) => format!("{:.3} ms", line.period / rate as f64 * 1000.0),
+            None => format!("{:.1} samples", line.period),
+        };
+        response.on_hover_text(format!(
+            "line {}: {} · swing {:.2}× threshold · period {period}",
+            row as usize, line.state, line.swing
+        ));
+    }
+}
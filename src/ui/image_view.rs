@@ -0,0 +1,285 @@
+//! Decoded-image viewport: scroll to zoom around the pointer with
+//! nearest-neighbor magnification, drag to pan, fit-width and 1:1 zoom,
+//! and a readout of the pixel under the pointer. Rows that map to the audio
+//! show their timecode, seek on click and carry a playhead marker; the sync
+//! gutter runs alongside.
+
+use eframe::egui;
+use egui::{Pos2, Rect, TextureHandle, Vec2};
+
+use crate::pipeline::PipelineResult;
+use crate::ui::{gutter, theme};
+use crate::utils::format_timecode;
+
+/// Texture sampling for the decoded image: magnified pixels stay square,
+/// zoomed-out views are still filtered.
+pub const TEXTURE_OPTIONS: egui::TextureOptions = egui::TextureOptions {
+    magnification: egui::TextureFilter::Nearest,
+    ..egui::TextureOptions::LINEAR
+};
+
+/// Deepest zoom, in points per image pixel.
+const MAX_SCALE: f32 = 32.0;
+
+/// Zoom factor per point of scroll-wheel travel.
+const ZOOM_PER_SCROLL_POINT: f32 = 0.004;
+
+/// From this many points per pixel the hovered pixel is outlined.
+const OUTLINE_SCALE: f32 = 6.0;
+
+/// Height of the pixel readout along the bottom of the viewport.
+const READOUT_HEIGHT: f32 = 18.0;
+
+/// Visible part of the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct View {
+    /// Image coordinates at the viewport's top-left corner
+    origin: Vec2,
+    /// Points per image pixel
+    scale: f32,
+}
+
+impl View {
+    fn image_at(&self, viewport: Rect, pos: Pos2) -> Vec2 {
+        self.origin + (pos - viewport.min) / self.scale
+    }
+
+    fn screen_of(&self, viewport: Rect, image: Vec2) -> Pos2 {
+        viewport.min + (image - self.origin) * self.scale
+    }
+
+    /// Keep the image covering the viewport where it is large enough to;
+    /// a smaller dimension stays pinned to the top-left.
+    fn clamp_to(&mut self, viewport: Vec2, image: Vec2) {
+        let max = (image - viewport / self.scale).max(Vec2::ZERO);
+        self.origin = self.origin.clamp(Vec2::ZERO, max);
+    }
+}
+
+/// Zoom and pan of the decoded image, kept across decodes so a live image
+/// can be watched at one magnification.
+#[derive(Debug, Default)]
+pub struct ImageView {
+    /// Points per image pixel; `None` fits the image width
+    scale: Option<f32>,
+    origin: Vec2,
+    /// Playhead row seen on the previous frame, to page the view when
+    /// playback runs off its edge
+    last_marker: Option<f32>,
+}
+
+impl ImageView {
+    pub fn is_fit(&self) -> bool {
+        self.scale.is_none()
+    }
+
+    /// Whether one image pixel covers one screen pixel.
+    pub fn is_actual_size(&self, pixels_per_point: f32) -> bool {
+        self.scale.is_some_and(|s| (s * pixels_per_point - 1.0).abs() < 1e-3)
+    }
+
+    /// Fit the image width, back at the top.
+    pub fn fit(&mut self) {
+        self.scale = None;
+        self.origin = Vec2::ZERO;
+    }
+
+    /// One image pixel per screen pixel.
+    pub fn actual_size(&mut self, pixels_per_point: f32) {
+        self.scale = Some(1.0 / pixels_per_point.max(0.01));
+    }
+
+    /// Draw `result` (uploaded as `texture`) filling the available space.
+    /// `playhead` is the playback position in source samples, converted to
+    /// timecodes with `sample_rate`. Returns a position to seek to when a
+    /// row that maps to the audio is clicked.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        texture: &TextureHandle,
+        result: &PipelineResult,
+        playhead: usize,
+        sample_rate: Option<u32>,
+    ) -> Option<usize> {
+        let size = texture.size_vec2();
+        let (rect, response) = ui.allocate_exact_size(ui.available_size(), egui::Sense::click_and_drag());
+        let line_sync = result
+            .line_sync
+            .as_deref()
+            .filter(|sync| sync.len() == result.height as usize);
+        let viewport = match line_sync {
+            Some(_) => rect.with_max_x(rect.right() - gutter::GUTTER_WIDTH - 2.0),
+            None => rect,
+        };
+        if size.x < 1.0 || size.y < 1.0 || viewport.width() < 1.0 || viewport.height() < 1.0 {
+            return None;
+        }
+
+        let fit = viewport.width() / size.x;
+        let min_scale = fit.min(viewport.height() / size.y).min(1.0);
+        let mut view = View {
+            origin: self.origin,
+            scale: self.scale.unwrap_or(fit).clamp(min_scale, MAX_SCALE),
+        };
+        let pointer = response.hover_pos().filter(|p| viewport.contains(*p));
+
+        if let Some(pointer) = pointer {
+            let (scroll, zoom) = ui.input(|i| (i.smooth_scroll_delta.y, i.zoom_delta()));
+            let factor = zoom * (scroll * ZOOM_PER_SCROLL_POINT).exp();
+            if factor != 1.0 {
+                // Keep the pixel under the pointer fixed while zooming
+                let anchor = view.image_at(viewport, pointer);
+                view.scale = (view.scale * factor).clamp(min_scale, MAX_SCALE);
+                view.origin = anchor - (pointer - viewport.min) / view.scale;
+                self.scale = Some(view.scale);
+            }
+        }
+        if response.double_clicked() {
+            self.scale = None;
+            view = View {
+                origin: Vec2::ZERO,
+                scale: fit,
+            };
+        }
+        if response.dragged() {
+            view.origin -= response.drag_delta() / view.scale;
+        }
+
+        // Page down (or up) when playback carries the marker out of a view
+        // it was visible in; seeks elsewhere leave the view alone
+        let marker = playhead_row(result, playhead);
+        let rows_visible = |view: &View, row: f32| (view.origin.y..view.origin.y + viewport.height() / view.scale).contains(&row);
+        if let (Some(row), Some(last)) = (marker, self.last_marker) {
+            if row != last && rows_visible(&view, last) && !rows_visible(&view, row) {
+                view.origin.y = row - 0.1 * viewport.height() / view.scale;
+            }
+        }
+        self.last_marker = marker;
+
+        view.clamp_to(viewport.size(), size);
+        self.origin = view.origin;
+
+        let painter = ui.painter_at(viewport);
+        painter.rect_filled(viewport, 0.0, theme::WELL);
+        let image_rect = Rect::from_min_size(view.screen_of(viewport, Vec2::ZERO), size * view.scale);
+        painter.image(
+            texture.id(),
+            image_rect,
+            Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+            egui::Color32::WHITE,
+        );
+
+        if let Some(sync) = line_sync {
+            let gutter_rect = rect.with_min_x(rect.right() - gutter::GUTTER_WIDTH);
+            gutter::draw(ui, gutter_rect, sync, image_rect.top() - rect.top(), view.scale, sample_rate);
+        }
+
+        if let Some(row) = marker {
+            let y = view.screen_of(viewport, Vec2::new(0.0, row)).y;
+            painter.hline(viewport.x_range(), y, egui::Stroke::new(1.5, theme::ACCENT));
+        }
+
+        if self.scale.is_some() {
+            painter.text(
+                viewport.right_top() + Vec2::new(-6.0, 4.0),
+                egui::Align2::RIGHT_TOP,
+                format!(
+                    "{:.0}% · double-click to fit",
+                    view.scale * ui.ctx().pixels_per_point() * 100.0
+                ),
+                egui::FontId::monospace(10.0),
+                theme::TEXT_MUTED,
+            );
+        }
+
+        // Pixel under the pointer: outline, readout, click to seek its row
+        let pixel = pointer
+            .map(|p| view.image_at(viewport, p))
+            .filter(|p| p.x < size.x && p.y < size.y)?;
+        let (column, row) = (pixel.x as usize, pixel.y as usize);
+        if view.scale >= OUTLINE_SCALE {
+            let min = view.screen_of(viewport, Vec2::new(column as f32, row as f32));
+            painter.rect_stroke(
+                Rect::from_min_size(min, Vec2::splat(view.scale)),
+                0.0,
+                egui::Stroke::new(1.0, theme::AMBER),
+                egui::StrokeKind::Outside,
+            );
+        } else {
+            let y = view.screen_of(viewport, Vec2::new(0.0, row as f32 + 0.5)).y;
+            painter.hline(viewport.x_range(), y, egui::Stroke::new(1.0, theme::TEXT_MUTED));
+        }
+
+        let span = result.row_span(row);
+        let mut readout = format!("row {row} · col {column} · {}", pixel_value(result, column, row));
+        if let Some(level) = result.level_at(column, row) {
+            readout.push_str(&format!(" · level {level:.4}"));
+        }
+        if let (Some(span), Some(rate)) = (&span, sample_rate) {
+            readout.push_str(&format!(
+                " · {} · click to seek",
+                format_timecode(span.start as f64 / rate.max(1) as f64)
+            ));
+        }
+        let strip = viewport.with_min_y(viewport.bottom() - READOUT_HEIGHT);
+        painter.rect_filled(strip, 0.0, theme::WELL.gamma_multiply(0.85));
+        painter.text(
+            strip.left_center() + Vec2::new(6.0, 0.0),
+            egui::Align2::LEFT_CENTER,
+            readout,
+            egui::FontId::monospace(11.0),
+            theme::TEXT_BRIGHT,
+        );
+
+        if response.clicked() {
+            return span.map(|span| span.start);
+        }
+        None
+    }
+}
+
+/// Fractional row being played at source sample `position`, placed within
+/// its row by how far the line has played.
+fn playhead_row(result: &PipelineResult, position: usize) -> Option<f32> {
+    let row = result.row_at(position)?;
+    let span = result.row_span(row)?;
+    Some(row as f32 + (position - span.start) as f32 / span.len().max(1) as f32)
+}
+
+/// 8-bit value of a pixel: gray level, or R/G/B for color results.
+fn pixel_value(result: &PipelineResult, column: usize, row: usize) -> String {
+    let index = row * result.width as usize + column;
+    if result.is_color() {
+        match result.pixels.get(index * 3..index * 3 + 3) {
+            Some(&[r, g, b]) => format!("rgb {r} {g} {b}"),
+            _ => "-".to_string(),
+        }
+    } else {
+        result
+            .pixels
+            .get(index)
+            .map_or_else(|| "-".to_string(), |v| format!("value {v}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_maps_points_and_clamps_to_the_image() {
+        let viewport = Rect::from_min_size(Pos2::new(10.0, 20.0), Vec2::new(100.0, 50.0));
+        let mut view = View {
+            origin: Vec2::new(4.0, 2.0),
+            scale: 4.0,
+        };
+        let pos = Pos2::new(30.0, 40.0);
+        assert_eq!(view.image_at(viewport, pos), Vec2::new(9.0, 7.0));
+        assert_eq!(view.screen_of(viewport, view.image_at(viewport, pos)), pos);
+
+        // 25×12.5 pixels visible of a 40×10 image: pinned to the top
+        view.origin = Vec2::new(30.0, 5.0);
+        view.clamp_to(viewport.size(), Vec2::new(40.0, 10.0));
+        assert_eq!(view.origin, Vec2::new(15.0, 0.0));
+    }
+}
@@ -1,5 +1,9 @@
 pub mod batch;
+pub mod catalog;
+pub mod compare;
 pub mod controls;
+pub mod gutter;
+pub mod image_view;
 pub mod spectrum;
 pub mod theme;
 pub mod waveform;
@@ -3,7 +3,8 @@
 use eframe::egui;
 use egui_plot::{Line, Plot, PlotPoints};
 
-use crate::audio::WavReader;
+use crate::audio::WaveformChannel;
+use crate::sample_source::SampleSource;
 use crate::ui::theme;
 
 pub struct SpectrumPanel {
@@ -11,10 +12,10 @@ pub struct SpectrumPanel {
     pub use_log_scale: bool,
     pub use_db_scale: bool,
     pub show_peak: bool,
-    /// (buffer address, buffer length, window start) of the cached spectrum.
+    /// (source address, channel, source length, window start) of the cached spectrum.
     /// The FFT (and its planner) only rerun when the analysis window moves —
     /// not on every repaint, which during playback happens at frame rate.
-    spectrum_key: Option<(usize, usize, usize)>,
+    spectrum_key: Option<(usize, WaveformChannel, usize, usize)>,
     spectrum: Vec<(f64, f64)>,
 }
 
@@ -41,7 +42,7 @@ fn to_db(magnitude: f64) -> f64 {
 
 impl SpectrumPanel {
     /// Drop the cached spectrum. Must be called when a new file is loaded:
-    /// the cache key uses buffer address + length, which a new same-length
+    /// the cache key uses source address + length, which a new same-length
     /// allocation can collide with (ABA).
     pub fn invalidate(&mut self) {
         self.spectrum_key = None;
@@ -54,9 +55,9 @@ impl SpectrumPanel {
     pub fn draw(
         &mut self,
         ui: &mut egui::Ui,
-        wav_reader: &Option<WavReader>,
+        source: Option<&dyn SampleSource>,
         current_position_samples: usize,
-        selected_channel: crate::audio::WaveformChannel,
+        selected_channel: WaveformChannel,
     ) -> Option<(f64, f64)> {
         // Scale controls
         ui.horizontal(|ui| {
@@ -65,18 +66,16 @@ impl SpectrumPanel {
             ui.checkbox(&mut self.show_peak, "Peak");
         });
 
-        let Some(reader) = wav_reader else {
+        let Some(source) = source else {
             ui.add_space(4.0);
             ui.label(egui::RichText::new("No audio loaded").color(theme::TEXT_MUTED));
             return None;
         };
 
-        let samples = reader.get_samples(selected_channel);
-
         // Use a window around current position
         let window_size = 4096;
         let start = current_position_samples;
-        let end = (start + window_size).min(samples.len());
+        let end = (start + window_size).min(source.len());
 
         if start >= end {
             ui.add_space(4.0);
@@ -84,10 +83,16 @@ impl SpectrumPanel {
             return None;
         }
 
-        let key = (samples.as_ptr() as usize, samples.len(), start);
+        let source_addr = source as *const dyn SampleSource as *const () as usize;
+        let key = (source_addr, selected_channel, source.len(), start);
         if self.spectrum_key != Some(key) {
-            let window_samples = &samples[start..end];
-            self.spectrum = crate::analysis::compute_spectrum(window_samples, reader.sample_rate);
+            self.spectrum = match source.read(selected_channel, start..end) {
+                Ok(window_samples) => crate::analysis::compute_spectrum(&window_samples, source.sample_rate()),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Spectrum window read failed");
+                    Vec::new()
+                }
+            };
             self.spectrum_key = Some(key);
         }
 
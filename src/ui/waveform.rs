@@ -1,50 +1,175 @@
-//! Full-width waveform strip with click-to-seek, playhead, hover cursor,
-//! cached sync-position markers, and a time axis.
+//! Full-width waveform strip with click-to-seek, scroll-to-zoom, drag-to-pan,
+//! playhead, hover cursor, cached sync-position markers, and a time axis.
+
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use eframe::egui;
 
-use crate::audio::{WavReader, WaveformChannel};
+use crate::audio::WaveformChannel;
+use crate::envelope::{min_max, EnvelopePyramid};
+use crate::sample_source::SampleSource;
 use crate::ui::theme;
-use crate::utils::format_duration;
-
-/// Waveform strip with a cached min/max envelope. The envelope scan walks the
-/// entire channel buffer, which for the record rips is hundreds of millions
-/// of samples — recomputing it per frame during playback (continuous
-/// repaints) would freeze the UI. The cache is keyed by channel buffer
-/// identity and pixel width and rebuilt only when those change.
-#[derive(Default)]
+use crate::utils::{format_duration, format_timecode};
+
+/// Longest run of samples read per envelope column from a streamed source
+/// while the envelope pyramid is still building. Wider columns are estimated
+/// from a probe at their start, so the first paint of a multi-gigabyte rip
+/// costs a few MB of reads, not the file.
+const STREAMED_PROBE_SAMPLES: usize = 16_384;
+
+/// Visible spans up to this many frames are read from the source in one go
+/// (exact envelope) instead of going through the pyramid or probes.
+const DIRECT_READ_SAMPLES: usize = 1 << 20;
+
+/// Deepest zoom: pixels per sample at the finest level.
+const MAX_PIXELS_PER_SAMPLE: f64 = 16.0;
+
+/// Zoom factor per point of scroll-wheel travel.
+const ZOOM_PER_SCROLL_POINT: f64 = 0.004;
+
+/// At or above this many pixels per sample, each sample is drawn as a dot.
+const SAMPLE_DOT_PIXELS: f64 = 4.0;
+
+/// Visible window of the strip, in source frames.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct View {
+    /// Frame at the left edge (fractional while panning)
+    start: f64,
+    samples_per_px: f64,
+}
+
+impl View {
+    fn sample_at(&self, px: f64) -> f64 {
+        self.start + px * self.samples_per_px
+    }
+
+    fn px_of(&self, sample: usize) -> f64 {
+        (sample as f64 - self.start) / self.samples_per_px
+    }
+}
+
+/// Cached trace identity: source address, channel, source length, pixel
+/// width, view (as bits) and whether the pyramid was available.
+type TraceKey = (usize, WaveformChannel, usize, u32, (u64, u64), bool);
+
+/// What the strip draws for the current view.
+enum Trace {
+    /// Per-pixel-column (min, max) amplitude; inverted pairs are empty columns
+    Columns(Vec<(f32, f32)>),
+    /// Individual samples from frame `first`, once zoomed past one per pixel
+    Samples { first: usize, values: Vec<f32> },
+}
+
+/// Waveform strip over a multi-resolution envelope. Walking the whole channel
+/// per frame is out of the question for the record rips (hundreds of millions
+/// of samples, continuous repaints during playback), so on load an
+/// [`EnvelopePyramid`] is built on a background thread and every view is
+/// drawn from the coarsest level that still resolves a pixel. Deep zooms read
+/// the visible samples directly. The drawn trace is cached and rebuilt only
+/// when the source, channel, width or view changes.
 pub struct WaveformPanel {
-    /// (channel buffer address, buffer length, pixel width) of the cached envelope
-    envelope_key: Option<(usize, usize, u32)>,
-    /// Per-pixel-column (min, max) amplitude
-    envelope: Vec<(f32, f32)>,
+    trace_key: Option<TraceKey>,
+    trace: Trace,
+    /// Zoomed window; `None` fits the whole file to the strip
+    view: Option<View>,
+    pyramid: Option<Arc<EnvelopePyramid>>,
+    pyramid_rx: Option<Receiver<Option<EnvelopePyramid>>>,
+    /// Playhead seen on the previous frame, to page the view when playback
+    /// runs off its edge
+    last_playhead: usize,
+}
+
+impl Default for WaveformPanel {
+    fn default() -> Self {
+        Self {
+            trace_key: None,
+            trace: Trace::Columns(Vec::new()),
+            view: None,
+            pyramid: None,
+            pyramid_rx: None,
+            last_playhead: 0,
+        }
+    }
 }
 
 impl WaveformPanel {
-    /// Drop the cached envelope. Must be called when a new file is loaded:
-    /// the cache key uses buffer address + length, and the allocator can hand
-    /// a new same-length file the same address (ABA), which would silently
-    /// display the previous file's envelope.
+    /// Drop the cached trace, envelope and zoom. Must be called when a new
+    /// file is loaded: the cache key uses source address + length, and the
+    /// allocator can hand a new same-length file the same address (ABA),
+    /// which would silently display the previous file's envelope.
     pub fn invalidate(&mut self) {
-        self.envelope_key = None;
-        self.envelope.clear();
+        self.trace_key = None;
+        self.trace = Trace::Columns(Vec::new());
+        self.view = None;
+        self.pyramid = None;
+        self.pyramid_rx = None;
+    }
+
+    /// Start building the envelope pyramid for `source` (loaded from
+    /// `path`) on a background thread. With `persist`, a matching sidecar
+    /// next to the file is reused and a fresh build is saved there. Until
+    /// it lands the strip falls back to direct scans and probes.
+    pub fn build_envelope(&mut self, source: Arc<dyn SampleSource>, path: PathBuf, persist: bool) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let start = Instant::now();
+            let pyramid = match EnvelopePyramid::load_or_build(source.as_ref(), &path, persist) {
+                Ok(pyramid) => {
+                    tracing::info!(elapsed_ms = start.elapsed().as_millis() as u64, "Waveform envelope ready");
+                    Some(pyramid)
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Waveform envelope build failed");
+                    None
+                }
+            };
+            // Receiver is dropped when another file was loaded meanwhile
+            let _ = tx.send(pyramid);
+        });
+        self.pyramid_rx = Some(rx);
+    }
+
+    /// Collect a finished envelope build, if one is in flight.
+    fn poll_envelope(&mut self, ctx: &egui::Context) {
+        let Some(rx) = &self.pyramid_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(pyramid) => {
+                self.pyramid = pyramid.map(Arc::new);
+                self.pyramid_rx = None;
+                self.trace_key = None;
+                ctx.request_repaint();
+            }
+            Err(TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(200)),
+            Err(TryRecvError::Disconnected) => {
+                tracing::warn!("Waveform envelope thread exited without a result");
+                self.pyramid_rx = None;
+            }
+        }
     }
 
     /// Draw the waveform strip. Returns a new sample position when the user
-    /// clicks or drags to seek. `sync_positions` are precomputed on file
+    /// clicks to seek (or drags while fully zoomed out). Scrolling zooms
+    /// around the pointer, dragging a zoomed strip pans it, and a
+    /// double-click zooms back out. `sync_positions` are precomputed on file
     /// load (never per frame) and rendered as amber tick markers.
     pub fn draw(
         &mut self,
         ui: &mut egui::Ui,
-        wav_reader: &Option<WavReader>,
+        source: Option<&dyn SampleSource>,
         selected_channel: WaveformChannel,
         current_position_samples: usize,
         hover_position: &mut Option<f32>,
         sync_positions: &[usize],
     ) -> Option<usize> {
         let mut seek_to = None;
+        self.poll_envelope(ui.ctx());
 
-        let Some(reader) = wav_reader else {
+        let Some(source) = source else {
             let (rect, _) = ui.allocate_exact_size(
                 egui::Vec2::new(ui.available_width(), ui.available_height().max(60.0)),
                 egui::Sense::hover(),
@@ -60,14 +185,13 @@ impl WaveformPanel {
             painter.text(
                 rect.center(),
                 egui::Align2::CENTER_CENTER,
-                "No audio loaded — open a WAV file to begin",
+                "No audio loaded — open an audio file to begin",
                 egui::FontId::proportional(13.0),
                 theme::TEXT_MUTED,
             );
             return None;
         };
 
-        let samples = reader.get_samples(selected_channel);
         let available_width = ui.available_width();
         let available_height = ui.available_height().max(60.0);
 
@@ -77,14 +201,61 @@ impl WaveformPanel {
         );
         let rect = response.rect;
 
-        if response.clicked() || response.dragged() {
+        let samples_len = source.len();
+        let width = rect.width().max(1.0) as f64;
+        let fit = samples_len.max(1) as f64 / width;
+        let min_samples_per_px = (1.0 / MAX_PIXELS_PER_SAMPLE).min(fit);
+        let mut view = self.view.unwrap_or(View {
+            start: 0.0,
+            samples_per_px: fit,
+        });
+        // A resize can leave a stored zoom wider than the whole file
+        view.samples_per_px = view.samples_per_px.clamp(min_samples_per_px, fit);
+
+        if response.hovered() {
+            let (scroll, zoom) = ui.input(|i| (i.smooth_scroll_delta.y, i.zoom_delta()));
+            let factor = zoom as f64 * (scroll as f64 * ZOOM_PER_SCROLL_POINT).exp();
+            if factor != 1.0 {
+                if let Some(pointer) = response.hover_pos() {
+                    // Keep the sample under the pointer fixed while zooming
+                    let px = (pointer.x - rect.min.x) as f64;
+                    let anchor = view.sample_at(px);
+                    view.samples_per_px = (view.samples_per_px / factor).clamp(min_samples_per_px, fit);
+                    view.start = anchor - px * view.samples_per_px;
+                }
+            }
+        }
+        if response.double_clicked() {
+            view = View {
+                start: 0.0,
+                samples_per_px: fit,
+            };
+        }
+
+        let zoomed = view.samples_per_px < fit;
+        if response.dragged() && zoomed {
+            view.start -= response.drag_delta().x as f64 * view.samples_per_px;
+        } else if response.clicked() || response.dragged() {
             let click_pos = response.interact_pointer_pos().unwrap_or_default();
-            let relative_x = (click_pos.x - rect.min.x) / rect.width();
-            let samples_len = samples.len();
-            let new_position = (relative_x * samples_len as f32) as usize;
-            seek_to = Some(new_position.clamp(0, samples_len.saturating_sub(1)));
+            let new_position = view.sample_at((click_pos.x - rect.min.x) as f64).max(0.0) as usize;
+            seek_to = Some(new_position.min(samples_len.saturating_sub(1)));
         }
 
+        // Page forward (or back) when playback carries the playhead out of a
+        // zoomed view it was visible in; seeks elsewhere leave the view alone
+        if zoomed && current_position_samples != self.last_playhead {
+            let was_visible = (0.0..=width).contains(&view.px_of(self.last_playhead));
+            let now_visible = (0.0..=width).contains(&view.px_of(current_position_samples));
+            if was_visible && !now_visible {
+                view.start = current_position_samples as f64 - 0.1 * width * view.samples_per_px;
+            }
+        }
+        self.last_playhead = current_position_samples;
+
+        let max_start = (samples_len as f64 - width * view.samples_per_px).max(0.0);
+        view.start = view.start.clamp(0.0, max_start);
+        self.view = (view.samples_per_px < fit).then_some(view);
+
         if response.hovered() {
             if let Some(hover_pos) = response.hover_pos() {
                 let relative_x = (hover_pos.x - rect.min.x) / rect.width();
@@ -97,8 +268,9 @@ impl WaveformPanel {
         self.draw_internal(
             ui,
             &rect,
-            samples,
-            reader.sample_rate,
+            source,
+            selected_channel,
+            view,
             current_position_samples,
             *hover_position,
             sync_positions,
@@ -107,13 +279,80 @@ impl WaveformPanel {
         seek_to
     }
 
+    /// Recompute the trace for `view`. Past one sample per pixel the samples
+    /// themselves are drawn; otherwise small spans are read exactly, larger
+    /// ones come from the pyramid, and until that is built resident channels
+    /// are scanned and streamed ones probed at most
+    /// [`STREAMED_PROBE_SAMPLES`] per column.
+    fn rebuild_trace(&mut self, source: &dyn SampleSource, channel: WaveformChannel, view: View, width_px: u32) {
+        let total_samples = source.len();
+        let first = (view.start.floor().max(0.0) as usize).min(total_samples);
+        let last = ((view.sample_at(width_px as f64).ceil() as usize).saturating_add(1)).min(total_samples);
+        let read = |range: std::ops::Range<usize>| {
+            source.read(channel, range).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Waveform envelope read failed");
+                Vec::new()
+            })
+        };
+
+        if view.samples_per_px < 1.0 {
+            self.trace = Trace::Samples {
+                first,
+                values: read(first..last),
+            };
+            return;
+        }
+
+        let column_range = |x: u32| {
+            let start = (view.sample_at(x as f64) as usize).min(total_samples);
+            let end = (view.sample_at((x + 1) as f64) as usize).clamp(start, total_samples);
+            start..end
+        };
+
+        if last - first <= DIRECT_READ_SAMPLES {
+            let samples = read(first..last);
+            let columns = (0..width_px)
+                .map(|x| {
+                    let range = column_range(x);
+                    let lo = (range.start - first).min(samples.len());
+                    let hi = (range.end - first).clamp(lo, samples.len());
+                    min_max(&samples[lo..hi])
+                })
+                .collect();
+            self.trace = Trace::Columns(columns);
+            return;
+        }
+
+        if let Some(columns) = self
+            .pyramid
+            .as_ref()
+            .and_then(|p| p.columns(channel, view.start, view.samples_per_px, width_px as usize))
+        {
+            self.trace = Trace::Columns(columns);
+            return;
+        }
+
+        let resident = source.resident(channel);
+        let columns = (0..width_px)
+            .map(|x| {
+                let range = column_range(x);
+                match &resident {
+                    Some(samples) => min_max(&samples[range]),
+                    None => min_max(&read(range.start..range.end.min(range.start + STREAMED_PROBE_SAMPLES))),
+                }
+            })
+            .collect();
+        self.trace = Trace::Columns(columns);
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn draw_internal(
         &mut self,
         ui: &mut egui::Ui,
         rect: &egui::Rect,
-        samples: &[f32],
-        sample_rate: u32,
+        source: &dyn SampleSource,
+        channel: WaveformChannel,
+        view: View,
         current_position: usize,
         hover_position: Option<f32>,
         sync_positions: &[usize],
@@ -133,7 +372,8 @@ impl WaveformPanel {
         );
 
         // Handle empty samples gracefully
-        if samples.is_empty() {
+        let total_samples = source.len();
+        if total_samples == 0 {
             painter.text(
                 rect.center(),
                 egui::Align2::CENTER_CENTER,
@@ -150,6 +390,8 @@ impl WaveformPanel {
             rect.min,
             egui::Pos2::new(rect.max.x, (rect.max.y - axis_height).max(rect.min.y)),
         );
+        let x_of = |sample: usize| wave_rect.min.x + view.px_of(sample) as f32;
+        let visible = |x: f32| (wave_rect.min.x..=wave_rect.max.x).contains(&x);
 
         // Faint zero-amplitude center line
         let center_y = wave_rect.center().y;
@@ -161,52 +403,62 @@ impl WaveformPanel {
             egui::Stroke::new(1.0, theme::PANEL_BORDER),
         );
 
-        // Min/max envelope per pixel column, drawn in cyan. Rebuilt only when
-        // the channel buffer or pixel width changes — never per frame.
+        // Trace in cyan. Rebuilt only when the source, channel, pixel width,
+        // view or envelope availability changes — never per frame.
         let width_px = wave_rect.width() as u32;
-        let key = (samples.as_ptr() as usize, samples.len(), width_px);
-        if self.envelope_key != Some(key) {
-            let samples_per_pixel = samples.len().max(1) as f32 / width_px.max(1) as f32;
-            self.envelope.clear();
-            self.envelope.reserve(width_px as usize);
-            for pixel_x in 0..width_px {
-                let start_sample = (pixel_x as f32 * samples_per_pixel) as usize;
-                let end_sample = (((pixel_x + 1) as f32 * samples_per_pixel) as usize).min(samples.len());
-                let mut min_val = 1.0f32;
-                let mut max_val = -1.0f32;
-                if start_sample < samples.len() {
-                    for sample in &samples[start_sample..end_sample] {
-                        let clamped = sample.clamp(-1.0, 1.0);
-                        min_val = min_val.min(clamped);
-                        max_val = max_val.max(clamped);
-                    }
-                }
-                self.envelope.push((min_val, max_val));
-            }
-            self.envelope_key = Some(key);
+        let source_addr = source as *const dyn SampleSource as *const () as usize;
+        let key = (
+            source_addr,
+            channel,
+            total_samples,
+            width_px,
+            (view.start.to_bits(), view.samples_per_px.to_bits()),
+            self.pyramid.is_some(),
+        );
+        if self.trace_key != Some(key) {
+            self.rebuild_trace(source, channel, view, width_px);
+            self.trace_key = Some(key);
         }
 
         let trace_color = theme::CYAN.gamma_multiply(0.85);
         let amplitude_scale = wave_rect.height() * 0.42;
-        for (pixel_x, &(min_val, max_val)) in self.envelope.iter().enumerate() {
-            if max_val < min_val {
-                continue; // column had no samples
-            }
-            let min_y = center_y - min_val * amplitude_scale;
-            let max_y = center_y - max_val * amplitude_scale;
-            let x = wave_rect.min.x + pixel_x as f32;
+        match &self.trace {
+            Trace::Columns(columns) => {
+                for (pixel_x, &(min_val, max_val)) in columns.iter().enumerate() {
+                    if max_val < min_val {
+                        continue; // column had no samples
+                    }
+                    let min_y = center_y - min_val * amplitude_scale;
+                    let max_y = center_y - max_val * amplitude_scale;
+                    let x = wave_rect.min.x + pixel_x as f32;
 
-            painter.line_segment(
-                [egui::Pos2::new(x, min_y), egui::Pos2::new(x, max_y)],
-                egui::Stroke::new(1.0, trace_color),
-            );
+                    painter.line_segment(
+                        [egui::Pos2::new(x, min_y), egui::Pos2::new(x, max_y)],
+                        egui::Stroke::new(1.0, trace_color),
+                    );
+                }
+            }
+            Trace::Samples { first, values } => {
+                let points: Vec<egui::Pos2> = values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &v)| egui::Pos2::new(x_of(first + i), center_y - v.clamp(-1.0, 1.0) * amplitude_scale))
+                    .collect();
+                let clipped = painter.with_clip_rect(wave_rect);
+                clipped.add(egui::Shape::line(points.clone(), egui::Stroke::new(1.0, trace_color)));
+                if 1.0 / view.samples_per_px >= SAMPLE_DOT_PIXELS {
+                    for point in points {
+                        clipped.circle_filled(point, 2.0, trace_color);
+                    }
+                }
+            }
         }
 
         // Amber tick markers at detected sync-tone positions (cached on load)
         let sync_color = theme::AMBER.gamma_multiply(0.75);
         for &sync_pos in sync_positions {
-            if sync_pos < samples.len() {
-                let x = wave_rect.min.x + (sync_pos as f32 / samples.len() as f32) * wave_rect.width();
+            let x = x_of(sync_pos);
+            if sync_pos < total_samples && visible(x) {
                 painter.line_segment(
                     [
                         egui::Pos2::new(x, wave_rect.min.y),
@@ -225,8 +477,8 @@ impl WaveformPanel {
         }
 
         // Playhead cursor in teal
-        if current_position < samples.len() {
-            let position_x = wave_rect.min.x + (current_position as f32 / samples.len() as f32) * wave_rect.width();
+        let position_x = x_of(current_position);
+        if current_position < total_samples && visible(position_x) {
             painter.line_segment(
                 [
                     egui::Pos2::new(position_x, wave_rect.min.y),
@@ -248,14 +500,40 @@ impl WaveformPanel {
             );
         }
 
-        // Time axis labels along the bottom
-        let total_secs = samples.len() as f32 / sample_rate.max(1) as f32;
+        // Zoom readout and envelope status in the top-right corner
+        let zoom = total_samples as f64 / (view.samples_per_px * wave_rect.width().max(1.0) as f64);
+        let status = match (self.pyramid_rx.is_some(), zoom > 1.0) {
+            (true, true) => Some(format!("×{zoom:.0} · building overview…")),
+            (true, false) => Some("building overview…".to_string()),
+            (false, true) => Some(format!("×{zoom:.0} · double-click to reset")),
+            (false, false) => None,
+        };
+        if let Some(status) = status {
+            painter.text(
+                egui::Pos2::new(wave_rect.max.x - 6.0, wave_rect.min.y + 4.0),
+                egui::Align2::RIGHT_TOP,
+                status,
+                egui::FontId::monospace(10.0),
+                theme::TEXT_MUTED,
+            );
+        }
+
+        // Time axis labels along the bottom, for the visible window. Zoomed
+        // views need millisecond resolution to tell the ticks apart.
+        let rate = source.sample_rate().max(1) as f64;
+        let view_start_secs = view.start / rate;
+        let view_secs = view.samples_per_px * wave_rect.width() as f64 / rate;
         let label_font = egui::FontId::monospace(10.0);
         let ticks = 5;
         for i in 0..=ticks {
-            let frac = i as f32 / ticks as f32;
-            let x = wave_rect.min.x + frac * wave_rect.width();
-            let text = format_duration(total_secs * frac);
+            let frac = i as f64 / ticks as f64;
+            let x = wave_rect.min.x + frac as f32 * wave_rect.width();
+            let secs = view_start_secs + frac * view_secs;
+            let text = if zoom > 1.0 {
+                format_timecode(secs)
+            } else {
+                format_duration(secs as f32)
+            };
             let align = if i == 0 {
                 egui::Align2::LEFT_BOTTOM
             } else if i == ticks {
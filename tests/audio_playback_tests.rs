@@ -4,7 +4,7 @@
 //! real audio hardware. We use synthetic audio signals with known properties
 //! to verify correctness.
 
-use voyager_explorer::audio::WavReader;
+use voyager_explorer::audio::{WavReader, WaveformChannel};
 use voyager_explorer::audio_state::{AudioError, AudioPlaybackState};
 use voyager_explorer::sstv::{DecoderMode, DecoderParams, SstvDecoder};
 use voyager_explorer::test_fixtures::*;
@@ -21,11 +21,15 @@ fn test_wav_loading_with_synthetic_tone() {
     // Verify properties
     assert_eq!(reader.sample_rate, 44100);
     assert_eq!(reader.channels, 1);
-    assert_eq!(reader.left_channel.len(), 22050); // 0.5s * 44100
-    assert_eq!(reader.right_channel.len(), 22050); // Mono duplicated
+    assert_eq!(reader.get_samples(WaveformChannel::Left).len(), 22050); // 0.5s * 44100
+    assert_eq!(reader.get_samples(WaveformChannel::Right).len(), 22050); // Mono duplicated
 
     // Verify amplitude is approximately 0.6
-    let max_amplitude = reader.left_channel.iter().map(|&s| s.abs()).fold(0.0f32, f32::max);
+    let max_amplitude = reader
+        .get_samples(WaveformChannel::Left)
+        .iter()
+        .map(|&s| s.abs())
+        .fold(0.0f32, f32::max);
     assert!((max_amplitude - 0.6).abs() < 0.05, "Amplitude should be ~0.6");
 }
 
@@ -202,9 +206,15 @@ fn test_stereo_wav_generation() {
 
     let reader = WavReader::from_file(mono_wav.path()).expect("load mono wav");
     assert_eq!(reader.channels, 1);
-    assert_eq!(reader.left_channel.len(), reader.right_channel.len());
+    assert_eq!(
+        reader.get_samples(WaveformChannel::Left).len(),
+        reader.get_samples(WaveformChannel::Right).len()
+    );
     // For mono, left and right should be identical
-    assert_eq!(reader.left_channel, reader.right_channel);
+    assert_eq!(
+        reader.get_samples(WaveformChannel::Left),
+        reader.get_samples(WaveformChannel::Right)
+    );
 }
 
 #[test]
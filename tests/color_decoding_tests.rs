@@ -1,8 +1,9 @@
-use voyager_explorer::sstv::{DecoderMode, DecoderParams, SstvDecoder};
+use voyager_explorer::backend;
+use voyager_explorer::sstv::{DecoderMode, DecoderParams};
 
 #[test]
 fn test_pseudocolor_decoding_logic() {
-    let decoder = SstvDecoder::new();
+    let decoder = backend::for_mode(DecoderMode::PseudoColor);
     let params = DecoderParams {
         line_duration_ms: 10.0,
         sync_lock: false, // plain level lines, no sync structure
@@ -20,7 +21,7 @@ fn test_pseudocolor_decoding_logic() {
     samples.extend(std::iter::repeat_n(0.0, samples_per_line));
     samples.extend(std::iter::repeat_n(0.0, samples_per_line));
 
-    let pixels = decoder.decode(&samples, &params, sample_rate).expect("Decode failed");
+    let pixels = decoder.decode(&samples, &params, sample_rate).expect("Decode failed").pixels;
 
     assert_eq!(pixels.len(), 512 * 3, "Should produce exactly one line of RGB pixels");
 
@@ -38,7 +39,7 @@ fn test_pseudocolor_decoding_logic() {
 #[test]
 fn test_pseudocolor_partial_lines() {
     // 4 lines = 1 complete RGB triplet + 1 leftover line that must be dropped.
-    let decoder = SstvDecoder::new();
+    let decoder = backend::for_mode(DecoderMode::PseudoColor);
     let params = DecoderParams {
         line_duration_ms: 10.0,
         sync_lock: false,
@@ -50,7 +51,7 @@ fn test_pseudocolor_partial_lines() {
     let samples_per_line = (sample_rate as f32 * params.line_duration_ms / 1000.0) as usize;
 
     let samples = vec![1.0; samples_per_line * 4];
-    let pixels = decoder.decode(&samples, &params, sample_rate).expect("Decode failed");
+    let pixels = decoder.decode(&samples, &params, sample_rate).expect("Decode failed").pixels;
 
     assert_eq!(pixels.len(), 512 * 3, "Should produce exactly one line of RGB pixels");
 }
@@ -59,7 +60,7 @@ fn test_pseudocolor_partial_lines() {
 fn test_pseudocolor_preserves_gray_levels() {
     // Intermediate levels must yield intermediate channel intensities — the
     // old binary decoder could only produce 8 colors.
-    let decoder = SstvDecoder::new();
+    let decoder = backend::for_mode(DecoderMode::PseudoColor);
     let params = DecoderParams {
         line_duration_ms: 10.0,
         sync_lock: false,
@@ -75,7 +76,7 @@ fn test_pseudocolor_preserves_gray_levels() {
     samples.extend(std::iter::repeat_n(0.5, samples_per_line)); // G mid
     samples.extend(std::iter::repeat_n(0.0, samples_per_line)); // B none
 
-    let pixels = decoder.decode(&samples, &params, sample_rate).expect("Decode failed");
+    let pixels = decoder.decode(&samples, &params, sample_rate).expect("Decode failed").pixels;
     let g = pixels[1];
     assert!((90..=170).contains(&g), "mid-level green should land mid-range, got {g}");
 }
@@ -0,0 +1,50 @@
+//! Golden-image regression check over `tests/golden/manifest.toml`. The
+//! committed synthetic cases always run; cases over the record rips are
+//! skipped where their audio is not checked out.
+
+use std::path::Path;
+
+use voyager_explorer::golden::{run_manifest, CaseOutcome, GoldenOptions};
+
+#[test]
+fn golden_cases_match_their_baselines() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/manifest.toml");
+    let options = GoldenOptions {
+        bless: false,
+        diff_dir: Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden-diffs"),
+        only: None,
+    };
+    let reports = run_manifest(&manifest, &options).expect("read golden manifest");
+    assert!(!reports.is_empty(), "golden manifest has no cases");
+
+    let skipped: Vec<String> = reports
+        .iter()
+        .filter_map(|report| match &report.outcome {
+            CaseOutcome::Skipped { reason } => Some(format!("{}: {reason}", report.name)),
+            _ => None,
+        })
+        .collect();
+    for line in &skipped {
+        eprintln!("warning: skipped golden case {line}");
+    }
+    assert!(
+        skipped.len() < reports.len(),
+        "every golden case was skipped, so nothing was checked:\n{}",
+        skipped.join("\n")
+    );
+
+    let failures: Vec<String> = reports
+        .iter()
+        .filter_map(|report| match &report.outcome {
+            CaseOutcome::Failed { reason, diff, .. } => Some(format!(
+                "{}: {reason}{}",
+                report.name,
+                diff.as_ref()
+                    .map(|d| format!(" (images in {})", d.display()))
+                    .unwrap_or_default()
+            )),
+            _ => None,
+        })
+        .collect();
+    assert!(failures.is_empty(), "golden regressions:\n{}", failures.join("\n"));
+}
@@ -4,8 +4,9 @@
 //! exact pixel equality — the encoder deliberately does not mirror decoder
 //! internals.
 
+use proptest::prelude::*;
 use voyager_explorer::sstv::{DecoderParams, SstvDecoder};
-use voyager_explorer::test_fixtures::{encode_image_to_audio, encode_image_to_audio_with, EncodeOptions};
+use voyager_explorer::test_fixtures::{encode_image_to_audio, encode_image_to_audio_with, EncodeOptions, TapeDegradation};
 
 /// Pearson correlation between two equal-length pixel slices.
 fn correlation(a: &[u8], b: &[u8]) -> f64 {
@@ -102,7 +103,7 @@ fn roundtrip_with_slant_requires_sync_lock() {
     // across the image — fatal for fixed-period slicing.
     let opts = EncodeOptions {
         slant_samples_per_line: 0.5,
-        noise_amplitude: 0.0,
+        ..Default::default()
     };
     let audio = encode_image_to_audio_with(&pixels, width, sample_rate, 8.32, &opts);
     let decoder = SstvDecoder::new();
@@ -141,6 +142,7 @@ fn roundtrip_with_noise() {
     let opts = EncodeOptions {
         slant_samples_per_line: 0.2,
         noise_amplitude: 0.05,
+        ..Default::default()
     };
     let audio = encode_image_to_audio_with(&pixels, width, sample_rate, params.line_duration_ms, &opts);
     let decoded = SstvDecoder::new().decode(&audio, &params, sample_rate).expect("decode");
@@ -149,17 +151,147 @@ fn roundtrip_with_noise() {
     assert!(corr > 0.7, "noisy roundtrip correlation too low: {corr:.3}");
 }
 
+#[test]
+fn roundtrip_through_a_worn_tape_transfer() {
+    let width = 512;
+    let n_lines = 64;
+    let sample_rate = 48_000;
+    let params = DecoderParams::default();
+    let pixels = test_image(width, n_lines);
+
+    let opts = EncodeOptions {
+        tape: TapeDegradation::typical(1),
+        ..Default::default()
+    };
+    let audio = encode_image_to_audio_with(&pixels, width, sample_rate, params.line_duration_ms, &opts);
+    let decoded = SstvDecoder::new().decode(&audio, &params, sample_rate).expect("decode");
+
+    let corr = image_correlation(&pixels, &decoded, width);
+    assert!(corr > 0.8, "worn-tape roundtrip correlation too low: {corr:.3}");
+}
+
+/// Sync-to-sync slicing already stretches each line to its own length; what
+/// the fitted time base adds under wow and flutter is line starts free of
+/// the detector's jitter on a noisy signal.
+#[test]
+fn time_base_correction_steadies_noisy_fluttering_syncs() {
+    let width = 512;
+    let n_lines = 200;
+    let sample_rate = 48_000;
+    let pixels = test_image(width, n_lines);
+    let opts = EncodeOptions {
+        tape: TapeDegradation {
+            seed: 3,
+            wow_depth: 0.01,
+            wow_hz: 1.5,
+            flutter_depth: 0.004,
+            flutter_hz: 9.0,
+            ..Default::default()
+        },
+        noise_amplitude: 0.2,
+        ..Default::default()
+    };
+    let params = DecoderParams::default();
+    let audio = encode_image_to_audio_with(&pixels, width, sample_rate, params.line_duration_ms, &opts);
+    let decoder = SstvDecoder::new();
+
+    let plain = decoder.decode(&audio, &params, sample_rate).expect("decode");
+    let corrected_params = DecoderParams {
+        time_base: true,
+        ..params
+    };
+    let corrected = decoder.decode(&audio, &corrected_params, sample_rate).expect("decode");
+    let (before, after) = (
+        image_correlation(&pixels, &plain, width),
+        image_correlation(&pixels, &corrected, width),
+    );
+    assert!(after > before, "time-base correction did not help: {before:.3} -> {after:.3}");
+
+    let lines = decoder.decode_lines(&audio, &corrected_params, sample_rate).expect("decode");
+    let flutter = lines.flutter.expect("time-base correction ran");
+    assert!((flutter.peak_hz - 1.5).abs() < 0.6, "{flutter}");
+    assert!(lines.spans.iter().all(|span| span.end <= audio.len()));
+}
+
+/// Line starts fall between samples when the line period is not a whole
+/// number of them; aligning only to the nearest sample would jitter a
+/// vertical edge by up to half a sample (about a pixel) from row to row.
+#[test]
+fn vertical_edges_stay_straight_at_sub_sample_line_starts() {
+    let width = 512;
+    let n_lines = 96;
+    let pixels: Vec<u8> = (0..width * n_lines)
+        .map(|i| if i % width < width / 2 { 40 } else { 200 })
+        .collect();
+    // Render at four times the rate and box-decimate, so each line start
+    // lands on its own fraction of a 48 kHz sample. Whole-sample alignment
+    // leaves about 0.23 px of spread here.
+    let params = DecoderParams::default();
+    let fine = encode_image_to_audio(&pixels, width, 4 * 48_000, params.line_duration_ms);
+    let audio: Vec<f32> = fine.chunks_exact(4).map(|c| c.iter().sum::<f32>() / 4.0).collect();
+    let decoded = SstvDecoder::new().decode(&audio, &params, 48_000).expect("decode");
+
+    // Sub-pixel position of each row's crossing halfway between its levels
+    let edges: Vec<f64> = decoded
+        .chunks_exact(width)
+        .map(|row| {
+            let level = |x: usize| row[x] as f64;
+            let mid = (level(width / 4) + level(3 * width / 4)) / 2.0;
+            let x = (width / 4..3 * width / 4)
+                .find(|&x| level(x + 1) >= mid)
+                .expect("edge in row");
+            x as f64 + (mid - level(x)) / (level(x + 1) - level(x)).max(1.0)
+        })
+        .collect();
+    let mean = edges.iter().sum::<f64>() / edges.len() as f64;
+    let spread = (edges.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / edges.len() as f64).sqrt();
+    assert!(spread < 0.16, "edge wanders {spread:.3} px between rows");
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    /// Property: sync-locked decoding stays recognizable across seeds and
+    /// strengths of the typical impairments, up to twice as strong
+    #[test]
+    fn prop_roundtrip_survives_tape_impairments(seed in any::<u64>(), strength in 0.0f32..=2.0) {
+        let width = 256;
+        let n_lines = 48;
+        let sample_rate = 48_000;
+        let params = DecoderParams { width: width as u32, ..Default::default() };
+        let pixels = test_image(width, n_lines);
+
+        let typical = TapeDegradation::typical(seed);
+        let tape = TapeDegradation {
+            wow_depth: typical.wow_depth * strength,
+            flutter_depth: typical.flutter_depth * strength,
+            dropouts_per_sec: typical.dropouts_per_sec * strength,
+            dc_drift: typical.dc_drift * strength,
+            hum_amplitude: typical.hum_amplitude * strength,
+            lossy: (typical.lossy * strength).min(1.0),
+            ..typical
+        };
+        let opts = EncodeOptions { tape, ..Default::default() };
+        let audio = encode_image_to_audio_with(&pixels, width, sample_rate, params.line_duration_ms, &opts);
+        let decoded = SstvDecoder::new().decode(&audio, &params, sample_rate).expect("decode");
+        let corr = image_correlation(&pixels, &decoded, width);
+        prop_assert!(corr > 0.7, "seed {seed} strength {strength:.2}: correlation {corr:.3}");
+    }
+}
+
 /// Gate 1 regression: decode the real record excerpt and assert the output is
 /// a structured grayscale image, not a degenerate blob. Ignored by default
 /// because it needs the multi-megabyte asset; run with `--ignored` locally.
 #[test]
 #[ignore = "requires assets/sync_image1.wav"]
 fn decode_real_sync_image1() {
-    let reader = voyager_explorer::audio::WavReader::from_file("assets/sync_image1.wav").expect("load asset");
-    let samples = reader.left_channel.as_ref();
+    use voyager_explorer::audio::{WavReader, WaveformChannel};
+
+    let reader = WavReader::from_file("assets/sync_image1.wav").expect("load asset");
+    let samples = reader.get_samples(WaveformChannel::Left);
     let params = DecoderParams::default();
     let decoded = SstvDecoder::new()
-        .decode(samples, &params, reader.sample_rate)
+        .decode(&samples, &params, reader.sample_rate)
         .expect("decode");
 
     let width = params.effective_width();
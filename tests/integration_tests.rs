@@ -84,10 +84,10 @@ fn test_full_workflow_wav_to_image() {
     // Verify WAV properties
     assert_eq!(wav_reader.sample_rate, 44100);
     assert_eq!(wav_reader.channels, 1);
-    assert!(!wav_reader.left_channel.is_empty());
+    assert!(!wav_reader.get_samples(WaveformChannel::Left).is_empty());
 
     // Test duration formatting
-    let duration_secs = wav_reader.left_channel.len() as f32 / wav_reader.sample_rate as f32;
+    let duration_secs = wav_reader.get_samples(WaveformChannel::Left).len() as f32 / wav_reader.sample_rate as f32;
     let formatted_duration = format_duration(duration_secs);
     assert!(formatted_duration.contains(":"));
 
@@ -102,6 +102,7 @@ fn test_full_workflow_wav_to_image() {
 
     // Get samples from left channel
     let samples = wav_reader.get_samples(WaveformChannel::Left);
+    let samples = &*samples;
     assert!(!samples.is_empty());
 
     // Test sync detection
@@ -167,7 +168,9 @@ fn test_stereo_channel_selection() {
 
     // Get samples from both channels
     let left_samples = wav_reader.get_samples(WaveformChannel::Left);
+    let left_samples = &*left_samples;
     let right_samples = wav_reader.get_samples(WaveformChannel::Right);
+    let right_samples = &*right_samples;
 
     assert_eq!(left_samples.len(), right_samples.len());
     assert_ne!(left_samples, right_samples); // Should be different
@@ -196,12 +199,12 @@ fn test_empty_audio_handling() {
     // Should handle empty file gracefully (might error, which is acceptable)
     match result {
         Ok(reader) => {
-            assert!(reader.left_channel.is_empty());
+            assert!(reader.get_samples(WaveformChannel::Left).is_empty());
 
             // Test decoder with empty samples
             let decoder = SstvDecoder::new();
             let params = DecoderParams::default();
-            let result = decoder.decode(&reader.left_channel, &params, reader.sample_rate);
+            let result = decoder.decode(&reader.get_samples(WaveformChannel::Left), &params, reader.sample_rate);
             // Should return error for empty samples
             assert!(result.is_err(), "Empty samples should return error");
         }
@@ -218,6 +221,7 @@ fn test_parameter_variations() {
     let temp_wav = create_test_sstv_wav(44100, 1.0);
     let wav_reader = WavReader::from_file(temp_wav.path()).unwrap();
     let samples = wav_reader.get_samples(WaveformChannel::Left);
+    let samples = &*samples;
     let decoder = SstvDecoder::new();
 
     // Test with different line durations